@@ -1,26 +1,51 @@
 use std::any::Any;
-use std::collections::BTreeMap;
-use std::time::Duration;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use frost_signer::config::{Config, Error as ConfigError};
+use crate::dkg_report::{DkgReport, Misbehavior};
+use frost_signer::config::{Config, Error as ConfigError, PrivateKey, PublicKeys, SignerKeyIds};
 use frost_signer::{
+    complaint::{
+        check_commitment, public_key_point, zero_commitment, BadCommitment, Blame, DkgComplaint,
+    },
+    journal::{Error as JournalError, Journal, PegContext},
     net::{Error as HttpNetError, Message, NetListen},
+    reshare::{dealer_public_key, key_public_key},
     signing_round::{
-        DkgBegin, DkgPublicShare, MessageTypes, NonceRequest, NonceResponse, Signable,
-        SignatureShareRequest,
+        BatchMessage, DkgBegin, DkgFailure, DkgPrivateShares, DkgPublicShare, DkgStatus,
+        MessageTypes, NoncePoolRequest, NonceRequest, NonceResponse, ReshareBegin,
+        SignatureShareRequest, SignatureType,
     },
+    util::unix_time,
+    validation::TransactionContext,
+    wsts_version::{WstsAggregator, WstsVersion},
 };
-use hashbrown::HashSet;
+use hashbrown::{HashMap, HashSet};
 use p256k1::ecdsa::PublicKey;
+use rand_core::OsRng;
 use tracing::{debug, info, warn};
+
 use wsts::{
     common::{PolyCommitment, PublicNonce, Signature, SignatureShare},
     compute,
     errors::AggregatorError,
     taproot::{Error as TaprootError, SchnorrProof},
-    v1, Point, Scalar,
+    Point, Scalar,
 };
 
+/// A message to sign in a batch
+#[derive(Clone, Debug, Default)]
+pub struct MessageToSign {
+    pub message: Vec<u8>,
+    /// The peg operation the message is signed for, recorded in the journal
+    pub context: Option<PegContext>,
+    /// The transaction the message is the input sighash of, sent along for signers to validate
+    pub transaction: Option<TransactionContext>,
+}
+
+/// How long signers accept a request after the coordinator sent it
+pub const DEFAULT_REQUEST_TTL: Duration = Duration::from_secs(60);
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("{0}")]
@@ -39,6 +64,24 @@ pub enum Error {
     ConfigError(#[from] ConfigError),
     #[error("Received invalid signer message.")]
     InvalidSignerMessage,
+    #[error("Journal Error: {0}")]
+    JournalError(#[from] JournalError),
+    #[error("Signer {0} failed DKG: {1}")]
+    DkgFailed(u32, String),
+    #[error(
+        "Only {0} key ids remain after excluding misbehaving signers, below the threshold of {1}"
+    )]
+    NotEnoughKeys(u32, u32),
+    #[error("Signature shares of signers {0:?} failed to verify")]
+    BadSignatureShares(Vec<u32>),
+    #[error("Signers {0:?} published no nonces to their pools")]
+    EmptyNoncePools(Vec<u32>),
+    #[error("Signer {0} dealt shares of a different secret than its key shares")]
+    BadDealing(u32),
+    #[error("{0} dealers do not fit in the {1} parties after resharing")]
+    TooManyDealers(usize, u32),
+    #[error("Key id {0} is not held by exactly one signer")]
+    BadKeyAssignment(u32),
 }
 
 #[derive(clap::Subcommand, Debug)]
@@ -60,11 +103,23 @@ pub struct Coordinator<Network: NetListen> {
     threshold: u32,
     network: Network,
     dkg_public_shares: BTreeMap<u32, DkgPublicShare>,
+    dkg_private_shares: BTreeMap<u32, DkgPrivateShares>,
     public_nonces: BTreeMap<u32, NonceResponse>,
     signature_shares: BTreeMap<u32, Vec<SignatureShare>>,
     aggregate_public_key: Point,
-    network_private_key: Scalar,
+    network_private_key: PrivateKey,
     public_key: PublicKey,
+    journal: Option<Journal>,
+    request_ttl: Duration,
+    public_keys: PublicKeys,
+    excluded_signers: BTreeSet<u32>,
+    dkg_report: Option<DkgReport>,
+    bad_signers: BTreeSet<u32>,
+    current_nonce_pool_id: u64,
+    nonce_pool_size: u32,
+    /// Unused nonces each signer published in advance, in the order it published them
+    nonce_pools: BTreeMap<u32, VecDeque<NonceResponse>>,
+    wsts_version: WstsVersion,
 }
 
 impl<Network: NetListen> Coordinator<Network> {
@@ -80,11 +135,22 @@ impl<Network: NetListen> Coordinator<Network> {
             threshold: config.keys_threshold,
             network,
             dkg_public_shares: Default::default(),
+            dkg_private_shares: Default::default(),
             public_nonces: Default::default(),
             aggregate_public_key: Point::default(),
             signature_shares: Default::default(),
-            network_private_key: config.network_private_key,
+            network_private_key: config.network_private_key.clone(),
             public_key: config.coordinator_public_key,
+            journal: None,
+            request_ttl: DEFAULT_REQUEST_TTL,
+            public_keys: config.public_keys.clone(),
+            excluded_signers: Default::default(),
+            dkg_report: None,
+            bad_signers: Default::default(),
+            current_nonce_pool_id: 0,
+            nonce_pool_size: 0,
+            nonce_pools: Default::default(),
+            wsts_version: config.wsts_version,
         })
     }
 
@@ -104,9 +170,151 @@ impl<Network: NetListen> Coordinator<Network> {
         &self.dkg_public_shares
     }
 
+    /// Restore the commitments of a completed DKG round, continuing with its round id
     pub fn set_dkg_public_shares(&mut self, dkg_public_shares: BTreeMap<u32, DkgPublicShare>) {
+        if let Some(dkg_public_share) = dkg_public_shares.values().next() {
+            self.current_dkg_id = dkg_public_share.dkg_id;
+        }
+        self.nonce_pools.clear();
         self.dkg_public_shares = dkg_public_shares;
     }
+
+    /// Record every subsequent signature in the given journal
+    pub fn set_journal(&mut self, journal: Journal) {
+        self.journal = Some(journal);
+    }
+
+    /// The report of the last DKG run, if one completed
+    pub fn get_dkg_report(&self) -> Option<&DkgReport> {
+        self.dkg_report.as_ref()
+    }
+
+    /// Signers excluded from DKG and signing for misbehaving in an earlier DKG round
+    pub fn get_excluded_signers(&self) -> &BTreeSet<u32> {
+        &self.excluded_signers
+    }
+
+    pub fn set_excluded_signers(&mut self, excluded_signers: BTreeSet<u32>) {
+        self.excluded_signers = excluded_signers;
+    }
+
+    /// Signers whose signature shares failed to verify while signing the last message
+    pub fn get_bad_signers(&self) -> &BTreeSet<u32> {
+        &self.bad_signers
+    }
+
+    /// Signers taking part in DKG and signing
+    fn included_signers(&self) -> HashSet<u32> {
+        (1..=self.total_signers)
+            .filter(|signer_id| !self.excluded_signers.contains(signer_id))
+            .collect()
+    }
+
+    /// Included signers holding key ids which have not sent bad signature shares for the current message
+    fn signing_signers(&self) -> HashSet<u32> {
+        self.included_signers()
+            .into_iter()
+            .filter(|signer_id| !self.bad_signers.contains(signer_id))
+            .filter(|signer_id| !self.public_keys.signer_key_ids(*signer_id).is_empty())
+            .collect()
+    }
+
+    /// Number of keys held by the signing signers
+    fn signing_keys(&self) -> u32 {
+        self.signing_signers()
+            .iter()
+            .map(|signer_id| self.public_keys.signer_key_ids(*signer_id).len() as u32)
+            .sum()
+    }
+
+    /// Party ids, counted from 1, of the excluded signers
+    fn excluded_party_ids(&self) -> BTreeSet<u32> {
+        self.excluded_signers
+            .iter()
+            .flat_map(|signer_id| self.wsts_version.party_ids(&self.public_keys, *signer_id))
+            .collect()
+    }
+
+    /// Number of keys held by the excluded signers
+    fn excluded_keys(&self) -> u32 {
+        self.excluded_signers
+            .iter()
+            .map(|signer_id| self.public_keys.signer_key_ids(*signer_id).len() as u32)
+            .sum()
+    }
+
+    /// The signer of the party id, counted from 0
+    fn party_signer(&self, party_id: u32) -> Option<u32> {
+        self.wsts_version
+            .party_signer(&self.public_keys, party_id + 1)
+    }
+
+    /// The signer holding the key id, counted from 0
+    fn key_id_signer(&self, key_id: u32) -> Option<u32> {
+        (1..=self.total_signers).find(|signer_id| {
+            self.public_keys
+                .signer_key_ids(*signer_id)
+                .contains(&(key_id + 1))
+        })
+    }
+
+    /// Keep this many nonces of each signer in advance, so that signing needs no nonce request.
+    /// Pools are replenished once half of them was used. Zero disables nonce pools.
+    pub fn set_nonce_pool_size(&mut self, nonce_pool_size: u32) {
+        self.nonce_pool_size = nonce_pool_size;
+    }
+
+    /// Set how long signers accept a request after it was sent
+    pub fn set_request_ttl(&mut self, request_ttl: Duration) {
+        self.request_ttl = request_ttl;
+    }
+
+    /// The unix time a request sent now expires at
+    fn request_expiry(&self) -> u64 {
+        unix_time() + self.request_ttl.as_secs()
+    }
+}
+
+/// The aggregate nonce of the message from the nonces with the given index in the responses
+#[allow(non_snake_case)]
+fn aggregate_nonce<'a>(
+    msg: &[u8],
+    nonce_responses: impl Iterator<Item = &'a NonceResponse> + Clone,
+    nonce_index: u32,
+) -> Point {
+    // Nonces are bound to key ids in v1 and to signers in v2
+    let party_ids = nonce_responses
+        .clone()
+        .flat_map(|pn| pn.party_ids())
+        .collect::<Vec<u32>>();
+    let nonces = nonce_responses
+        .flat_map(|pn| pn.message_nonces(nonce_index).to_vec())
+        .collect::<Vec<PublicNonce>>();
+    let (_, R) = compute::intermediate(msg, &party_ids, &nonces);
+    R
+}
+
+/// The id following the current one. Signers reject ids they have already seen, so ids start
+/// from the current time in milliseconds to keep increasing across restarts of the coordinator.
+fn next_id(current: u64) -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    current.saturating_add(1).max(now)
+}
+
+/// Check that the key ids, counted from 1, are 1 to the number of key ids, each held by one
+/// signer, returning their number
+fn check_key_assignment(signer_key_ids: &BTreeMap<u32, Vec<u32>>) -> Result<u32, Error> {
+    let mut key_ids: Vec<u32> = signer_key_ids.values().flatten().copied().collect();
+    key_ids.sort_unstable();
+    for (expected, key_id) in (1u32..).zip(&key_ids) {
+        if *key_id != expected {
+            return Err(Error::BadKeyAssignment(expected.min(*key_id)));
+        }
+    }
+    Ok(key_ids.len() as u32)
 }
 
 impl<Network: NetListen> Coordinator<Network>
@@ -137,28 +345,219 @@ where
         }
     }
 
+    /// Run DKG until a round completes. Signers blamed for a failed round are excluded from the next one.
     pub fn run_distributed_key_generation(&mut self) -> Result<Point, Error> {
-        self.current_dkg_id = self.current_dkg_id.wrapping_add(1);
-        info!("Starting DKG round #{}", self.current_dkg_id);
-        self.start_public_shares()?;
-        let public_key = self.wait_for_public_shares()?;
-        self.start_private_shares()?;
-        self.wait_for_dkg_end()?;
-        Ok(public_key)
+        let mut report = DkgReport::default();
+        // Pool nonces belong to the DKG round they were published for
+        self.nonce_pools.clear();
+        loop {
+            let remaining_keys = self.total_keys - self.excluded_keys();
+            if remaining_keys < self.threshold {
+                return Err(Error::NotEnoughKeys(remaining_keys, self.threshold));
+            }
+            self.current_dkg_id = next_id(self.current_dkg_id);
+            report.dkg_ids.push(self.current_dkg_id);
+            info!("Starting DKG round #{}", self.current_dkg_id);
+            self.start_public_shares()?;
+            let mut misbehavior = self.wait_for_public_shares()?;
+            if misbehavior.is_empty() {
+                self.start_private_shares()?;
+                misbehavior = self.wait_for_dkg_end()?;
+            }
+            if misbehavior.is_empty() {
+                let public_key = self.aggregate_public_key;
+                report.aggregate_public_key = public_key;
+                report.excluded_signers = self.excluded_signers.clone();
+                info!("DKG report: {:?}", report);
+                self.dkg_report = Some(report);
+                return Ok(public_key);
+            }
+            for (signer_id, misbehavior) in misbehavior {
+                warn!(
+                    "DKG Round #{} Failed: excluding signer #{} for {:?}",
+                    self.current_dkg_id, signer_id, misbehavior
+                );
+                self.excluded_signers.insert(signer_id);
+                report
+                    .misbehavior
+                    .entry(signer_id)
+                    .or_default()
+                    .extend(misbehavior);
+            }
+        }
+    }
+
+    /// Reshare the secret of the aggregate public key to a new assignment of key ids, counted from
+    /// 1, and a new threshold. Signers holding at least the current threshold of key ids deal
+    /// shares of their key shares to the new key ids, so that the aggregate public key and the
+    /// addresses derived from it stay the same, while the key shares of signers left without
+    /// key ids become useless.
+    pub fn run_resharing(
+        &mut self,
+        signer_key_ids: &SignerKeyIds,
+        threshold: u32,
+    ) -> Result<Point, Error> {
+        let aggregate_public_key = self.get_aggregate_public_key()?;
+        let signer_key_ids: BTreeMap<u32, Vec<u32>> = signer_key_ids
+            .iter()
+            .map(|(signer_id, key_ids)| (*signer_id, key_ids.clone()))
+            .collect();
+        let total_keys = check_key_assignment(&signer_key_ids)?;
+        // Shares are encrypted for the network key of the signer holding the key id
+        for (signer_id, key_ids) in &signer_key_ids {
+            if !key_ids.is_empty() && !self.public_keys.signers.contains_key(signer_id) {
+                return Err(ConfigError::UnknownSignerID(*signer_id).into());
+            }
+        }
+        if total_keys < threshold {
+            return Err(Error::NotEnoughKeys(total_keys, threshold));
+        }
+
+        // Deal from as few signers as hold the current threshold of key ids
+        let mut signing_signers: Vec<u32> = self.signing_signers().into_iter().collect();
+        signing_signers.sort_unstable();
+        let mut dealers = BTreeMap::new();
+        let mut dealt_keys = 0;
+        for signer_id in signing_signers {
+            if dealt_keys >= self.threshold {
+                break;
+            }
+            let key_ids = self.public_keys.signer_key_ids(signer_id);
+            dealt_keys += key_ids.len() as u32;
+            dealers.insert(signer_id, key_ids);
+        }
+        if dealt_keys < self.threshold {
+            return Err(Error::NotEnoughKeys(dealt_keys, self.threshold));
+        }
+        let total_parties = self
+            .wsts_version
+            .total_parties(self.total_signers, total_keys);
+        if dealers.len() > total_parties as usize {
+            return Err(Error::TooManyDealers(dealers.len(), total_parties));
+        }
+
+        // Each dealer's part of the secret must match the public keys of the key ids it deals
+        let dealer_key_ids: Vec<u32> = dealers
+            .values()
+            .flatten()
+            .map(|key_id| key_id - 1)
+            .collect();
+        let commitments: Vec<&PolyCommitment> = self
+            .dkg_public_shares
+            .values()
+            .map(|share| &share.public_share)
+            .collect();
+        let key_public_keys: HashMap<u32, Point> = dealer_key_ids
+            .iter()
+            .map(|key_id| {
+                (
+                    *key_id,
+                    key_public_key(*key_id, commitments.iter().copied()),
+                )
+            })
+            .collect();
+        let mut dealer_public_keys = BTreeMap::new();
+        for (party_id, (signer_id, key_ids)) in (1u32..).zip(&dealers) {
+            let key_ids: Vec<u32> = key_ids.iter().map(|key_id| key_id - 1).collect();
+            let public_key = dealer_public_key(&key_public_keys, &key_ids, &dealer_key_ids)
+                .ok_or(Error::BadDealing(*signer_id))?;
+            dealer_public_keys.insert(*signer_id, (party_id, public_key));
+        }
+
+        let key_epoch = self.current_dkg_id;
+        self.current_dkg_id = next_id(self.current_dkg_id);
+        info!(
+            "Starting resharing round #{}: {} key ids with a threshold of {} from dealers {:?}",
+            self.current_dkg_id,
+            total_keys,
+            threshold,
+            dealers.keys()
+        );
+        let reshare_begin = ReshareBegin {
+            dkg_id: self.current_dkg_id,
+            expires_at: self.request_expiry(),
+            threshold,
+            signer_key_ids: signer_key_ids.clone(),
+            dealers,
+            aggregate_public_key,
+        };
+        let reshare_begin_message = Message {
+            sig: self
+                .network_private_key
+                .sign(&reshare_begin)
+                .expect("Failed to sign ReshareBegin"),
+            msg: MessageTypes::ReshareBegin(reshare_begin),
+        };
+        self.network.send_message(reshare_begin_message)?;
+
+        let mut dkg_public_shares = match self.wait_for_reshare_end(threshold, &dealer_public_keys)
+        {
+            Ok(dkg_public_shares) => dkg_public_shares,
+            Err(e) => {
+                // Signers keep the key shares of the last key epoch when resharing fails
+                self.current_dkg_id = key_epoch;
+                return Err(e);
+            }
+        };
+
+        // The aggregator expects one commitment per party, those beyond the dealers commit to zero
+        for party_id in 1..=total_parties {
+            dkg_public_shares
+                .entry(party_id)
+                .or_insert_with(|| DkgPublicShare {
+                    dkg_id: self.current_dkg_id,
+                    dkg_public_id: self.current_dkg_public_id,
+                    party_id,
+                    public_share: zero_commitment(party_id, threshold, &mut OsRng),
+                });
+        }
+        self.dkg_public_shares = dkg_public_shares;
+        self.public_keys.key_ids = signer_key_ids
+            .iter()
+            .flat_map(|(signer_id, key_ids)| {
+                let public_key = self.public_keys.signers.get(signer_id).copied();
+                key_ids
+                    .iter()
+                    .filter_map(move |key_id| Some((*key_id, public_key?)))
+            })
+            .collect();
+        self.total_keys = total_keys;
+        self.threshold = threshold;
+        // Pool nonces belong to the key epoch they were published for
+        self.nonce_pools.clear();
+        info!(
+            "Resharing round #{} kept aggregate public key {}",
+            self.current_dkg_id, aggregate_public_key
+        );
+        Ok(aggregate_public_key)
     }
 
     fn start_public_shares(&mut self) -> Result<(), Error> {
         self.dkg_public_shares.clear();
+        // Excluded parties contribute the zero polynomial, like signers assume
+        for party_id in self.excluded_party_ids() {
+            self.dkg_public_shares.insert(
+                party_id,
+                DkgPublicShare {
+                    dkg_id: self.current_dkg_id,
+                    dkg_public_id: self.current_dkg_public_id,
+                    party_id,
+                    public_share: zero_commitment(party_id, self.threshold, &mut OsRng),
+                },
+            );
+        }
         info!(
             "DKG Round #{}: Starting Public Share Distribution Round #{}",
             self.current_dkg_id, self.current_dkg_public_id
         );
         let dkg_begin = DkgBegin {
             dkg_id: self.current_dkg_id,
+            expires_at: self.request_expiry(),
+            excluded_signers: self.excluded_signers.iter().copied().collect(),
         };
 
         let dkg_begin_message = Message {
-            sig: dkg_begin.sign(&self.network_private_key).expect(""),
+            sig: self.network_private_key.sign(&dkg_begin).expect(""),
             msg: MessageTypes::DkgBegin(dkg_begin),
         };
         self.network.send_message(dkg_begin_message)?;
@@ -166,33 +565,40 @@ where
     }
 
     fn start_private_shares(&mut self) -> Result<(), Error> {
+        self.dkg_private_shares.clear();
         info!(
             "DKG Round #{}: Starting Private Share Distribution",
             self.current_dkg_id
         );
         let dkg_begin = DkgBegin {
             dkg_id: self.current_dkg_id,
+            expires_at: self.request_expiry(),
+            excluded_signers: self.excluded_signers.iter().copied().collect(),
         };
         let dkg_private_begin_msg = Message {
-            sig: dkg_begin.sign(&self.network_private_key).expect(""),
+            sig: self.network_private_key.sign(&dkg_begin).expect(""),
             msg: MessageTypes::DkgPrivateBegin(dkg_begin),
         };
         self.network.send_message(dkg_private_begin_msg)?;
         Ok(())
     }
 
-    fn collect_nonces(&mut self) -> Result<(), Error> {
+    fn collect_nonces(&mut self, message_count: u32) -> Result<(), Error> {
         self.public_nonces.clear();
+        self.current_sign_nonce_id = self.current_sign_nonce_id.saturating_add(1);
 
         let nonce_request = NonceRequest {
             dkg_id: self.current_dkg_id,
             sign_id: self.current_sign_id,
             sign_nonce_id: self.current_sign_nonce_id,
+            expires_at: self.request_expiry(),
+            message_count,
         };
 
         let nonce_request_message = Message {
-            sig: nonce_request
-                .sign(&self.network_private_key)
+            sig: self
+                .network_private_key
+                .sign(&nonce_request)
                 .expect("Failed to sign NonceRequest"),
             msg: MessageTypes::NonceRequest(nonce_request),
         };
@@ -203,9 +609,20 @@ where
         );
         self.network.send_message(nonce_request_message)?;
 
+        let signing_signers = self.signing_signers();
         loop {
             match self.wait_for_next_message()?.msg {
                 MessageTypes::NonceRequest(_) => {}
+                MessageTypes::NonceResponse(nonce_response)
+                    if nonce_response.sign_id != self.current_sign_id => {}
+                MessageTypes::NonceResponse(nonce_response)
+                    if !signing_signers.contains(&nonce_response.signer_id) =>
+                {
+                    debug!(
+                        "Ignoring NonceResponse from excluded signer #{}",
+                        nonce_response.signer_id
+                    );
+                }
                 MessageTypes::NonceResponse(nonce_response) => {
                     let signer_id = nonce_response.signer_id;
                     self.public_nonces.insert(signer_id, nonce_response);
@@ -221,7 +638,7 @@ where
                 }
             }
 
-            if self.public_nonces.len() == usize::try_from(self.total_signers).unwrap() {
+            if self.public_nonces.len() == signing_signers.len() {
                 debug!("Nonce threshold of {} met.", self.threshold);
                 break;
             }
@@ -229,46 +646,199 @@ where
         Ok(())
     }
 
+    /// The aggregate nonce of the message from the nonces with the given index in the responses
+    fn compute_aggregate_nonce(&self, msg: &[u8], nonce_index: u32) -> Point {
+        aggregate_nonce(msg, self.public_nonces.values(), nonce_index)
+    }
+
+    /// Ask the signers for more pool nonces if any of their pools has fewer than the needed
+    /// nonces or is at most half full
+    fn replenish_nonce_pools(&mut self, needed: usize) -> Result<(), Error> {
+        let signing_signers = self.signing_signers();
+        let pool_size = self.nonce_pool_size as usize;
+        let shortest = signing_signers
+            .iter()
+            .map(|signer_id| self.nonce_pools.get(signer_id).map_or(0, VecDeque::len))
+            .min()
+            .unwrap_or_default();
+        if shortest >= needed && shortest > pool_size / 2 {
+            return Ok(());
+        }
+        self.current_nonce_pool_id = next_id(self.current_nonce_pool_id);
+        let nonce_pool_request = NoncePoolRequest {
+            dkg_id: self.current_dkg_id,
+            pool_id: self.current_nonce_pool_id,
+            count: (pool_size.max(needed) - shortest) as u32,
+            expires_at: self.request_expiry(),
+        };
+        info!(
+            "Requesting {} pool nonces from each signer for DKG round #{}",
+            nonce_pool_request.count, nonce_pool_request.dkg_id
+        );
+        let nonce_pool_request_message = Message {
+            sig: self
+                .network_private_key
+                .sign(&nonce_pool_request)
+                .expect("Failed to sign NoncePoolRequest"),
+            msg: MessageTypes::NoncePoolRequest(nonce_pool_request),
+        };
+        self.network.send_message(nonce_pool_request_message)?;
+
+        let mut ids_to_await = signing_signers;
+        while !ids_to_await.is_empty() {
+            match self.wait_for_next_message()?.msg {
+                MessageTypes::NoncePoolResponse(response)
+                    if response.pool_id == self.current_nonce_pool_id
+                        && response.dkg_id == self.current_dkg_id =>
+                {
+                    if ids_to_await.remove(&response.signer_id) {
+                        debug!(
+                            "Signer #{} published pool nonces from #{}",
+                            response.signer_id, response.start_index
+                        );
+                        self.nonce_pools
+                            .entry(response.signer_id)
+                            .or_default()
+                            .extend(response.nonce_responses(self.current_sign_id));
+                    }
+                }
+                MessageTypes::NoncePoolRequest(_) => {}
+                msg => {
+                    warn!("NoncePool loop got unexpected msg {:?}", msg.type_id());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect pool nonces for the messages, the next ones of each signer for each message.
+    /// Nonces giving an aggregate nonce with odd y are skipped.
     #[allow(non_snake_case)]
-    fn compute_aggregate_nonce(&mut self, msg: &[u8]) -> Result<Point, Error> {
-        info!("Computing aggregate nonce...");
-        self.collect_nonces()?;
-        // XXX this needs to be key_ids for v1 and signer_ids for v2
-        let party_ids = self
-            .public_nonces
-            .values()
-            .flat_map(|pn| pn.key_ids.clone())
-            .collect::<Vec<u32>>();
-        let nonces = self
-            .public_nonces
-            .values()
-            .flat_map(|pn| pn.nonces.clone())
-            .collect::<Vec<PublicNonce>>();
-        let (_, R) = compute::intermediate(msg, &party_ids, &nonces);
-        Ok(R)
+    fn collect_pool_nonces(
+        &mut self,
+        messages: &[MessageToSign],
+    ) -> Result<Vec<(Vec<NonceResponse>, u32)>, Error> {
+        let mut message_nonces = vec![];
+        for (message_index, message) in messages.iter().enumerate() {
+            loop {
+                self.replenish_nonce_pools(messages.len() - message_index)?;
+                let signing_signers = self.signing_signers();
+                let empty_pools: Vec<u32> = signing_signers
+                    .iter()
+                    .filter(|signer_id| {
+                        self.nonce_pools
+                            .get(*signer_id)
+                            .map_or(true, VecDeque::is_empty)
+                    })
+                    .copied()
+                    .collect();
+                if !empty_pools.is_empty() {
+                    return Err(Error::EmptyNoncePools(empty_pools));
+                }
+                let nonce_responses: BTreeMap<u32, NonceResponse> = signing_signers
+                    .into_iter()
+                    .filter_map(|signer_id| {
+                        let mut nonce_response =
+                            self.nonce_pools.get_mut(&signer_id)?.pop_front()?;
+                        nonce_response.sign_id = self.current_sign_id;
+                        Some((signer_id, nonce_response))
+                    })
+                    .collect();
+                let R = aggregate_nonce(&message.message, nonce_responses.values(), 0);
+                if R.has_even_y() {
+                    debug!("Success: R has even y coord: {}", &R);
+                    message_nonces.push((nonce_responses.values().cloned().collect(), 0));
+                    self.public_nonces = nonce_responses;
+                    break;
+                }
+                warn!(
+                    "Failure: R does not have even y coord: {}, skipping pool nonces",
+                    R
+                );
+            }
+        }
+        Ok(message_nonces)
+    }
+
+    /// Collect nonces for the messages, returning the nonce responses and the index of its nonces
+    /// in them for each message. Messages whose aggregate nonce has odd y get new nonces in the
+    /// next request, so the number of requests grows with the log of the number of messages.
+    #[allow(non_snake_case)]
+    fn collect_message_nonces(
+        &mut self,
+        messages: &[MessageToSign],
+    ) -> Result<Vec<(Vec<NonceResponse>, u32)>, Error> {
+        info!(
+            "Computing aggregate nonces for {} messages...",
+            messages.len()
+        );
+        if self.nonce_pool_size > 0 {
+            return self.collect_pool_nonces(messages);
+        }
+        let mut message_nonces = vec![None; messages.len()];
+        let mut pending: Vec<usize> = (0..messages.len()).collect();
+        while !pending.is_empty() {
+            self.collect_nonces(pending.len() as u32)?;
+            let nonce_responses: Vec<NonceResponse> =
+                self.public_nonces.values().cloned().collect();
+            let mut odd = vec![];
+            for (nonce_index, message_index) in pending.into_iter().enumerate() {
+                let nonce_index = nonce_index as u32;
+                let R = self.compute_aggregate_nonce(&messages[message_index].message, nonce_index);
+                if R.has_even_y() {
+                    debug!("Success: R has even y coord: {}", &R);
+                    message_nonces[message_index] = Some((nonce_responses.clone(), nonce_index));
+                } else {
+                    warn!("Failure: R does not have even y coord: {}", R);
+                    odd.push(message_index);
+                }
+            }
+            pending = odd;
+        }
+        Ok(message_nonces.into_iter().flatten().collect())
     }
 
     fn request_signature_shares(
         &self,
-        nonce_responses: &[NonceResponse],
-        msg: &[u8],
+        messages: &[MessageToSign],
+        message_nonces: &[(Vec<NonceResponse>, u32)],
+        signature_type: SignatureType,
     ) -> Result<(), Error> {
+        let mut batch: Vec<BatchMessage> = messages
+            .iter()
+            .zip(message_nonces)
+            .map(|(message, (nonce_responses, nonce_index))| BatchMessage {
+                message: message.message.clone(),
+                context: message.transaction.clone(),
+                nonce_responses: nonce_responses.clone(),
+                nonce_index: *nonce_index,
+            })
+            .collect();
+        let first = batch.remove(0);
         let signature_share_request = SignatureShareRequest {
             dkg_id: self.current_dkg_id,
             sign_id: self.current_sign_id,
             correlation_id: 0,
-            nonce_responses: nonce_responses.to_vec(),
-            message: msg.to_vec(),
+            nonce_responses: first.nonce_responses,
+            message: first.message,
+            signature_type,
+            expires_at: self.request_expiry(),
+            context: first.context,
+            nonce_index: first.nonce_index,
+            batch,
         };
 
         info!(
-            "Sending SignShareRequest dkg_id #{} sign_id #{} to signers",
-            signature_share_request.dkg_id, signature_share_request.sign_id
+            "Sending SignShareRequest dkg_id #{} sign_id #{} for {} messages to signers",
+            signature_share_request.dkg_id,
+            signature_share_request.sign_id,
+            messages.len()
         );
 
         let signature_share_request_message = Message {
-            sig: signature_share_request
-                .sign(&self.network_private_key)
+            sig: self
+                .network_private_key
+                .sign(&signature_share_request)
                 .expect("Failed to sign SignShareRequest"),
             msg: MessageTypes::SignShareRequest(signature_share_request),
         };
@@ -283,7 +853,10 @@ where
         let mut signers: HashSet<u32> = HashSet::from_iter(self.public_nonces.keys().cloned());
         while !signers.is_empty() {
             match self.wait_for_next_message()?.msg {
-                MessageTypes::SignShareResponse(response) => {
+                // Responses of an earlier attempt may still arrive after a retry
+                MessageTypes::SignShareResponse(response)
+                    if response.sign_id == self.current_sign_id =>
+                {
                     if let Some(_party_id) = signers.take(&response.signer_id) {
                         info!(
                             "Insert signature shares for signer_id {}",
@@ -306,24 +879,131 @@ where
         Ok(())
     }
 
-    #[allow(non_snake_case)]
     pub fn sign_message(&mut self, msg: &[u8]) -> Result<(Signature, SchnorrProof), Error> {
-        debug!("Attempting to Sign Message");
+        self.sign_message_with_context(msg, SignatureType::Frost, None, None)
+    }
+
+    /// Sign all messages in a single round trip of nonce and signature share requests
+    pub fn sign_messages(
+        &mut self,
+        msgs: &[Vec<u8>],
+    ) -> Result<Vec<(Signature, SchnorrProof)>, Error> {
+        let messages: Vec<MessageToSign> = msgs
+            .iter()
+            .map(|msg| MessageToSign {
+                message: msg.clone(),
+                ..Default::default()
+            })
+            .collect();
+        self.sign_batch(&messages, SignatureType::Frost)
+    }
+
+    /// The public key a signature of the given type verifies against
+    pub fn signing_public_key(&self, signature_type: SignatureType) -> Point {
+        match signature_type {
+            SignatureType::Frost => self.aggregate_public_key,
+            SignatureType::Taproot(merkle_root) => {
+                compute::tweaked_public_key(&self.aggregate_public_key, merkle_root)
+            }
+        }
+    }
+
+    /// Sign the message with the key of the given signature type, recording the peg operation it was signed for in the journal.
+    /// If the message is the sighash of a transaction input, the transaction is sent along so that signers can validate it.
+    pub fn sign_message_with_context(
+        &mut self,
+        msg: &[u8],
+        signature_type: SignatureType,
+        context: Option<PegContext>,
+        transaction: Option<TransactionContext>,
+    ) -> Result<(Signature, SchnorrProof), Error> {
+        let message = MessageToSign {
+            message: msg.to_vec(),
+            context,
+            transaction,
+        };
+        let mut signatures = self.sign_batch(&[message], signature_type)?;
+        Ok(signatures.remove(0))
+    }
+
+    /// Sign the messages with the key of the given signature type in one signing round, returning
+    /// their signatures in order. If signature shares fail to verify, the round is retried without
+    /// their signers for as long as the remaining signers hold the threshold of keys.
+    pub fn sign_batch(
+        &mut self,
+        messages: &[MessageToSign],
+        signature_type: SignatureType,
+    ) -> Result<Vec<(Signature, SchnorrProof)>, Error> {
+        debug!("Attempting to Sign {} Messages", messages.len());
         if self.aggregate_public_key == Point::default() {
             return Err(Error::NoAggregatePublicKey);
         }
+        if messages.is_empty() {
+            return Ok(vec![]);
+        }
+        self.bad_signers.clear();
+        let signed = loop {
+            let bad_signers: BTreeSet<u32> = match self.sign_round(messages, signature_type) {
+                // When the aggregate signature fails to verify, the aggregator checks each share
+                // against the public key share and nonce of its party
+                Err(Error::Aggregator(AggregatorError::BadPartySigs(party_ids))) => party_ids
+                    .iter()
+                    .filter_map(|party_id| self.party_signer(*party_id))
+                    .collect(),
+                Err(Error::BadSignatureShares(signer_ids)) => signer_ids.into_iter().collect(),
+                // Signers which lost their pools never answer for its nonces, so start over
+                Err(Error::Timeout) => {
+                    self.nonce_pools.clear();
+                    return Err(Error::Timeout);
+                }
+                result => break result?,
+            };
+            warn!(
+                "Sign round #{}: signature shares from signers {:?} failed to verify",
+                self.current_sign_id, bad_signers
+            );
+            self.bad_signers.extend(bad_signers.iter());
+            let signing_keys = self.signing_keys();
+            if bad_signers.is_empty() || signing_keys < self.threshold {
+                return Err(Error::BadSignatureShares(
+                    self.bad_signers.iter().copied().collect(),
+                ));
+            }
+            info!(
+                "Retrying without signers {:?}, {} keys remain of threshold {}",
+                self.bad_signers, signing_keys, self.threshold
+            );
+        };
 
-        //Continually compute a new aggregate nonce until we have a valid even R
-        loop {
-            let R = self.compute_aggregate_nonce(msg)?;
-            if R.has_even_y() {
-                debug!("Success: R has even y coord: {}", &R);
-                break;
-            } else {
-                warn!("Failure: R does not have even y coord: {}", R);
+        // Never hand out a signature which could not be recorded
+        if let Some(journal) = &mut self.journal {
+            for (message, ((_, proof), signer_ids)) in messages.iter().zip(&signed) {
+                let entry = journal.append(
+                    &message.message,
+                    message.context.clone(),
+                    signer_ids.clone(),
+                    Some(&proof.to_bytes()),
+                )?;
+                debug!("Signature recorded in journal entry #{}", entry.sequence);
             }
         }
 
+        Ok(signed.into_iter().map(|(signature, _)| signature).collect())
+    }
+
+    /// Run a signing round with a new sign id among the signers not excluded or found bad,
+    /// returning the signature of each message with the ids of the signers whose nonces and
+    /// signature shares it was made from
+    fn sign_round(
+        &mut self,
+        messages: &[MessageToSign],
+        signature_type: SignatureType,
+    ) -> Result<Vec<((Signature, SchnorrProof), Vec<u32>)>, Error> {
+        self.current_sign_id = next_id(self.current_sign_id);
+        self.current_sign_nonce_id = 0;
+
+        let message_nonces = self.collect_message_nonces(messages)?;
+
         // make an array of dkg public share polys for SignatureAggregator
         debug!(
             "collecting commitments from 1..{} in {:?}",
@@ -337,51 +1017,88 @@ where
             .collect();
 
         debug!(
-            "SignatureAggregator::new total_keys: {} threshold: {} commitments: {}",
+            "SignatureAggregator::new {:?} total_keys: {} threshold: {} commitments: {}",
+            self.wsts_version,
             self.total_keys,
             self.threshold,
             polys.len()
         );
 
-        let mut aggregator = v1::SignatureAggregator::new(self.total_keys, self.threshold, polys)?;
-
-        let nonce_responses: Vec<NonceResponse> = self.public_nonces.values().cloned().collect();
+        let mut aggregator =
+            WstsAggregator::new(self.wsts_version, self.total_keys, self.threshold, polys)?;
 
         // request signature shares
-        self.request_signature_shares(&nonce_responses, msg)?;
+        self.request_signature_shares(messages, &message_nonces, signature_type)?;
         self.collect_signature_shares()?;
 
-        let nonces = nonce_responses
-            .iter()
-            .flat_map(|nr| nr.nonces.clone())
-            .collect::<Vec<PublicNonce>>();
-        let shares = &self
+        // Each signer sends one share per nonce for each message
+        let short_signers: Vec<u32> = self
             .public_nonces
-            .iter()
-            .flat_map(|(i, _)| self.signature_shares[i].clone())
-            .collect::<Vec<SignatureShare>>();
-
-        info!(
-            "aggregator.sign({:?}, {:?}, {:?})",
-            msg,
-            nonces.len(),
-            shares.len()
-        );
+            .values()
+            .filter(|nr| {
+                self.signature_shares[&nr.signer_id].len()
+                    != nr.nonces_per_message() * messages.len()
+            })
+            .map(|nr| nr.signer_id)
+            .collect();
+        if !short_signers.is_empty() {
+            return Err(Error::BadSignatureShares(short_signers));
+        }
 
-        let sig = aggregator.sign(msg, &nonces, shares)?;
+        let mut signatures = vec![];
+        for (message_index, (message, (nonce_responses, nonce_index))) in
+            messages.iter().zip(&message_nonces).enumerate()
+        {
+            let msg = message.message.as_slice();
+            let nonces = nonce_responses
+                .iter()
+                .flat_map(|nr| nr.message_nonces(*nonce_index).to_vec())
+                .collect::<Vec<PublicNonce>>();
+            let shares = nonce_responses
+                .iter()
+                .flat_map(|nr| {
+                    let start = nr.nonces_per_message() * message_index;
+                    self.signature_shares[&nr.signer_id][start..start + nr.nonces_per_message()]
+                        .to_vec()
+                })
+                .collect::<Vec<SignatureShare>>();
+            let key_ids = nonce_responses
+                .iter()
+                .flat_map(|nr| nr.key_ids.clone())
+                .collect::<Vec<u32>>();
+            let signer_ids = nonce_responses
+                .iter()
+                .map(|nr| nr.signer_id)
+                .collect::<Vec<u32>>();
+
+            info!(
+                "aggregator.sign({:?}, {:?}, {:?})",
+                msg,
+                nonces.len(),
+                shares.len()
+            );
+
+            let sig = match signature_type {
+                SignatureType::Frost => aggregator.sign(msg, &nonces, &shares, &key_ids)?,
+                SignatureType::Taproot(merkle_root) => {
+                    aggregator.sign_taproot(msg, &nonces, &shares, &key_ids, merkle_root)?
+                }
+            };
 
-        info!("Signature ({}, {})", sig.R, sig.z);
+            info!("Signature ({}, {})", sig.R, sig.z);
 
-        let proof = SchnorrProof::new(&sig).map_err(Error::Taproot)?;
+            let proof = SchnorrProof::new(&sig).map_err(Error::Taproot)?;
 
-        info!("SchnorrProof ({}, {})", proof.r, proof.s);
+            info!("SchnorrProof ({}, {})", proof.r, proof.s);
 
-        if !proof.verify(&self.aggregate_public_key.x(), msg) {
-            warn!("SchnorrProof failed to verify!");
-            return Err(Error::SchnorrProofFailed);
+            if !proof.verify(&self.signing_public_key(signature_type).x(), msg) {
+                warn!("SchnorrProof failed to verify!");
+                return Err(Error::SchnorrProofFailed);
+            }
+            signatures.push(((sig, proof), signer_ids));
         }
 
-        Ok((sig, proof))
+        Ok(signatures)
     }
 
     fn calculate_aggregate_public_key(&mut self) -> Result<Point, Error> {
@@ -392,8 +1109,13 @@ where
         Ok(self.aggregate_public_key)
     }
 
-    fn wait_for_public_shares(&mut self) -> Result<Point, Error> {
-        let mut ids_to_await: HashSet<u32> = (1..=self.total_signers).collect();
+    /// Wait for the commitments of all included signers and compute the aggregate public key from
+    /// them, returning the misbehavior of signers whose commitments were rejected instead
+    fn wait_for_public_shares(&mut self) -> Result<BTreeMap<u32, Vec<Misbehavior>>, Error> {
+        let mut ids_to_await = self.included_signers();
+        let excluded_party_ids = self.excluded_party_ids();
+        let mut bad_commitments: BTreeMap<u32, BadCommitment> = BTreeMap::new();
+        let mut failure = None;
 
         info!(
             "DKG Round #{}: waiting for Dkg Public Shares from signers {:?}",
@@ -401,17 +1123,29 @@ where
         );
 
         loop {
+            if ids_to_await.is_empty() && !bad_commitments.is_empty() {
+                let misbehavior = self.blame_bad_commitments(&bad_commitments);
+                if misbehavior.is_empty() {
+                    return Err(Error::InvalidSignerMessage);
+                }
+                return Ok(misbehavior);
+            }
             if ids_to_await.is_empty() {
+                if let Some((signer_id, reason)) = failure.take() {
+                    return Err(Error::DkgFailed(signer_id, reason));
+                }
                 let key = self.calculate_aggregate_public_key()?;
                 // check to see if aggregate public key has even y
                 if key.has_even_y() {
                     debug!("Aggregate public key has even y coord!");
                     info!("Aggregate public key: {}", key);
                     self.aggregate_public_key = key;
-                    return Ok(key);
+                    return Ok(BTreeMap::new());
                 } else {
                     warn!("DKG Round #{} Failed: Aggregate public key does not have even y coord, re-running dkg.", self.current_dkg_id);
-                    ids_to_await = (1..=self.total_signers).collect();
+                    ids_to_await = self.included_signers();
+                    // Signers ignore a repeated DkgBegin, so the new attempt needs a new round id
+                    self.current_dkg_id = next_id(self.current_dkg_id);
                     self.start_public_shares()?;
                 }
             }
@@ -424,7 +1158,43 @@ where
                         dkg_end_msg.dkg_id, dkg_end_msg.signer_id, ids_to_await
                     );
                 }
+                MessageTypes::DkgPublicShare(dkg_public_share)
+                    if excluded_party_ids.contains(&dkg_public_share.party_id) =>
+                {
+                    debug!(
+                        "Ignoring DkgPublicShare from excluded party #{}",
+                        dkg_public_share.party_id
+                    );
+                }
+                // Signers end the round early if they rejected a commitment
+                MessageTypes::DkgEnd(dkg_end_msg) if dkg_end_msg.dkg_id == self.current_dkg_id => {
+                    if !ids_to_await.remove(&dkg_end_msg.signer_id) {
+                        continue;
+                    }
+                    warn!(
+                        "DKG Round #{}: signer #{} failed: {:?}",
+                        self.current_dkg_id, dkg_end_msg.signer_id, dkg_end_msg.status
+                    );
+                    if let DkgStatus::Failure(reason) = dkg_end_msg.status {
+                        failure = Some((dkg_end_msg.signer_id, format!("{:?}", reason)));
+                    }
+                }
                 MessageTypes::DkgPublicShare(dkg_public_share) => {
+                    let party_id = dkg_public_share.party_id;
+                    if let Err(bad_commitment) =
+                        check_commitment(party_id, self.threshold, &dkg_public_share.public_share)
+                    {
+                        warn!(
+                            "DKG round #{}: rejecting DkgPublicShare from party #{}: {}",
+                            self.current_dkg_id, party_id, bad_commitment
+                        );
+                        self.dkg_public_shares.remove(&party_id);
+                        bad_commitments.insert(party_id, bad_commitment);
+                        continue;
+                    }
+                    if bad_commitments.contains_key(&party_id) {
+                        continue;
+                    }
                     self.dkg_public_shares
                         .insert(dkg_public_share.party_id, dkg_public_share.clone());
 
@@ -438,22 +1208,211 @@ where
         }
     }
 
-    fn wait_for_dkg_end(&mut self) -> Result<(), Error> {
-        let mut ids_to_await: HashSet<u32> = (1..=self.total_signers).collect();
+    /// Wait for all included signers to end the round, returning the misbehavior of the signers
+    /// blamed by their complaints
+    fn wait_for_dkg_end(&mut self) -> Result<BTreeMap<u32, Vec<Misbehavior>>, Error> {
+        let mut ids_to_await = self.included_signers();
+        let mut misbehavior: BTreeMap<u32, Vec<Misbehavior>> = BTreeMap::new();
+        let mut failure = None;
         info!(
             "DKG Round #{}: waiting for Dkg End from signers {:?}",
             self.current_dkg_id, ids_to_await
         );
         while !ids_to_await.is_empty() {
-            if let MessageTypes::DkgEnd(dkg_end_msg) = self.wait_for_next_message()?.msg {
-                ids_to_await.remove(&dkg_end_msg.signer_id);
-                debug!(
-                    "DKG_End round #{} from signer #{}. Waiting on {:?}",
-                    dkg_end_msg.dkg_id, dkg_end_msg.signer_id, ids_to_await
-                );
+            match self.wait_for_next_message()?.msg {
+                MessageTypes::DkgPrivateShares(dkg_private_shares)
+                    if dkg_private_shares.dkg_id == self.current_dkg_id =>
+                {
+                    self.dkg_private_shares
+                        .insert(dkg_private_shares.key_id, dkg_private_shares);
+                }
+                MessageTypes::DkgEnd(dkg_end_msg) if dkg_end_msg.dkg_id == self.current_dkg_id => {
+                    if !ids_to_await.remove(&dkg_end_msg.signer_id) {
+                        continue;
+                    }
+                    debug!(
+                        "DKG_End round #{} from signer #{}. Waiting on {:?}",
+                        dkg_end_msg.dkg_id, dkg_end_msg.signer_id, ids_to_await
+                    );
+                    match dkg_end_msg.status {
+                        DkgStatus::Success => {}
+                        DkgStatus::Failure(DkgFailure::BadPrivateShares(complaints)) => {
+                            let mut judged = false;
+                            for complaint in &complaints {
+                                if let Some((signer_id, blame)) =
+                                    self.judge_complaint(dkg_end_msg.signer_id, complaint)
+                                {
+                                    misbehavior.entry(signer_id).or_default().push(blame);
+                                    judged = true;
+                                }
+                            }
+                            if !judged {
+                                failure = Some((
+                                    dkg_end_msg.signer_id,
+                                    format!("{} unjudgeable complaints", complaints.len()),
+                                ));
+                            }
+                        }
+                        DkgStatus::Failure(reason) => {
+                            warn!(
+                                "DKG Round #{}: signer #{} failed: {:?}",
+                                self.current_dkg_id, dkg_end_msg.signer_id, reason
+                            );
+                            failure = Some((dkg_end_msg.signer_id, format!("{:?}", reason)));
+                        }
+                    }
+                }
+                _ => {}
             }
         }
-        Ok(())
+        match failure {
+            Some((signer_id, reason)) if misbehavior.is_empty() => {
+                Err(Error::DkgFailed(signer_id, reason))
+            }
+            _ => Ok(misbehavior),
+        }
+    }
+
+    /// Wait for all included signers to end the resharing round, checking the commitment of each
+    /// dealer against its part of the secret. Returns the commitments of the dealers keyed by
+    /// their party id counted from 1.
+    fn wait_for_reshare_end(
+        &mut self,
+        threshold: u32,
+        dealer_public_keys: &BTreeMap<u32, (u32, Point)>,
+    ) -> Result<BTreeMap<u32, DkgPublicShare>, Error> {
+        let mut dkg_public_shares = BTreeMap::new();
+        let mut ids_to_await = self.included_signers();
+        let mut failure = None;
+        while !ids_to_await.is_empty() {
+            match self.wait_for_next_message()?.msg {
+                MessageTypes::ReshareShares(reshare_shares)
+                    if reshare_shares.dkg_id == self.current_dkg_id =>
+                {
+                    let Some((party_id, public_key)) =
+                        dealer_public_keys.get(&reshare_shares.signer_id)
+                    else {
+                        continue;
+                    };
+                    if check_commitment(*party_id, threshold, &reshare_shares.commitment).is_err()
+                        || reshare_shares.commitment.A[0] != *public_key
+                    {
+                        return Err(Error::BadDealing(reshare_shares.signer_id));
+                    }
+                    dkg_public_shares.insert(
+                        *party_id,
+                        DkgPublicShare {
+                            dkg_id: self.current_dkg_id,
+                            dkg_public_id: self.current_dkg_public_id,
+                            party_id: *party_id,
+                            public_share: reshare_shares.commitment,
+                        },
+                    );
+                }
+                MessageTypes::ReshareEnd(dkg_end) if dkg_end.dkg_id == self.current_dkg_id => {
+                    if !ids_to_await.remove(&dkg_end.signer_id) {
+                        continue;
+                    }
+                    debug!(
+                        "Reshare_End round #{} from signer #{}. Waiting on {:?}",
+                        dkg_end.dkg_id, dkg_end.signer_id, ids_to_await
+                    );
+                    if let DkgStatus::Failure(reason) = dkg_end.status {
+                        warn!(
+                            "Resharing round #{}: signer #{} failed: {:?}",
+                            self.current_dkg_id, dkg_end.signer_id, reason
+                        );
+                        failure = Some((dkg_end.signer_id, format!("{:?}", reason)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some((signer_id, reason)) = failure {
+            return Err(Error::DkgFailed(signer_id, reason));
+        }
+        Ok(dkg_public_shares)
+    }
+
+    /// Blame the signers of the parties, counted from 1, whose commitments were rejected
+    fn blame_bad_commitments(
+        &self,
+        bad_commitments: &BTreeMap<u32, BadCommitment>,
+    ) -> BTreeMap<u32, Vec<Misbehavior>> {
+        let mut misbehavior: BTreeMap<u32, Vec<Misbehavior>> = BTreeMap::new();
+        for (party_id, reason) in bad_commitments {
+            match self.party_signer(party_id.wrapping_sub(1)) {
+                Some(signer_id) => {
+                    misbehavior
+                        .entry(signer_id)
+                        .or_default()
+                        .push(Misbehavior::BadCommitment {
+                            dkg_id: self.current_dkg_id,
+                            party_id: *party_id,
+                            reason: *reason,
+                        })
+                }
+                None => warn!("Unable to blame unknown party #{}", party_id),
+            }
+        }
+        misbehavior
+    }
+
+    /// Check a complaint against the broadcast commitment and encrypted share of the accused
+    /// party, returning the signer to blame. Complaints which can not be judged blame nobody.
+    fn judge_complaint(
+        &self,
+        complainer: u32,
+        complaint: &DkgComplaint,
+    ) -> Option<(u32, Misbehavior)> {
+        let false_complaint = Misbehavior::FalseComplaint {
+            dkg_id: self.current_dkg_id,
+            key_id: complaint.dst_key_id,
+            src_key_id: complaint.src_key_id,
+        };
+        // Signers may only complain about shares sent to their own key ids
+        if self.key_id_signer(complaint.dst_key_id) != Some(complainer) {
+            return Some((complainer, false_complaint));
+        }
+        let Some(accused) = self.party_signer(complaint.src_key_id) else {
+            return Some((complainer, false_complaint));
+        };
+        let complainer_key = public_key_point(&self.public_keys.signers[&complainer])?;
+        let Some(commitment) = self.dkg_public_shares.get(&(complaint.src_key_id + 1)) else {
+            warn!(
+                "Unable to judge complaint of signer #{}: missing commitment of party #{}",
+                complainer, complaint.src_key_id
+            );
+            return None;
+        };
+        let Some(private_shares) = self.dkg_private_shares.get(&complaint.src_key_id) else {
+            warn!(
+                "Unable to judge complaint of signer #{}: missing private shares of party #{}",
+                complainer, complaint.src_key_id
+            );
+            return None;
+        };
+        let encrypted_share = private_shares
+            .private_shares
+            .get(&complaint.dst_key_id)
+            .map(Vec::as_slice);
+        match complaint.judge(
+            &complainer_key,
+            Some(&private_shares.ephemeral_key),
+            encrypted_share,
+            &commitment.public_share,
+        ) {
+            Blame::Accused => Some((
+                accused,
+                Misbehavior::BadPrivateShare {
+                    dkg_id: self.current_dkg_id,
+                    key_id: complaint.src_key_id,
+                    dst_key_id: complaint.dst_key_id,
+                    reason: complaint.reason,
+                },
+            )),
+            Blame::Complainer => Some((complainer, false_complaint)),
+        }
     }
 
     fn wait_for_next_message(&mut self) -> Result<Message, Error> {
@@ -489,8 +1448,10 @@ mod test {
 
     use frost_signer::{
         config::{Config, PublicKeys, SignerKeyIds},
+        dkg_state,
         net::{HttpNet, HttpNetListen},
         signer::Signer,
+        signing_round::SigningRound,
     };
 
     use hashbrown::HashMap;
@@ -498,7 +1459,12 @@ mod test {
     use rand::rngs::StdRng;
     use rand_core::{OsRng, RngCore, SeedableRng};
     use relay_server::Server as RelayServer;
-    use std::{env, thread};
+    use std::{
+        cell::{Cell, RefCell},
+        collections::VecDeque,
+        env, thread,
+        time::{Duration, Instant},
+    };
     use test_utils::parse_env;
 
     fn create_signer_key_ids(signer_id: u32, keys_per_signer: u32) -> Vec<u32> {
@@ -567,6 +1533,36 @@ mod test {
         schnorr_proof.verify(&public_key.x(), &msg);
     }
 
+    #[test]
+    fn integration_test_frost_coordinator_should_provide_valid_taproot_signatures() {
+        let msg = vec![1, 3, 3, 7];
+        let merkle_root = Some([7; 32]);
+        let relay_url = "http://127.0.0.1:9779".to_string();
+        let (coordinator_config, coordinator_net_listen) =
+            spawn_processes_and_get_config(relay_url);
+
+        let mut coordinator = Coordinator::new(
+            DEVNET_COORDINATOR_ID,
+            &coordinator_config,
+            coordinator_net_listen,
+        )
+        .unwrap();
+
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+        let tweaked_public_key = compute::tweaked_public_key(&public_key, merkle_root);
+        assert_eq!(
+            coordinator.signing_public_key(SignatureType::Taproot(merkle_root)),
+            tweaked_public_key
+        );
+
+        let (_, schnorr_proof) = coordinator
+            .sign_message_with_context(&msg, SignatureType::Taproot(merkle_root), None, None)
+            .unwrap();
+
+        assert!(schnorr_proof.verify(&tweaked_public_key.x(), &msg));
+        assert!(!schnorr_proof.verify(&public_key.x(), &msg));
+    }
+
     #[test]
     fn integration_test_frost_coordinator_should_provide_valid_signatures_after_restart() {
         let msg = vec![1, 3, 3, 7];
@@ -599,6 +1595,508 @@ mod test {
         schnorr_proof.verify(&public_key.x(), &msg);
     }
 
+    /// Relays messages between the coordinator and in-process signing rounds. Signer messages
+    /// pass through `tamper` before anyone else sees them, like a malicious signer would send them.
+    struct LocalNet {
+        rounds: RefCell<Vec<SigningRound>>,
+        inbox: RefCell<VecDeque<Message>>,
+        tamper: fn(&mut MessageTypes),
+        /// Number of messages the coordinator sent
+        requests: Cell<usize>,
+        /// Time a message takes to reach the signers and their responses to come back
+        latency: Duration,
+    }
+
+    impl LocalNet {
+        fn new(
+            config: &Config,
+            signer_private_keys: &[Scalar],
+            tamper: fn(&mut MessageTypes),
+        ) -> Self {
+            let rounds = signer_private_keys
+                .iter()
+                .enumerate()
+                .map(|(i, private_key)| {
+                    let signer_id = i as u32 + 1;
+                    let key_ids = config.public_keys.signer_key_ids(signer_id);
+                    SigningRound::new(
+                        config.keys_threshold,
+                        config.total_signers,
+                        config.total_keys,
+                        signer_id,
+                        key_ids.iter().map(|key_id| key_id - 1).collect(),
+                        *private_key,
+                        config.public_keys.clone(),
+                    )
+                    .with_wsts_version(config.wsts_version)
+                })
+                .collect();
+            Self {
+                rounds: RefCell::new(rounds),
+                inbox: RefCell::new(VecDeque::new()),
+                tamper,
+                requests: Cell::new(0),
+                latency: Duration::ZERO,
+            }
+        }
+    }
+
+    impl NetListen for LocalNet {
+        type Error = HttpNetError;
+
+        fn listen(&self) {}
+
+        fn poll(&mut self, _id: u32) {}
+
+        fn next_message(&mut self) -> Option<Message> {
+            self.inbox.borrow_mut().pop_front()
+        }
+
+        fn send_message(&self, msg: Message) -> Result<(), Self::Error> {
+            self.requests.set(self.requests.get() + 1);
+            thread::sleep(self.latency);
+            let mut pending = VecDeque::from([msg.msg]);
+            while let Some(msg) = pending.pop_front() {
+                for round in self.rounds.borrow_mut().iter_mut() {
+                    for mut out in round.process(msg.clone()).unwrap_or_default() {
+                        (self.tamper)(&mut out);
+                        pending.push_back(out);
+                    }
+                }
+                self.inbox
+                    .borrow_mut()
+                    .push_back(Message { msg, sig: vec![] });
+            }
+            Ok(())
+        }
+    }
+
+    /// Three signers with two key ids each and a threshold of four keys, so that one can be excluded
+    fn local_coordinator(tamper: fn(&mut MessageTypes)) -> Coordinator<LocalNet> {
+        local_coordinator_with_version(WstsVersion::V1, tamper)
+    }
+
+    fn local_coordinator_with_version(
+        wsts_version: WstsVersion,
+        tamper: fn(&mut MessageTypes),
+    ) -> Coordinator<LocalNet> {
+        let mut rng = OsRng;
+        let coordinator_private_key = Scalar::random(&mut rng);
+        let signer_private_keys: Vec<Scalar> = (0..3).map(|_| Scalar::random(&mut rng)).collect();
+        let mut config = Config::new(
+            4,
+            ecdsa::PublicKey::new(&coordinator_private_key).unwrap(),
+            create_public_keys(&signer_private_keys, 2),
+            (0..3)
+                .map(|i| (i + 1, create_signer_key_ids(i, 2)))
+                .collect::<SignerKeyIds>(),
+            coordinator_private_key.into(),
+            "http://127.0.0.1:1".to_string(),
+        );
+        config.wsts_version = wsts_version;
+        let net = LocalNet::new(&config, &signer_private_keys, tamper);
+        Coordinator::new(DEVNET_COORDINATOR_ID, &config, net).unwrap()
+    }
+
+    #[test]
+    fn honest_signers_complete_dkg_and_sign() {
+        let mut coordinator = local_coordinator(|_| {});
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+        let report = coordinator.get_dkg_report().unwrap();
+        assert!(report.excluded_signers.is_empty());
+        assert!(report.misbehavior.is_empty());
+
+        let msg = vec![1, 3, 3, 7];
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+    }
+
+    #[test]
+    fn rogue_commitments_are_rejected_and_their_signer_excluded() {
+        // Signer 3 shifts the key of its first key id after proving knowledge of the original
+        let mut coordinator = local_coordinator(|msg| {
+            if let MessageTypes::DkgPublicShare(share) = msg {
+                if share.party_id == 5 {
+                    share.public_share.A[0] =
+                        share.public_share.A[0] + Point::from(Scalar::from(1u32));
+                }
+            }
+        });
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+
+        let report = coordinator.get_dkg_report().unwrap();
+        assert_eq!(report.aggregate_public_key, public_key);
+        assert_eq!(report.excluded_signers, BTreeSet::from([3]));
+        assert_eq!(
+            report.misbehavior[&3],
+            vec![Misbehavior::BadCommitment {
+                dkg_id: report.dkg_ids[0],
+                party_id: 5,
+                reason: BadCommitment::InvalidProof,
+            }]
+        );
+        // Only commitments which passed the checks make up the aggregate public key
+        let aggregate =
+            coordinator
+                .get_dkg_public_shares()
+                .values()
+                .fold(Point::default(), |sum, share| {
+                    assert!(check_commitment(share.party_id, 4, &share.public_share).is_ok());
+                    sum + share.public_share.A[0]
+                });
+        assert_eq!(aggregate, public_key);
+
+        // The remaining signers still hold the threshold of keys
+        let msg = vec![1, 3, 3, 7];
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+    }
+
+    #[test]
+    fn dkg_fails_without_enough_honest_keys() {
+        // Both signers 2 and 3 send commitments of the wrong degree
+        let mut coordinator = local_coordinator(|msg| {
+            if let MessageTypes::DkgPublicShare(share) = msg {
+                if share.party_id > 2 {
+                    share.public_share.A.pop();
+                }
+            }
+        });
+        assert!(matches!(
+            coordinator.run_distributed_key_generation(),
+            Err(Error::NotEnoughKeys(2, 4))
+        ));
+    }
+
+    #[test]
+    fn bad_signature_shares_are_attributed_and_signing_retried() {
+        // Signer 3 corrupts its signature shares
+        let mut coordinator = local_coordinator(|msg| {
+            if let MessageTypes::SignShareResponse(response) = msg {
+                if response.signer_id == 3 {
+                    response.signature_shares[0].z_i =
+                        response.signature_shares[0].z_i + Scalar::from(1u32);
+                }
+            }
+        });
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+
+        let msg = vec![1, 3, 3, 7];
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+        assert_eq!(coordinator.get_bad_signers(), &BTreeSet::from([3]));
+
+        // Each message gets a fresh chance
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+        assert_eq!(coordinator.get_bad_signers(), &BTreeSet::from([3]));
+    }
+
+    #[test]
+    fn signing_fails_when_bad_signers_leave_too_few_keys() {
+        let mut coordinator = local_coordinator(|msg| {
+            if let MessageTypes::SignShareResponse(response) = msg {
+                if response.signer_id > 1 {
+                    response.signature_shares[0].z_i = Scalar::random(&mut OsRng);
+                }
+            }
+        });
+        coordinator.run_distributed_key_generation().unwrap();
+
+        match coordinator.sign_message(&[1, 3, 3, 7]) {
+            Err(Error::BadSignatureShares(signer_ids)) => assert_eq!(signer_ids, vec![2, 3]),
+            Err(e) => panic!("expected bad signature shares, got {}", e),
+            Ok(_) => panic!("expected bad signature shares, got a signature"),
+        }
+    }
+
+    #[test]
+    fn v2_signers_complete_dkg_and_sign() {
+        let mut coordinator = local_coordinator_with_version(WstsVersion::V2, |_| {});
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+        let report = coordinator.get_dkg_report().unwrap();
+        assert!(report.excluded_signers.is_empty());
+        // Each signer commits to a single polynomial
+        assert_eq!(coordinator.get_dkg_public_shares().len(), 3);
+
+        let msg = vec![1, 3, 3, 7];
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+
+        let merkle_root = Some([7; 32]);
+        let tweaked_public_key = compute::tweaked_public_key(&public_key, merkle_root);
+        let (_, schnorr_proof) = coordinator
+            .sign_message_with_context(&msg, SignatureType::Taproot(merkle_root), None, None)
+            .unwrap();
+        assert!(schnorr_proof.verify(&tweaked_public_key.x(), &msg));
+    }
+
+    #[test]
+    fn v2_rogue_commitments_exclude_their_signer() {
+        // In v2 the party id of a commitment is its signer id
+        let mut coordinator = local_coordinator_with_version(WstsVersion::V2, |msg| {
+            if let MessageTypes::DkgPublicShare(share) = msg {
+                if share.party_id == 3 {
+                    share.public_share.A.pop();
+                }
+            }
+        });
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+
+        let report = coordinator.get_dkg_report().unwrap();
+        assert_eq!(report.excluded_signers, BTreeSet::from([3]));
+        assert!(matches!(
+            report.misbehavior[&3][..],
+            [Misbehavior::BadCommitment { party_id: 3, .. }]
+        ));
+
+        let msg = vec![1, 3, 3, 7];
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+    }
+
+    #[test]
+    fn v2_bad_signature_shares_are_attributed_to_their_signer() {
+        let mut coordinator = local_coordinator_with_version(WstsVersion::V2, |msg| {
+            if let MessageTypes::SignShareResponse(response) = msg {
+                if response.signer_id == 2 {
+                    response.signature_shares[0].z_i =
+                        response.signature_shares[0].z_i + Scalar::from(1u32);
+                }
+            }
+        });
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+
+        let msg = vec![1, 3, 3, 7];
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+        assert_eq!(coordinator.get_bad_signers(), &BTreeSet::from([2]));
+    }
+
+    /// Reshare the key of three signers with two key ids each to signers 1 and 2, leaving signer 3 out
+    fn reshare_without_signer_3(wsts_version: WstsVersion) {
+        let mut coordinator = local_coordinator_with_version(wsts_version, |_| {});
+        let directory = env::temp_dir().join(format!("dkg_state_{}", OsRng.next_u64()));
+        for round in coordinator.network.rounds.borrow_mut().iter_mut() {
+            round.dkg_state_directory = Some(directory.clone());
+        }
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+        let key_epoch = coordinator.current_dkg_id;
+        let old_private_keys = coordinator.network.rounds.borrow()[2]
+            .signer
+            .frost_signer
+            .private_keys();
+
+        let signer_key_ids: SignerKeyIds = [(1, vec![1, 2]), (2, vec![3, 4]), (3, vec![])]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            coordinator.run_resharing(&signer_key_ids, 3).unwrap(),
+            public_key
+        );
+        assert_eq!(coordinator.get_aggregate_public_key().unwrap(), public_key);
+        assert!(!coordinator.signing_signers().contains(&3));
+
+        // The remaining signers sign for the same key with the new threshold
+        let msg = vec![1, 3, 3, 7];
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+
+        // Signer 3 dropped its key shares, and those it held are no shares of the new key ids
+        let rounds = coordinator.network.rounds.borrow();
+        assert!(rounds
+            .iter()
+            .all(|round| round.key_epoch == Some(coordinator.current_dkg_id)));
+        assert!(rounds[2].signer.frost_signer.private_keys().is_empty());
+        // Nor are they left on disk
+        for signer_id in 1..=3 {
+            assert!(!dkg_state::path(&directory, signer_id, key_epoch).exists());
+            assert!(dkg_state::path(&directory, signer_id, coordinator.current_dkg_id).exists());
+        }
+        let commitments: Vec<&PolyCommitment> = coordinator
+            .get_dkg_public_shares()
+            .values()
+            .map(|share| &share.public_share)
+            .collect();
+        for key_id in 0..4 {
+            let public_key = key_public_key(key_id, commitments.iter().copied());
+            assert!(old_private_keys
+                .values()
+                .all(|private_key| Point::from(*private_key) != public_key));
+        }
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn resharing_keeps_the_aggregate_key_and_drops_removed_signers() {
+        reshare_without_signer_3(WstsVersion::V1);
+    }
+
+    #[test]
+    fn v2_resharing_keeps_the_aggregate_key_and_drops_removed_signers() {
+        reshare_without_signer_3(WstsVersion::V2);
+    }
+
+    #[test]
+    fn dealers_of_a_different_secret_are_caught() {
+        let mut coordinator = local_coordinator(|msg| {
+            if let MessageTypes::ReshareShares(reshare_shares) = msg {
+                if reshare_shares.signer_id == 2 {
+                    reshare_shares.commitment.A[0] =
+                        reshare_shares.commitment.A[0] + Point::from(Scalar::from(1u32));
+                }
+            }
+        });
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+        let signer_key_ids: SignerKeyIds = [(1, vec![1, 2, 3]), (2, vec![4, 5, 6])]
+            .into_iter()
+            .collect();
+        assert!(matches!(
+            coordinator.run_resharing(&signer_key_ids, 4),
+            Err(Error::BadDealing(2))
+        ));
+        // The key shares of the last DKG round still sign
+        let msg = vec![1, 3, 3, 7];
+        let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msg));
+    }
+
+    fn messages(count: u8) -> Vec<Vec<u8>> {
+        (0..count).map(|i| vec![1, 3, 3, 7, i]).collect()
+    }
+
+    #[test]
+    fn batches_are_signed_in_one_signing_round() {
+        let mut coordinator = local_coordinator(|_| {});
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+        let msgs = messages(8);
+
+        let requests = coordinator.network.requests.get();
+        let signatures = coordinator.sign_messages(&msgs).unwrap();
+        assert_eq!(signatures.len(), msgs.len());
+        for ((_, schnorr_proof), msg) in signatures.iter().zip(&msgs) {
+            assert!(schnorr_proof.verify(&public_key.x(), msg));
+        }
+        // Nonce requests until every aggregate nonce is even, then a single signature share request
+        let batch_requests = coordinator.network.requests.get() - requests;
+
+        let requests = coordinator.network.requests.get();
+        for msg in &msgs {
+            coordinator.sign_message(msg).unwrap();
+        }
+        // At least one nonce request and one signature share request per message
+        let single_requests = coordinator.network.requests.get() - requests;
+        assert!(single_requests >= 2 * msgs.len());
+        assert!(batch_requests < single_requests);
+    }
+
+    #[test]
+    fn batches_are_retried_without_bad_signers() {
+        // Signer 2 corrupts its signature share for the last message only
+        let mut coordinator = local_coordinator(|msg| {
+            if let MessageTypes::SignShareResponse(response) = msg {
+                if response.signer_id == 2 {
+                    let share = response.signature_shares.last_mut().unwrap();
+                    share.z_i = share.z_i + Scalar::from(1u32);
+                }
+            }
+        });
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+        let journal_path = env::temp_dir().join(format!("journal_{}", OsRng.next_u64()));
+        coordinator.set_journal(Journal::open(&journal_path).unwrap());
+
+        let msgs = messages(3);
+        let signatures = coordinator.sign_messages(&msgs).unwrap();
+        for ((_, schnorr_proof), msg) in signatures.iter().zip(&msgs) {
+            assert!(schnorr_proof.verify(&public_key.x(), msg));
+        }
+        assert_eq!(coordinator.get_bad_signers(), &BTreeSet::from([2]));
+
+        // Each message is journaled with the signers it was signed by
+        let entries = Journal::read(&journal_path).unwrap();
+        assert_eq!(entries.len(), msgs.len());
+        assert!(entries.iter().all(|entry| entry.signer_ids == vec![1, 3]));
+        std::fs::remove_file(journal_path).unwrap();
+    }
+
+    #[test]
+    fn nonce_pools_make_signing_a_single_round_trip() {
+        let mut coordinator = local_coordinator(|_| {});
+        coordinator.set_nonce_pool_size(128);
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+        let msgs = messages(5);
+
+        // The first signature fills the pools
+        let requests = coordinator.network.requests.get();
+        let (_, schnorr_proof) = coordinator.sign_message(&msgs[0]).unwrap();
+        assert!(schnorr_proof.verify(&public_key.x(), &msgs[0]));
+        assert_eq!(coordinator.network.requests.get() - requests, 2);
+
+        for msg in &msgs[1..] {
+            let requests = coordinator.network.requests.get();
+            let (_, schnorr_proof) = coordinator.sign_message(msg).unwrap();
+            assert!(schnorr_proof.verify(&public_key.x(), msg));
+            assert_eq!(coordinator.network.requests.get() - requests, 1);
+        }
+
+        // Batches use pool nonces too
+        let requests = coordinator.network.requests.get();
+        let signatures = coordinator.sign_messages(&msgs).unwrap();
+        for ((_, schnorr_proof), msg) in signatures.iter().zip(&msgs) {
+            assert!(schnorr_proof.verify(&public_key.x(), msg));
+        }
+        assert_eq!(coordinator.network.requests.get() - requests, 1);
+    }
+
+    #[test]
+    fn nonce_pools_survive_bad_signers() {
+        let mut coordinator = local_coordinator(|msg| {
+            if let MessageTypes::SignShareResponse(response) = msg {
+                if response.signer_id == 3 {
+                    response.signature_shares[0].z_i =
+                        response.signature_shares[0].z_i + Scalar::from(1u32);
+                }
+            }
+        });
+        coordinator.set_nonce_pool_size(8);
+        let public_key = coordinator.run_distributed_key_generation().unwrap();
+
+        for msg in messages(3) {
+            let (_, schnorr_proof) = coordinator.sign_message(&msg).unwrap();
+            assert!(schnorr_proof.verify(&public_key.x(), &msg));
+            assert_eq!(coordinator.get_bad_signers(), &BTreeSet::from([3]));
+        }
+    }
+
+    /// Compares signing the inputs of a fulfillment one at a time with signing them in a batch over
+    /// a relay with realistic latency. Run with `cargo test -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_batch_signing() {
+        let inputs = parse_env::<u8>("inputs", 16);
+        let latency = Duration::from_millis(parse_env::<u64>("relay_latency_ms", 100));
+        let mut coordinator = local_coordinator(|_| {});
+        coordinator.run_distributed_key_generation().unwrap();
+        coordinator.network.latency = latency;
+        let msgs = messages(inputs);
+
+        let start = Instant::now();
+        for msg in &msgs {
+            coordinator.sign_message(msg).unwrap();
+        }
+        let single = start.elapsed();
+
+        let start = Instant::now();
+        coordinator.sign_messages(&msgs).unwrap();
+        let batch = start.elapsed();
+
+        println!(
+            "{} inputs with {:?} relay latency: {:?} one at a time, {:?} batched",
+            inputs, latency, single, batch
+        );
+        assert!(batch < single);
+    }
+
     fn spawn_processes_and_get_config(relay_url: String) -> (Config, HttpNetListen) {
         env::set_var("RUST_LOG", "info");
 
@@ -625,7 +2123,7 @@ mod test {
             coordinator_public_key,
             public_keys.clone(),
             signer_key_ids.clone(),
-            coordinator_private_key,
+            coordinator_private_key.into(),
             relay_url.clone(),
         );
         let signer_configs = signer_private_keys
@@ -636,7 +2134,7 @@ mod test {
                     coordinator_public_key,
                     public_keys.clone(),
                     signer_key_ids.clone(),
-                    k.clone(),
+                    (*k).into(),
                     relay_url.clone(),
                 )
             })
@@ -0,0 +1,41 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use frost_signer::complaint::{BadCommitment, ComplaintReason};
+use serde::{Deserialize, Serialize};
+use wsts::Point;
+
+/// Misbehavior a signer was blamed for during DKG
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Misbehavior {
+    /// Broadcast a polynomial commitment, for the party counted from 1, which was rejected
+    BadCommitment {
+        dkg_id: u64,
+        party_id: u32,
+        reason: BadCommitment,
+    },
+    /// Sent a private share to another key id which a complaint proved bad
+    BadPrivateShare {
+        dkg_id: u64,
+        key_id: u32,
+        dst_key_id: u32,
+        reason: ComplaintReason,
+    },
+    /// Complained about a good private share, or without a valid proof
+    FalseComplaint {
+        dkg_id: u64,
+        key_id: u32,
+        src_key_id: u32,
+    },
+}
+
+/// The outcome of a DKG run, including every attempt needed to exclude misbehaving signers
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct DkgReport {
+    /// Round ids of all attempts, the last one produced the aggregate public key
+    pub dkg_ids: Vec<u64>,
+    pub aggregate_public_key: Point,
+    /// Signers which contributed nothing to the aggregate public key and can not sign for it
+    pub excluded_signers: BTreeSet<u32>,
+    /// What each excluded signer was blamed for
+    pub misbehavior: BTreeMap<u32, Vec<Misbehavior>>,
+}
@@ -1,4 +1,5 @@
 pub mod coordinator;
+pub mod dkg_report;
 
 use coordinator::{Coordinator, Error};
 use frost_signer::{
@@ -1,7 +1,7 @@
 use clap::Parser;
 
-use frost_coordinator::{coordinator::Command, create_coordinator_from_path};
-use frost_signer::logging;
+use frost_coordinator::{coordinator::Command, create_coordinator};
+use frost_signer::{config::Config, config_loader::ConfigOverrides, logging};
 use tracing::{error, warn};
 
 #[derive(Parser, Debug)]
@@ -10,6 +10,8 @@ pub struct Cli {
     /// Config file path
     #[arg(short, long)]
     config: String,
+    #[command(flatten)]
+    overrides: ConfigOverrides,
     /// Subcommand action to take
     #[command(subcommand)]
     pub command: Command,
@@ -19,7 +21,17 @@ fn main() {
     logging::initiate_tracing_subscriber();
 
     let cli = Cli::parse();
-    match create_coordinator_from_path(cli.config) {
+    let config = match Config::load(&cli.config, &cli.overrides) {
+        Ok(config) => config,
+        Err(e) => {
+            error!(
+                "An error occurred reading config file {}: {}",
+                cli.config, e
+            );
+            return;
+        }
+    };
+    match create_coordinator(&config) {
         Ok(mut coordinator) => {
             let result = coordinator.run(&cli.command);
             if let Err(e) = result {
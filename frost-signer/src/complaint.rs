@@ -0,0 +1,467 @@
+use p256k1::{
+    ecdsa,
+    point::{Compressed, Point},
+    scalar::Scalar,
+};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wsts::{common::PolyCommitment, schnorr::ID};
+
+use crate::util::{decrypt, make_shared_secret_from_key};
+
+/// Why a signer complains about a DKG private share
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum ComplaintReason {
+    /// No share was sent
+    MissingShare,
+    /// The share could not be decrypted
+    Undecryptable,
+    /// The decrypted share is not a scalar
+    InvalidScalar,
+    /// The share does not match the polynomial commitment of the sender
+    InconsistentShare,
+}
+
+/// Why the polynomial commitment of a key id was rejected
+#[derive(thiserror::Error, Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum BadCommitment {
+    #[error("commitment is for party {0}")]
+    WrongPartyId(u32),
+    #[error("commitment has {0} coefficients instead of the threshold")]
+    WrongDegree(usize),
+    #[error("proof of knowledge of the secret is invalid")]
+    InvalidProof,
+}
+
+/// Who a judged complaint blames
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Blame {
+    /// The sender of the share sent a bad share
+    Accused,
+    /// The complainer complained about a good share, or proved nothing
+    Complainer,
+}
+
+/// A complaint about the private share one key id sent to another. Unless the share is missing, it
+/// reveals the Diffie-Hellman key of the complainer and the ephemeral key the share was encrypted
+/// to, with a proof of its correctness, so that anyone who saw the encrypted share on the relay
+/// can decrypt it and check it against the commitment of the sender. The ephemeral key is new in
+/// every DKG round, so the revealed key opens no other shares.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct DkgComplaint {
+    /// Key id, counted from 0, which sent the share
+    pub src_key_id: u32,
+    /// Key id, counted from 0, of the complainer the share was sent to
+    pub dst_key_id: u32,
+    pub reason: ComplaintReason,
+    /// None for a missing share, which needs no decrypting
+    pub proof: Option<SharedKeyProof>,
+}
+
+impl DkgComplaint {
+    /// Complain about a share which was sent encrypted to the ephemeral key
+    pub fn new<RNG: RngCore + CryptoRng>(
+        src_key_id: u32,
+        dst_key_id: u32,
+        reason: ComplaintReason,
+        private_key: &Scalar,
+        ephemeral_key: &Point,
+        rng: &mut RNG,
+    ) -> Self {
+        Self {
+            src_key_id,
+            dst_key_id,
+            reason,
+            proof: Some(SharedKeyProof::new(private_key, ephemeral_key, rng)),
+        }
+    }
+
+    /// Complain about a share which was not sent
+    pub fn missing_share(src_key_id: u32, dst_key_id: u32) -> Self {
+        Self {
+            src_key_id,
+            dst_key_id,
+            reason: ComplaintReason::MissingShare,
+            proof: None,
+        }
+    }
+
+    pub fn hash(&self, hasher: &mut Sha256) {
+        hasher.update("DKG_COMPLAINT".as_bytes());
+        hasher.update(self.src_key_id.to_be_bytes());
+        hasher.update(self.dst_key_id.to_be_bytes());
+        hasher.update([self.reason as u8]);
+        if let Some(proof) = &self.proof {
+            for point in [
+                &proof.shared_key,
+                &proof.commitment_g,
+                &proof.commitment_key,
+            ] {
+                hasher.update(point.compress().as_bytes());
+            }
+            hasher.update(proof.response.to_bytes());
+        }
+    }
+
+    /// Decide who is to blame given the network public key of the complainer, the ephemeral key
+    /// and the encrypted share the accused sent, if it sent them, and the polynomial commitment it
+    /// broadcast
+    pub fn judge(
+        &self,
+        complainer_public_key: &Point,
+        ephemeral_key: Option<&Point>,
+        encrypted_share: Option<&[u8]>,
+        commitment: &PolyCommitment,
+    ) -> Blame {
+        let (Some(ephemeral_key), Some(encrypted_share)) = (ephemeral_key, encrypted_share) else {
+            return Blame::Accused;
+        };
+        let Some(proof) = &self.proof else {
+            // The share the complainer calls missing was sent
+            return Blame::Complainer;
+        };
+        if !proof.verify(complainer_public_key, ephemeral_key) {
+            return Blame::Complainer;
+        }
+        let shared_secret = make_shared_secret_from_key(&proof.shared_key);
+        let Ok(plain) = decrypt(&shared_secret, encrypted_share) else {
+            return Blame::Accused;
+        };
+        let Ok(share) = Scalar::try_from(&plain[..]) else {
+            return Blame::Accused;
+        };
+        if is_consistent_share(&share, self.dst_key_id, commitment) {
+            Blame::Complainer
+        } else {
+            Blame::Accused
+        }
+    }
+}
+
+/// A Diffie-Hellman shared key with a Chaum-Pedersen proof that its discrete log with respect to
+/// the other signer's public key equals the discrete log of the prover's public key
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SharedKeyProof {
+    /// The prover's private key times the other signer's public key
+    pub shared_key: Point,
+    pub commitment_g: Point,
+    pub commitment_key: Point,
+    pub response: Scalar,
+}
+
+impl SharedKeyProof {
+    pub fn new<RNG: RngCore + CryptoRng>(
+        private_key: &Scalar,
+        other_public_key: &Point,
+        rng: &mut RNG,
+    ) -> Self {
+        let public_key = Point::from(*private_key);
+        let shared_key = private_key * other_public_key;
+        let nonce = Scalar::random(rng);
+        let commitment_g = Point::from(nonce);
+        let commitment_key = &nonce * other_public_key;
+        let challenge = challenge(
+            &public_key,
+            other_public_key,
+            &shared_key,
+            &commitment_g,
+            &commitment_key,
+        );
+        Self {
+            shared_key,
+            commitment_g,
+            commitment_key,
+            response: nonce + challenge * *private_key,
+        }
+    }
+
+    pub fn verify(&self, public_key: &Point, other_public_key: &Point) -> bool {
+        let challenge = challenge(
+            public_key,
+            other_public_key,
+            &self.shared_key,
+            &self.commitment_g,
+            &self.commitment_key,
+        );
+        Point::from(self.response) == self.commitment_g + &challenge * public_key
+            && &self.response * other_public_key
+                == self.commitment_key + &challenge * &self.shared_key
+    }
+}
+
+fn challenge(
+    public_key: &Point,
+    other_public_key: &Point,
+    shared_key: &Point,
+    commitment_g: &Point,
+    commitment_key: &Point,
+) -> Scalar {
+    let mut hasher = Sha256::new();
+    hasher.update("DH_SHARED_KEY_PROOF/".as_bytes());
+    for point in [
+        public_key,
+        other_public_key,
+        shared_key,
+        commitment_g,
+        commitment_key,
+    ] {
+        hasher.update(point.compress().as_bytes());
+    }
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(hasher.finalize().as_slice());
+    Scalar::from(bytes)
+}
+
+/// Check the private share sent to the key id, counted from 0, against the sender's polynomial commitment
+pub fn is_consistent_share(share: &Scalar, key_id: u32, commitment: &PolyCommitment) -> bool {
+    // Shares are evaluations of the sender's polynomial at the key id counted from 1
+    let x = Scalar::from(key_id + 1);
+    let expected = commitment
+        .A
+        .iter()
+        .rev()
+        .fold(Point::default(), |acc, a| &x * &acc + *a);
+    Point::from(*share) == expected
+}
+
+/// Check that the commitment broadcast for the key id, counted from 1, commits to a polynomial of
+/// degree `threshold - 1` and proves knowledge of its secret. Without the proof a party could
+/// choose its commitment after seeing the others' and cancel or bias the aggregate public key.
+pub fn check_commitment(
+    party_id: u32,
+    threshold: u32,
+    commitment: &PolyCommitment,
+) -> Result<(), BadCommitment> {
+    if commitment.id.id != Scalar::from(party_id) {
+        return Err(BadCommitment::WrongPartyId(commitment.id.id.get_u32()));
+    }
+    if commitment.A.len() != threshold as usize {
+        return Err(BadCommitment::WrongDegree(commitment.A.len()));
+    }
+    if !commitment.id.verify(&commitment.A[0]) {
+        return Err(BadCommitment::InvalidProof);
+    }
+    Ok(())
+}
+
+/// The commitment to the zero polynomial of a key id excluded from DKG. Its proof of knowledge is
+/// valid since anyone knows the zero secret, and it adds nothing to the aggregate public key.
+pub fn zero_commitment<RNG: RngCore + CryptoRng>(
+    party_id: u32,
+    threshold: u32,
+    rng: &mut RNG,
+) -> PolyCommitment {
+    PolyCommitment {
+        id: ID::new(&Scalar::from(party_id), &Scalar::new(), rng),
+        A: vec![Point::default(); threshold as usize],
+    }
+}
+
+/// Convert a network public key to a curve point
+pub fn public_key_point(public_key: &ecdsa::PublicKey) -> Option<Point> {
+    Point::try_from(&Compressed::from(public_key.to_bytes())).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::util::{encrypt, make_shared_secret};
+
+    /// A commitment to a random polynomial of degree 2 and the share of key id 3 under it
+    fn commitment_and_share() -> (PolyCommitment, Scalar) {
+        let coefficients: Vec<Scalar> = (0..3).map(|_| Scalar::random(&mut OsRng)).collect();
+        let x = Scalar::from(4u32);
+        let share = coefficients
+            .iter()
+            .rev()
+            .fold(Scalar::new(), |acc, a| acc * x + *a);
+        let commitment = PolyCommitment {
+            id: ID::new(&Scalar::from(1u32), &coefficients[0], &mut OsRng),
+            A: coefficients.into_iter().map(Point::from).collect(),
+        };
+        (commitment, share)
+    }
+
+    #[test]
+    fn shares_are_checked_against_commitments() {
+        let (commitment, share) = commitment_and_share();
+        assert!(is_consistent_share(&share, 3, &commitment));
+        assert!(!is_consistent_share(&share, 2, &commitment));
+        assert!(!is_consistent_share(
+            &(share + Scalar::from(1u32)),
+            3,
+            &commitment
+        ));
+    }
+
+    #[test]
+    fn commitments_are_checked() {
+        let (commitment, _) = commitment_and_share();
+        assert_eq!(check_commitment(1, 3, &commitment), Ok(()));
+        assert_eq!(
+            check_commitment(2, 3, &commitment),
+            Err(BadCommitment::WrongPartyId(1))
+        );
+        assert_eq!(
+            check_commitment(1, 4, &commitment),
+            Err(BadCommitment::WrongDegree(3))
+        );
+        assert_eq!(
+            check_commitment(1, 3, &zero_commitment(1, 3, &mut OsRng)),
+            Ok(())
+        );
+
+        // A rogue key chosen to cancel another party's secret, with a proof copied from that party
+        let mut rogue = commitment.clone();
+        rogue.A[0] = Point::from(Scalar::random(&mut OsRng)) - commitment.A[0];
+        assert_eq!(
+            check_commitment(1, 3, &rogue),
+            Err(BadCommitment::InvalidProof)
+        );
+
+        // A proof of knowledge for a different secret
+        let mut forged = commitment;
+        forged.id = ID::new(&Scalar::from(1u32), &Scalar::random(&mut OsRng), &mut OsRng);
+        assert_eq!(
+            check_commitment(1, 3, &forged),
+            Err(BadCommitment::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn shared_key_proofs_are_verified() {
+        let (x, y) = (Scalar::random(&mut OsRng), Scalar::random(&mut OsRng));
+        let (public_x, public_y) = (Point::from(x), Point::from(y));
+        let proof = SharedKeyProof::new(&x, &public_y, &mut OsRng);
+        assert!(proof.verify(&public_x, &public_y));
+        assert!(!proof.verify(&public_y, &public_x));
+
+        let mut forged = proof.clone();
+        forged.shared_key = Point::from(Scalar::random(&mut OsRng));
+        assert!(!forged.verify(&public_x, &public_y));
+    }
+
+    #[test]
+    fn complaints_blame_the_right_party() {
+        // The accused encrypts its shares of the round to a new ephemeral key
+        let (ephemeral_key, complainer_key) =
+            (Scalar::random(&mut OsRng), Scalar::random(&mut OsRng));
+        let (ephemeral_public, complainer_public) =
+            (Point::from(ephemeral_key), Point::from(complainer_key));
+        let shared_secret = make_shared_secret(&ephemeral_key, &complainer_public);
+        let (commitment, share) = commitment_and_share();
+        let complaint = |reason| {
+            DkgComplaint::new(0, 3, reason, &complainer_key, &ephemeral_public, &mut OsRng)
+        };
+
+        // A good share
+        let good = encrypt(&shared_secret, &share.to_bytes(), &mut OsRng).unwrap();
+        assert_eq!(
+            complaint(ComplaintReason::InconsistentShare).judge(
+                &complainer_public,
+                Some(&ephemeral_public),
+                Some(good.as_slice()),
+                &commitment
+            ),
+            Blame::Complainer
+        );
+
+        // A share which does not match the commitment
+        let bad = encrypt(
+            &shared_secret,
+            &(share + Scalar::from(1u32)).to_bytes(),
+            &mut OsRng,
+        )
+        .unwrap();
+        assert_eq!(
+            complaint(ComplaintReason::InconsistentShare).judge(
+                &complainer_public,
+                Some(&ephemeral_public),
+                Some(bad.as_slice()),
+                &commitment
+            ),
+            Blame::Accused
+        );
+
+        // A share encrypted under some other key
+        let garbage = encrypt(&[7; 32], &share.to_bytes(), &mut OsRng).unwrap();
+        assert_eq!(
+            complaint(ComplaintReason::Undecryptable).judge(
+                &complainer_public,
+                Some(&ephemeral_public),
+                Some(garbage.as_slice()),
+                &commitment
+            ),
+            Blame::Accused
+        );
+
+        // A share too short to hold a nonce
+        assert_eq!(
+            complaint(ComplaintReason::Undecryptable).judge(
+                &complainer_public,
+                Some(&ephemeral_public),
+                Some(&[1; 5][..]),
+                &commitment
+            ),
+            Blame::Accused
+        );
+
+        // No share at all, which is complained about without revealing a key
+        let missing = DkgComplaint::missing_share(0, 3);
+        assert_eq!(missing.proof, None);
+        assert_eq!(
+            missing.judge(&complainer_public, None, None, &commitment),
+            Blame::Accused
+        );
+        assert_eq!(
+            missing.judge(
+                &complainer_public,
+                Some(&ephemeral_public),
+                Some(good.as_slice()),
+                &commitment
+            ),
+            Blame::Complainer
+        );
+
+        // A complaint by someone other than the recipient proves nothing
+        let other_public = Point::from(Scalar::random(&mut OsRng));
+        assert_eq!(
+            complaint(ComplaintReason::Undecryptable).judge(
+                &other_public,
+                Some(&ephemeral_public),
+                Some(garbage.as_slice()),
+                &commitment
+            ),
+            Blame::Complainer
+        );
+    }
+
+    #[test]
+    fn revealed_keys_open_only_their_round() {
+        let complainer_key = Scalar::random(&mut OsRng);
+        let complainer_public = Point::from(complainer_key);
+        let (commitment, share) = commitment_and_share();
+        let round_share = || {
+            let ephemeral_key = Scalar::random(&mut OsRng);
+            let shared_secret = make_shared_secret(&ephemeral_key, &complainer_public);
+            let encrypted = encrypt(&shared_secret, &share.to_bytes(), &mut OsRng).unwrap();
+            (Point::from(ephemeral_key), encrypted)
+        };
+        let (ephemeral_public, _) = round_share();
+        let (_, later_share) = round_share();
+
+        let complaint = DkgComplaint::new(
+            0,
+            3,
+            ComplaintReason::Undecryptable,
+            &complainer_key,
+            &ephemeral_public,
+            &mut OsRng,
+        );
+        let revealed = make_shared_secret_from_key(&complaint.proof.unwrap().shared_key);
+        assert!(decrypt(&revealed, &later_share).is_err());
+    }
+}
@@ -1,14 +1,20 @@
 use clap::Parser;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use p256k1::{
     ecdsa::{self, KeyError},
+    point::Point,
     scalar::{Error as ScalarError, Scalar},
 };
 use serde::Deserialize;
-use std::fs;
+use std::{fmt, path::PathBuf, time::Duration};
 use toml;
+use zeroize::Zeroizing;
 
-use crate::util::parse_public_key;
+use crate::config_loader::{self, ConfigOverrides};
+use crate::signing_round::Signable;
+use crate::state_machine::StateTimeouts;
+use crate::util::{make_shared_secret, parse_public_key};
+use crate::wsts_version::WstsVersion;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -22,8 +28,25 @@ pub enum Error {
     InvalidPrivateKey(ScalarError),
     #[error("Invalid Key ID. Must specify Key IDs greater than 0.")]
     InvalidKeyID,
+    #[error("Key ID {0} is assigned to more than one signer.")]
+    DuplicateKeyID(u32),
+    #[error("Invalid keys_threshold {0}. Must be between 1 and the total number of keys {1}.")]
+    InvalidThreshold(u32, u32),
+    #[error("Signer ID {0} is not one of the configured signers.")]
+    UnknownSignerID(u32),
+    #[error("Signer ID {0} is served more than once.")]
+    DuplicateSignerID(u32),
+    #[error("Config Loader Error: {0}")]
+    LoaderError(#[from] config_loader::Error),
+    #[error("Reading the signer set from the sBTC contract requires sbtc_contract and stacks_node_rpc_url")]
+    MissingContract,
+    #[error("Failed to read the signer set: {0}")]
+    SignerSetError(String),
 }
 
+/// How often the signer set is read from its source by default
+const DEFAULT_SIGNER_SET_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
@@ -35,6 +58,9 @@ pub struct Cli {
     #[arg(short, long)]
     pub config: String,
 
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+
     /// Start a signing round
     #[arg(short, long)]
     pub start: bool,
@@ -50,21 +76,46 @@ struct RawSigners {
     pub key_ids: Vec<u32>,
 }
 
+#[derive(Clone, Deserialize, Default, Debug)]
+struct RawIdentity {
+    pub signer_id: u32,
+    pub network_private_key: Zeroizing<String>,
+}
+
 #[derive(Clone, Deserialize, Default, Debug)]
 struct RawConfig {
     pub http_relay_url: String,
+    // The signer set may be read from the sBTC contract instead
+    #[serde(default)]
     pub keys_threshold: u32,
-    pub network_private_key: String,
+    pub network_private_key: Zeroizing<String>,
+    #[serde(default)]
     signers: Vec<RawSigners>,
+    #[serde(default)]
     coordinator_public_key: String,
+    journal_path: Option<String>,
+    stacks_node_rpc_url: Option<String>,
+    dkg_state_directory: Option<String>,
+    sbtc_contract: Option<String>,
+    replay_state_path: Option<String>,
+    nonce_pool_path: Option<String>,
+    #[serde(default)]
+    wsts_version: WstsVersion,
+    dkg_public_timeout: Option<u64>,
+    dkg_private_timeout: Option<u64>,
+    sign_timeout: Option<u64>,
+    #[serde(default)]
+    identities: Vec<RawIdentity>,
+    status_listen: Option<String>,
+    signer_set_cycle_length: Option<u64>,
+    signer_set_poll_interval: Option<u64>,
 }
 
 pub type SignerKeyIds = HashMap<u32, Vec<u32>>;
 
 impl RawConfig {
     pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<RawConfig, Error> {
-        let content = fs::read_to_string(path)?;
-        Ok(toml::from_str(&content)?)
+        config_loader::deserialize(config_loader::read(path)?)
     }
 
     pub fn public_keys(&self) -> Result<PublicKeys, Error> {
@@ -98,10 +149,46 @@ impl RawConfig {
         parse_public_key(&self.coordinator_public_key).map_err(Error::InvalidPublicKey)
     }
 
-    pub fn network_private_key(&self) -> Result<Scalar, Error> {
+    pub fn signer_set(&self) -> Result<SignerSet, Error> {
+        Ok(SignerSet {
+            keys_threshold: self.keys_threshold,
+            coordinator_public_key: self.coordinator_public_key()?,
+            public_keys: self.public_keys()?,
+            signer_key_ids: self.signer_key_ids(),
+        })
+    }
+
+    pub fn network_private_key(&self) -> Result<PrivateKey, Error> {
         let network_private_key = Scalar::try_from(self.network_private_key.as_str())
             .map_err(Error::InvalidPrivateKey)?;
-        Ok(network_private_key)
+        Ok(network_private_key.into())
+    }
+
+    pub fn identities(&self) -> Result<Vec<SignerIdentity>, Error> {
+        self.identities
+            .iter()
+            .map(|identity| {
+                Ok(SignerIdentity {
+                    signer_id: identity.signer_id,
+                    network_private_key: Scalar::try_from(identity.network_private_key.as_str())
+                        .map_err(Error::InvalidPrivateKey)?
+                        .into(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn state_timeouts(&self) -> StateTimeouts {
+        let default = StateTimeouts::default();
+        StateTimeouts {
+            dkg_public: self
+                .dkg_public_timeout
+                .map_or(default.dkg_public, Duration::from_secs),
+            dkg_private: self
+                .dkg_private_timeout
+                .map_or(default.dkg_private, Duration::from_secs),
+            sign: self.sign_timeout.map_or(default.sign, Duration::from_secs),
+        }
     }
 }
 
@@ -111,16 +198,160 @@ pub struct PublicKeys {
     pub key_ids: HashMap<u32, ecdsa::PublicKey>,
 }
 
+impl PublicKeys {
+    /// The key ids, counted from 1, registered with the network public key of the signer
+    pub fn signer_key_ids(&self, signer_id: u32) -> Vec<u32> {
+        let Some(signer_key) = self.signers.get(&signer_id) else {
+            return vec![];
+        };
+        let mut key_ids: Vec<u32> = self
+            .key_ids
+            .iter()
+            .filter(|(_, key)| key.to_bytes() == signer_key.to_bytes())
+            .map(|(key_id, _)| *key_id)
+            .collect();
+        key_ids.sort();
+        key_ids
+    }
+}
+
+/// The signers with their key ids, the threshold and the coordinator, as listed in the config file
+/// or registered in the sBTC contract
 #[derive(Clone, Debug)]
+pub struct SignerSet {
+    pub keys_threshold: u32,
+    pub coordinator_public_key: ecdsa::PublicKey,
+    pub public_keys: PublicKeys,
+    pub signer_key_ids: SignerKeyIds,
+}
+
+// Public keys are compared by their encoding
+impl PartialEq for SignerSet {
+    fn eq(&self, other: &Self) -> bool {
+        let same_keys = |a: &HashMap<u32, ecdsa::PublicKey>, b: &HashMap<u32, ecdsa::PublicKey>| {
+            a.len() == b.len()
+                && a.iter().all(|(id, key)| {
+                    b.get(id)
+                        .map_or(false, |other| other.to_bytes() == key.to_bytes())
+                })
+        };
+        self.keys_threshold == other.keys_threshold
+            && self.coordinator_public_key.to_bytes() == other.coordinator_public_key.to_bytes()
+            && same_keys(&self.public_keys.signers, &other.public_keys.signers)
+            && same_keys(&self.public_keys.key_ids, &other.public_keys.key_ids)
+            && self.signer_key_ids == other.signer_key_ids
+    }
+}
+
+/// A network private key which is zeroized when dropped and kept out of logs
+#[derive(Clone)]
+pub struct PrivateKey(Zeroizing<[u8; 32]>);
+
+impl PrivateKey {
+    /// Sign a message with the key
+    pub fn sign<S: Signable>(&self, message: &S) -> Result<Vec<u8>, ecdsa::Error> {
+        self.with_scalar(|scalar| message.sign(scalar))
+    }
+
+    /// The ECDH shared secret of the key and a public key
+    pub fn shared_secret(&self, public_key: &Point) -> [u8; 32] {
+        self.with_scalar(|scalar| make_shared_secret(scalar, public_key))
+    }
+
+    /// The public key of the key
+    pub fn public_key(&self) -> Point {
+        self.with_scalar(|scalar| Point::from(scalar))
+    }
+
+    /// Run `f` with the key as a scalar, which is overwritten once `f` returns.
+    /// `f` must not keep copies of the scalar around.
+    pub fn with_scalar<T>(&self, f: impl FnOnce(&Scalar) -> T) -> T {
+        let mut scalar = Scalar::from(*self.0);
+        let result = f(&scalar);
+        scalar = Scalar::new();
+        // Keeps the overwrite from being optimized away
+        std::hint::black_box(&scalar);
+        result
+    }
+}
+
+impl From<Scalar> for PrivateKey {
+    fn from(scalar: Scalar) -> Self {
+        Self(Zeroizing::new(scalar.to_bytes()))
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(config_loader::REDACTED)
+    }
+}
+
+/// A signer slot served by the process in addition to the one it was started for
+#[derive(Clone)]
+pub struct SignerIdentity {
+    pub signer_id: u32,
+    pub network_private_key: PrivateKey,
+}
+
+// Keeps the network private key out of logs
+impl fmt::Debug for SignerIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignerIdentity")
+            .field("signer_id", &self.signer_id)
+            .field("network_private_key", &config_loader::REDACTED)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
 pub struct Config {
     pub http_relay_url: String,
     pub keys_threshold: u32,
-    pub network_private_key: Scalar,
+    pub network_private_key: PrivateKey,
     pub public_keys: PublicKeys,
     pub signer_key_ids: SignerKeyIds,
     pub coordinator_public_key: ecdsa::PublicKey,
     pub total_signers: u32,
     pub total_keys: u32,
+    /// Optional path of the signing journal. Signing requests are not journaled if unset.
+    pub journal_path: Option<PathBuf>,
+    /// Optional RPC URL of the signer's own stacks node. If set, only signing requests for
+    /// transactions fulfilling peg out requests observed by the node are signed.
+    pub stacks_node_rpc_url: Option<String>,
+    /// Optional directory the encrypted DKG party state is saved to and reloaded from on startup.
+    /// A restarted signer must rejoin DKG if unset.
+    pub dkg_state_directory: Option<PathBuf>,
+    /// Optional sBTC contract, e.g. `<address>.<name>`, whose bitcoin wallet public key saved DKG
+    /// state is checked against. Requires `stacks_node_rpc_url`.
+    pub sbtc_contract: Option<String>,
+    /// Optional path the ids of accepted protocol messages are saved to, so that messages seen
+    /// before a restart are still rejected as replays. Kept in memory only if unset.
+    pub replay_state_path: Option<PathBuf>,
+    /// Optional path the nonce pool is saved to, so that pool nonces published before a restart
+    /// can still be used and never sign twice. Published nonces are lost on restart if unset.
+    pub nonce_pool_path: Option<PathBuf>,
+    /// The wsts protocol version, `v1` or `v2`, which must be the same for all signers and the
+    /// coordinator. Defaults to v1.
+    pub wsts_version: WstsVersion,
+    /// Deadlines after which the signer abandons a DKG or signing round, e.g. because the
+    /// coordinator went away, set in seconds by `dkg_public_timeout`, `dkg_private_timeout` and
+    /// `sign_timeout`.
+    pub state_timeouts: StateTimeouts,
+    /// Further signer slots served by the same process, each with its own network private key.
+    /// They share the relay connection, but keep their own signing round and state, stored at
+    /// the configured paths suffixed with their signer id.
+    pub identities: Vec<SignerIdentity>,
+    /// Optional local address, e.g. `127.0.0.1:9800`, the signer serves its health and status
+    /// on. Not served if unset.
+    pub status_listen: Option<String>,
+    /// Optional length in burn blocks of the cycles a signer set read from the sBTC contract
+    /// serves. A new signer set takes over at the start of the cycle after it is registered, or
+    /// as soon as it is seen if unset.
+    pub signer_set_cycle_length: Option<u64>,
+    /// How often the signer set is read from the sBTC contract, set in seconds. Defaults to a
+    /// minute.
+    pub signer_set_poll_interval: Duration,
 }
 
 impl Config {
@@ -129,7 +360,7 @@ impl Config {
         coordinator_public_key: ecdsa::PublicKey,
         public_keys: PublicKeys,
         signer_key_ids: SignerKeyIds,
-        network_private_key: Scalar,
+        network_private_key: PrivateKey,
         http_relay_url: String,
     ) -> Config {
         Self {
@@ -141,32 +372,276 @@ impl Config {
             total_keys: public_keys.key_ids.len().try_into().unwrap(),
             public_keys,
             signer_key_ids,
+            journal_path: None,
+            stacks_node_rpc_url: None,
+            dkg_state_directory: None,
+            sbtc_contract: None,
+            replay_state_path: None,
+            nonce_pool_path: None,
+            wsts_version: WstsVersion::default(),
+            state_timeouts: StateTimeouts::default(),
+            identities: vec![],
+            status_listen: None,
+            signer_set_cycle_length: None,
+            signer_set_poll_interval: DEFAULT_SIGNER_SET_POLL_INTERVAL,
+        }
+    }
+
+    /// Load the config like `load`, but with the signer set read from the sBTC contract, given
+    /// the configured `stacks_node_rpc_url` and `sbtc_contract`, instead of the one the config
+    /// file lists, which it may then leave out
+    pub fn load_with_signer_set<E: fmt::Display>(
+        path: impl AsRef<std::path::Path>,
+        overrides: &ConfigOverrides,
+        signer_set: impl FnOnce(&str, &str) -> Result<SignerSet, E>,
+    ) -> Result<Config, Error> {
+        let raw_config: RawConfig = config_loader::load(Some(path), overrides)?;
+        let (Some(stacks_node_rpc_url), Some(sbtc_contract)) =
+            (&raw_config.stacks_node_rpc_url, &raw_config.sbtc_contract)
+        else {
+            return Err(Error::MissingContract);
+        };
+        let signer_set = signer_set(stacks_node_rpc_url, sbtc_contract)
+            .map_err(|e| Error::SignerSetError(e.to_string()))?;
+        let config = Config::from_raw_config(&raw_config, signer_set)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn signer_set(&self) -> SignerSet {
+        SignerSet {
+            keys_threshold: self.keys_threshold,
+            coordinator_public_key: self.coordinator_public_key,
+            public_keys: self.public_keys.clone(),
+            signer_key_ids: self.signer_key_ids.clone(),
         }
     }
 
+    /// Switch to another signer set, keeping all other settings
+    pub fn set_signer_set(&mut self, signer_set: SignerSet) {
+        self.total_signers = signer_set.public_keys.signers.len().try_into().unwrap();
+        self.total_keys = signer_set.public_keys.key_ids.len().try_into().unwrap();
+        self.keys_threshold = signer_set.keys_threshold;
+        self.coordinator_public_key = signer_set.coordinator_public_key;
+        self.public_keys = signer_set.public_keys;
+        self.signer_key_ids = signer_set.signer_key_ids;
+    }
+
     pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Config, Error> {
         let raw_config = RawConfig::from_path(path)?;
         Config::try_from(&raw_config)
     }
+
+    /// Load the config file with SBTC_* environment and command line overrides, and validate it
+    pub fn load(
+        path: impl AsRef<std::path::Path>,
+        overrides: &ConfigOverrides,
+    ) -> Result<Config, Error> {
+        let raw_config: RawConfig = config_loader::load(Some(path), overrides)?;
+        let config = Config::try_from(&raw_config)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The config of the file with the signer set
+    fn from_raw_config(raw_config: &RawConfig, signer_set: SignerSet) -> Result<Config, Error> {
+        let mut config = Config::new(
+            signer_set.keys_threshold,
+            signer_set.coordinator_public_key,
+            signer_set.public_keys,
+            signer_set.signer_key_ids,
+            raw_config.network_private_key()?,
+            raw_config.http_relay_url.clone(),
+        );
+        config.journal_path = raw_config.journal_path.as_ref().map(PathBuf::from);
+        config.stacks_node_rpc_url = raw_config.stacks_node_rpc_url.clone();
+        config.dkg_state_directory = raw_config.dkg_state_directory.as_ref().map(PathBuf::from);
+        config.sbtc_contract = raw_config.sbtc_contract.clone();
+        config.replay_state_path = raw_config.replay_state_path.as_ref().map(PathBuf::from);
+        config.nonce_pool_path = raw_config.nonce_pool_path.as_ref().map(PathBuf::from);
+        config.wsts_version = raw_config.wsts_version;
+        config.state_timeouts = raw_config.state_timeouts();
+        config.identities = raw_config.identities()?;
+        config.status_listen = raw_config.status_listen.clone();
+        config.signer_set_cycle_length = raw_config.signer_set_cycle_length;
+        config.signer_set_poll_interval = raw_config
+            .signer_set_poll_interval
+            .map_or(DEFAULT_SIGNER_SET_POLL_INTERVAL, Duration::from_secs);
+        Ok(config)
+    }
+
+    /// Check the consistency of the threshold and key assignments
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.keys_threshold == 0 || self.keys_threshold > self.total_keys {
+            return Err(Error::InvalidThreshold(
+                self.keys_threshold,
+                self.total_keys,
+            ));
+        }
+        let mut key_ids: Vec<u32> = self.signer_key_ids.values().flatten().copied().collect();
+        key_ids.sort_unstable();
+        if let Some(key_id) = key_ids
+            .windows(2)
+            .find(|pair| pair[0] == pair[1])
+            .map(|pair| pair[0])
+        {
+            return Err(Error::DuplicateKeyID(key_id));
+        }
+        let mut signer_ids = HashSet::new();
+        for identity in &self.identities {
+            if !self.public_keys.signers.contains_key(&identity.signer_id) {
+                return Err(Error::UnknownSignerID(identity.signer_id));
+            }
+            if !signer_ids.insert(identity.signer_id) {
+                return Err(Error::DuplicateSignerID(identity.signer_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the process is not started for a signer slot it also serves as a further identity
+    pub fn validate_signer_id(&self, signer_id: u32) -> Result<(), Error> {
+        if !self.public_keys.signers.contains_key(&signer_id) {
+            return Err(Error::UnknownSignerID(signer_id));
+        }
+        if self
+            .identities
+            .iter()
+            .any(|identity| identity.signer_id == signer_id)
+        {
+            return Err(Error::DuplicateSignerID(signer_id));
+        }
+        Ok(())
+    }
+}
+
+// Keeps the network private key out of logs
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("http_relay_url", &self.http_relay_url)
+            .field("keys_threshold", &self.keys_threshold)
+            .field("network_private_key", &config_loader::REDACTED)
+            .field("public_keys", &self.public_keys)
+            .field("signer_key_ids", &self.signer_key_ids)
+            .field("coordinator_public_key", &self.coordinator_public_key)
+            .field("total_signers", &self.total_signers)
+            .field("total_keys", &self.total_keys)
+            .field("journal_path", &self.journal_path)
+            .field("replay_state_path", &self.replay_state_path)
+            .field("nonce_pool_path", &self.nonce_pool_path)
+            .field("wsts_version", &self.wsts_version)
+            .field("state_timeouts", &self.state_timeouts)
+            .field("identities", &self.identities)
+            .field("status_listen", &self.status_listen)
+            .field("signer_set_cycle_length", &self.signer_set_cycle_length)
+            .field("signer_set_poll_interval", &self.signer_set_poll_interval)
+            .field(
+                "stacks_node_rpc_url",
+                &self
+                    .stacks_node_rpc_url
+                    .as_deref()
+                    .map(config_loader::redact_url),
+            )
+            .finish()
+    }
 }
 
 impl TryFrom<&RawConfig> for Config {
     type Error = Error;
     fn try_from(raw_config: &RawConfig) -> Result<Self, Error> {
-        Ok(Config::new(
-            raw_config.keys_threshold,
-            raw_config.coordinator_public_key()?,
-            raw_config.public_keys()?,
-            raw_config.signer_key_ids(),
-            raw_config.network_private_key()?,
-            raw_config.http_relay_url.clone(),
-        ))
+        Config::from_raw_config(raw_config, raw_config.signer_set()?)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Config, Error, RawConfig, RawSigners};
+    use p256k1::{ecdsa, point::Point, scalar::Scalar};
+    use rand_core::OsRng;
+
+    use super::{Config, Error, PrivateKey, RawConfig, RawIdentity, RawSigners};
+    use crate::signing_round::{DkgEnd, DkgStatus, Signable};
+    use crate::util::make_shared_secret;
+
+    fn raw_config(keys_threshold: u32, key_ids: &[&[u32]]) -> RawConfig {
+        RawConfig {
+            keys_threshold,
+            network_private_key: "9aSCCR6eirt1NAHwJtSz4HMwBHTyMo62SyPMvVDt5DQn"
+                .to_string()
+                .into(),
+            coordinator_public_key: "22Rm48xUdpuTuva5gz9S7yDaaw9f8sjMcPSTHYVzPLNcj".to_string(),
+            signers: key_ids
+                .iter()
+                .map(|key_ids| RawSigners {
+                    public_key: "22Rm48xUdpuTuva5gz9S7yDaaw9f8sjMcPSTHYVzPLNcj".to_string(),
+                    key_ids: key_ids.to_vec(),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        let config = Config::try_from(&raw_config(3, &[&[1, 2], &[3, 4]])).unwrap();
+        assert!(config.validate().is_ok());
+
+        let config = Config::try_from(&raw_config(0, &[&[1, 2], &[3, 4]])).unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(Error::InvalidThreshold(0, 4))
+        ));
+
+        let config = Config::try_from(&raw_config(5, &[&[1, 2], &[3, 4]])).unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(Error::InvalidThreshold(5, 4))
+        ));
+
+        // Key ID 2 is assigned to both signers
+        let config = Config::try_from(&raw_config(2, &[&[1, 2], &[2, 3]])).unwrap();
+        assert!(matches!(config.validate(), Err(Error::DuplicateKeyID(2))));
+    }
+
+    #[test]
+    fn validate_identities_test() {
+        let identity = |signer_id| RawIdentity {
+            signer_id,
+            network_private_key: "9aSCCR6eirt1NAHwJtSz4HMwBHTyMo62SyPMvVDt5DQn"
+                .to_string()
+                .into(),
+        };
+        let mut raw_config = raw_config(3, &[&[1], &[2], &[3, 4]]);
+        raw_config.identities = vec![identity(2), identity(3)];
+        let config = Config::try_from(&raw_config).unwrap();
+        assert!(config.validate().is_ok());
+        assert!(config.validate_signer_id(1).is_ok());
+        assert!(matches!(
+            config.validate_signer_id(2),
+            Err(Error::DuplicateSignerID(2))
+        ));
+        assert!(matches!(
+            config.validate_signer_id(4),
+            Err(Error::UnknownSignerID(4))
+        ));
+
+        raw_config.identities = vec![identity(2), identity(2)];
+        let config = Config::try_from(&raw_config).unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(Error::DuplicateSignerID(2))
+        ));
+
+        raw_config.identities = vec![identity(4)];
+        let config = Config::try_from(&raw_config).unwrap();
+        assert!(matches!(config.validate(), Err(Error::UnknownSignerID(4))));
+
+        raw_config.identities[0].network_private_key = "invalid".to_string().into();
+        assert!(matches!(
+            Config::try_from(&raw_config),
+            Err(Error::InvalidPrivateKey(_))
+        ));
+    }
 
     #[test]
     fn try_from_raw_config_test() {
@@ -185,10 +660,28 @@ mod test {
             Err(Error::InvalidPrivateKey(_))
         ));
 
-        raw_config.network_private_key = "9aSCCR6eirt1NAHwJtSz4HMwBHTyMo62SyPMvVDt5DQn".to_string();
+        raw_config.network_private_key = "9aSCCR6eirt1NAHwJtSz4HMwBHTyMo62SyPMvVDt5DQn"
+            .to_string()
+            .into();
         assert!(Config::try_from(&raw_config).is_ok());
     }
 
+    #[test]
+    fn set_signer_set_test() {
+        let mut config = Config::try_from(&raw_config(3, &[&[1, 2], &[3, 4]])).unwrap();
+        let signer_set = Config::try_from(&raw_config(2, &[&[1], &[2], &[3]]))
+            .unwrap()
+            .signer_set();
+        assert!(config.signer_set() != signer_set);
+
+        config.set_signer_set(signer_set.clone());
+        assert!(config.signer_set() == signer_set);
+        assert_eq!(config.keys_threshold, 2);
+        assert_eq!(config.total_signers, 3);
+        assert_eq!(config.total_keys, 3);
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn coordinator_public_key_test() {
         let mut config = RawConfig::default();
@@ -261,4 +754,26 @@ mod test {
         assert_eq!(public_keys.signers.len(), 2);
         assert_eq!(public_keys.key_ids.len(), 4);
     }
+
+    #[test]
+    fn private_key_test() {
+        let scalar = Scalar::random(&mut OsRng);
+        let private_key = PrivateKey::from(scalar);
+        assert_eq!(private_key.public_key(), Point::from(&scalar));
+
+        let other = Scalar::random(&mut OsRng);
+        assert_eq!(
+            private_key.shared_secret(&Point::from(&other)),
+            make_shared_secret(&other, &Point::from(&scalar))
+        );
+
+        let end = DkgEnd {
+            dkg_id: 1,
+            signer_id: 1,
+            status: DkgStatus::Success,
+        };
+        let signature = private_key.sign(&end).unwrap();
+        let public_key = ecdsa::PublicKey::new(&scalar).unwrap();
+        assert!(end.verify(&signature, &public_key));
+    }
 }
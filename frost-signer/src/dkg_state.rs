@@ -0,0 +1,353 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use p256k1::{point::Point, scalar::Scalar};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::info;
+use wsts::traits::SignerState;
+use zeroize::Zeroizing;
+
+use crate::util::{decrypt, encrypt};
+use crate::wsts_version::WstsVersion;
+
+/// Version of the DKG state file format
+pub const DKG_STATE_VERSION: u32 = 1;
+
+const FILE_EXTENSION: &str = "state";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("Serialization Error: {0}")]
+    SerializationError(#[from] bincode::Error),
+    #[error("Failed to encrypt DKG state")]
+    Encryption,
+    #[error("Wrong network private key or corrupted DKG state {0}")]
+    Decryption(PathBuf),
+    #[error("Unsupported DKG state version {0}")]
+    UnsupportedVersion(u32),
+    #[error("DKG state {path} belongs to signer {found}, not signer {expected}")]
+    SignerMismatch {
+        path: PathBuf,
+        expected: u32,
+        found: u32,
+    },
+    #[error("Aggregate public key {found} of DKG round #{dkg_id} does not match the on-chain key {expected}")]
+    AggregateKeyMismatch {
+        dkg_id: u64,
+        expected: String,
+        found: String,
+    },
+    #[error("DKG state of round #{dkg_id} is for wsts {found:?}, not the configured {expected:?}")]
+    WstsVersionMismatch {
+        dkg_id: u64,
+        expected: WstsVersion,
+        found: WstsVersion,
+    },
+}
+
+/// The party state of a signer after a successful DKG round, which it needs to keep signing
+/// with the aggregate key after a restart
+#[derive(Serialize, Deserialize)]
+pub struct DkgState {
+    pub version: u32,
+    pub dkg_id: u64,
+    pub signer_id: u32,
+    pub signer: SignerState,
+    /// The protocol version the party state is for
+    pub wsts_version: WstsVersion,
+}
+
+impl DkgState {
+    pub fn new(
+        dkg_id: u64,
+        signer_id: u32,
+        wsts_version: WstsVersion,
+        signer: SignerState,
+    ) -> Self {
+        Self {
+            version: DKG_STATE_VERSION,
+            dkg_id,
+            signer_id,
+            signer,
+            wsts_version,
+        }
+    }
+
+    /// Check that the party state is for the configured protocol version
+    pub fn check_wsts_version(&self, expected: WstsVersion) -> Result<(), Error> {
+        if self.wsts_version != expected {
+            return Err(Error::WstsVersionMismatch {
+                dkg_id: self.dkg_id,
+                expected,
+                found: self.wsts_version,
+            });
+        }
+        Ok(())
+    }
+
+    /// The aggregate public key the DKG round produced
+    pub fn aggregate_public_key(&self) -> Point {
+        self.signer.group_key
+    }
+
+    /// Check the aggregate public key against the x coordinate of the key registered on chain
+    pub fn check_aggregate_public_key(&self, expected_x: &[u8; 32]) -> Result<(), Error> {
+        let found_x = self.aggregate_public_key().x().to_bytes();
+        if &found_x != expected_x {
+            return Err(Error::AggregateKeyMismatch {
+                dkg_id: self.dkg_id,
+                expected: hex::encode(expected_x),
+                found: hex::encode(found_x),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Path of the state file of the signer's DKG round
+pub fn path(directory: impl AsRef<Path>, signer_id: u32, dkg_id: u64) -> PathBuf {
+    directory
+        .as_ref()
+        .join(format!("signer-{signer_id}-dkg-{dkg_id}.{FILE_EXTENSION}"))
+}
+
+/// Encrypt the state with a key derived from the network private key and write it to the
+/// directory, replacing any state of the same round
+pub fn save(
+    directory: impl AsRef<Path>,
+    network_private_key: &Scalar,
+    state: &DkgState,
+) -> Result<PathBuf, Error> {
+    let plain = Zeroizing::new(bincode::serialize(state)?);
+    let encrypted = encrypt(&encryption_key(network_private_key), &plain, &mut OsRng)
+        .map_err(|_| Error::Encryption)?;
+
+    fs::create_dir_all(&directory)?;
+    let path = path(&directory, state.signer_id, state.dkg_id);
+    // Write to a temporary file first so that a crash can not leave a truncated state behind
+    let temp_path = path.with_extension("tmp");
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temp_path)?;
+    file.write_all(&encrypted)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, &path)?;
+    info!(
+        "Saved DKG state of round #{} to {}",
+        state.dkg_id,
+        path.display()
+    );
+    Ok(path)
+}
+
+/// Read and decrypt a state file
+pub fn load(path: impl AsRef<Path>, network_private_key: &Scalar) -> Result<DkgState, Error> {
+    let path = path.as_ref();
+    let encrypted = fs::read(path)?;
+    let plain = Zeroizing::new(
+        decrypt(&encryption_key(network_private_key), &encrypted)
+            .map_err(|_| Error::Decryption(path.to_path_buf()))?,
+    );
+    let state: DkgState = bincode::deserialize(&plain)?;
+    if state.version != DKG_STATE_VERSION {
+        return Err(Error::UnsupportedVersion(state.version));
+    }
+    Ok(state)
+}
+
+/// Load the state of the signer's most recent DKG round, if any was saved. Given the x coordinate
+/// of the aggregate public key registered on chain, load the most recent state for that key
+/// instead, e.g. when a newer DKG round has not been registered yet, and fail if no state is.
+pub fn load_latest(
+    directory: impl AsRef<Path>,
+    signer_id: u32,
+    network_private_key: &Scalar,
+    on_chain_public_key: Option<&[u8; 32]>,
+) -> Result<Option<DkgState>, Error> {
+    let mut saved = saved(directory, signer_id)?;
+    // Most recent first
+    saved.sort_by(|(a, _), (b, _)| b.cmp(a));
+    let mut mismatch = None;
+    for (_, path) in saved {
+        let state = load(&path, network_private_key)?;
+        if state.signer_id != signer_id {
+            return Err(Error::SignerMismatch {
+                path,
+                expected: signer_id,
+                found: state.signer_id,
+            });
+        }
+        match on_chain_public_key.map(|x| state.check_aggregate_public_key(x)) {
+            None | Some(Ok(())) => return Ok(Some(state)),
+            // Report the most recent state if none matches
+            Some(Err(e)) => {
+                mismatch.get_or_insert(e);
+            }
+        }
+    }
+    mismatch.map_or(Ok(None), Err)
+}
+
+/// Delete the states of the signer's DKG rounds before the given one, e.g. once resharing made
+/// their key shares obsolete
+pub fn remove_older(directory: impl AsRef<Path>, signer_id: u32, dkg_id: u64) -> Result<(), Error> {
+    for (older_id, path) in saved(directory, signer_id)? {
+        if older_id < dkg_id {
+            fs::remove_file(&path)?;
+            info!(
+                "Removed DKG state of round #{} at {}",
+                older_id,
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The DKG round ids and paths of the signer's saved states
+fn saved(directory: impl AsRef<Path>, signer_id: u32) -> Result<Vec<(u64, PathBuf)>, Error> {
+    let directory = directory.as_ref();
+    if !directory.exists() {
+        return Ok(vec![]);
+    }
+    let prefix = format!("signer-{signer_id}-dkg-");
+    let mut saved = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some(FILE_EXTENSION) {
+            continue;
+        }
+        let dkg_id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.strip_prefix(&prefix))
+            .and_then(|dkg_id| dkg_id.parse::<u64>().ok());
+        if let Some(dkg_id) = dkg_id {
+            saved.push((dkg_id, path));
+        }
+    }
+    Ok(saved)
+}
+
+/// Derive the state encryption key, so that only the holder of the network private key can read the shares
+fn encryption_key(network_private_key: &Scalar) -> Zeroizing<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update("DKG_STATE_KEY/".as_bytes());
+    hasher.update(network_private_key.to_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(hasher.finalize().as_slice());
+    key
+}
+
+#[cfg(test)]
+mod test {
+    use rand_core::RngCore;
+    use wsts::{traits::Signer as SignerTrait, v1};
+
+    use super::*;
+
+    fn temp_directory() -> PathBuf {
+        std::env::temp_dir().join(format!("dkg_state_{}", OsRng.next_u64()))
+    }
+
+    fn state(dkg_id: u64, signer_id: u32) -> DkgState {
+        let signer = v1::Signer::new(signer_id, &[0, 1], 4, 3, &mut OsRng);
+        DkgState::new(dkg_id, signer_id, WstsVersion::V1, signer.save())
+    }
+
+    #[test]
+    fn save_and_load_latest() {
+        let directory = temp_directory();
+        let network_private_key = Scalar::random(&mut OsRng);
+        assert!(load_latest(&directory, 1, &network_private_key, None)
+            .unwrap()
+            .is_none());
+
+        for dkg_id in [2, 10, 3] {
+            save(&directory, &network_private_key, &state(dkg_id, 1)).unwrap();
+        }
+        save(&directory, &network_private_key, &state(11, 2)).unwrap();
+
+        let latest = load_latest(&directory, 1, &network_private_key, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.dkg_id, 10);
+        assert_eq!(latest.signer_id, 1);
+
+        remove_older(&directory, 1, 10).unwrap();
+        assert!(!path(&directory, 1, 2).exists());
+        assert!(!path(&directory, 1, 3).exists());
+        assert!(path(&directory, 1, 10).exists());
+        assert!(path(&directory, 2, 11).exists());
+
+        // Only the holder of the network private key can read the state
+        assert!(matches!(
+            load(path(&directory, 1, 10), &Scalar::random(&mut OsRng)),
+            Err(Error::Decryption(_))
+        ));
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn latest_state_of_the_on_chain_key_is_loaded() {
+        let directory = temp_directory();
+        let network_private_key = Scalar::random(&mut OsRng);
+        let group_key = Point::from(Scalar::random(&mut OsRng));
+        for dkg_id in [3, 5] {
+            let mut state = state(dkg_id, 1);
+            state.signer.group_key = group_key;
+            save(&directory, &network_private_key, &state).unwrap();
+        }
+        // A newer round whose key is not registered yet
+        let mut newer = state(8, 1);
+        newer.signer.group_key = Point::from(Scalar::random(&mut OsRng));
+        save(&directory, &network_private_key, &newer).unwrap();
+
+        let on_chain_x = group_key.x().to_bytes();
+
+        let latest = load_latest(&directory, 1, &network_private_key, Some(&on_chain_x))
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.dkg_id, 5);
+
+        assert!(matches!(
+            load_latest(&directory, 1, &network_private_key, Some(&[0; 32])),
+            Err(Error::AggregateKeyMismatch { dkg_id: 8, .. })
+        ));
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn wsts_version_is_checked() {
+        let state = state(5, 1);
+        assert!(state.check_wsts_version(WstsVersion::V1).is_ok());
+        assert!(matches!(
+            state.check_wsts_version(WstsVersion::V2),
+            Err(Error::WstsVersionMismatch { dkg_id: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn aggregate_public_key_is_checked() {
+        let mut state = state(1, 1);
+        state.signer.group_key = Point::from(Scalar::random(&mut OsRng));
+        let x = state.aggregate_public_key().x().to_bytes();
+        assert!(state.check_aggregate_public_key(&x).is_ok());
+        assert!(matches!(
+            state.check_aggregate_public_key(&[0; 32]),
+            Err(Error::AggregateKeyMismatch { dkg_id: 1, .. })
+        ));
+    }
+}
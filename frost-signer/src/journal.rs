@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The previous hash recorded by the first entry of every journal
+pub const GENESIS_HASH: [u8; 32] = [0; 32];
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Journal JSON Error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Journal entry {0} is out of sequence")]
+    InvalidSequence(u64),
+    #[error("Journal entry {0} does not link to the previous entry")]
+    BrokenChain(u64),
+    #[error("Journal entry {0} has an invalid hash")]
+    InvalidHash(u64),
+}
+
+/// The peg operation a signature was produced for
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PegContext {
+    /// Identifies the originating peg op, e.g. `peg-out-request:<stacks burn op txid>`
+    pub peg_op: String,
+    /// The bitcoin transaction the signature was produced for
+    pub txid: String,
+    /// The input of `txid` being signed
+    pub input_index: u32,
+}
+
+/// A single signing operation. `hash` commits to every other field, including
+/// `prev_hash`, so that modifying or removing an entry breaks the chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub sequence: u64,
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    /// Hex encoded message or sighash which was signed
+    pub message: String,
+    pub context: Option<PegContext>,
+    pub signer_ids: Vec<u32>,
+    /// Hex encoded schnorr proof, if the author aggregated the signature
+    pub proof: Option<String>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl JournalEntry {
+    pub fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update("SIGNING_JOURNAL_ENTRY".as_bytes());
+        hasher.update(self.sequence.to_be_bytes());
+        hasher.update(self.timestamp.to_be_bytes());
+        hash_str(&mut hasher, &self.message);
+        match &self.context {
+            Some(context) => {
+                hasher.update([1u8]);
+                hash_str(&mut hasher, &context.peg_op);
+                hash_str(&mut hasher, &context.txid);
+                hasher.update(context.input_index.to_be_bytes());
+            }
+            None => hasher.update([0u8]),
+        }
+        hasher.update((self.signer_ids.len() as u64).to_be_bytes());
+        for signer_id in &self.signer_ids {
+            hasher.update(signer_id.to_be_bytes());
+        }
+        match &self.proof {
+            Some(proof) => {
+                hasher.update([1u8]);
+                hash_str(&mut hasher, proof);
+            }
+            None => hasher.update([0u8]),
+        }
+        hash_str(&mut hasher, &self.prev_hash);
+        hex::encode(hasher.finalize())
+    }
+}
+
+// Length prefix variable sized fields so that adjacent fields can not be shifted into each other
+fn hash_str(hasher: &mut Sha256, s: &str) {
+    hasher.update((s.len() as u64).to_be_bytes());
+    hasher.update(s.as_bytes());
+}
+
+/// An append-only, hash-chained journal stored as one JSON entry per line
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    next_sequence: u64,
+    last_hash: String,
+}
+
+impl Journal {
+    /// Open the journal at the given path, creating it if it does not exist.
+    /// An existing journal is verified before any new entries are appended to it.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let entries = if path.exists() {
+            Self::read(&path)?
+        } else {
+            vec![]
+        };
+        Self::verify(&entries)?;
+        let (next_sequence, last_hash) = match entries.last() {
+            Some(entry) => (entry.sequence + 1, entry.hash.clone()),
+            None => (0, hex::encode(GENESIS_HASH)),
+        };
+        Ok(Self {
+            path,
+            next_sequence,
+            last_hash,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a signing operation to the journal, flushing it to disk before returning
+    pub fn append(
+        &mut self,
+        message: &[u8],
+        context: Option<PegContext>,
+        signer_ids: Vec<u32>,
+        proof: Option<&[u8]>,
+    ) -> Result<JournalEntry, Error> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut entry = JournalEntry {
+            sequence: self.next_sequence,
+            timestamp,
+            message: hex::encode(message),
+            context,
+            signer_ids,
+            proof: proof.map(hex::encode),
+            prev_hash: self.last_hash.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        self.next_sequence += 1;
+        self.last_hash = entry.hash.clone();
+        Ok(entry)
+    }
+
+    /// Read all entries from the journal at the given path without verifying them
+    pub fn read(path: impl AsRef<Path>) -> Result<Vec<JournalEntry>, Error> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut entries = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line)?);
+        }
+        Ok(entries)
+    }
+
+    /// Check that the entries are sequential, correctly hashed and chained from the genesis hash
+    pub fn verify(entries: &[JournalEntry]) -> Result<(), Error> {
+        let mut prev_hash = hex::encode(GENESIS_HASH);
+        for (i, entry) in entries.iter().enumerate() {
+            if entry.sequence != i as u64 {
+                return Err(Error::InvalidSequence(entry.sequence));
+            }
+            if entry.prev_hash != prev_hash {
+                return Err(Error::BrokenChain(entry.sequence));
+            }
+            if entry.hash != entry.compute_hash() {
+                return Err(Error::InvalidHash(entry.sequence));
+            }
+            prev_hash = entry.hash.clone();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand_core::{OsRng, RngCore};
+
+    fn temp_journal_path() -> PathBuf {
+        std::env::temp_dir().join(format!("signing_journal_{}.jsonl", OsRng.next_u64()))
+    }
+
+    fn peg_context(input_index: u32) -> PegContext {
+        PegContext {
+            peg_op: "peg-out-request:00".to_string(),
+            txid: "11".to_string(),
+            input_index,
+        }
+    }
+
+    #[test]
+    fn append_and_reopen_journal() {
+        let path = temp_journal_path();
+        let mut journal = Journal::open(&path).unwrap();
+        journal
+            .append(&[1, 2, 3], Some(peg_context(0)), vec![1, 2], Some(&[4; 64]))
+            .unwrap();
+        journal.append(&[5, 6], None, vec![1], None).unwrap();
+
+        // Reopening continues the existing chain
+        let mut journal = Journal::open(&path).unwrap();
+        let entry = journal.append(&[7], None, vec![2], None).unwrap();
+        assert_eq!(entry.sequence, 2);
+
+        let entries = Journal::read(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].prev_hash, hex::encode(GENESIS_HASH));
+        assert_eq!(entries[2].prev_hash, entries[1].hash);
+        assert!(Journal::verify(&entries).is_ok());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn tampered_journal_fails_verification() {
+        let path = temp_journal_path();
+        let mut journal = Journal::open(&path).unwrap();
+        for i in 0..3 {
+            journal
+                .append(
+                    &[i as u8],
+                    Some(peg_context(i)),
+                    vec![1, 2],
+                    Some(&[i as u8; 64]),
+                )
+                .unwrap();
+        }
+        let entries = Journal::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut modified = entries.clone();
+        modified[1].signer_ids = vec![1];
+        assert!(matches!(
+            Journal::verify(&modified),
+            Err(Error::InvalidHash(1))
+        ));
+
+        let mut rehashed = entries.clone();
+        rehashed[1].proof = None;
+        rehashed[1].hash = rehashed[1].compute_hash();
+        assert!(matches!(
+            Journal::verify(&rehashed),
+            Err(Error::BrokenChain(2))
+        ));
+
+        let mut removed = entries;
+        removed.remove(1);
+        assert!(matches!(
+            Journal::verify(&removed),
+            Err(Error::InvalidSequence(2))
+        ));
+    }
+}
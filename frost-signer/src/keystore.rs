@@ -0,0 +1,513 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::OpenOptions,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use p256k1::{
+    point::Point,
+    scalar::{Error as ScalarError, Scalar},
+};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use toml::Value;
+use zeroize::Zeroizing;
+
+use crate::util::{decrypt, encrypt};
+
+/// Version of the keystore file format
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Environment variable holding the passphrase of a keystore
+pub const PASSPHRASE_ENV: &str = "SBTC_KEYSTORE_PASSPHRASE";
+
+/// Names of the config values which a keystore can hold
+pub const KEY_NAMES: [&str; 3] = ["network_private_key", "stacks_private_key", "secret_key"];
+
+const CIPHER: &str = "aes-256-gcm";
+const KDF: &str = "scrypt";
+const SALT_SIZE: usize = 32;
+
+// Scrypt cost parameters of new keystores. log_n = 15 takes 32MiB of memory to unlock.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("JSON Error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Hex Error: {0}")]
+    HexError(#[from] hex::FromHexError),
+    #[error("Unsupported keystore: {0}")]
+    Unsupported(String),
+    #[error("Invalid scrypt parameters: {0}")]
+    InvalidKdfParams(String),
+    #[error("Wrong passphrase or corrupted keystore")]
+    Decryption,
+    #[error("No keystore passphrase. Set passphrase_file or {}", PASSPHRASE_ENV)]
+    MissingPassphrase,
+    #[error("Invalid keystore config: {0}")]
+    InvalidConfig(String),
+    #[error("Invalid {0}: {1}")]
+    InvalidKey(String, String),
+    #[error("Unknown key name {0}. Expected one of {1:?}")]
+    UnknownKey(String, [&'static str; 3]),
+}
+
+/// A secret which is zeroized when dropped and never printed
+#[derive(Clone, Default)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    pub fn new(secret: String) -> Self {
+        Self(Zeroizing::new(secret))
+    }
+
+    /// The secret value. Take care not to copy it into unprotected memory.
+    pub fn expose(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(crate::config_loader::REDACTED)
+    }
+}
+
+/// Secrets by the name of the config value they provide, e.g. network_private_key
+pub type Secrets = BTreeMap<String, Secret>;
+
+/// Parameters of the scrypt key derivation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    /// Hex encoded salt
+    pub salt: String,
+}
+
+/// A file holding secrets encrypted with AES-GCM under a key derived from a passphrase with scrypt
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub kdf: String,
+    pub kdf_params: KdfParams,
+    pub cipher: String,
+    /// Hex encoded nonce and ciphertext
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    /// Encrypt the secrets under the passphrase
+    pub fn encrypt(secrets: &Secrets, passphrase: &str) -> Result<Self, Error> {
+        Self::encrypt_with(secrets, passphrase, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)
+    }
+
+    fn encrypt_with(
+        secrets: &Secrets,
+        passphrase: &str,
+        log_n: u8,
+        r: u32,
+        p: u32,
+    ) -> Result<Self, Error> {
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        let kdf_params = KdfParams {
+            log_n,
+            r,
+            p,
+            salt: hex::encode(salt),
+        };
+        let key = derive_key(passphrase, &kdf_params)?;
+        let plaintext: BTreeMap<&str, &str> = secrets
+            .iter()
+            .map(|(name, secret)| (name.as_str(), secret.expose()))
+            .collect();
+        let plaintext = Zeroizing::new(serde_json::to_vec(&plaintext)?);
+        let ciphertext = encrypt(&key, &plaintext, &mut OsRng).map_err(|_| Error::Decryption)?;
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            kdf: KDF.to_string(),
+            kdf_params,
+            cipher: CIPHER.to_string(),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the secrets with the passphrase
+    pub fn decrypt(&self, passphrase: &str) -> Result<Secrets, Error> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(Error::Unsupported(format!("version {}", self.version)));
+        }
+        if self.kdf != KDF {
+            return Err(Error::Unsupported(format!("kdf {}", self.kdf)));
+        }
+        if self.cipher != CIPHER {
+            return Err(Error::Unsupported(format!("cipher {}", self.cipher)));
+        }
+        let key = derive_key(passphrase, &self.kdf_params)?;
+        let ciphertext = hex::decode(&self.ciphertext)?;
+        let plaintext = Zeroizing::new(decrypt(&key, &ciphertext).map_err(|_| Error::Decryption)?);
+        let secrets: BTreeMap<String, String> = serde_json::from_slice(&plaintext)?;
+        Ok(secrets
+            .into_iter()
+            .map(|(name, secret)| (name, Secret::new(secret)))
+            .collect())
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write the keystore to a new file, which only the owner can read
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn derive_key(passphrase: &str, params: &KdfParams) -> Result<Zeroizing<[u8; 32]>, Error> {
+    let salt = hex::decode(&params.salt)?;
+    let scrypt_params = scrypt::Params::new(params.log_n, params.r, params.p, 32)
+        .map_err(|e| Error::InvalidKdfParams(e.to_string()))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    scrypt::scrypt(passphrase.as_bytes(), &salt, &scrypt_params, key.as_mut())
+        .map_err(|e| Error::InvalidKdfParams(e.to_string()))?;
+    Ok(key)
+}
+
+/// Read a passphrase from the file, the SBTC_KEYSTORE_PASSPHRASE environment variable or,
+/// if neither is available and `stdin` is set, the first line of stdin
+pub fn read_passphrase(
+    passphrase_file: Option<&Path>,
+    stdin: bool,
+) -> Result<Zeroizing<String>, Error> {
+    let passphrase = if let Some(path) = passphrase_file {
+        Zeroizing::new(std::fs::read_to_string(path)?)
+    } else if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        Zeroizing::new(passphrase)
+    } else if stdin {
+        let mut line = Zeroizing::new(String::new());
+        std::io::stdin().lock().read_line(&mut line)?;
+        line
+    } else {
+        return Err(Error::MissingPassphrase);
+    };
+    let trimmed = passphrase.trim_end_matches(['\r', '\n']);
+    if trimmed.is_empty() {
+        return Err(Error::MissingPassphrase);
+    }
+    Ok(Zeroizing::new(trimmed.to_string()))
+}
+
+/// Unlock the keystore configured by a `[keystore]` table and fill in the config values it holds.
+/// Values set in the config take precedence over the keystore. The table is removed from the config.
+pub fn unlock(config: &mut Value) -> Result<(), Error> {
+    let table = match config.as_table_mut() {
+        Some(table) => table,
+        None => return Ok(()),
+    };
+    let settings = match table.remove("keystore") {
+        Some(Value::Table(settings)) => settings,
+        Some(_) => {
+            return Err(Error::InvalidConfig(
+                "keystore must be a table with a path".to_string(),
+            ))
+        }
+        None => return Ok(()),
+    };
+    if settings.contains_key("passphrase") {
+        return Err(Error::InvalidConfig(format!(
+            "Do not put the passphrase in the config. Use passphrase_file or {}",
+            PASSPHRASE_ENV
+        )));
+    }
+    let path = settings
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::InvalidConfig("keystore.path is missing".to_string()))?;
+    let passphrase_file = settings
+        .get("passphrase_file")
+        .and_then(Value::as_str)
+        .map(PathBuf::from);
+    let passphrase = read_passphrase(passphrase_file.as_deref(), false)?;
+    let secrets = Keystore::from_path(path)?.decrypt(&passphrase)?;
+    for (name, secret) in secrets {
+        if !table.contains_key(&name) {
+            table.insert(name, Value::String(secret.expose().to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Generate a random secret in the format of the named config value
+pub fn generate(name: &str) -> Result<Secret, Error> {
+    let scalar = Scalar::random(&mut OsRng);
+    let secret = match name {
+        "network_private_key" => scalar.to_string(),
+        // Stacks private keys carry a suffix marking their public key as compressed
+        "stacks_private_key" => format!("{}01", hex::encode(scalar.to_bytes())),
+        "secret_key" => hex::encode(scalar.to_bytes()),
+        _ => return Err(Error::UnknownKey(name.to_string(), KEY_NAMES)),
+    };
+    Ok(Secret::new(secret))
+}
+
+/// The public key of a secret in the format of the named config value
+pub fn public_key(name: &str, secret: &Secret) -> Result<String, Error> {
+    let invalid = |e: ScalarError| Error::InvalidKey(name.to_string(), e.to_string());
+    let scalar = match name {
+        "network_private_key" => Scalar::try_from(secret.expose()).map_err(invalid)?,
+        "stacks_private_key" | "secret_key" => {
+            let bytes = Zeroizing::new(hex::decode(secret.expose())?);
+            let bytes: [u8; 32] = bytes
+                .get(..32)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| Error::InvalidKey(name.to_string(), "too short".to_string()))?;
+            Scalar::from(bytes)
+        }
+        _ => return Err(Error::UnknownKey(name.to_string(), KEY_NAMES)),
+    };
+    let point = Point::from(scalar);
+    Ok(match name {
+        "network_private_key" => point.to_string(),
+        _ => hex::encode(point.compress().as_bytes()),
+    })
+}
+
+/// Keystore subcommands. Flatten into the command line interface of a binary.
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum KeystoreCommand {
+    /// Create a keystore holding newly generated keys
+    Create {
+        /// Path of the keystore file to create
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Name of a key to generate: network_private_key, stacks_private_key or secret_key
+        #[arg(
+            long = "key",
+            value_name = "NAME",
+            default_value = "network_private_key"
+        )]
+        keys: Vec<String>,
+        /// Read the passphrase from this file instead of SBTC_KEYSTORE_PASSPHRASE or stdin
+        #[arg(long)]
+        passphrase_file: Option<PathBuf>,
+    },
+    /// Create a keystore holding the keys of a plaintext config file
+    Import {
+        /// Path of the plaintext config file to import keys from
+        #[arg(long)]
+        from: PathBuf,
+        /// Path of the keystore file to create
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Read the passphrase from this file instead of SBTC_KEYSTORE_PASSPHRASE or stdin
+        #[arg(long)]
+        passphrase_file: Option<PathBuf>,
+    },
+    /// Print the public keys of the keys in a keystore
+    ExportPublic {
+        /// Path of the keystore file
+        #[arg(short, long)]
+        keystore: PathBuf,
+        /// Read the passphrase from this file instead of SBTC_KEYSTORE_PASSPHRASE or stdin
+        #[arg(long)]
+        passphrase_file: Option<PathBuf>,
+    },
+}
+
+/// Execute a keystore subcommand. Returns the public keys of the keys in the keystore.
+pub fn execute(command: &KeystoreCommand) -> Result<BTreeMap<String, String>, Error> {
+    match command {
+        KeystoreCommand::Create {
+            output,
+            keys,
+            passphrase_file,
+        } => {
+            let secrets = keys
+                .iter()
+                .map(|name| Ok((name.clone(), generate(name)?)))
+                .collect::<Result<Secrets, Error>>()?;
+            let passphrase = read_passphrase(passphrase_file.as_deref(), true)?;
+            Keystore::encrypt(&secrets, &passphrase)?.write(output)?;
+            public_keys(&secrets)
+        }
+        KeystoreCommand::Import {
+            from,
+            output,
+            passphrase_file,
+        } => {
+            let content = Zeroizing::new(std::fs::read_to_string(from)?);
+            let config: Value = toml::from_str(&content)
+                .map_err(|e| Error::InvalidConfig(format!("{}: {}", from.display(), e)))?;
+            let secrets: Secrets = KEY_NAMES
+                .iter()
+                .filter_map(|name| {
+                    let secret = config.get(*name)?.as_str()?;
+                    Some((name.to_string(), Secret::new(secret.to_string())))
+                })
+                .collect();
+            if secrets.is_empty() {
+                return Err(Error::InvalidConfig(format!(
+                    "{} holds none of {:?}",
+                    from.display(),
+                    KEY_NAMES
+                )));
+            }
+            let public_keys = public_keys(&secrets)?;
+            let passphrase = read_passphrase(passphrase_file.as_deref(), true)?;
+            Keystore::encrypt(&secrets, &passphrase)?.write(output)?;
+            Ok(public_keys)
+        }
+        KeystoreCommand::ExportPublic {
+            keystore,
+            passphrase_file,
+        } => {
+            let passphrase = read_passphrase(passphrase_file.as_deref(), true)?;
+            public_keys(&Keystore::from_path(keystore)?.decrypt(&passphrase)?)
+        }
+    }
+}
+
+fn public_keys(secrets: &Secrets) -> Result<BTreeMap<String, String>, Error> {
+    secrets
+        .iter()
+        .map(|(name, secret)| Ok((name.clone(), public_key(name, secret)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}_{}", name, OsRng.next_u64()))
+    }
+
+    fn secrets() -> Secrets {
+        KEY_NAMES
+            .iter()
+            .map(|name| (name.to_string(), generate(name).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let secrets = secrets();
+        let keystore = Keystore::encrypt_with(&secrets, "hunter2", 4, 8, 1).unwrap();
+        let serialized = serde_json::to_string(&keystore).unwrap();
+        for secret in secrets.values() {
+            assert!(!serialized.contains(secret.expose()));
+        }
+
+        let decrypted = keystore.decrypt("hunter2").unwrap();
+        assert_eq!(decrypted.len(), secrets.len());
+        for (name, secret) in &secrets {
+            assert_eq!(decrypted[name].expose(), secret.expose());
+        }
+        assert!(matches!(
+            keystore.decrypt("hunter3"),
+            Err(Error::Decryption)
+        ));
+    }
+
+    #[test]
+    fn secrets_are_not_printed() {
+        let secret = Secret::new("9aSCCR6eirt1NAHwJtSz4HMwBHTyMo62SyPMvVDt5DQn".to_string());
+        assert_eq!(format!("{:?}", secret), crate::config_loader::REDACTED);
+    }
+
+    #[test]
+    fn public_keys_match_key_formats() {
+        let secret = Secret::new(
+            "b244296d5907de9864c0b0d51f98a13c52890be0404e83f273144cd5b9960eed01".to_string(),
+        );
+        let stacks_public_key = public_key("stacks_private_key", &secret).unwrap();
+        let secret_key = Secret::new(secret.expose()[..64].to_string());
+        assert_eq!(
+            public_key("secret_key", &secret_key).unwrap(),
+            stacks_public_key
+        );
+        assert_eq!(stacks_public_key.len(), 66);
+
+        let network_private_key = generate("network_private_key").unwrap();
+        let scalar = Scalar::try_from(network_private_key.expose()).unwrap();
+        assert_eq!(
+            public_key("network_private_key", &network_private_key).unwrap(),
+            Point::from(scalar).to_string()
+        );
+        assert!(matches!(
+            generate("wallet_key"),
+            Err(Error::UnknownKey(_, _))
+        ));
+    }
+
+    #[test]
+    fn unlock_fills_in_missing_keys() {
+        let path = temp_path("keystore");
+        let passphrase_file = temp_path("passphrase");
+        std::fs::write(&passphrase_file, "hunter2\n").unwrap();
+        let secrets = secrets();
+        Keystore::encrypt_with(&secrets, "hunter2", 4, 8, 1)
+            .unwrap()
+            .write(&path)
+            .unwrap();
+        // Keystores are never overwritten
+        assert!(Keystore::encrypt_with(&secrets, "hunter2", 4, 8, 1)
+            .unwrap()
+            .write(&path)
+            .is_err());
+
+        let mut config: Value = toml::from_str(&format!(
+            r#"
+secret_key = "from config"
+
+[keystore]
+path = "{}"
+passphrase_file = "{}"
+"#,
+            path.display(),
+            passphrase_file.display()
+        ))
+        .unwrap();
+        unlock(&mut config).unwrap();
+
+        assert!(config.get("keystore").is_none());
+        assert_eq!(config["secret_key"].as_str(), Some("from config"));
+        assert_eq!(
+            config["network_private_key"].as_str(),
+            Some(secrets["network_private_key"].expose())
+        );
+        assert_eq!(
+            config["stacks_private_key"].as_str(),
+            Some(secrets["stacks_private_key"].expose())
+        );
+
+        let mut config: Value = toml::from_str(&format!(
+            "[keystore]\npath = \"{}\"\npassphrase = \"hunter2\"",
+            path.display()
+        ))
+        .unwrap();
+        assert!(matches!(unlock(&mut config), Err(Error::InvalidConfig(_))));
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(passphrase_file).unwrap();
+    }
+}
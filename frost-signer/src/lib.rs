@@ -1,10 +1,22 @@
+pub mod complaint;
 pub mod config;
+pub mod config_loader;
+pub mod dkg_state;
+pub mod journal;
+pub mod keystore;
 pub mod logging;
 pub mod net;
+pub mod nonce_pool;
+pub mod replay;
+pub mod reshare;
 pub mod signer;
+pub mod signer_set;
 pub mod signing_round;
 pub mod state_machine;
+pub mod status;
 pub mod util;
+pub mod validation;
+pub mod wsts_version;
 
 // set via _compile-time_ envars
 const GIT_BRANCH: Option<&'static str> = option_env!("GIT_BRANCH");
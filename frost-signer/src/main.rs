@@ -10,14 +10,19 @@ fn main() {
 
     let cli = Cli::parse();
 
-    match Config::from_path(&cli.config) {
+    match Config::load(&cli.config, &cli.overrides) {
         Ok(config) => {
+            if let Err(e) = config.validate_signer_id(cli.id) {
+                error!("Invalid signer id #{}: {}", cli.id, e);
+                return;
+            }
             let mut signer = Signer::new(config, cli.id);
-            info!(
-                "{} signer id #{}",
-                frost_signer::version(),
-                signer.signer_id
-            ); // sign-on message
+            let signer_ids: Vec<u32> = signer
+                .identities()
+                .iter()
+                .map(|identity| identity.signer_id)
+                .collect();
+            info!("{} signer ids {:?}", frost_signer::version(), signer_ids); // sign-on message
 
             //Start listening for p2p messages
             if let Err(e) = signer.start_p2p_sync() {
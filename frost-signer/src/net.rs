@@ -37,6 +37,11 @@ impl HttpNet {
             connected: true,
         }
     }
+
+    /// Whether the last poll of the relay succeeded
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
 }
 
 // these functions manipulate the inbound message queue
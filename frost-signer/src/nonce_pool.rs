@@ -0,0 +1,227 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use p256k1::scalar::Scalar;
+use rand::{rngs::StdRng, SeedableRng};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::info;
+use zeroize::Zeroizing;
+
+/// Most nonces a signer publishes for a single pool request
+pub const MAX_NONCE_POOL_REQUEST: u32 = 1024;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("JSON Error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Pool nonce #{0} was not published")]
+    Unpublished(u64),
+    #[error("Pool nonce #{0} is at or below a nonce which was already used, next unused is #{1}")]
+    Consumed(u64, u64),
+    #[error("Nonce pool is for DKG round #{0}, not #{1}")]
+    WrongRound(u64, u64),
+}
+
+/// The nonces a signer published in advance for a DKG round and how many of them it used
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolState {
+    pub dkg_id: u64,
+    /// Mixed with the network private key to derive the nonces, so that a pool whose state was
+    /// lost never publishes the same nonces again
+    pub seed: [u8; 32],
+    /// Nonces with lower indices were published
+    pub published: u64,
+    /// Nonces with lower indices were used or skipped and never sign again
+    pub next_unused: u64,
+}
+
+impl PoolState {
+    fn new(dkg_id: u64) -> Self {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        Self {
+            dkg_id,
+            seed,
+            published: 0,
+            next_unused: 0,
+        }
+    }
+}
+
+/// Nonces a signer commits to ahead of signing, so that the coordinator can request signature
+/// shares without a nonce round trip. Pool nonces are derived from their index, and each index
+/// signs at most once: using a nonce consumes it and every nonce before it. If opened with a
+/// path, the pool is saved there before a nonce is published or used.
+#[derive(Debug)]
+pub struct NoncePool {
+    path: Option<PathBuf>,
+    state: PoolState,
+}
+
+impl Default for NoncePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NoncePool {
+    /// A pool kept in memory only, whose nonces are lost on restart
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            state: PoolState::new(0),
+        }
+    }
+
+    /// A pool which is saved to the path, loading the pool saved there before
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let state = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            PoolState::new(0)
+        };
+        Ok(Self {
+            path: Some(path),
+            state,
+        })
+    }
+
+    pub fn state(&self) -> &PoolState {
+        &self.state
+    }
+
+    /// Publish the next nonces for the DKG round, returning their indices. A new DKG round
+    /// starts a new pool.
+    pub fn publish(&mut self, dkg_id: u64, count: u32) -> Result<Range<u64>, Error> {
+        let mut state = if self.state.dkg_id == dkg_id {
+            self.state.clone()
+        } else {
+            info!("Starting nonce pool for DKG round #{}", dkg_id);
+            PoolState::new(dkg_id)
+        };
+        let start = state.published;
+        state.published += u64::from(count.min(MAX_NONCE_POOL_REQUEST));
+        self.save(&state)?;
+        self.state = state;
+        Ok(start..self.state.published)
+    }
+
+    /// Mark the nonce as used before it signs anything, failing if it or a later nonce was used
+    pub fn consume(&mut self, dkg_id: u64, index: u64) -> Result<(), Error> {
+        if dkg_id != self.state.dkg_id {
+            return Err(Error::WrongRound(self.state.dkg_id, dkg_id));
+        }
+        if index >= self.state.published {
+            return Err(Error::Unpublished(index));
+        }
+        if index < self.state.next_unused {
+            return Err(Error::Consumed(index, self.state.next_unused));
+        }
+        let mut state = self.state.clone();
+        state.next_unused = index + 1;
+        self.save(&state)?;
+        self.state = state;
+        Ok(())
+    }
+
+    /// The generator of the nonces with the given index
+    pub fn rng(&self, network_private_key: &Scalar, index: u64) -> StdRng {
+        let mut hasher = Sha256::new();
+        hasher.update("NONCE_POOL/".as_bytes());
+        hasher.update(network_private_key.to_bytes());
+        hasher.update(self.state.seed);
+        hasher.update(self.state.dkg_id.to_be_bytes());
+        hasher.update(index.to_be_bytes());
+        let mut seed = Zeroizing::new([0u8; 32]);
+        seed.copy_from_slice(hasher.finalize().as_slice());
+        StdRng::from_seed(*seed)
+    }
+
+    /// Write and sync a temporary file first so that neither a crash nor a power loss can lose a
+    /// used nonce or leave a truncated pool behind
+    fn save(&self, state: &PoolState) -> Result<(), Error> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let temp_path = path.with_extension("tmp");
+            let mut options = OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            let mut file = options.open(&temp_path)?;
+            file.write_all(serde_json::to_string_pretty(state)?.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp_path, path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nonces_sign_once_and_in_order() {
+        let mut pool = NoncePool::new();
+        assert_eq!(pool.publish(1, 4).unwrap(), 0..4);
+        assert_eq!(pool.publish(1, 2).unwrap(), 4..6);
+
+        assert!(matches!(pool.consume(1, 6), Err(Error::Unpublished(6))));
+        assert!(matches!(pool.consume(2, 0), Err(Error::WrongRound(1, 2))));
+        pool.consume(1, 0).unwrap();
+        assert!(matches!(pool.consume(1, 0), Err(Error::Consumed(0, 1))));
+        // Skipped nonces are never used
+        pool.consume(1, 3).unwrap();
+        assert!(matches!(pool.consume(1, 2), Err(Error::Consumed(2, 4))));
+        pool.consume(1, 4).unwrap();
+
+        // A new DKG round starts over with different nonces
+        let network_private_key = Scalar::random(&mut OsRng);
+        let mut rng = pool.rng(&network_private_key, 0);
+        let old_nonce = rng.next_u64();
+        assert_eq!(pool.publish(2, 1).unwrap(), 0..1);
+        assert_ne!(pool.rng(&network_private_key, 0).next_u64(), old_nonce);
+        pool.consume(2, 0).unwrap();
+    }
+
+    #[test]
+    fn used_nonces_stay_used_after_restart() {
+        let network_private_key = Scalar::random(&mut OsRng);
+        let path = std::env::temp_dir().join(format!("nonce_pool_{}.json", OsRng.next_u64()));
+
+        let mut pool = NoncePool::open(&path).unwrap();
+        pool.publish(7, 8).unwrap();
+        pool.consume(7, 2).unwrap();
+        let nonce = pool.rng(&network_private_key, 5).next_u64();
+
+        let mut restarted = NoncePool::open(&path).unwrap();
+        assert_eq!(restarted.state(), pool.state());
+        assert_eq!(restarted.rng(&network_private_key, 5).next_u64(), nonce);
+        assert!(matches!(
+            restarted.consume(7, 2),
+            Err(Error::Consumed(2, 3))
+        ));
+        assert_eq!(restarted.publish(7, 1).unwrap(), 8..9);
+        fs::remove_file(&path).unwrap();
+
+        // Nonces of a lost pool are never published again
+        let mut lost = NoncePool::open(&path).unwrap();
+        lost.publish(7, 8).unwrap();
+        assert_ne!(lost.rng(&network_private_key, 5).next_u64(), nonce);
+        fs::remove_file(path).unwrap();
+    }
+}
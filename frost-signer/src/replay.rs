@@ -0,0 +1,273 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{signing_round::MessageTypes, util::unix_time};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("JSON Error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("{0} expired at {1}")]
+    Expired(&'static str, u64),
+    #[error("{0} carries no expiry")]
+    MissingExpiry(&'static str),
+    #[error("Stale {0}: {1}")]
+    Stale(&'static str, String),
+    #[error("Out of order {0}: {1}")]
+    OutOfOrder(&'static str, String),
+}
+
+/// The highest ids a signer accepted, per message type for coordinator requests and per sender
+/// for messages of other signers
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeenIds {
+    pub dkg_begin: u64,
+    pub dkg_private_begin: u64,
+    /// `(sign_id, sign_nonce_id)` of the last nonce request
+    pub nonce_request: (u64, u64),
+    pub sign_share_request: u64,
+    #[serde(default)]
+    pub nonce_pool_request: u64,
+    /// Highest round ids of signer messages, keyed by message type and sender id, e.g. `DkgEnd:2`.
+    /// DKG messages are identified by `(dkg_id, 0)`, nonce responses by `(sign_id, sign_nonce_id)`
+    /// signature share responses by `(sign_id, 0)` and nonce pool responses by `(pool_id, 0)`.
+    pub senders: BTreeMap<String, (u64, u64)>,
+}
+
+/// Rejects replayed, out of order and expired protocol messages. If opened with a path, the ids
+/// it accepted are saved there, so that messages seen before a restart stay rejected.
+#[derive(Default, Debug)]
+pub struct ReplayGuard {
+    path: Option<PathBuf>,
+    seen: SeenIds,
+}
+
+impl ReplayGuard {
+    /// A guard whose accepted ids are kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A guard which saves its accepted ids to the path, loading any ids saved there before
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let seen = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            SeenIds::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            seen,
+        })
+    }
+
+    pub fn seen(&self) -> &SeenIds {
+        &self.seen
+    }
+
+    /// Check the message against the ids seen so far and the current time, recording its ids if it is accepted
+    pub fn check(&mut self, message: &MessageTypes) -> Result<(), Error> {
+        self.check_at(message, unix_time())
+    }
+
+    /// Check the message as of the given unix time in seconds
+    pub fn check_at(&mut self, message: &MessageTypes, now: u64) -> Result<(), Error> {
+        let mut seen = self.seen.clone();
+        match message {
+            MessageTypes::DkgBegin(msg) => {
+                check_expiry("DkgBegin", msg.expires_at, now)?;
+                if msg.dkg_id <= seen.dkg_begin {
+                    return Err(Error::Stale(
+                        "DkgBegin",
+                        format!("dkg_id {} after {}", msg.dkg_id, seen.dkg_begin),
+                    ));
+                }
+                seen.dkg_begin = msg.dkg_id;
+            }
+            MessageTypes::DkgPrivateBegin(msg) => {
+                check_expiry("DkgPrivateBegin", msg.expires_at, now)?;
+                if msg.dkg_id <= seen.dkg_private_begin {
+                    return Err(Error::Stale(
+                        "DkgPrivateBegin",
+                        format!("dkg_id {} after {}", msg.dkg_id, seen.dkg_private_begin),
+                    ));
+                }
+                if msg.dkg_id != seen.dkg_begin {
+                    return Err(Error::OutOfOrder(
+                        "DkgPrivateBegin",
+                        format!("dkg_id {} during DKG round {}", msg.dkg_id, seen.dkg_begin),
+                    ));
+                }
+                seen.dkg_private_begin = msg.dkg_id;
+            }
+            MessageTypes::NonceRequest(msg) => {
+                check_expiry("NonceRequest", msg.expires_at, now)?;
+                let ids = (msg.sign_id, msg.sign_nonce_id);
+                if ids <= seen.nonce_request {
+                    return Err(Error::Stale(
+                        "NonceRequest",
+                        format!(
+                            "sign_id {} sign_nonce_id {} after sign_id {} sign_nonce_id {}",
+                            ids.0, ids.1, seen.nonce_request.0, seen.nonce_request.1
+                        ),
+                    ));
+                }
+                seen.nonce_request = ids;
+            }
+            MessageTypes::SignShareRequest(msg) => {
+                check_expiry("SignShareRequest", msg.expires_at, now)?;
+                if msg.sign_id <= seen.sign_share_request {
+                    return Err(Error::Stale(
+                        "SignShareRequest",
+                        format!("sign_id {} after {}", msg.sign_id, seen.sign_share_request),
+                    ));
+                }
+                // Nonces published to the signers' pools are used without a nonce request, each once
+                if msg.sign_id != seen.nonce_request.0 && !msg.uses_nonce_pools() {
+                    return Err(Error::OutOfOrder(
+                        "SignShareRequest",
+                        format!(
+                            "sign_id {} after nonce request for sign_id {}",
+                            msg.sign_id, seen.nonce_request.0
+                        ),
+                    ));
+                }
+                seen.sign_share_request = msg.sign_id;
+            }
+            MessageTypes::NoncePoolRequest(msg) => {
+                check_expiry("NoncePoolRequest", msg.expires_at, now)?;
+                if msg.pool_id <= seen.nonce_pool_request {
+                    return Err(Error::Stale(
+                        "NoncePoolRequest",
+                        format!("pool_id {} after {}", msg.pool_id, seen.nonce_pool_request),
+                    ));
+                }
+                seen.nonce_pool_request = msg.pool_id;
+            }
+            // Resharing starts a new key epoch, so its ids follow those of DKG rounds
+            MessageTypes::ReshareBegin(msg) => {
+                check_expiry("ReshareBegin", msg.expires_at, now)?;
+                if msg.dkg_id <= seen.dkg_begin {
+                    return Err(Error::Stale(
+                        "ReshareBegin",
+                        format!("dkg_id {} after {}", msg.dkg_id, seen.dkg_begin),
+                    ));
+                }
+                seen.dkg_begin = msg.dkg_id;
+            }
+            MessageTypes::DkgPublicShare(msg) => {
+                check_dkg_sender(&mut seen, "DkgPublicShare", msg.party_id, msg.dkg_id)?
+            }
+            MessageTypes::DkgPrivateShares(msg) => {
+                check_dkg_sender(&mut seen, "DkgPrivateShares", msg.key_id, msg.dkg_id)?
+            }
+            MessageTypes::DkgEnd(msg) => {
+                check_dkg_sender(&mut seen, "DkgEnd", msg.signer_id, msg.dkg_id)?
+            }
+            MessageTypes::DkgPublicEnd(msg) => {
+                check_dkg_sender(&mut seen, "DkgPublicEnd", msg.signer_id, msg.dkg_id)?
+            }
+            MessageTypes::ReshareShares(msg) => {
+                check_dkg_sender(&mut seen, "ReshareShares", msg.signer_id, msg.dkg_id)?
+            }
+            MessageTypes::ReshareEnd(msg) => {
+                check_dkg_sender(&mut seen, "ReshareEnd", msg.signer_id, msg.dkg_id)?
+            }
+            MessageTypes::NonceResponse(msg) => check_sender(
+                &mut seen,
+                "NonceResponse",
+                msg.signer_id,
+                (msg.sign_id, msg.sign_nonce_id),
+            )?,
+            MessageTypes::SignShareResponse(msg) => check_sender(
+                &mut seen,
+                "SignShareResponse",
+                msg.signer_id,
+                (msg.sign_id, 0),
+            )?,
+            MessageTypes::NoncePoolResponse(msg) => check_sender(
+                &mut seen,
+                "NoncePoolResponse",
+                msg.signer_id,
+                (msg.pool_id, 0),
+            )?,
+        }
+        if seen != self.seen {
+            self.save(&seen)?;
+            self.seen = seen;
+        }
+        Ok(())
+    }
+
+    /// Write the ids to a temporary file first so that a crash can not leave a truncated file behind
+    fn save(&self, seen: &SeenIds) -> Result<(), Error> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let temp_path = path.with_extension("tmp");
+            fs::write(&temp_path, serde_json::to_string_pretty(seen)?)?;
+            fs::rename(&temp_path, path)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_expiry(message: &'static str, expires_at: u64, now: u64) -> Result<(), Error> {
+    if expires_at == 0 {
+        return Err(Error::MissingExpiry(message));
+    }
+    if now > expires_at {
+        return Err(Error::Expired(message, expires_at));
+    }
+    Ok(())
+}
+
+/// DKG messages of signers must belong to the current DKG round or a later one, and each sender
+/// sends one of each type per round
+fn check_dkg_sender(
+    seen: &mut SeenIds,
+    message: &'static str,
+    sender_id: u32,
+    dkg_id: u64,
+) -> Result<(), Error> {
+    if dkg_id < seen.dkg_begin {
+        return Err(Error::Stale(
+            message,
+            format!(
+                "dkg_id {} from {} during DKG round {}",
+                dkg_id, sender_id, seen.dkg_begin
+            ),
+        ));
+    }
+    check_sender(seen, message, sender_id, (dkg_id, 0))
+}
+
+/// Each sender sends one message of a type per round, so its round ids must increase
+fn check_sender(
+    seen: &mut SeenIds,
+    message: &'static str,
+    sender_id: u32,
+    ids: (u64, u64),
+) -> Result<(), Error> {
+    let highest = seen
+        .senders
+        .entry(format!("{message}:{sender_id}"))
+        .or_default();
+    if ids <= *highest {
+        return Err(Error::Stale(
+            message,
+            format!("ids {:?} from {} after {:?}", ids, sender_id, highest),
+        ));
+    }
+    *highest = ids;
+    Ok(())
+}
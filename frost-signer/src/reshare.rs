@@ -0,0 +1,230 @@
+use hashbrown::HashMap;
+use p256k1::{point::Point, scalar::Scalar};
+use rand_core::{CryptoRng, RngCore};
+use wsts::{common::PolyCommitment, schnorr::ID};
+
+/// The Lagrange coefficient at zero of the key id, counted from 0, among the key ids. Key ids are
+/// evaluated at their id counted from 1, like the private shares of DKG.
+pub fn lagrange_coefficient(key_id: u32, key_ids: &[u32]) -> Scalar {
+    let x_i = Scalar::from(key_id + 1);
+    let mut numerator = Scalar::from(1u32);
+    let mut denominator = Scalar::from(1u32);
+    for other in key_ids.iter().filter(|other| **other != key_id) {
+        let x_j = Scalar::from(other + 1);
+        numerator = numerator * x_j;
+        denominator = denominator * (x_j - x_i);
+    }
+    numerator / denominator
+}
+
+/// The part of the aggregate secret a dealer reshares: the Lagrange weighted sum of the private
+/// keys it holds among the key ids, counted from 0, of all dealers. The parts of all dealers add
+/// up to the aggregate secret, which no one ever computes.
+pub fn dealer_secret(private_keys: &HashMap<u32, Scalar>, dealer_key_ids: &[u32]) -> Scalar {
+    private_keys
+        .iter()
+        .filter(|(key_id, _)| dealer_key_ids.contains(key_id))
+        .fold(Scalar::new(), |sum, (key_id, private_key)| {
+            sum + lagrange_coefficient(*key_id, dealer_key_ids) * *private_key
+        })
+}
+
+/// The public key of the part of the aggregate secret a dealer holding the key ids reshares,
+/// given the public keys of all key ids, counted from 0
+pub fn dealer_public_key(
+    public_keys: &HashMap<u32, Point>,
+    key_ids: &[u32],
+    dealer_key_ids: &[u32],
+) -> Option<Point> {
+    key_ids.iter().try_fold(Point::default(), |sum, key_id| {
+        let public_key = public_keys.get(key_id)?;
+        Some(sum + &lagrange_coefficient(*key_id, dealer_key_ids) * public_key)
+    })
+}
+
+/// The public key of the key id, counted from 0, under the commitments of all parties
+pub fn key_public_key<'a>(
+    key_id: u32,
+    commitments: impl IntoIterator<Item = &'a PolyCommitment>,
+) -> Point {
+    commitments
+        .into_iter()
+        .fold(Point::default(), |sum, commitment| {
+            sum + eval_commitment(commitment, key_id)
+        })
+}
+
+/// Evaluate the committed polynomial in the exponent at the key id counted from 1
+fn eval_commitment(commitment: &PolyCommitment, key_id: u32) -> Point {
+    let x = Scalar::from(key_id + 1);
+    commitment
+        .A
+        .iter()
+        .rev()
+        .fold(Point::default(), |acc, a| &x * &acc + *a)
+}
+
+/// The shares of a dealer's secret for a new set of key ids
+pub struct Dealing {
+    /// Commitment to the polynomial whose constant term is the dealer's secret, under the party
+    /// id of the dealer
+    pub commitment: PolyCommitment,
+    /// The private share of each new key id, counted from 0
+    pub shares: HashMap<u32, Scalar>,
+}
+
+/// Share the secret to the key ids, counted from 0, with a random polynomial of degree
+/// `threshold - 1`, so that any `threshold` of the key ids can sign for it
+pub fn deal<RNG: RngCore + CryptoRng>(
+    party_id: u32,
+    secret: &Scalar,
+    threshold: u32,
+    key_ids: &[u32],
+    rng: &mut RNG,
+) -> Dealing {
+    let mut coefficients = vec![*secret];
+    coefficients.extend((1..threshold).map(|_| Scalar::random(rng)));
+    let shares = key_ids
+        .iter()
+        .map(|key_id| {
+            let x = Scalar::from(key_id + 1);
+            let share = coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::new(), |acc, a| acc * x + *a);
+            (*key_id, share)
+        })
+        .collect();
+    Dealing {
+        commitment: PolyCommitment {
+            id: ID::new(&Scalar::from(party_id), secret, rng),
+            A: coefficients.into_iter().map(Point::from).collect(),
+        },
+        shares,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::complaint::{check_commitment, is_consistent_share};
+
+    /// Shares of a random secret for key ids 0..5 with a threshold of 3
+    fn shared_secret() -> (Scalar, HashMap<u32, Scalar>) {
+        let secret = Scalar::random(&mut OsRng);
+        let dealing = deal(1, &secret, 3, &[0, 1, 2, 3, 4], &mut OsRng);
+        (secret, dealing.shares)
+    }
+
+    fn interpolate(shares: &HashMap<u32, Scalar>, key_ids: &[u32]) -> Scalar {
+        key_ids.iter().fold(Scalar::new(), |sum, key_id| {
+            sum + lagrange_coefficient(*key_id, key_ids) * shares[key_id]
+        })
+    }
+
+    /// Reshare from the dealers, holding key ids 0..3 between them, to key ids 0..4 with a threshold of 2
+    fn reshare(shares: &HashMap<u32, Scalar>) -> (Vec<Dealing>, HashMap<u32, Scalar>) {
+        let dealers: [&[u32]; 2] = [&[0, 1], &[2]];
+        let dealer_key_ids: Vec<u32> = dealers.concat();
+        let new_key_ids = [0, 1, 2, 3];
+        let dealings: Vec<Dealing> = dealers
+            .iter()
+            .enumerate()
+            .map(|(i, key_ids)| {
+                let held = key_ids
+                    .iter()
+                    .map(|key_id| (*key_id, shares[key_id]))
+                    .collect();
+                let secret = dealer_secret(&held, &dealer_key_ids);
+                deal(i as u32 + 1, &secret, 2, &new_key_ids, &mut OsRng)
+            })
+            .collect();
+        let new_shares = new_key_ids
+            .iter()
+            .map(|key_id| {
+                let share = dealings
+                    .iter()
+                    .fold(Scalar::new(), |sum, dealing| sum + dealing.shares[key_id]);
+                (*key_id, share)
+            })
+            .collect();
+        (dealings, new_shares)
+    }
+
+    #[test]
+    fn dealings_are_consistent_with_their_commitments() {
+        let secret = Scalar::random(&mut OsRng);
+        let dealing = deal(2, &secret, 3, &[0, 4, 7], &mut OsRng);
+        assert_eq!(check_commitment(2, 3, &dealing.commitment), Ok(()));
+        assert_eq!(dealing.commitment.A[0], Point::from(secret));
+        for (key_id, share) in &dealing.shares {
+            assert!(is_consistent_share(share, *key_id, &dealing.commitment));
+            assert_eq!(
+                eval_commitment(&dealing.commitment, *key_id),
+                Point::from(*share)
+            );
+        }
+    }
+
+    #[test]
+    fn any_threshold_of_shares_interpolates_the_secret() {
+        let (secret, shares) = shared_secret();
+        assert_eq!(interpolate(&shares, &[0, 1, 2]), secret);
+        assert_eq!(interpolate(&shares, &[4, 1, 3]), secret);
+        assert_ne!(interpolate(&shares, &[0, 1]), secret);
+    }
+
+    #[test]
+    fn resharing_keeps_the_secret_and_its_public_key() {
+        let (secret, shares) = shared_secret();
+        let (dealings, new_shares) = reshare(&shares);
+
+        let aggregate_public_key = dealings.iter().fold(Point::default(), |sum, dealing| {
+            sum + dealing.commitment.A[0]
+        });
+        assert_eq!(aggregate_public_key, Point::from(secret));
+
+        // Each dealer's part is checked against the public keys of the old key ids
+        let public_keys: HashMap<u32, Point> = shares
+            .iter()
+            .map(|(key_id, share)| (*key_id, Point::from(*share)))
+            .collect();
+        assert_eq!(
+            dealer_public_key(&public_keys, &[0, 1], &[0, 1, 2]),
+            Some(dealings[0].commitment.A[0])
+        );
+        assert_eq!(
+            dealer_public_key(&public_keys, &[2], &[0, 1, 2]),
+            Some(dealings[1].commitment.A[0])
+        );
+
+        // The new key ids hold shares of the same secret under the new threshold
+        let commitments: Vec<&PolyCommitment> =
+            dealings.iter().map(|dealing| &dealing.commitment).collect();
+        for (key_id, share) in &new_shares {
+            assert_eq!(
+                key_public_key(*key_id, commitments.iter().copied()),
+                Point::from(*share)
+            );
+        }
+        assert_eq!(interpolate(&new_shares, &[0, 3]), secret);
+        assert_eq!(interpolate(&new_shares, &[1, 2]), secret);
+    }
+
+    #[test]
+    fn old_shares_do_not_combine_with_new_ones() {
+        let (secret, shares) = shared_secret();
+        let (_, new_shares) = reshare(&shares);
+
+        // Key id 4 was dropped, its old share is of no use with the new shares
+        let mut mixed = new_shares.clone();
+        mixed.insert(4, shares[&4]);
+        assert_ne!(interpolate(&mixed, &[0, 4]), secret);
+        assert_ne!(interpolate(&mixed, &[0, 1, 4]), secret);
+
+        // Nor are fewer than the new threshold of new shares
+        assert_ne!(interpolate(&new_shares, &[2]), secret);
+    }
+}
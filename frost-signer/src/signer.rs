@@ -1,94 +1,405 @@
-use crate::config::{Config, PublicKeys};
+use crate::config::{Config, PrivateKey, PublicKeys, SignerSet};
+use crate::dkg_state::{self, Error as DkgStateError};
+use crate::journal::{Error as JournalError, Journal};
 use crate::net::{Error as HttpNetError, HttpNet, HttpNetListen, Message, Net, NetListen};
+use crate::nonce_pool::{Error as NoncePoolError, NoncePool};
+use crate::replay::{Error as ReplayError, ReplayGuard};
+use crate::signer_set::{Rollover, SignerSetSource};
 use crate::signing_round::{Error as SigningRoundError, MessageTypes, Signable, SigningRound};
+use crate::state_machine::States;
+use crate::status::{self, IdentityStatus, StatusHandle};
+use crate::validation::SignRequestValidator;
+use crate::wsts_version::WstsVersion;
 use p256k1::ecdsa;
-use std::sync::mpsc;
-use std::sync::mpsc::{Receiver, Sender};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{mpsc, Arc};
 use std::thread::spawn;
+use std::time::Instant;
 use std::{thread, time};
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
+
+/// How often an idle signer checks whether the round in progress outlived its deadline
+const STATE_CHECK_INTERVAL: time::Duration = time::Duration::from_secs(1);
 
 // on-disk format for frost save data
 #[derive(Clone)]
 pub struct Signer {
     pub config: Config,
     pub signer_id: u32,
+    pub validator: Option<Arc<dyn SignRequestValidator>>,
+    /// X coordinate of the aggregate public key registered on chain, which saved DKG state must match
+    pub on_chain_public_key: Option<[u8; 32]>,
+    /// Source of the signer sets to roll over to. The configured signer set is kept if unset.
+    pub signer_set_source: Option<Arc<dyn SignerSetSource>>,
 }
 
 impl Signer {
     pub fn new(config: Config, signer_id: u32) -> Self {
-        Self { config, signer_id }
+        Self {
+            config,
+            signer_id,
+            validator: None,
+            on_chain_public_key: None,
+            signer_set_source: None,
+        }
+    }
+
+    /// Only produce signature shares for requests the validator accepts
+    pub fn with_validator(mut self, validator: Arc<dyn SignRequestValidator>) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Refuse to start with saved DKG state for any other aggregate public key
+    pub fn with_on_chain_public_key(mut self, public_key_x: [u8; 32]) -> Self {
+        self.on_chain_public_key = Some(public_key_x);
+        self
+    }
+
+    /// Roll over to the signer sets of the source as they come, once no round is in progress
+    pub fn with_signer_set_source(mut self, source: Arc<dyn SignerSetSource>) -> Self {
+        self.signer_set_source = Some(source);
+        self
     }
 
     pub fn start_p2p_sync(&mut self) -> Result<(), Error> {
         let public_keys = self.config.public_keys.clone();
         let coordinator_public_key = self.config.coordinator_public_key;
+        let wsts_version = self.config.wsts_version;
 
         //Create http relay
         let net: HttpNet = HttpNet::new(self.config.http_relay_url.clone());
         let net_queue = HttpNetListen::new(net.clone(), vec![]);
+        let replay_guard = match &self.config.replay_state_path {
+            Some(path) => ReplayGuard::open(path)?,
+            None => ReplayGuard::new(),
+        };
+        let status = StatusHandle::default();
+        if let Some(address) = &self.config.status_listen {
+            status::serve(address, status.clone()).map_err(Error::StatusError)?;
+        }
         // thread coordination
         let (tx, rx): (Sender<Message>, Receiver<Message>) = mpsc::channel();
+        let (signer_sets_tx, signer_sets_rx) = mpsc::channel();
+        let (rollovers_tx, rollovers_rx) = mpsc::channel();
+
+        // follow the signer set source without blocking the signing rounds on it
+        if let Some(source) = self.signer_set_source.clone() {
+            let rollover_status = status.clone();
+            spawn(move || rollover_loop(source, rollovers_tx, rollover_status));
+        }
 
         // start p2p sync
         let id = self.signer_id;
-        spawn(move || poll_loop(net_queue, tx, id, public_keys, coordinator_public_key));
+        let poll_status = status.clone();
+        spawn(move || {
+            poll_loop(
+                net_queue,
+                tx,
+                id,
+                public_keys,
+                coordinator_public_key,
+                wsts_version,
+                replay_guard,
+                poll_status,
+                signer_sets_rx,
+            )
+        });
 
         // listen to p2p messages
-        self.start_signing_round(&net, rx)
+        let result = self.start_signing_round(&net, rx, &status, signer_sets_tx, rollovers_rx);
+        if let Err(e) = &result {
+            status.error(e);
+        }
+        result
     }
 
-    fn start_signing_round(&self, net: &HttpNet, rx: Receiver<Message>) -> Result<(), Error> {
-        let network_private_key = self.config.network_private_key;
+    /// The signer slots served by the process, each as a signer of its own. Further identities
+    /// keep their journal and nonce pool at the configured paths suffixed with their signer id.
+    pub fn identities(&self) -> Vec<Signer> {
+        let mut primary = self.clone();
+        primary.config.identities = vec![];
+        let mut identities = vec![primary];
+        for identity in &self.config.identities {
+            let mut config = self.config.clone();
+            config.identities = vec![];
+            config.network_private_key = identity.network_private_key.clone();
+            config.journal_path = config
+                .journal_path
+                .map(|path| identity_path(&path, identity.signer_id));
+            config.nonce_pool_path = config
+                .nonce_pool_path
+                .map(|path| identity_path(&path, identity.signer_id));
+            identities.push(Signer {
+                config,
+                signer_id: identity.signer_id,
+                validator: self.validator.clone(),
+                on_chain_public_key: self.on_chain_public_key,
+                signer_set_source: None,
+            });
+        }
+        identities
+    }
+
+    /// The signing round of the identity, restored from its saved state
+    fn signing_round(&self) -> Result<SigningRound, Error> {
         let mut round = SigningRound::from(self);
+        if let Some(journal_path) = &self.config.journal_path {
+            round.journal = Some(Journal::open(journal_path)?);
+        }
+        round.validator = self.validator.clone();
+        if let Some(path) = &self.config.nonce_pool_path {
+            round.nonce_pool = NoncePool::open(path)?;
+        }
+        if let Some(directory) = &self.config.dkg_state_directory {
+            round.dkg_state_directory = Some(directory.clone());
+            let state = self.config.network_private_key.with_scalar(|key| {
+                dkg_state::load_latest(
+                    directory,
+                    self.signer_id,
+                    key,
+                    self.on_chain_public_key.as_ref(),
+                )
+            })?;
+            match state {
+                Some(state) => {
+                    state.check_wsts_version(self.config.wsts_version)?;
+                    round.load_dkg_state(&state);
+                }
+                None if self.on_chain_public_key.is_some() => {
+                    warn!("No saved DKG state for signer_id {}. Unable to sign for the on-chain aggregate public key until a new DKG round completes", self.signer_id);
+                }
+                None => {}
+            }
+        }
+        Ok(round)
+    }
+
+    /// The signing rounds of the identities in the signer set, with the network private key each
+    /// signs its messages with
+    fn signing_rounds(
+        &self,
+        status: &StatusHandle,
+    ) -> Result<Vec<(PrivateKey, SigningRound)>, Error> {
+        let mut rounds = vec![];
+        for identity in self.identities() {
+            if !identity
+                .config
+                .public_keys
+                .signers
+                .contains_key(&identity.signer_id)
+            {
+                warn!(
+                    "Signer id {} is not in the signer set and sits it out",
+                    identity.signer_id
+                );
+                continue;
+            }
+            rounds.push((
+                identity.config.network_private_key.clone(),
+                identity.signing_round()?,
+            ));
+        }
+        status.set_identities(
+            rounds
+                .iter()
+                .map(|(_, round)| IdentityStatus::from(round))
+                .collect(),
+        );
+        Ok(rounds)
+    }
+
+    /// The signer with the new signer set and its signing rounds, restarted from their saved DKG
+    /// state
+    fn roll_over(
+        &self,
+        rollover: Rollover,
+        status: &StatusHandle,
+    ) -> Result<(Signer, Vec<(PrivateKey, SigningRound)>), Error> {
+        let signer_set = rollover.signer_set;
+        info!(
+            "Rolling over to a signer set of {} signers with {} of {} keys",
+            signer_set.public_keys.signers.len(),
+            signer_set.keys_threshold,
+            signer_set.public_keys.key_ids.len()
+        );
+        let mut signer = self.clone();
+        signer.config.set_signer_set(signer_set);
+        signer.on_chain_public_key = rollover.on_chain_public_key;
+        let rounds = signer.signing_rounds(status)?;
+        Ok((signer, rounds))
+    }
+
+    /// Take the latest signer set received from the signer set source, and roll over to it
+    /// between rounds, so that no round mixes the signer sets. If the signing rounds of the new
+    /// signer set fail to start, the signer keeps running with the old one until the source hands
+    /// out the new one again.
+    fn check_rollover(
+        &mut self,
+        rounds: &mut Vec<(PrivateKey, SigningRound)>,
+        pending_rollover: &mut Option<Rollover>,
+        rollovers: &Receiver<Rollover>,
+        signer_sets: &Sender<SignerSet>,
+        status: &StatusHandle,
+    ) -> Result<(), Error> {
+        while let Ok(rollover) = rollovers.try_recv() {
+            *pending_rollover = Some(rollover);
+        }
+        if !rounds.iter().all(|(_, round)| round.state == States::Idle) {
+            return Ok(());
+        }
+        let Some(rollover) = pending_rollover.take() else {
+            return Ok(());
+        };
+        match self.roll_over(rollover, status) {
+            Ok((signer, new_rounds)) => {
+                let signer_set = signer.config.signer_set();
+                signer_sets
+                    .send(signer_set.clone())
+                    .map_err(|_| Error::SendError)?;
+                if let Some(source) = &signer.signer_set_source {
+                    source.rolled_over(&signer_set);
+                }
+                *self = signer;
+                *rounds = new_rounds;
+            }
+            Err(e) => {
+                warn!("Failed to roll over to the new signer set: {}", e);
+                status.error(format!("Failed to roll over to the new signer set: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    fn start_signing_round(
+        &mut self,
+        net: &HttpNet,
+        rx: Receiver<Message>,
+        status: &StatusHandle,
+        signer_sets: Sender<SignerSet>,
+        rollovers: Receiver<Rollover>,
+    ) -> Result<(), Error> {
+        let mut rounds = self.signing_rounds(status)?;
+        let mut pending_rollover = None;
         loop {
-            // Retreive a message from coordinator
-            let inbound = rx.recv()?; // blocking
-            let outbounds = round.process(inbound.msg)?;
-            for out in outbounds {
-                let msg = Message {
-                    msg: out.clone(),
-                    sig: match out {
-                        MessageTypes::DkgBegin(msg) | MessageTypes::DkgPrivateBegin(msg) => msg
-                            .sign(&network_private_key)
-                            .expect("failed to sign DkgBegin")
-                            .to_vec(),
-                        MessageTypes::DkgEnd(msg) | MessageTypes::DkgPublicEnd(msg) => msg
-                            .sign(&network_private_key)
-                            .expect("failed to sign DkgEnd")
-                            .to_vec(),
-                        MessageTypes::DkgPublicShare(msg) => msg
-                            .sign(&network_private_key)
-                            .expect("failed to sign DkgPublicShare")
-                            .to_vec(),
-                        MessageTypes::DkgPrivateShares(msg) => msg
-                            .sign(&network_private_key)
-                            .expect("failed to sign DkgPrivateShare")
-                            .to_vec(),
-                        MessageTypes::NonceRequest(msg) => msg
-                            .sign(&network_private_key)
-                            .expect("failed to sign NonceRequest")
-                            .to_vec(),
-                        MessageTypes::NonceResponse(msg) => msg
-                            .sign(&network_private_key)
-                            .expect("failed to sign NonceResponse")
-                            .to_vec(),
-                        MessageTypes::SignShareRequest(msg) => msg
-                            .sign(&network_private_key)
-                            .expect("failed to sign SignShareRequest")
-                            .to_vec(),
-                        MessageTypes::SignShareResponse(msg) => msg
-                            .sign(&network_private_key)
-                            .expect("failed to sign SignShareResponse")
-                            .to_vec(),
-                    },
-                };
-                net.send_message(msg)?;
+            // Retreive a message from coordinator, waking up regularly to abandon stale rounds
+            let inbound = match rx.recv_timeout(STATE_CHECK_INTERVAL) {
+                Ok(inbound) => inbound,
+                Err(RecvTimeoutError::Timeout) => {
+                    for (_, round) in &mut rounds {
+                        let state = format!("{:?}", round.state);
+                        if round.expire_state(Instant::now())? {
+                            status.error(format!(
+                                "Signer #{} abandoned round #{} in state {}",
+                                round.signer.signer_id, round.dkg_id, state
+                            ));
+                        }
+                        status.set_identity(IdentityStatus::from(&*round));
+                    }
+                    self.check_rollover(
+                        &mut rounds,
+                        &mut pending_rollover,
+                        &rollovers,
+                        &signer_sets,
+                        status,
+                    )?;
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(mpsc::RecvError.into()),
+            };
+            // Every identity sees every message, including those of the other local identities
+            for (network_private_key, round) in &mut rounds {
+                let outbound = round.process(inbound.msg.clone());
+                status.set_identity(IdentityStatus::from(&*round));
+                for out in outbound? {
+                    net.send_message(sign_message(out, network_private_key))?;
+                    status.message_sent();
+                }
             }
+            self.check_rollover(
+                &mut rounds,
+                &mut pending_rollover,
+                &rollovers,
+                &signer_sets,
+                status,
+            )?;
         }
     }
 }
 
+/// Sign an outbound message with the network private key of the identity sending it
+fn sign_message(out: MessageTypes, network_private_key: &PrivateKey) -> Message {
+    Message {
+        msg: out.clone(),
+        sig: match out {
+            MessageTypes::DkgBegin(msg) | MessageTypes::DkgPrivateBegin(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign DkgBegin")
+                .to_vec(),
+            MessageTypes::DkgEnd(msg)
+            | MessageTypes::DkgPublicEnd(msg)
+            | MessageTypes::ReshareEnd(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign DkgEnd")
+                .to_vec(),
+            MessageTypes::DkgPublicShare(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign DkgPublicShare")
+                .to_vec(),
+            MessageTypes::DkgPrivateShares(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign DkgPrivateShare")
+                .to_vec(),
+            MessageTypes::NonceRequest(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign NonceRequest")
+                .to_vec(),
+            MessageTypes::NonceResponse(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign NonceResponse")
+                .to_vec(),
+            MessageTypes::SignShareRequest(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign SignShareRequest")
+                .to_vec(),
+            MessageTypes::SignShareResponse(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign SignShareResponse")
+                .to_vec(),
+            MessageTypes::NoncePoolRequest(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign NoncePoolRequest")
+                .to_vec(),
+            MessageTypes::NoncePoolResponse(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign NoncePoolResponse")
+                .to_vec(),
+            MessageTypes::ReshareBegin(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign ReshareBegin")
+                .to_vec(),
+            MessageTypes::ReshareShares(msg) => network_private_key
+                .sign(&msg)
+                .expect("failed to sign ReshareShares")
+                .to_vec(),
+        },
+    }
+}
+
+/// The path with the signer id appended to its file name, e.g. `nonce-pool-3.json`
+fn identity_path(path: &Path, signer_id: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, signer_id, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, signer_id),
+    };
+    path.with_file_name(file_name)
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Http Network Error: {0}")]
@@ -102,6 +413,21 @@ pub enum Error {
 
     #[error("Failed to send message")]
     SendError,
+
+    #[error("Journal Error: {0}")]
+    JournalError(#[from] JournalError),
+
+    #[error("DKG State Error: {0}")]
+    DkgStateError(#[from] DkgStateError),
+
+    #[error("Replay Guard Error: {0}")]
+    ReplayError(#[from] ReplayError),
+
+    #[error("Nonce Pool Error: {0}")]
+    NoncePoolError(#[from] NoncePoolError),
+
+    #[error("Status Error: {0}")]
+    StatusError(std::io::Error),
 }
 
 impl From<mpsc::SendError<Message>> for Error {
@@ -110,18 +436,29 @@ impl From<mpsc::SendError<Message>> for Error {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn poll_loop(
     mut net: HttpNetListen,
     tx: Sender<Message>,
     id: u32,
-    public_keys: PublicKeys,
-    coordinator_public_key: ecdsa::PublicKey,
+    mut public_keys: PublicKeys,
+    mut coordinator_public_key: ecdsa::PublicKey,
+    wsts_version: WstsVersion,
+    mut replay_guard: ReplayGuard,
+    status: StatusHandle,
+    signer_sets: Receiver<SignerSet>,
 ) -> Result<(), Error> {
     const BASE_TIMEOUT: u64 = 2;
     const MAX_TIMEOUT: u64 = 128;
     let mut timeout = BASE_TIMEOUT;
     loop {
+        // Verify messages against the signer set the signing rounds run with
+        while let Ok(signer_set) = signer_sets.try_recv() {
+            public_keys = signer_set.public_keys;
+            coordinator_public_key = signer_set.coordinator_public_key;
+        }
         net.poll(id);
+        status.set_relay_connected(net.net.is_connected());
         match net.next_message() {
             None => {
                 timeout = if timeout == 0 {
@@ -134,9 +471,24 @@ fn poll_loop(
             }
             Some(m) => {
                 timeout = 0;
-                if verify_msg(&m, &public_keys, &coordinator_public_key) {
-                    // Only send verified messages down the pipe
-                    tx.send(m)?;
+                if verify_msg(&m, &public_keys, &coordinator_public_key, wsts_version) {
+                    // Only send verified messages that are neither replayed nor expired down the pipe
+                    match replay_guard.check(&m.msg) {
+                        Ok(()) => {
+                            status.message_received(is_from_coordinator(&m.msg));
+                            tx.send(m)?
+                        }
+                        Err(e) => {
+                            warn!("Dropping message: {}", e);
+                            status.message_received(false);
+                            status.message_dropped();
+                            status.error(format!("Dropped message: {}", e));
+                        }
+                    }
+                } else {
+                    status.message_received(false);
+                    status.message_dropped();
+                    status.error("Dropped message with an invalid signature or unknown sender");
                 }
             }
         };
@@ -144,10 +496,51 @@ fn poll_loop(
     }
 }
 
+/// Ask the signer set source for the signer set to roll over to about once a second, and send it
+/// to the signing rounds
+fn rollover_loop(
+    source: Arc<dyn SignerSetSource>,
+    rollovers: Sender<Rollover>,
+    status: StatusHandle,
+) {
+    loop {
+        match source.rollover() {
+            Ok(Some(rollover)) => {
+                // The signing rounds ended
+                if rollovers.send(rollover).is_err() {
+                    return;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("{}", e);
+                status.error(e);
+            }
+        }
+        thread::sleep(STATE_CHECK_INTERVAL);
+    }
+}
+
+/// Whether the message is one the coordinator signs
+fn is_from_coordinator(msg: &MessageTypes) -> bool {
+    matches!(
+        msg,
+        MessageTypes::DkgBegin(_)
+            | MessageTypes::DkgPrivateBegin(_)
+            | MessageTypes::NonceRequest(_)
+            | MessageTypes::SignShareRequest(_)
+            | MessageTypes::NoncePoolRequest(_)
+            | MessageTypes::ReshareBegin(_)
+    )
+}
+
+/// Check the signature of the message. DKG shares are signed by the key of their party, which is
+/// a key id in v1 and a signer in v2.
 fn verify_msg(
     m: &Message,
     public_keys: &PublicKeys,
     coordinator_public_key: &ecdsa::PublicKey,
+    wsts_version: WstsVersion,
 ) -> bool {
     match &m.msg {
         MessageTypes::DkgBegin(msg) | MessageTypes::DkgPrivateBegin(msg) => {
@@ -156,7 +549,9 @@ fn verify_msg(
                 return false;
             }
         }
-        MessageTypes::DkgEnd(msg) | MessageTypes::DkgPublicEnd(msg) => {
+        MessageTypes::DkgEnd(msg)
+        | MessageTypes::DkgPublicEnd(msg)
+        | MessageTypes::ReshareEnd(msg) => {
             if let Some(public_key) = public_keys.signers.get(&msg.signer_id) {
                 debug!("HERE WE GO");
                 debug!("{:?}", public_key.to_bytes());
@@ -174,7 +569,7 @@ fn verify_msg(
             }
         }
         MessageTypes::DkgPublicShare(msg) => {
-            if let Some(public_key) = public_keys.key_ids.get(&msg.party_id) {
+            if let Some(public_key) = wsts_version.party_public_key(public_keys, msg.party_id) {
                 if !msg.verify(&m.sig, public_key) {
                     warn!("Received a DkgPublicShare message with an invalid signature.");
                     return false;
@@ -188,11 +583,11 @@ fn verify_msg(
             }
         }
         MessageTypes::DkgPrivateShares(msg) => {
-            // Private shares have key IDs from [0, N) to reference IDs from [1, N]
+            // Private shares have party IDs from [0, N) to reference IDs from [1, N]
             // in Frost V4 to enable easy indexing hence ID + 1
             // TODO: Once Frost V5 is released, this off by one adjustment will no longer be required
             let key_id = msg.key_id + 1;
-            if let Some(public_key) = public_keys.key_ids.get(&key_id) {
+            if let Some(public_key) = wsts_version.party_public_key(public_keys, key_id) {
                 if !msg.verify(&m.sig, public_key) {
                     warn!("Received a DkgPrivateShares message with an invalid signature from key_id {} key {}", msg.key_id, &public_key);
                     return false;
@@ -245,6 +640,47 @@ fn verify_msg(
                 return false;
             }
         }
+        MessageTypes::NoncePoolRequest(msg) => {
+            if !msg.verify(&m.sig, coordinator_public_key) {
+                warn!("Received a NoncePoolRequest message with an invalid signature.");
+                return false;
+            }
+        }
+        MessageTypes::NoncePoolResponse(msg) => {
+            if let Some(public_key) = public_keys.signers.get(&msg.signer_id) {
+                if !msg.verify(&m.sig, public_key) {
+                    warn!("Received a NoncePoolResponse message with an invalid signature.");
+                    return false;
+                }
+            } else {
+                warn!(
+                    "Received a NoncePoolResponse message with an unknown id: {}",
+                    msg.signer_id
+                );
+                return false;
+            }
+        }
+        MessageTypes::ReshareBegin(msg) => {
+            if !msg.verify(&m.sig, coordinator_public_key) {
+                warn!("Received a ReshareBegin message with an invalid signature.");
+                return false;
+            }
+        }
+        // Dealers sign their shares with their signer key, whatever the protocol version
+        MessageTypes::ReshareShares(msg) => {
+            if let Some(public_key) = public_keys.signers.get(&msg.signer_id) {
+                if !msg.verify(&m.sig, public_key) {
+                    warn!("Received a ReshareShares message with an invalid signature.");
+                    return false;
+                }
+            } else {
+                warn!(
+                    "Received a ReshareShares message with an unknown id: {}",
+                    msg.signer_id
+                );
+                return false;
+            }
+        }
     }
     true
 }
@@ -269,7 +705,17 @@ mod test {
         },
     };
 
-    use super::verify_msg;
+    use super::{sign_message, verify_msg, Signer};
+    use crate::config::SignerSet;
+    use crate::config::{Config, SignerIdentity};
+    use crate::dkg_state::{self, DkgState};
+    use crate::signer_set::{Error as SignerSetError, Rollover, SignerSetSource};
+    use crate::status::StatusHandle;
+    use crate::wsts_version::WstsVersion;
+    use rand_core::RngCore;
+    use std::path::PathBuf;
+    use std::sync::{mpsc, Arc, Mutex};
+    use wsts::{traits::Signer as SignerTrait, v1, Point};
 
     fn generate_key_pair() -> (Scalar, PublicKey) {
         // Generate a secret and public key
@@ -315,7 +761,11 @@ mod test {
     fn verify_msg_dkg_begin() {
         let config = TestConfig::new();
         //Test DkgBegin && DkgPrivateBegin branch
-        let inner = DkgBegin { dkg_id: 0 };
+        let inner = DkgBegin {
+            dkg_id: 0,
+            expires_at: 0,
+            excluded_signers: vec![],
+        };
         let sig = inner.sign(&config.coordinator_sec_key).unwrap();
         // DkgBegin
         let msg = MessageTypes::DkgBegin(inner.clone());
@@ -331,12 +781,14 @@ mod test {
         assert!(verify_msg(
             &dkg_begin,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
         assert!(verify_msg(
             &dkg_private_begin,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         // Check with incorrect public key
@@ -344,11 +796,13 @@ mod test {
             &dkg_begin,
             &config.public_keys,
             &config.public_keys.key_ids.get(&1).unwrap(),
+            WstsVersion::V1,
         ));
         assert!(!verify_msg(
             &dkg_private_begin,
             &config.public_keys,
             &config.public_keys.key_ids.get(&1).unwrap(),
+            WstsVersion::V1,
         ));
     }
 
@@ -376,13 +830,15 @@ mod test {
         assert!(verify_msg(
             &dkg_end,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         assert!(verify_msg(
             &dkg_public_end,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         //Let us sign with the wrong sec key...
@@ -397,13 +853,15 @@ mod test {
         assert!(!verify_msg(
             &dkg_end,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         assert!(!verify_msg(
             &dkg_public_end,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -429,12 +887,14 @@ mod test {
         assert!(!verify_msg(
             &dkg_end,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
         assert!(!verify_msg(
             &dkg_public_end,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -462,7 +922,8 @@ mod test {
         assert!(verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         // Let's sign with the wrong sec key...
@@ -473,7 +934,8 @@ mod test {
         assert!(!verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -498,7 +960,41 @@ mod test {
         assert!(!verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
+        ));
+    }
+
+    #[test]
+    fn verify_msg_dkg_public_share_v2_party_is_signer() {
+        let config = TestConfig::new();
+        let inner = DkgPublicShare {
+            dkg_id: 0,
+            dkg_public_id: 0,
+            party_id: 2,
+            public_share: PolyCommitment {
+                id: ID::new(&Scalar::new(), &Scalar::new(), &mut OsRng),
+                A: vec![],
+            },
+        };
+        let sig = inner.sign(&config.sec_keys[1]).unwrap();
+        let message = Message {
+            msg: MessageTypes::DkgPublicShare(inner),
+            sig,
+        };
+
+        // Party 2 is signer 2 in v2, but key id 2 of signer 1 in v1
+        assert!(verify_msg(
+            &message,
+            &config.public_keys,
+            &config.coordinator_pub_key,
+            WstsVersion::V2,
+        ));
+        assert!(!verify_msg(
+            &message,
+            &config.public_keys,
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -508,6 +1004,7 @@ mod test {
         let inner = DkgPrivateShares {
             dkg_id: 0,
             key_id: 1,
+            ephemeral_key: Point::default(),
             private_shares: HashMap::new(),
         };
         let sig = inner.sign(&config.sec_keys[0]).unwrap();
@@ -520,7 +1017,8 @@ mod test {
         assert!(verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         // Let us sign with the wrong sec key...
@@ -530,7 +1028,8 @@ mod test {
         assert!(!verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -540,6 +1039,7 @@ mod test {
         let inner = DkgPrivateShares {
             dkg_id: 0,
             key_id: 10, // we don't know this key id...
+            ephemeral_key: Point::default(),
             private_shares: HashMap::new(),
         };
         let sig = inner.sign(&config.sec_keys[0]).unwrap();
@@ -549,7 +1049,8 @@ mod test {
         assert!(!verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -561,6 +1062,8 @@ mod test {
             dkg_id: 0,
             sign_id: 0,
             sign_nonce_id: 0,
+            expires_at: 0,
+            message_count: 1,
         };
 
         let sig = inner.sign(&config.coordinator_sec_key).unwrap();
@@ -570,13 +1073,15 @@ mod test {
         assert!(verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
         // Let's check with the wrong pub key
         assert!(!verify_msg(
             &message,
             &config.public_keys,
             &config.public_keys.key_ids.get(&1).unwrap(),
+            WstsVersion::V1,
         ));
     }
 
@@ -590,6 +1095,8 @@ mod test {
             signer_id: 1,
             key_ids: vec![],
             nonces: vec![],
+            pool_index: None,
+            wsts_version: Default::default(),
         };
         let sig = inner.sign(&config.sec_keys[0]).unwrap();
         let msg = MessageTypes::NonceResponse(inner.clone());
@@ -601,7 +1108,8 @@ mod test {
         assert!(verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         // Let's sign with the wrong sec key...
@@ -611,7 +1119,8 @@ mod test {
         assert!(!verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -625,6 +1134,8 @@ mod test {
             signer_id: 10, // We don't have 10 signers...
             key_ids: vec![],
             nonces: vec![],
+            pool_index: None,
+            wsts_version: Default::default(),
         };
         let sig = inner.sign(&config.sec_keys[0]).unwrap();
         let msg = MessageTypes::NonceResponse(inner);
@@ -633,7 +1144,8 @@ mod test {
         assert!(!verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -654,8 +1166,15 @@ mod test {
                     D: Default::default(),
                     E: Default::default(),
                 }],
+                pool_index: None,
+                wsts_version: Default::default(),
             }],
             message: vec![],
+            signature_type: Default::default(),
+            expires_at: 0,
+            context: None,
+            nonce_index: 0,
+            batch: vec![],
         };
         let sig = inner.sign(&config.coordinator_sec_key).unwrap();
         let msg = MessageTypes::SignShareRequest(inner);
@@ -664,14 +1183,16 @@ mod test {
         assert!(verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         // Let's check the wrong pub key...
         assert!(!verify_msg(
             &message,
             &config.public_keys,
-            &config.public_keys.key_ids.get(&1).unwrap()
+            &config.public_keys.key_ids.get(&1).unwrap(),
+            WstsVersion::V1,
         ));
     }
 
@@ -696,7 +1217,8 @@ mod test {
         assert!(verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
 
         // Let's sign with the wrong sec key...
@@ -705,7 +1227,8 @@ mod test {
         assert!(!verify_msg(
             &message,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
 
@@ -726,7 +1249,159 @@ mod test {
         assert!(!verify_msg(
             &sign_share_response,
             &config.public_keys,
-            &config.coordinator_pub_key
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
+        ));
+    }
+
+    #[test]
+    fn identities_keep_their_own_key_and_state_paths() {
+        let config = TestConfig::new();
+        let mut signer_config = Config::new(
+            3,
+            config.coordinator_pub_key,
+            config.public_keys.clone(),
+            HashMap::from([(1, vec![1, 2]), (2, vec![3, 4])]),
+            config.sec_keys[0].into(),
+            "http://127.0.0.1:1".to_string(),
+        );
+        signer_config.nonce_pool_path = Some(PathBuf::from("state/nonce-pool.json"));
+        signer_config.journal_path = Some(PathBuf::from("state/journal"));
+        signer_config.identities = vec![SignerIdentity {
+            signer_id: 2,
+            network_private_key: config.sec_keys[1].into(),
+        }];
+
+        let identities = Signer::new(signer_config, 1).identities();
+        assert_eq!(identities.len(), 2);
+        let (primary, other) = (&identities[0], &identities[1]);
+        assert_eq!(primary.signer_id, 1);
+        assert_eq!(
+            primary.config.network_private_key.public_key(),
+            Point::from(&config.sec_keys[0])
+        );
+        assert_eq!(
+            primary.config.nonce_pool_path,
+            Some(PathBuf::from("state/nonce-pool.json"))
+        );
+        assert_eq!(other.signer_id, 2);
+        assert_eq!(
+            other.config.network_private_key.public_key(),
+            Point::from(&config.sec_keys[1])
+        );
+        assert_eq!(
+            other.config.nonce_pool_path,
+            Some(PathBuf::from("state/nonce-pool-2.json"))
+        );
+        assert_eq!(
+            other.config.journal_path,
+            Some(PathBuf::from("state/journal-2"))
+        );
+        assert!(identities
+            .iter()
+            .all(|identity| identity.config.identities.is_empty()));
+
+        // Each identity signs its messages with its own key
+        let end = DkgEnd {
+            dkg_id: 1,
+            signer_id: 2,
+            status: DkgStatus::Success,
+        };
+        let message = sign_message(MessageTypes::DkgEnd(end), &other.config.network_private_key);
+        assert!(verify_msg(
+            &message,
+            &config.public_keys,
+            &config.coordinator_pub_key,
+            WstsVersion::V1,
         ));
     }
+
+    /// Records the signer sets the signer rolled over to
+    #[derive(Default)]
+    struct RecordingSource(Mutex<Vec<SignerSet>>);
+
+    impl SignerSetSource for RecordingSource {
+        fn rollover(&self) -> Result<Option<Rollover>, SignerSetError> {
+            Ok(None)
+        }
+
+        fn rolled_over(&self, signer_set: &SignerSet) {
+            self.0.lock().unwrap().push(signer_set.clone());
+        }
+    }
+
+    #[test]
+    fn failed_rollovers_keep_the_old_signer_set() {
+        let config = TestConfig::new();
+        let mut signer_config = Config::new(
+            3,
+            config.coordinator_pub_key,
+            config.public_keys.clone(),
+            HashMap::from([(1, vec![1, 2]), (2, vec![3, 4])]),
+            config.sec_keys[0].into(),
+            "http://127.0.0.1:1".to_string(),
+        );
+        let directory = std::env::temp_dir().join(format!("dkg_state_{}", OsRng.next_u64()));
+        signer_config.dkg_state_directory = Some(directory.clone());
+        let mut party = v1::Signer::new(1, &[0, 1], 4, 3, &mut OsRng).save();
+        party.group_key = Point::from(Scalar::random(&mut OsRng));
+        let group_key_x = party.group_key.x().to_bytes();
+        let state = DkgState::new(1, 1, WstsVersion::V1, party);
+        dkg_state::save(&directory, &config.sec_keys[0], &state).unwrap();
+
+        let source = Arc::new(RecordingSource::default());
+        let mut signer = Signer::new(signer_config, 1).with_signer_set_source(source.clone());
+        let status = StatusHandle::default();
+        let mut rounds = signer.signing_rounds(&status).unwrap();
+        let (rollovers_tx, rollovers_rx) = mpsc::channel();
+        let (signer_sets_tx, signer_sets_rx) = mpsc::channel();
+        let mut signer_set = signer.config.signer_set();
+        signer_set.keys_threshold = 2;
+
+        // No saved state matches the registered key
+        rollovers_tx
+            .send(Rollover {
+                signer_set: signer_set.clone(),
+                on_chain_public_key: Some([0; 32]),
+            })
+            .unwrap();
+        let mut pending_rollover = None;
+        signer
+            .check_rollover(
+                &mut rounds,
+                &mut pending_rollover,
+                &rollovers_rx,
+                &signer_sets_tx,
+                &status,
+            )
+            .unwrap();
+        assert_eq!(signer.config.keys_threshold, 3);
+        assert_eq!(rounds[0].1.key_epoch, Some(1));
+        assert!(signer_sets_rx.try_recv().is_err());
+        assert_eq!(status.report().recent_errors.len(), 1);
+        // The source hands the signer set out again until the signer rolls over to it
+        assert!(source.0.lock().unwrap().is_empty());
+
+        rollovers_tx
+            .send(Rollover {
+                signer_set,
+                on_chain_public_key: Some(group_key_x),
+            })
+            .unwrap();
+        signer
+            .check_rollover(
+                &mut rounds,
+                &mut pending_rollover,
+                &rollovers_rx,
+                &signer_sets_tx,
+                &status,
+            )
+            .unwrap();
+        assert_eq!(signer.config.keys_threshold, 2);
+        assert_eq!(rounds[0].1.threshold, 2);
+        assert_eq!(signer_sets_rx.try_recv().unwrap().keys_threshold, 2);
+        assert_eq!(source.0.lock().unwrap().len(), 1);
+        assert_eq!(source.0.lock().unwrap()[0].keys_threshold, 2);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
 }
@@ -0,0 +1,29 @@
+use crate::config::SignerSet;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Signer set unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// A new signer set for the signer to run with
+#[derive(Clone, Debug)]
+pub struct Rollover {
+    pub signer_set: SignerSet,
+    /// X coordinate of the aggregate public key registered on chain, which saved DKG state must
+    /// match, if any
+    pub on_chain_public_key: Option<[u8; 32]>,
+}
+
+/// Supplies the signer set from outside the config file, e.g. from the sBTC contract, so that a
+/// running signer follows changes to it
+pub trait SignerSetSource: Send + Sync {
+    /// The signer set to roll over to, once it is time to. Called about once a second from a
+    /// thread of its own, so it may block, but should limit how often it reads the signer set.
+    /// The signer set should be handed out again until the signer reports that it rolled over,
+    /// so that a failed rollover is retried.
+    fn rollover(&self) -> Result<Option<Rollover>, Error>;
+
+    /// Note that the signer runs with the signer set now
+    fn rolled_over(&self, signer_set: &SignerSet);
+}
@@ -7,20 +7,29 @@ use p256k1::{
 use rand_core::{CryptoRng, OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc, time::Instant};
 use tracing::{debug, info, warn};
 pub use wsts;
 use wsts::{
     common::{PolyCommitment, PublicNonce, SignatureShare},
-    traits::Signer as SignerTrait,
-    v1,
+    traits::SignerState,
 };
 
 use crate::{
-    config::PublicKeys,
+    complaint::{
+        check_commitment, is_consistent_share, public_key_point, zero_commitment, BadCommitment,
+        ComplaintReason, DkgComplaint,
+    },
+    config::{PrivateKey, PublicKeys},
+    dkg_state::{self, DkgState},
+    journal::{Error as JournalError, Journal},
+    nonce_pool::{Error as NoncePoolError, NoncePool},
+    reshare::{deal, dealer_secret},
     signer::Signer as FrostSigner,
-    state_machine::{Error as StateMachineError, StateMachine, States},
+    state_machine::{Error as StateMachineError, StateMachine, StateTimeouts, States},
     util::{decrypt, encrypt, make_shared_secret},
+    validation::{SignRequestValidator, TransactionContext},
+    wsts_version::{WstsSigner, WstsVersion},
 };
 
 #[derive(thiserror::Error, Debug)]
@@ -37,6 +46,10 @@ pub enum Error {
     InvalidSignatureShare,
     #[error("State Machine Error: {0}")]
     StateMachineError(#[from] StateMachineError),
+    #[error("Journal Error: {0}")]
+    JournalError(#[from] JournalError),
+    #[error("Nonce Pool Error: {0}")]
+    NoncePoolError(#[from] NoncePoolError),
 }
 
 pub trait Signable {
@@ -79,15 +92,45 @@ pub struct SigningRound {
     pub total_keys: u32,
     pub signer: Signer,
     pub state: States,
+    /// When the signer entered its current state
+    pub state_entered_at: Instant,
+    /// Deadlines after which the signer abandons the round in progress
+    pub state_timeouts: StateTimeouts,
+    /// Id of the DKG round which produced the key shares the signer holds, if any completed
+    pub key_epoch: Option<u64>,
+    /// The party state of the key epoch, set aside while a DKG round runs with moved key ids
+    pub epoch_signer: Option<WstsSigner>,
     pub commitments: BTreeMap<u32, PolyCommitment>,
     pub shares: HashMap<u32, HashMap<u32, Vec<u8>>>,
+    /// Ephemeral keys the shares of the current DKG round were encrypted to, keyed by party id
+    /// counted from 0
+    pub ephemeral_keys: HashMap<u32, Point>,
+    /// Key ids, counted from 0, of the signers excluded from the current DKG round
+    pub excluded_key_ids: HashSet<u32>,
+    /// Party ids, counted from 0, of the signers excluded from the current DKG round
+    pub excluded_party_ids: HashSet<u32>,
+    /// Rejected commitments of the current DKG round, keyed by party id counted from 1
+    pub bad_commitments: BTreeMap<u32, BadCommitment>,
+    /// The resharing in progress, whose dealers' commitments and shares are gathered keyed by
+    /// their party id counted from 1
+    pub resharing: Option<ReshareBegin>,
     pub public_nonces: Vec<PublicNonce>,
-    pub network_private_key: Scalar,
+    /// Party states holding the nonces generated for the current sign id, keyed by sign nonce id
+    /// and message index. Each is removed when it is used, so that no nonce signs twice.
+    pub message_nonces: HashMap<(u64, u32), SignerState>,
+    /// Nonces published ahead of signing for the current DKG round
+    pub nonce_pool: NoncePool,
+    pub network_private_key: PrivateKey,
     pub public_keys: PublicKeys,
+    pub journal: Option<Journal>,
+    /// Checks signing requests before signature shares are produced. Every request is signed if unset.
+    pub validator: Option<Arc<dyn SignRequestValidator>>,
+    /// Directory the party state is saved to after each successful DKG round. Not saved if unset.
+    pub dkg_state_directory: Option<PathBuf>,
 }
 
 pub struct Signer {
-    pub frost_signer: wsts::v1::Signer,
+    pub frost_signer: WstsSigner,
     pub signer_id: u32,
 }
 
@@ -95,6 +138,7 @@ impl StateMachine for SigningRound {
     fn move_to(&mut self, state: States) -> Result<(), StateMachineError> {
         self.can_move_to(&state)?;
         self.state = state;
+        self.state_entered_at = Instant::now();
         Ok(())
     }
 
@@ -110,6 +154,7 @@ impl StateMachine for SigningRound {
             States::DkgPublicGather => prev_state == &States::DkgPublicDistribute,
             States::DkgPrivateDistribute => prev_state == &States::DkgPublicGather,
             States::DkgPrivateGather => prev_state == &States::DkgPrivateDistribute,
+            States::ReshareGather => prev_state == &States::Idle,
             States::SignGather => prev_state == &States::Idle,
             States::Signed => prev_state == &States::SignGather,
         };
@@ -128,7 +173,89 @@ impl StateMachine for SigningRound {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum DkgStatus {
     Success,
-    Failure(String),
+    Failure(DkgFailure),
+}
+
+impl DkgStatus {
+    fn hash(&self, hasher: &mut Sha256) {
+        match self {
+            DkgStatus::Success => hasher.update([0]),
+            DkgStatus::Failure(failure) => {
+                hasher.update([1]);
+                failure.hash(hasher);
+            }
+        }
+    }
+}
+
+/// Why a signer failed to complete a DKG round
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum DkgFailure {
+    /// Private shares of other key ids were bad. Each complaint names the sending key id and
+    /// proves the share was bad, or else blames the complainer.
+    BadPrivateShares(Vec<DkgComplaint>),
+    /// Commitments of other key ids, counted from 1, were rejected. Anyone who saw them on the
+    /// relay can check them.
+    BadPublicShares(BTreeMap<u32, BadCommitment>),
+    /// Computing the party secrets from the shares and commitments failed
+    ComputeSecrets(String),
+    /// The party state could not be saved
+    SaveState(String),
+    /// Private shares dealt by the signers were bad while resharing
+    BadReshareShares(Vec<u32>),
+    /// The secrets dealt while resharing do not add up to the aggregate public key
+    ReshareKeyMismatch,
+    /// A dealer lacks the private keys of key ids, counted from 1, it was asked to reshare
+    MissingKeyShares(Vec<u32>),
+    /// Signers given key ids while resharing have no public key in the config
+    UnknownSigners(Vec<u32>),
+}
+
+impl DkgFailure {
+    fn hash(&self, hasher: &mut Sha256) {
+        match self {
+            DkgFailure::BadPrivateShares(complaints) => {
+                hasher.update([0]);
+                for complaint in complaints {
+                    complaint.hash(hasher);
+                }
+            }
+            DkgFailure::ComputeSecrets(reason) => {
+                hasher.update([1]);
+                hasher.update(reason.as_bytes());
+            }
+            DkgFailure::SaveState(reason) => {
+                hasher.update([2]);
+                hasher.update(reason.as_bytes());
+            }
+            DkgFailure::BadPublicShares(bad_commitments) => {
+                hasher.update([3]);
+                for (party_id, bad_commitment) in bad_commitments {
+                    hasher.update(party_id.to_be_bytes());
+                    hasher.update(bad_commitment.to_string().as_bytes());
+                }
+            }
+            DkgFailure::BadReshareShares(signer_ids) => {
+                hasher.update([4]);
+                for signer_id in signer_ids {
+                    hasher.update(signer_id.to_be_bytes());
+                }
+            }
+            DkgFailure::ReshareKeyMismatch => hasher.update([5]),
+            DkgFailure::MissingKeyShares(key_ids) => {
+                hasher.update([6]);
+                for key_id in key_ids {
+                    hasher.update(key_id.to_be_bytes());
+                }
+            }
+            DkgFailure::UnknownSigners(signer_ids) => {
+                hasher.update([7]);
+                for signer_id in signer_ids {
+                    hasher.update(signer_id.to_be_bytes());
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -143,6 +270,11 @@ pub enum MessageTypes {
     NonceResponse(NonceResponse),
     SignShareRequest(SignatureShareRequest),
     SignShareResponse(SignatureShareResponse),
+    NoncePoolRequest(NoncePoolRequest),
+    NoncePoolResponse(NoncePoolResponse),
+    ReshareBegin(ReshareBegin),
+    ReshareShares(ReshareShares),
+    ReshareEnd(DkgEnd),
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -168,8 +300,13 @@ impl Signable for DkgPublicShare {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct DkgPrivateShares {
     pub dkg_id: u64,
+    /// Party id, counted from 0, of the sender: its key id in v1 and its signer id minus one in v2
     pub key_id: u32,
-    /// Encrypt the shares using AES-GCM with a key derived from ECDH
+    /// Public key of a new ephemeral key of the sender, so that a key revealed to judge a
+    /// complaint opens no shares of other rounds
+    pub ephemeral_key: Point,
+    /// Encrypt the shares using AES-GCM with a key derived from ECDH of the ephemeral key and the
+    /// recipient's network key
     pub private_shares: HashMap<u32, Vec<u8>>,
 }
 
@@ -178,11 +315,14 @@ impl Signable for DkgPrivateShares {
         hasher.update("DKG_PRIVATE_SHARES".as_bytes());
         hasher.update(self.dkg_id.to_be_bytes());
         hasher.update(self.key_id.to_be_bytes());
-        // make sure we iterate sequentially
+        hasher.update(self.ephemeral_key.compress().as_bytes());
+        // make sure we iterate sequentially. Shares for key ids excluded from the round are missing
         // TODO: change this once WSTS goes to 1 based indexing for key_ids, or change to BTreeMap
-        for id in 0..self.private_shares.len() as u32 {
+        let mut ids: Vec<&u32> = self.private_shares.keys().collect();
+        ids.sort();
+        for id in ids {
             hasher.update(id.to_be_bytes());
-            hasher.update(&self.private_shares[&id]);
+            hasher.update(&self.private_shares[id]);
         }
     }
 }
@@ -190,12 +330,22 @@ impl Signable for DkgPrivateShares {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct DkgBegin {
     pub dkg_id: u64, //TODO: Strong typing for this, alternatively introduce a type alias
+    /// Unix time in seconds after which signers ignore the request
+    #[serde(default)]
+    pub expires_at: u64,
+    /// Signers blamed for misbehaving in an earlier attempt, whose key ids contribute nothing to the round
+    #[serde(default)]
+    pub excluded_signers: Vec<u32>,
 }
 
 impl Signable for DkgBegin {
     fn hash(&self, hasher: &mut Sha256) {
         hasher.update("DKG_BEGIN".as_bytes());
         hasher.update(self.dkg_id.to_be_bytes());
+        hasher.update(self.expires_at.to_be_bytes());
+        for signer_id in &self.excluded_signers {
+            hasher.update(signer_id.to_be_bytes());
+        }
     }
 }
 
@@ -211,6 +361,114 @@ impl Signable for DkgEnd {
         hasher.update("DKG_END".as_bytes());
         hasher.update(self.dkg_id.to_be_bytes());
         hasher.update(self.signer_id.to_be_bytes());
+        self.status.hash(hasher);
+    }
+}
+
+/// Asks the dealers to reshare the secret of the aggregate public key to a new assignment of key
+/// ids and a new threshold. The secret is never reconstructed, and the aggregate public key stays
+/// the same, while the key shares of the current DKG round become useless.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReshareBegin {
+    /// Id of the key epoch resharing starts. It follows the current DKG round.
+    pub dkg_id: u64,
+    /// Unix time in seconds after which signers ignore the request
+    pub expires_at: u64,
+    /// Number of key ids needed to sign after resharing
+    pub threshold: u32,
+    /// Key ids, counted from 1, each signer holds after resharing
+    pub signer_key_ids: BTreeMap<u32, Vec<u32>>,
+    /// Key ids, counted from 1, of the signers dealing their key shares. Together they hold at
+    /// least the current threshold of key ids.
+    pub dealers: BTreeMap<u32, Vec<u32>>,
+    pub aggregate_public_key: Point,
+}
+
+impl ReshareBegin {
+    /// The party id, counted from 1, of the dealer's commitment
+    pub fn dealer_party_id(&self, signer_id: u32) -> Option<u32> {
+        self.dealers
+            .keys()
+            .position(|dealer| *dealer == signer_id)
+            .map(|index| index as u32 + 1)
+    }
+
+    /// The key ids, counted from 0, of all dealers
+    pub fn dealer_key_ids(&self) -> Vec<u32> {
+        self.dealers
+            .values()
+            .flatten()
+            .map(|key_id| key_id - 1)
+            .collect()
+    }
+
+    /// The key ids, counted from 0, held after resharing
+    pub fn key_ids(&self) -> Vec<u32> {
+        self.signer_key_ids
+            .values()
+            .flatten()
+            .map(|key_id| key_id - 1)
+            .collect()
+    }
+
+    /// The signer holding the key id, counted from 0, after resharing
+    pub fn key_id_signer(&self, key_id: u32) -> Option<u32> {
+        self.signer_key_ids
+            .iter()
+            .find(|(_, key_ids)| key_ids.contains(&(key_id + 1)))
+            .map(|(signer_id, _)| *signer_id)
+    }
+}
+
+fn hash_signer_key_ids(hasher: &mut Sha256, signer_key_ids: &BTreeMap<u32, Vec<u32>>) {
+    for (signer_id, key_ids) in signer_key_ids {
+        hasher.update(signer_id.to_be_bytes());
+        hasher.update((key_ids.len() as u32).to_be_bytes());
+        for key_id in key_ids {
+            hasher.update(key_id.to_be_bytes());
+        }
+    }
+}
+
+impl Signable for ReshareBegin {
+    fn hash(&self, hasher: &mut Sha256) {
+        hasher.update("RESHARE_BEGIN".as_bytes());
+        hasher.update(self.dkg_id.to_be_bytes());
+        hasher.update(self.expires_at.to_be_bytes());
+        hasher.update(self.threshold.to_be_bytes());
+        hash_signer_key_ids(hasher, &self.signer_key_ids);
+        hasher.update("DEALERS".as_bytes());
+        hash_signer_key_ids(hasher, &self.dealers);
+        hasher.update(self.aggregate_public_key.compress().as_bytes());
+    }
+}
+
+/// The shares a dealer deals of its part of the secret, one for each key id held after resharing
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReshareShares {
+    pub dkg_id: u64,
+    pub signer_id: u32,
+    /// Commitment to the polynomial of the dealer's part, under its party id
+    pub commitment: PolyCommitment,
+    /// Shares keyed by key id counted from 0, encrypted for the signer holding the key id using
+    /// AES-GCM with a key derived from ECDH
+    pub private_shares: HashMap<u32, Vec<u8>>,
+}
+
+impl Signable for ReshareShares {
+    fn hash(&self, hasher: &mut Sha256) {
+        hasher.update("RESHARE_SHARES".as_bytes());
+        hasher.update(self.dkg_id.to_be_bytes());
+        hasher.update(self.signer_id.to_be_bytes());
+        for a in &self.commitment.A {
+            hasher.update(a.compress().as_bytes());
+        }
+        let mut ids: Vec<&u32> = self.private_shares.keys().collect();
+        ids.sort();
+        for id in ids {
+            hasher.update(id.to_be_bytes());
+            hasher.update(&self.private_shares[id]);
+        }
     }
 }
 
@@ -219,6 +477,16 @@ pub struct NonceRequest {
     pub dkg_id: u64,
     pub sign_id: u64,
     pub sign_nonce_id: u64,
+    /// Unix time in seconds after which signers ignore the request
+    #[serde(default)]
+    pub expires_at: u64,
+    /// Number of messages to generate nonces for, one nonce per party of the signer each
+    #[serde(default = "default_message_count")]
+    pub message_count: u32,
+}
+
+fn default_message_count() -> u32 {
+    1
 }
 
 impl Signable for NonceRequest {
@@ -227,6 +495,8 @@ impl Signable for NonceRequest {
         hasher.update(self.dkg_id.to_be_bytes());
         hasher.update(self.sign_id.to_be_bytes());
         hasher.update(self.sign_nonce_id.to_be_bytes());
+        hasher.update(self.expires_at.to_be_bytes());
+        hasher.update(self.message_count.to_be_bytes());
     }
 }
 
@@ -237,7 +507,40 @@ pub struct NonceResponse {
     pub sign_nonce_id: u64,
     pub signer_id: u32,
     pub key_ids: Vec<u32>,
+    /// One nonce per party of the signer for each message of the request, message by message
     pub nonces: Vec<PublicNonce>,
+    /// Index of the nonces in the signer's nonce pool, if they were published in advance
+    #[serde(default)]
+    pub pool_index: Option<u64>,
+    /// The protocol version the nonces are for
+    #[serde(default)]
+    pub wsts_version: WstsVersion,
+}
+
+impl NonceResponse {
+    /// Number of nonces and signature shares the signer sends per message: one per key id in v1
+    /// and one in v2
+    pub fn nonces_per_message(&self) -> usize {
+        match self.wsts_version {
+            WstsVersion::V1 => self.key_ids.len(),
+            WstsVersion::V2 => 1,
+        }
+    }
+
+    /// The party ids, counted from 0, the nonces are bound to
+    pub fn party_ids(&self) -> Vec<u32> {
+        match self.wsts_version {
+            WstsVersion::V1 => self.key_ids.clone(),
+            WstsVersion::V2 => vec![self.signer_id - 1],
+        }
+    }
+
+    /// The nonces of the signer's parties for the message with the given index in the request
+    pub fn message_nonces(&self, index: u32) -> &[PublicNonce] {
+        let len = self.nonces_per_message();
+        let start = len * index as usize;
+        self.nonces.get(start..start + len).unwrap_or_default()
+    }
 }
 
 impl Signable for NonceResponse {
@@ -256,6 +559,125 @@ impl Signable for NonceResponse {
             hasher.update(nonce.D.compress().as_bytes());
             hasher.update(nonce.E.compress().as_bytes());
         }
+
+        if let Some(pool_index) = self.pool_index {
+            hasher.update("POOL_INDEX".as_bytes());
+            hasher.update(pool_index.to_be_bytes());
+        }
+
+        if self.wsts_version == WstsVersion::V2 {
+            hasher.update("WSTS_V2".as_bytes());
+        }
+    }
+}
+
+/// Asks signers to publish more nonces to their pools for the current DKG round
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct NoncePoolRequest {
+    pub dkg_id: u64,
+    pub pool_id: u64,
+    /// Number of nonces to publish for each party of a signer
+    pub count: u32,
+    /// Unix time in seconds after which signers ignore the request
+    pub expires_at: u64,
+}
+
+impl Signable for NoncePoolRequest {
+    fn hash(&self, hasher: &mut Sha256) {
+        hasher.update("NONCE_POOL_REQUEST".as_bytes());
+        hasher.update(self.dkg_id.to_be_bytes());
+        hasher.update(self.pool_id.to_be_bytes());
+        hasher.update(self.count.to_be_bytes());
+        hasher.update(self.expires_at.to_be_bytes());
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct NoncePoolResponse {
+    pub dkg_id: u64,
+    pub pool_id: u64,
+    pub signer_id: u32,
+    pub key_ids: Vec<u32>,
+    /// Pool index of the first nonces
+    pub start_index: u64,
+    /// One nonce per party of the signer for each pool index from the start index on, index by index
+    pub nonces: Vec<PublicNonce>,
+    /// The protocol version the nonces are for
+    #[serde(default)]
+    pub wsts_version: WstsVersion,
+}
+
+impl NoncePoolResponse {
+    /// The published nonces as nonce responses for the given sign id, one per pool index
+    pub fn nonce_responses(&self, sign_id: u64) -> Vec<NonceResponse> {
+        let nonces_per_index = match self.wsts_version {
+            WstsVersion::V1 => self.key_ids.len(),
+            WstsVersion::V2 => 1,
+        };
+        if nonces_per_index == 0 {
+            return vec![];
+        }
+        self.nonces
+            .chunks_exact(nonces_per_index)
+            .zip(self.start_index..)
+            .map(|(nonces, pool_index)| NonceResponse {
+                dkg_id: self.dkg_id,
+                sign_id,
+                sign_nonce_id: 0,
+                signer_id: self.signer_id,
+                key_ids: self.key_ids.clone(),
+                nonces: nonces.to_vec(),
+                pool_index: Some(pool_index),
+                wsts_version: self.wsts_version,
+            })
+            .collect()
+    }
+}
+
+impl Signable for NoncePoolResponse {
+    fn hash(&self, hasher: &mut Sha256) {
+        hasher.update("NONCE_POOL_RESPONSE".as_bytes());
+        hasher.update(self.dkg_id.to_be_bytes());
+        hasher.update(self.pool_id.to_be_bytes());
+        hasher.update(self.signer_id.to_be_bytes());
+
+        for key_id in &self.key_ids {
+            hasher.update(key_id.to_be_bytes());
+        }
+
+        hasher.update(self.start_index.to_be_bytes());
+        for nonce in &self.nonces {
+            hasher.update(nonce.D.compress().as_bytes());
+            hasher.update(nonce.E.compress().as_bytes());
+        }
+
+        if self.wsts_version == WstsVersion::V2 {
+            hasher.update("WSTS_V2".as_bytes());
+        }
+    }
+}
+
+/// The key a signature is made with
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum SignatureType {
+    /// The untweaked aggregate public key
+    #[default]
+    Frost,
+    /// The BIP341 taproot output key, i.e. the aggregate public key tweaked to commit to
+    /// the script tree with the given merkle root. `None` commits to no scripts.
+    Taproot(Option<[u8; 32]>),
+}
+
+impl SignatureType {
+    fn hash(&self, hasher: &mut Sha256) {
+        match self {
+            SignatureType::Frost => hasher.update([0]),
+            SignatureType::Taproot(None) => hasher.update([1]),
+            SignatureType::Taproot(Some(merkle_root)) => {
+                hasher.update([2]);
+                hasher.update(merkle_root);
+            }
+        }
     }
 }
 
@@ -266,6 +688,81 @@ pub struct SignatureShareRequest {
     pub correlation_id: u64,
     pub nonce_responses: Vec<NonceResponse>,
     pub message: Vec<u8>,
+    #[serde(default)]
+    pub signature_type: SignatureType,
+    /// Unix time in seconds after which signers ignore the request
+    #[serde(default)]
+    pub expires_at: u64,
+    /// The transaction whose input sighash is the message, if the message signs a transaction
+    #[serde(default)]
+    pub context: Option<TransactionContext>,
+    /// Index of the message's nonces within the nonces of each response
+    #[serde(default)]
+    pub nonce_index: u32,
+    /// Further messages signed in the same round with the same signature type
+    #[serde(default)]
+    pub batch: Vec<BatchMessage>,
+}
+
+/// A message of a batch signing request with the nonces it is signed with
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BatchMessage {
+    pub message: Vec<u8>,
+    pub context: Option<TransactionContext>,
+    pub nonce_responses: Vec<NonceResponse>,
+    pub nonce_index: u32,
+}
+
+impl BatchMessage {
+    fn hash(&self, hasher: &mut Sha256) {
+        hasher.update("BATCH_MESSAGE".as_bytes());
+        for nonce_response in &self.nonce_responses {
+            nonce_response.hash(hasher);
+        }
+        hasher.update((self.message.len() as u64).to_be_bytes());
+        hasher.update(self.message.as_slice());
+        hasher.update(self.nonce_index.to_be_bytes());
+        if let Some(context) = &self.context {
+            context.hash(hasher);
+        }
+    }
+}
+
+impl SignatureShareRequest {
+    /// All messages of the request, in the order their signature shares are returned
+    pub fn messages(&self) -> Vec<BatchMessage> {
+        let first = BatchMessage {
+            message: self.message.clone(),
+            context: self.context.clone(),
+            nonce_responses: self.nonce_responses.clone(),
+            nonce_index: self.nonce_index,
+        };
+        std::iter::once(first).chain(self.batch.clone()).collect()
+    }
+
+    /// Whether every message is signed with nonces published to the signers' pools, so that
+    /// no nonce request preceded the request
+    pub fn uses_nonce_pools(&self) -> bool {
+        self.messages().iter().all(|message| {
+            !message.nonce_responses.is_empty()
+                && message
+                    .nonce_responses
+                    .iter()
+                    .all(|nr| nr.pool_index.is_some())
+        })
+    }
+
+    /// The request for a single message of the batch
+    pub fn for_message(&self, message: BatchMessage) -> SignatureShareRequest {
+        SignatureShareRequest {
+            nonce_responses: message.nonce_responses,
+            message: message.message,
+            context: message.context,
+            nonce_index: message.nonce_index,
+            batch: vec![],
+            ..self.clone()
+        }
+    }
 }
 
 impl Signable for SignatureShareRequest {
@@ -280,6 +777,15 @@ impl Signable for SignatureShareRequest {
         }
 
         hasher.update(self.message.as_slice());
+        self.signature_type.hash(hasher);
+        hasher.update(self.expires_at.to_be_bytes());
+        if let Some(context) = &self.context {
+            context.hash(hasher);
+        }
+        hasher.update(self.nonce_index.to_be_bytes());
+        for message in &self.batch {
+            message.hash(hasher);
+        }
     }
 }
 
@@ -289,6 +795,7 @@ pub struct SignatureShareResponse {
     pub sign_id: u64,
     pub correlation_id: u64,
     pub signer_id: u32,
+    /// One share per party of the signer for each message of the request, message by message
     pub signature_shares: Vec<SignatureShare>,
 }
 
@@ -319,7 +826,15 @@ impl SigningRound {
     ) -> SigningRound {
         assert!(threshold <= total_keys);
         let mut rng = OsRng;
-        let frost_signer = v1::Signer::new(signer_id, &key_ids, total_keys, threshold, &mut rng);
+        let frost_signer = WstsSigner::new(
+            WstsVersion::V1,
+            signer_id,
+            &key_ids,
+            total_signers,
+            total_keys,
+            threshold,
+            &mut rng,
+        );
         let signer = Signer {
             frost_signer,
             signer_id,
@@ -335,24 +850,149 @@ impl SigningRound {
             total_keys,
             signer,
             state: States::Idle,
+            state_entered_at: Instant::now(),
+            state_timeouts: StateTimeouts::default(),
+            key_epoch: None,
+            epoch_signer: None,
             commitments: BTreeMap::new(),
             shares: HashMap::new(),
+            ephemeral_keys: HashMap::new(),
+            excluded_key_ids: HashSet::new(),
+            excluded_party_ids: HashSet::new(),
+            bad_commitments: BTreeMap::new(),
+            resharing: None,
             public_nonces: vec![],
-            network_private_key,
+            message_nonces: HashMap::new(),
+            nonce_pool: NoncePool::new(),
+            network_private_key: network_private_key.into(),
             public_keys,
+            journal: None,
+            validator: None,
+            dkg_state_directory: None,
+        }
+    }
+
+    /// Run the given protocol version instead, with a new party state
+    pub fn with_wsts_version(mut self, version: WstsVersion) -> Self {
+        self.signer.frost_signer = WstsSigner::new(
+            version,
+            self.signer.signer_id,
+            &self.signer.frost_signer.get_key_ids(),
+            self.total_signers,
+            self.total_keys,
+            self.threshold,
+            &mut OsRng,
+        );
+        self
+    }
+
+    pub fn wsts_version(&self) -> WstsVersion {
+        self.signer.frost_signer.version()
+    }
+
+    /// Take on the key ids registered for the signer if they moved since the key shares it holds
+    /// were generated. The keys of the new assignment only exist once the next DKG round ends.
+    fn rebalance_key_ids(&mut self) {
+        let mut key_ids: Vec<u32> = self
+            .public_keys
+            .signer_key_ids(self.signer.signer_id)
+            .iter()
+            .map(|key_id| key_id - 1)
+            .collect();
+        let mut held_key_ids = self.signer.frost_signer.get_key_ids();
+        key_ids.sort_unstable();
+        held_key_ids.sort_unstable();
+        if key_ids.is_empty() || key_ids == held_key_ids {
+            return;
+        }
+        info!(
+            "Signer #{} key ids moved from {:?} to {:?}",
+            self.signer.signer_id, held_key_ids, key_ids
+        );
+        let rebalanced = WstsSigner::new(
+            self.wsts_version(),
+            self.signer.signer_id,
+            &key_ids,
+            self.total_signers,
+            self.total_keys,
+            self.threshold,
+            &mut OsRng,
+        );
+        let held = std::mem::replace(&mut self.signer.frost_signer, rebalanced);
+        self.epoch_signer.get_or_insert(held);
+    }
+
+    /// Return to the party state of the key epoch if the DKG round which moved the key ids did
+    /// not complete
+    fn restore_epoch_signer(&mut self) {
+        if let Some(held) = self.epoch_signer.take() {
+            info!(
+                "Signer #{} keeps key ids {:?} of its last key epoch",
+                self.signer.signer_id,
+                held.get_key_ids()
+            );
+            self.signer.frost_signer = held;
         }
     }
 
+    /// Number of parties sending commitments and private shares in a DKG round
+    fn total_parties(&self) -> u32 {
+        self.wsts_version()
+            .total_parties(self.total_signers, self.total_keys)
+    }
+
     fn reset<T: RngCore + CryptoRng>(&mut self, dkg_id: u64, rng: &mut T) {
         self.dkg_id = dkg_id;
         self.dkg_public_id = 0;
         self.commitments.clear();
         self.shares.clear();
+        self.ephemeral_keys.clear();
+        self.excluded_key_ids.clear();
+        self.excluded_party_ids.clear();
+        self.bad_commitments.clear();
+        self.resharing = None;
         self.public_nonces.clear();
         self.signer.frost_signer.reset_polys(rng);
     }
 
+    /// Abandon the round in progress if it outlived the deadline of its state, e.g. because the
+    /// coordinator went away. Returns whether the round was abandoned.
+    pub fn expire_state(&mut self, now: Instant) -> Result<bool, Error> {
+        let Some(timeout) = self.state.timeout(&self.state_timeouts) else {
+            return Ok(false);
+        };
+        if now.saturating_duration_since(self.state_entered_at) < timeout {
+            return Ok(false);
+        }
+        warn!(
+            "Round #{} timed out after {:?} in state {:?}",
+            self.dkg_id, timeout, self.state
+        );
+        self.abort_round()?;
+        Ok(true)
+    }
+
+    /// Drop the partial data of the round in progress and return to `Idle`, with the key shares
+    /// of the last completed DKG round if there is one
+    fn abort_round(&mut self) -> Result<(), Error> {
+        self.commitments.clear();
+        self.shares.clear();
+        self.ephemeral_keys.clear();
+        self.excluded_key_ids.clear();
+        self.excluded_party_ids.clear();
+        self.bad_commitments.clear();
+        self.resharing = None;
+        self.public_nonces.clear();
+        self.restore_epoch_signer();
+        if let Some(key_epoch) = self.key_epoch {
+            self.dkg_id = key_epoch;
+        }
+        self.move_to(States::Idle)?;
+        Ok(())
+    }
+
     pub fn process(&mut self, message: MessageTypes) -> Result<Vec<MessageTypes>, Error> {
+        self.expire_state(Instant::now())?;
         let out_msgs = match message {
             MessageTypes::DkgBegin(dkg_begin) => self.dkg_begin(dkg_begin),
             MessageTypes::DkgPrivateBegin(_) => self.dkg_private_begin(),
@@ -366,6 +1006,11 @@ impl SigningRound {
                 self.sign_share_request(sign_share_request)
             }
             MessageTypes::NonceRequest(nonce_request) => self.nonce_request(nonce_request),
+            MessageTypes::NoncePoolRequest(nonce_pool_request) => {
+                self.nonce_pool_request(nonce_pool_request)
+            }
+            MessageTypes::ReshareBegin(reshare_begin) => self.reshare_begin(reshare_begin),
+            MessageTypes::ReshareShares(reshare_shares) => self.reshare_shares(reshare_shares),
             _ => Ok(vec![]), // TODO
         };
 
@@ -379,6 +1024,10 @@ impl SigningRound {
                     let dkg_end_msgs = self.dkg_public_ended()?;
                     out.push(dkg_end_msgs);
                     self.move_to(States::DkgPrivateDistribute)?;
+                } else if self.public_shares_failed() {
+                    out.push(self.dkg_public_failed());
+                    self.restore_epoch_signer();
+                    self.move_to(States::Idle)?;
                 } else if self.can_dkg_end() {
                     debug!(
                         "can_dkg_end==true. shares {} commitments {}",
@@ -388,6 +1037,11 @@ impl SigningRound {
                     let dkg_end_msgs = self.dkg_ended()?;
                     out.push(dkg_end_msgs);
                     self.move_to(States::Idle)?;
+                } else if self.can_reshare_end() {
+                    if let Some(resharing) = self.resharing.take() {
+                        out.push(self.reshare_ended(resharing)?);
+                        self.move_to(States::Idle)?;
+                    }
                 }
                 Ok(out)
             }
@@ -409,6 +1063,20 @@ impl SigningRound {
         Ok(dkg_end)
     }
 
+    /// End the round without distributing private shares, so that no share is sent to a party
+    /// whose commitment was rejected
+    fn dkg_public_failed(&mut self) -> MessageTypes {
+        warn!(
+            "DKG round #{} failed: rejected commitments {:?}",
+            self.dkg_id, self.bad_commitments
+        );
+        MessageTypes::DkgEnd(DkgEnd {
+            dkg_id: self.dkg_id,
+            signer_id: self.signer.signer_id,
+            status: DkgStatus::Failure(DkgFailure::BadPublicShares(self.bad_commitments.clone())),
+        })
+    }
+
     fn dkg_ended(&mut self) -> Result<MessageTypes, Error> {
         let polys: Vec<PolyCommitment> = self.commitments.clone().into_values().collect();
 
@@ -416,37 +1084,85 @@ impl SigningRound {
 
         // go through private shares, and decrypt any for owned keys, leaving the rest as zero scalars
         let key_ids: HashSet<u32> = self.signer.frost_signer.get_key_ids().into_iter().collect();
-        let mut invalid_dkg_private_shares = Vec::new();
+
+        let mut complaints = Vec::new();
 
         for (src_key_id, encrypted_shares) in &self.shares {
             let mut decrypted_key_shares = HashMap::new();
 
+            if self.excluded_party_ids.contains(src_key_id) {
+                // Excluded parties contribute the zero polynomial
+                for dst_key_id in 0..self.total_keys {
+                    decrypted_key_shares.insert(dst_key_id, Scalar::new());
+                }
+                decrypted_shares.insert(*src_key_id, decrypted_key_shares);
+                continue;
+            }
+
+            let Some(ephemeral_key) = self.ephemeral_keys.get(src_key_id) else {
+                warn!(
+                    "Ignoring dkg private shares of party {} without an ephemeral key",
+                    src_key_id
+                );
+                continue;
+            };
+
+            for dst_key_id in &key_ids {
+                if !encrypted_shares.contains_key(dst_key_id) {
+                    warn!(
+                        "Missing dkg private share from key_id {} to key_id {}",
+                        src_key_id, dst_key_id
+                    );
+                    complaints.push(DkgComplaint::missing_share(*src_key_id, *dst_key_id));
+                }
+            }
+
             for (dst_key_id, private_share) in encrypted_shares {
                 if key_ids.contains(dst_key_id) {
                     debug!(
                         "decrypting dkg private share for key_id #{}",
                         dst_key_id + 1
                     );
-                    let compressed =
-                        Compressed::from(self.public_keys.key_ids[&(src_key_id + 1)].to_bytes());
-                    let src_public_key = Point::try_from(&compressed).unwrap();
-                    let shared_secret =
-                        make_shared_secret(&self.network_private_key, &src_public_key);
+                    let shared_secret = self.network_private_key.shared_secret(ephemeral_key);
 
-                    match decrypt(&shared_secret, private_share) {
+                    let reason = match decrypt(&shared_secret, private_share) {
                         Ok(plain) => match Scalar::try_from(&plain[..]) {
                             Ok(s) => {
-                                decrypted_key_shares.insert(*dst_key_id, s);
+                                let consistent = self
+                                    .commitments
+                                    .get(&(src_key_id + 1))
+                                    .map_or(false, |commitment| {
+                                        is_consistent_share(&s, *dst_key_id, commitment)
+                                    });
+                                if consistent {
+                                    decrypted_key_shares.insert(*dst_key_id, s);
+                                    None
+                                } else {
+                                    warn!("Dkg private share from key_id {} to key_id {} does not match its commitment", src_key_id, dst_key_id);
+                                    Some(ComplaintReason::InconsistentShare)
+                                }
                             }
                             Err(e) => {
                                 warn!("Failed to parse Scalar for dkg private share from key_id {} to key_id {}: {:?}", src_key_id, dst_key_id, e);
-                                invalid_dkg_private_shares.push(*src_key_id);
+                                Some(ComplaintReason::InvalidScalar)
                             }
                         },
                         Err(e) => {
                             warn!("Failed to decrypt dkg private share from key_id {} to key_id {}: {:?}", src_key_id, dst_key_id, e);
-                            invalid_dkg_private_shares.push(*src_key_id);
+                            Some(ComplaintReason::Undecryptable)
                         }
+                    };
+                    if let Some(reason) = reason {
+                        complaints.push(self.network_private_key.with_scalar(|key| {
+                            DkgComplaint::new(
+                                *src_key_id,
+                                *dst_key_id,
+                                reason,
+                                key,
+                                ephemeral_key,
+                                &mut OsRng,
+                            )
+                        }));
                     }
                 } else {
                     decrypted_key_shares.insert(*dst_key_id, Scalar::new());
@@ -456,29 +1172,35 @@ impl SigningRound {
             decrypted_shares.insert(*src_key_id, decrypted_key_shares);
         }
 
-        let dkg_end = if invalid_dkg_private_shares.is_empty() {
+        let status = if complaints.is_empty() {
             match self
                 .signer
                 .frost_signer
                 .compute_secrets(&decrypted_shares, &polys)
             {
-                Ok(()) => DkgEnd {
-                    dkg_id: self.dkg_id,
-                    signer_id: self.signer.signer_id,
-                    status: DkgStatus::Success,
-                },
-                Err(dkg_error_map) => DkgEnd {
-                    dkg_id: self.dkg_id,
-                    signer_id: self.signer.signer_id,
-                    status: DkgStatus::Failure(format!("{:?}", dkg_error_map)),
+                Ok(()) => match self.save_dkg_state() {
+                    Ok(()) => {
+                        self.key_epoch = Some(self.dkg_id);
+                        self.epoch_signer = None;
+                        DkgStatus::Success
+                    }
+                    Err(e) => {
+                        warn!("Failed to save DKG state of round #{}: {}", self.dkg_id, e);
+                        DkgStatus::Failure(DkgFailure::SaveState(e.to_string()))
+                    }
                 },
+                Err(dkg_error_map) => {
+                    DkgStatus::Failure(DkgFailure::ComputeSecrets(format!("{:?}", dkg_error_map)))
+                }
             }
         } else {
-            DkgEnd {
-                dkg_id: self.dkg_id,
-                signer_id: self.signer.signer_id,
-                status: DkgStatus::Failure(format!("{:?}", invalid_dkg_private_shares)),
-            }
+            DkgStatus::Failure(DkgFailure::BadPrivateShares(complaints))
+        };
+        self.restore_epoch_signer();
+        let dkg_end = DkgEnd {
+            dkg_id: self.dkg_id,
+            signer_id: self.signer.signer_id,
+            status,
         };
 
         let dkg_end = MessageTypes::DkgEnd(dkg_end);
@@ -489,6 +1211,32 @@ impl SigningRound {
         Ok(dkg_end)
     }
 
+    /// Persist the party state of the completed DKG round, so that the signer can keep signing after a restart
+    fn save_dkg_state(&self) -> Result<(), dkg_state::Error> {
+        if let Some(directory) = &self.dkg_state_directory {
+            let state = DkgState::new(
+                self.dkg_id,
+                self.signer.signer_id,
+                self.wsts_version(),
+                self.signer.frost_signer.save(),
+            );
+            self.network_private_key
+                .with_scalar(|key| dkg_state::save(directory, key, &state))?;
+        }
+        Ok(())
+    }
+
+    /// Restore the party state of a previous DKG round
+    pub fn load_dkg_state(&mut self, state: &DkgState) {
+        self.dkg_id = state.dkg_id;
+        self.key_epoch = Some(state.dkg_id);
+        self.signer.frost_signer = WstsSigner::load(state.wsts_version, &state.signer);
+        info!(
+            "Loaded DKG state of round #{} for signer_id {}",
+            state.dkg_id, self.signer.signer_id
+        );
+    }
+
     fn public_shares_done(&self) -> bool {
         debug!(
             "public_shares_done state {:?} commitments {}",
@@ -496,7 +1244,15 @@ impl SigningRound {
             self.commitments.len(),
         );
         self.state == States::DkgPublicGather
-            && self.commitments.len() == usize::try_from(self.total_keys).unwrap()
+            && self.commitments.len() == usize::try_from(self.total_parties()).unwrap()
+    }
+
+    /// Every party sent its commitment, but some were rejected
+    fn public_shares_failed(&self) -> bool {
+        self.state == States::DkgPublicGather
+            && !self.bad_commitments.is_empty()
+            && self.commitments.len() + self.bad_commitments.len()
+                == usize::try_from(self.total_parties()).unwrap()
     }
 
     fn can_dkg_end(&self) -> bool {
@@ -507,8 +1263,8 @@ impl SigningRound {
             self.shares.len()
         );
         self.state == States::DkgPrivateGather
-            && self.commitments.len() == usize::try_from(self.total_keys).unwrap()
-            && self.shares.len() == usize::try_from(self.total_keys).unwrap()
+            && self.commitments.len() == usize::try_from(self.total_parties()).unwrap()
+            && self.shares.len() == usize::try_from(self.total_parties()).unwrap()
     }
 
     fn nonce_request(&mut self, nonce_request: NonceRequest) -> Result<Vec<MessageTypes>, Error> {
@@ -516,7 +1272,19 @@ impl SigningRound {
         let mut msgs = vec![];
         let signer_id = self.signer.signer_id;
         let key_ids = self.signer.frost_signer.get_key_ids();
-        let nonces = self.signer.frost_signer.gen_nonces(&mut rng);
+        if nonce_request.sign_id != self.sign_id {
+            self.sign_id = nonce_request.sign_id;
+            self.message_nonces.clear();
+        }
+        // The signer only holds the latest nonces, so keep a copy of its state for each message
+        let mut nonces = vec![];
+        for index in 0..nonce_request.message_count.max(1) {
+            nonces.extend(self.signer.frost_signer.gen_nonces(&mut rng));
+            self.message_nonces.insert(
+                (nonce_request.sign_nonce_id, index),
+                self.signer.frost_signer.save(),
+            );
+        }
 
         let response = NonceResponse {
             dkg_id: nonce_request.dkg_id,
@@ -525,24 +1293,106 @@ impl SigningRound {
             signer_id,
             key_ids,
             nonces,
+            pool_index: None,
+            wsts_version: self.wsts_version(),
         };
 
         let response = MessageTypes::NonceResponse(response);
 
         info!(
-            "nonce request with dkg_id {:?}. response sent from signer_id {}",
-            nonce_request.dkg_id, signer_id
+            "nonce request with dkg_id {:?} for {} messages. response sent from signer_id {}",
+            nonce_request.dkg_id, nonce_request.message_count, signer_id
         );
         msgs.push(response);
 
         Ok(msgs)
     }
 
-    fn sign_share_request(
+    fn nonce_pool_request(
         &mut self,
-        sign_request: SignatureShareRequest,
+        nonce_pool_request: NoncePoolRequest,
     ) -> Result<Vec<MessageTypes>, Error> {
-        let mut msgs = vec![];
+        if nonce_pool_request.dkg_id != self.dkg_id {
+            warn!(
+                "Ignoring NoncePoolRequest for DKG round #{} during round #{}",
+                nonce_pool_request.dkg_id, self.dkg_id
+            );
+            return Ok(vec![]);
+        }
+        let indices = self
+            .nonce_pool
+            .publish(self.dkg_id, nonce_pool_request.count)?;
+        let mut nonces = vec![];
+        for index in indices.clone() {
+            let mut rng = self
+                .network_private_key
+                .with_scalar(|key| self.nonce_pool.rng(key, index));
+            nonces.extend(self.signer.frost_signer.gen_nonces(&mut rng));
+        }
+        info!(
+            "Publishing pool nonces #{}..#{} of DKG round #{} for signer_id {}",
+            indices.start, indices.end, self.dkg_id, self.signer.signer_id
+        );
+
+        let response = NoncePoolResponse {
+            dkg_id: self.dkg_id,
+            pool_id: nonce_pool_request.pool_id,
+            signer_id: self.signer.signer_id,
+            key_ids: self.signer.frost_signer.get_key_ids(),
+            start_index: indices.start,
+            nonces,
+            wsts_version: self.wsts_version(),
+        };
+        Ok(vec![MessageTypes::NoncePoolResponse(response)])
+    }
+
+    /// Load the nonces of the message into the signer. Pool nonces are consumed and derived
+    /// again, other nonces are taken from the nonce request of the signing round.
+    fn load_message_nonces(&mut self, message: &BatchMessage) -> Result<(), String> {
+        let Some(own_response) = message
+            .nonce_responses
+            .iter()
+            .find(|nr| nr.signer_id == self.signer.signer_id)
+        else {
+            return Err("no nonces of this signer".to_string());
+        };
+        match own_response.pool_index {
+            Some(pool_index) => {
+                self.nonce_pool
+                    .consume(self.dkg_id, pool_index)
+                    .map_err(|e| e.to_string())?;
+                let mut rng = self
+                    .network_private_key
+                    .with_scalar(|key| self.nonce_pool.rng(key, pool_index));
+                let nonces = self.signer.frost_signer.gen_nonces(&mut rng);
+                let published = own_response.message_nonces(message.nonce_index);
+                let matching = nonces.len() == published.len()
+                    && nonces
+                        .iter()
+                        .zip(published)
+                        .all(|(nonce, published)| nonce.D == published.D && nonce.E == published.E);
+                if !matching {
+                    return Err(format!("pool nonces #{} do not match", pool_index));
+                }
+            }
+            None => {
+                let Some(state) = self
+                    .message_nonces
+                    .remove(&(own_response.sign_nonce_id, message.nonce_index))
+                else {
+                    return Err("no unused nonces for it".to_string());
+                };
+                self.signer.frost_signer = WstsSigner::load(self.wsts_version(), &state);
+            }
+        }
+        Ok(())
+    }
+
+    fn sign_share_request(
+        &mut self,
+        sign_request: SignatureShareRequest,
+    ) -> Result<Vec<MessageTypes>, Error> {
+        let mut msgs = vec![];
 
         let signer_ids = sign_request
             .nonce_responses
@@ -550,58 +1400,145 @@ impl SigningRound {
             .map(|nr| nr.signer_id)
             .collect::<Vec<u32>>();
 
-        info!("Got SignatureShareRequest for signer_ids {:?}", signer_ids);
+        info!(
+            "Got SignatureShareRequest for {} messages for signer_ids {:?}",
+            sign_request.batch.len() + 1,
+            signer_ids
+        );
 
-        for signer_id in &signer_ids {
-            if *signer_id == self.signer.signer_id {
-                let key_ids: Vec<u32> = sign_request
-                    .nonce_responses
-                    .iter()
-                    .flat_map(|nr| nr.key_ids.iter().copied())
-                    .collect::<Vec<u32>>();
-                let nonces = sign_request
-                    .nonce_responses
-                    .iter()
-                    .flat_map(|nr| nr.nonces.clone())
-                    .collect::<Vec<PublicNonce>>();
-                let signature_shares = self.signer.frost_signer.sign(
-                    &sign_request.message,
-                    &signer_ids,
-                    &key_ids,
-                    &nonces,
-                );
+        if !signer_ids.contains(&self.signer.signer_id) {
+            debug!("SignShareRequest for {:?} dropped.", signer_ids);
+            return Ok(msgs);
+        }
 
-                let response = SignatureShareResponse {
-                    dkg_id: sign_request.dkg_id,
-                    sign_id: sign_request.sign_id,
-                    correlation_id: sign_request.correlation_id,
-                    signer_id: *signer_id,
-                    signature_shares,
-                };
+        let messages = sign_request.messages();
+        // Only sign the batch if every message in it is valid
+        if let Some(validator) = &self.validator {
+            for message in &messages {
+                let request = sign_request.for_message(message.clone());
+                if let Err(e) = validator.validate(&request) {
+                    warn!(
+                        "Refusing to sign message {} for sign_id {}: {}",
+                        hex::encode(&message.message),
+                        sign_request.sign_id,
+                        e
+                    );
+                    return Ok(msgs);
+                }
+            }
+        }
 
-                info!(
-                    "Sending SignatureShareResponse for signer_id {:?}",
-                    signer_id
+        let mut signature_shares = vec![];
+        for message in &messages {
+            if let Err(reason) = self.load_message_nonces(message) {
+                warn!(
+                    "Refusing to sign message {} for sign_id {}: {}",
+                    hex::encode(&message.message),
+                    sign_request.sign_id,
+                    reason
                 );
+                return Ok(vec![]);
+            }
 
-                let response = MessageTypes::SignShareResponse(response);
+            let signer_ids = message
+                .nonce_responses
+                .iter()
+                .map(|nr| nr.signer_id)
+                .collect::<Vec<u32>>();
+            let key_ids: Vec<u32> = message
+                .nonce_responses
+                .iter()
+                .flat_map(|nr| nr.key_ids.iter().copied())
+                .collect::<Vec<u32>>();
+            let nonces = message
+                .nonce_responses
+                .iter()
+                .flat_map(|nr| nr.message_nonces(message.nonce_index).to_vec())
+                .collect::<Vec<PublicNonce>>();
+            let shares = match sign_request.signature_type {
+                SignatureType::Frost => {
+                    self.signer
+                        .frost_signer
+                        .sign(&message.message, &signer_ids, &key_ids, &nonces)
+                }
+                SignatureType::Taproot(merkle_root) => self.signer.frost_signer.sign_taproot(
+                    &message.message,
+                    &signer_ids,
+                    &key_ids,
+                    &nonces,
+                    merkle_root,
+                ),
+            };
+            signature_shares.extend(shares);
 
-                msgs.push(response);
-            } else {
-                debug!("SignShareRequest for {} dropped.", signer_id);
+            if let Some(journal) = &mut self.journal {
+                journal.append(&message.message, None, signer_ids, None)?;
             }
         }
+
+        let response = SignatureShareResponse {
+            dkg_id: sign_request.dkg_id,
+            sign_id: sign_request.sign_id,
+            correlation_id: sign_request.correlation_id,
+            signer_id: self.signer.signer_id,
+            signature_shares,
+        };
+
+        info!(
+            "Sending SignatureShareResponse for signer_id {:?}",
+            self.signer.signer_id
+        );
+
+        msgs.push(MessageTypes::SignShareResponse(response));
         Ok(msgs)
     }
 
     fn dkg_begin(&mut self, dkg_begin: DkgBegin) -> Result<Vec<MessageTypes>, Error> {
         let mut rng = OsRng;
 
+        // A newer round pre-empts the one in progress, whatever state it is in
+        if self.state != States::Idle {
+            if dkg_begin.dkg_id <= self.dkg_id {
+                warn!(
+                    "Ignoring DkgBegin of round #{} during round #{}",
+                    dkg_begin.dkg_id, self.dkg_id
+                );
+                return Ok(vec![]);
+            }
+            warn!(
+                "DKG round #{} pre-empted by round #{} in state {:?}",
+                self.dkg_id, dkg_begin.dkg_id, self.state
+            );
+            self.abort_round()?;
+        }
+        self.rebalance_key_ids();
         self.reset(dkg_begin.dkg_id, &mut rng);
+        if dkg_begin.excluded_signers.contains(&self.signer.signer_id) {
+            warn!(
+                "Excluded from DKG round #{} for misbehaving in an earlier attempt",
+                self.dkg_id
+            );
+            self.restore_epoch_signer();
+            self.move_to(States::Idle)?;
+            return Ok(vec![]);
+        }
+        // Excluded parties neither send nor receive shares, their commitments are known to be zero
+        let wsts_version = self.wsts_version();
+        for signer_id in &dkg_begin.excluded_signers {
+            for key_id in self.public_keys.signer_key_ids(*signer_id) {
+                self.excluded_key_ids.insert(key_id - 1);
+            }
+            for party_id in wsts_version.party_ids(&self.public_keys, *signer_id) {
+                self.excluded_party_ids.insert(party_id - 1);
+                self.commitments.insert(
+                    party_id,
+                    zero_commitment(party_id, self.threshold, &mut rng),
+                );
+                self.shares.insert(party_id - 1, HashMap::new());
+            }
+        }
         self.move_to(States::DkgPublicDistribute)?;
 
-        let _party_state = self.signer.frost_signer.save();
-
         self.dkg_public_begin()
     }
 
@@ -634,17 +1571,23 @@ impl SigningRound {
     }
 
     fn dkg_private_begin(&mut self) -> Result<Vec<MessageTypes>, Error> {
+        // Shares are only sent once the commitments of all other parties were accepted
+        self.can_move_to(&States::DkgPrivateGather)?;
         let mut rng = OsRng;
         let mut msgs = vec![];
-        for (key_id, private_shares) in &self.signer.frost_signer.get_shares() {
+        for (party_id, private_shares) in &self.signer.frost_signer.get_shares() {
             info!(
-                "signer {} sending dkg private share for key_id #{}",
-                self.signer.signer_id, key_id
+                "signer {} sending dkg private share for party #{}",
+                self.signer.signer_id, party_id
             );
-            // encrypt each share for the recipient
+            // encrypt each share for the recipient, with a new ephemeral key for every round
+            let ephemeral_private_key = Scalar::random(&mut rng);
             let mut encrypted_shares = HashMap::new();
 
             for (dst_key_id, private_share) in private_shares {
+                if self.excluded_key_ids.contains(dst_key_id) {
+                    continue;
+                }
                 debug!(
                     "encrypting dkg private share for key_id #{}",
                     dst_key_id + 1
@@ -652,7 +1595,7 @@ impl SigningRound {
                 let compressed =
                     Compressed::from(self.public_keys.key_ids[&(dst_key_id + 1)].to_bytes());
                 let dst_public_key = Point::try_from(&compressed).unwrap();
-                let shared_secret = make_shared_secret(&self.network_private_key, &dst_public_key);
+                let shared_secret = make_shared_secret(&ephemeral_private_key, &dst_public_key);
                 let encrypted_share =
                     encrypt(&shared_secret, &private_share.to_bytes(), &mut rng).unwrap();
 
@@ -661,7 +1604,8 @@ impl SigningRound {
 
             let private_shares = DkgPrivateShares {
                 dkg_id: self.dkg_id,
-                key_id: *key_id,
+                key_id: *party_id,
+                ephemeral_key: Point::from(ephemeral_private_key),
                 private_shares: encrypted_shares,
             };
 
@@ -677,13 +1621,43 @@ impl SigningRound {
         &mut self,
         dkg_public_share: DkgPublicShare,
     ) -> Result<Vec<MessageTypes>, Error> {
+        if self
+            .excluded_party_ids
+            .contains(&dkg_public_share.party_id.wrapping_sub(1))
+        {
+            debug!(
+                "ignoring DkgPublicShare from excluded party #{}",
+                dkg_public_share.party_id
+            );
+            return Ok(vec![]);
+        }
+        if let Err(bad_commitment) = check_commitment(
+            dkg_public_share.party_id,
+            self.threshold,
+            &dkg_public_share.public_share,
+        ) {
+            warn!(
+                "rejecting DkgPublicShare from party #{}: {}",
+                dkg_public_share.party_id, bad_commitment
+            );
+            self.commitments.remove(&dkg_public_share.party_id);
+            self.bad_commitments
+                .insert(dkg_public_share.party_id, bad_commitment);
+            return Ok(vec![]);
+        }
+        if self
+            .bad_commitments
+            .contains_key(&dkg_public_share.party_id)
+        {
+            return Ok(vec![]);
+        }
         self.commitments
             .insert(dkg_public_share.party_id, dkg_public_share.public_share);
         info!(
-            "received DkgPublicShare from key #{} {}/{}",
+            "received DkgPublicShare from party #{} {}/{}",
             dkg_public_share.party_id,
             self.commitments.len(),
-            self.total_keys
+            self.total_parties()
         );
         Ok(vec![])
     }
@@ -692,18 +1666,368 @@ impl SigningRound {
         &mut self,
         dkg_private_shares: DkgPrivateShares,
     ) -> Result<Vec<MessageTypes>, Error> {
+        if self.excluded_party_ids.contains(&dkg_private_shares.key_id) {
+            debug!(
+                "ignoring DkgPrivateShares from excluded party #{}",
+                dkg_private_shares.key_id
+            );
+            return Ok(vec![]);
+        }
         let shares_clone = dkg_private_shares.private_shares.clone();
+        self.ephemeral_keys
+            .insert(dkg_private_shares.key_id, dkg_private_shares.ephemeral_key);
         self.shares
             .insert(dkg_private_shares.key_id, dkg_private_shares.private_shares);
         info!(
-            "received DkgPrivateShares from key #{} {}/{} {:?}",
+            "received DkgPrivateShares from party #{} {}/{} {:?}",
             dkg_private_shares.key_id,
             self.shares.len(),
-            self.total_keys,
+            self.total_parties(),
             shares_clone.keys(),
         );
         Ok(vec![])
     }
+
+    fn reshare_begin(&mut self, reshare_begin: ReshareBegin) -> Result<Vec<MessageTypes>, Error> {
+        // Resharing starts a new key epoch, so it pre-empts an older round like a DkgBegin does
+        if self.state != States::Idle {
+            if reshare_begin.dkg_id <= self.dkg_id {
+                warn!(
+                    "Ignoring ReshareBegin of round #{} during round #{}",
+                    reshare_begin.dkg_id, self.dkg_id
+                );
+                return Ok(vec![]);
+            }
+            warn!(
+                "Round #{} pre-empted by resharing round #{} in state {:?}",
+                self.dkg_id, reshare_begin.dkg_id, self.state
+            );
+            self.abort_round()?;
+        }
+        let group_key = self.signer.frost_signer.group_key();
+        let private_keys = self.signer.frost_signer.private_keys();
+        self.reset(reshare_begin.dkg_id, &mut OsRng);
+        self.move_to(States::ReshareGather)?;
+        info!(
+            "Resharing round #{}: {} key ids with a threshold of {} from dealers {:?}",
+            self.dkg_id,
+            reshare_begin.key_ids().len(),
+            reshare_begin.threshold,
+            reshare_begin.dealers.keys()
+        );
+
+        // Shares are encrypted for the network key of the signer holding the key id, so a signer
+        // unknown to the config could never receive them
+        let unknown: Vec<u32> = reshare_begin
+            .signer_key_ids
+            .iter()
+            .filter(|(signer_id, key_ids)| {
+                !key_ids.is_empty() && !self.public_keys.signers.contains_key(signer_id)
+            })
+            .map(|(signer_id, _)| *signer_id)
+            .collect();
+        if !unknown.is_empty() {
+            warn!(
+                "Rejecting resharing round #{} to signers {:?} missing from the config",
+                self.dkg_id, unknown
+            );
+            let dkg_end = DkgEnd {
+                dkg_id: self.dkg_id,
+                signer_id: self.signer.signer_id,
+                status: DkgStatus::Failure(DkgFailure::UnknownSigners(unknown)),
+            };
+            self.abort_round()?;
+            return Ok(vec![MessageTypes::ReshareEnd(dkg_end)]);
+        }
+
+        let mut msgs = vec![];
+        if let Some(dealer_key_ids) = reshare_begin.dealers.get(&self.signer.signer_id) {
+            // Dealers reshare the key shares of the current epoch, which must be of the same key
+            let missing: Vec<u32> = dealer_key_ids
+                .iter()
+                .filter(|key_id| !private_keys.contains_key(&(*key_id - 1)))
+                .copied()
+                .collect();
+            let failure = if !missing.is_empty() {
+                Some(DkgFailure::MissingKeyShares(missing))
+            } else if group_key != reshare_begin.aggregate_public_key {
+                Some(DkgFailure::ReshareKeyMismatch)
+            } else {
+                None
+            };
+            if let Some(failure) = failure {
+                warn!(
+                    "Unable to deal shares for resharing round #{}: {:?}",
+                    self.dkg_id, failure
+                );
+                let dkg_end = DkgEnd {
+                    dkg_id: self.dkg_id,
+                    signer_id: self.signer.signer_id,
+                    status: DkgStatus::Failure(failure),
+                };
+                self.abort_round()?;
+                return Ok(vec![MessageTypes::ReshareEnd(dkg_end)]);
+            }
+            let held: HashMap<u32, Scalar> = dealer_key_ids
+                .iter()
+                .map(|key_id| (key_id - 1, private_keys[&(key_id - 1)]))
+                .collect();
+            msgs.push(self.deal_shares(&reshare_begin, &held));
+        }
+        self.resharing = Some(reshare_begin);
+        Ok(msgs)
+    }
+
+    /// Deal shares of the dealer's part of the secret to the signers holding the new key ids
+    fn deal_shares(
+        &self,
+        reshare_begin: &ReshareBegin,
+        private_keys: &HashMap<u32, Scalar>,
+    ) -> MessageTypes {
+        let mut rng = OsRng;
+        let signer_id = self.signer.signer_id;
+        let party_id = reshare_begin.dealer_party_id(signer_id).unwrap_or_default();
+        let secret = dealer_secret(private_keys, &reshare_begin.dealer_key_ids());
+        let dealing = deal(
+            party_id,
+            &secret,
+            reshare_begin.threshold,
+            &reshare_begin.key_ids(),
+            &mut rng,
+        );
+
+        let mut encrypted_shares = HashMap::new();
+        for (key_id, share) in &dealing.shares {
+            let Some(dst_public_key) = reshare_begin
+                .key_id_signer(*key_id)
+                .and_then(|dst_signer_id| self.public_keys.signers.get(&dst_signer_id))
+                .and_then(public_key_point)
+            else {
+                warn!(
+                    "Unable to deal a share to key_id #{}: unknown signer",
+                    key_id + 1
+                );
+                continue;
+            };
+            let shared_secret = self.network_private_key.shared_secret(&dst_public_key);
+            let encrypted_share = encrypt(&shared_secret, &share.to_bytes(), &mut rng).unwrap();
+            encrypted_shares.insert(*key_id, encrypted_share);
+        }
+        info!(
+            "sending ReshareShares for round #{} as dealer party #{} from signer #{}",
+            self.dkg_id, party_id, signer_id
+        );
+        MessageTypes::ReshareShares(ReshareShares {
+            dkg_id: self.dkg_id,
+            signer_id,
+            commitment: dealing.commitment,
+            private_shares: encrypted_shares,
+        })
+    }
+
+    fn reshare_shares(
+        &mut self,
+        reshare_shares: ReshareShares,
+    ) -> Result<Vec<MessageTypes>, Error> {
+        let Some(resharing) = &self.resharing else {
+            debug!("ignoring ReshareShares outside of resharing");
+            return Ok(vec![]);
+        };
+        if self.state != States::ReshareGather || reshare_shares.dkg_id != self.dkg_id {
+            debug!(
+                "ignoring ReshareShares of round #{} during round #{}",
+                reshare_shares.dkg_id, self.dkg_id
+            );
+            return Ok(vec![]);
+        }
+        let Some(party_id) = resharing.dealer_party_id(reshare_shares.signer_id) else {
+            warn!(
+                "ignoring ReshareShares from signer #{} which is not a dealer",
+                reshare_shares.signer_id
+            );
+            return Ok(vec![]);
+        };
+        if let Err(bad_commitment) =
+            check_commitment(party_id, resharing.threshold, &reshare_shares.commitment)
+        {
+            warn!(
+                "rejecting ReshareShares from dealer #{}: {}",
+                reshare_shares.signer_id, bad_commitment
+            );
+            self.commitments.remove(&party_id);
+            self.bad_commitments.insert(party_id, bad_commitment);
+            return Ok(vec![]);
+        }
+        if self.bad_commitments.contains_key(&party_id) {
+            return Ok(vec![]);
+        }
+        let dealers = resharing.dealers.len();
+        self.commitments.insert(party_id, reshare_shares.commitment);
+        self.shares.insert(party_id, reshare_shares.private_shares);
+        info!(
+            "received ReshareShares from dealer #{} {}/{}",
+            reshare_shares.signer_id,
+            self.commitments.len(),
+            dealers
+        );
+        Ok(vec![])
+    }
+
+    fn can_reshare_end(&self) -> bool {
+        self.state == States::ReshareGather
+            && self.resharing.as_ref().map_or(false, |resharing| {
+                self.commitments.len() + self.bad_commitments.len() == resharing.dealers.len()
+            })
+    }
+
+    /// Take on the key shares dealt to the signer, or keep those of the current key epoch if resharing failed
+    fn reshare_ended(&mut self, resharing: ReshareBegin) -> Result<MessageTypes, Error> {
+        let status = if self.bad_commitments.is_empty() {
+            match self.reshared_private_keys(&resharing) {
+                Ok(private_keys) => self.take_reshared_keys(&resharing, &private_keys),
+                Err(failure) => DkgStatus::Failure(failure),
+            }
+        } else {
+            DkgStatus::Failure(DkgFailure::BadPublicShares(self.bad_commitments.clone()))
+        };
+        let dkg_end = DkgEnd {
+            dkg_id: self.dkg_id,
+            signer_id: self.signer.signer_id,
+            status,
+        };
+        if let DkgStatus::Failure(failure) = &dkg_end.status {
+            warn!("Resharing round #{} failed: {:?}", self.dkg_id, failure);
+            self.abort_round()?;
+        }
+        info!(
+            "RESHARE_END round #{} signer_id {}",
+            dkg_end.dkg_id, self.signer.signer_id
+        );
+        Ok(MessageTypes::ReshareEnd(dkg_end))
+    }
+
+    /// The private keys of the signer's new key ids, counted from 0: the sums of the shares the
+    /// dealers dealt to them
+    fn reshared_private_keys(
+        &self,
+        resharing: &ReshareBegin,
+    ) -> Result<HashMap<u32, Scalar>, DkgFailure> {
+        let aggregate_public_key = self
+            .commitments
+            .values()
+            .fold(Point::default(), |sum, commitment| sum + commitment.A[0]);
+        if aggregate_public_key != resharing.aggregate_public_key {
+            return Err(DkgFailure::ReshareKeyMismatch);
+        }
+
+        let key_ids: Vec<u32> = resharing
+            .signer_key_ids
+            .get(&self.signer.signer_id)
+            .map(|key_ids| key_ids.iter().map(|key_id| key_id - 1).collect())
+            .unwrap_or_default();
+        let mut private_keys: HashMap<u32, Scalar> = key_ids
+            .iter()
+            .map(|key_id| (*key_id, Scalar::new()))
+            .collect();
+        let mut bad_dealers = vec![];
+        for (dealer, party_id) in resharing.dealers.keys().zip(1u32..) {
+            let (Some(commitment), Some(encrypted_shares), Some(dealer_public_key)) = (
+                self.commitments.get(&party_id),
+                self.shares.get(&party_id),
+                self.public_keys
+                    .signers
+                    .get(dealer)
+                    .and_then(public_key_point),
+            ) else {
+                bad_dealers.push(*dealer);
+                continue;
+            };
+            let shared_secret = self.network_private_key.shared_secret(&dealer_public_key);
+            for key_id in &key_ids {
+                let share = encrypted_shares
+                    .get(key_id)
+                    .and_then(|encrypted_share| decrypt(&shared_secret, encrypted_share).ok())
+                    .and_then(|plain| Scalar::try_from(&plain[..]).ok())
+                    .filter(|share| is_consistent_share(share, *key_id, commitment));
+                match (share, private_keys.get_mut(key_id)) {
+                    (Some(share), Some(private_key)) => *private_key = *private_key + share,
+                    _ => {
+                        warn!(
+                            "Bad reshared share from dealer #{} to key_id #{}",
+                            dealer,
+                            key_id + 1
+                        );
+                        bad_dealers.push(*dealer);
+                        break;
+                    }
+                }
+            }
+        }
+        if bad_dealers.is_empty() {
+            Ok(private_keys)
+        } else {
+            Err(DkgFailure::BadReshareShares(bad_dealers))
+        }
+    }
+
+    /// Replace the key shares of the last key epoch with the reshared ones, and delete the saved
+    /// states of earlier rounds. Signers left without key ids keep nothing that could sign for the
+    /// aggregate public key.
+    fn take_reshared_keys(
+        &mut self,
+        resharing: &ReshareBegin,
+        private_keys: &HashMap<u32, Scalar>,
+    ) -> DkgStatus {
+        let total_keys = resharing.key_ids().len() as u32;
+        let mut key_ids: Vec<u32> = private_keys.keys().copied().collect();
+        key_ids.sort_unstable();
+        let reshared = WstsSigner::reshared(
+            self.wsts_version(),
+            self.signer.signer_id,
+            &key_ids,
+            self.total_signers,
+            total_keys,
+            resharing.threshold,
+            resharing.aggregate_public_key,
+            private_keys,
+            &mut OsRng,
+        );
+        // Keep the key shares of the last epoch until the reshared ones are saved
+        let held = std::mem::replace(&mut self.signer.frost_signer, reshared);
+        if let Err(e) = self.save_dkg_state() {
+            warn!("Failed to save DKG state of round #{}: {}", self.dkg_id, e);
+            self.signer.frost_signer = held;
+            return DkgStatus::Failure(DkgFailure::SaveState(e.to_string()));
+        }
+        drop(held);
+        if let Some(directory) = &self.dkg_state_directory {
+            if let Err(e) = dkg_state::remove_older(directory, self.signer.signer_id, self.dkg_id) {
+                warn!(
+                    "Failed to remove DKG states before round #{}: {}",
+                    self.dkg_id, e
+                );
+            }
+        }
+        self.threshold = resharing.threshold;
+        self.total_keys = total_keys;
+        let mut public_key_ids = HashMap::new();
+        for (signer_id, key_ids) in &resharing.signer_key_ids {
+            if let Some(public_key) = self.public_keys.signers.get(signer_id) {
+                for key_id in key_ids {
+                    public_key_ids.insert(*key_id, *public_key);
+                }
+            }
+        }
+        self.public_keys.key_ids = public_key_ids;
+        // Saved party states hold the private keys of the last epoch
+        self.message_nonces.clear();
+        self.key_epoch = Some(self.dkg_id);
+        info!(
+            "Resharing round #{} gave signer #{} key ids {:?}",
+            self.dkg_id, self.signer.signer_id, key_ids
+        );
+        DkgStatus::Success
+    }
 }
 
 impl From<&FrostSigner> for SigningRound {
@@ -717,15 +2041,17 @@ impl From<&FrostSigner> for SigningRound {
 
         assert!(signer.config.keys_threshold <= signer.config.total_keys);
         let mut rng = OsRng;
-        let frost_signer = v1::Signer::new(
+        let frost_signer = WstsSigner::new(
+            signer.config.wsts_version,
             signer_id,
             &key_ids,
+            signer.config.total_signers,
             signer.config.total_keys,
             signer.config.keys_threshold,
             &mut rng,
         );
 
-        let network_private_key = signer.config.network_private_key;
+        let network_private_key = signer.config.network_private_key.clone();
         let public_keys = signer.config.public_keys.clone();
 
         SigningRound {
@@ -741,11 +2067,25 @@ impl From<&FrostSigner> for SigningRound {
                 signer_id,
             },
             state: States::Idle,
+            state_entered_at: Instant::now(),
+            state_timeouts: signer.config.state_timeouts,
+            key_epoch: None,
+            epoch_signer: None,
             commitments: BTreeMap::new(),
             shares: HashMap::new(),
+            ephemeral_keys: HashMap::new(),
+            excluded_key_ids: HashSet::new(),
+            excluded_party_ids: HashSet::new(),
+            bad_commitments: BTreeMap::new(),
+            resharing: None,
             public_nonces: vec![],
+            message_nonces: HashMap::new(),
+            nonce_pool: NoncePool::new(),
             network_private_key,
             public_keys,
+            journal: None,
+            validator: None,
+            dkg_state_directory: None,
         }
     }
 }
@@ -753,13 +2093,25 @@ impl From<&FrostSigner> for SigningRound {
 #[cfg(test)]
 mod test {
     use hashbrown::HashMap;
+    use p256k1::{ecdsa, point::Point};
     use rand_core::{CryptoRng, OsRng, RngCore};
-    use wsts::{common::PolyCommitment, schnorr::ID, Scalar};
+    use wsts::{
+        common::{PolyCommitment, PublicNonce, SignatureShare},
+        schnorr::ID,
+        Scalar,
+    };
 
+    use crate::complaint::{zero_commitment, BadCommitment, Blame, ComplaintReason};
+    use crate::config::PublicKeys;
     use crate::signing_round::{
-        DkgPrivateShares, DkgPublicShare, DkgStatus, MessageTypes, SigningRound,
+        BatchMessage, DkgBegin, DkgFailure, DkgPrivateShares, DkgPublicShare, DkgStatus,
+        MessageTypes, NoncePoolRequest, NoncePoolResponse, NonceRequest, NonceResponse,
+        ReshareBegin, SignatureShareRequest, SignatureType, SigningRound,
     };
     use crate::state_machine::States;
+    use crate::util::encrypt;
+    use crate::wsts_version::{WstsAggregator, WstsVersion};
+    use std::collections::BTreeMap;
 
     fn get_rng() -> impl RngCore + CryptoRng {
         let rnd = OsRng;
@@ -772,12 +2124,13 @@ mod test {
         let mut rnd = get_rng();
         let mut signing_round =
             SigningRound::new(1, 1, 1, 1, vec![1], Default::default(), Default::default());
+        let secret = Scalar::random(&mut rnd);
         let public_share = DkgPublicShare {
             dkg_id: 0,
-            party_id: 0,
+            party_id: 1,
             public_share: PolyCommitment {
-                id: ID::new(&Scalar::new(), &Scalar::new(), &mut rnd),
-                A: vec![],
+                id: ID::new(&Scalar::from(1u32), &secret, &mut rnd),
+                A: vec![Point::from(secret)],
             },
             dkg_public_id: 0,
         };
@@ -785,6 +2138,64 @@ mod test {
         assert_eq!(1, signing_round.commitments.len())
     }
 
+    #[test]
+    fn bad_commitments_are_rejected() {
+        let mut rnd = get_rng();
+        let (_, mut rounds) = signing_rounds();
+        let mut public_shares =
+            broadcast(&mut rounds, &[MessageTypes::DkgBegin(dkg_begin(vec![]))]);
+
+        // Key id 4 of signer 2 picks its key to cancel the secret of key id 1, copying its proof
+        let commitment_1 = match &public_shares[0] {
+            MessageTypes::DkgPublicShare(share) if share.party_id == 1 => {
+                share.public_share.clone()
+            }
+            msg => panic!("expected DkgPublicShare of key #1, got {:?}", msg),
+        };
+        // Key id 3 of signer 2 commits to a polynomial of the wrong degree
+        for message in &mut public_shares {
+            if let MessageTypes::DkgPublicShare(share) = message {
+                match share.party_id {
+                    3 => share
+                        .public_share
+                        .A
+                        .push(Point::from(Scalar::random(&mut rnd))),
+                    4 => {
+                        share.public_share.A[0] = share.public_share.A[0] - commitment_1.A[0];
+                        share.public_share.id = ID {
+                            id: Scalar::from(4u32),
+                            ..commitment_1.id.clone()
+                        };
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let ends = rounds[0].process_all(&public_shares);
+        let Some(MessageTypes::DkgEnd(dkg_end)) = ends.last() else {
+            panic!("expected DkgEnd, got {:?}", ends);
+        };
+        let DkgStatus::Failure(DkgFailure::BadPublicShares(bad_commitments)) = &dkg_end.status
+        else {
+            panic!("expected rejected commitments, got {:?}", dkg_end.status);
+        };
+        assert_eq!(
+            bad_commitments.clone().into_iter().collect::<Vec<_>>(),
+            vec![
+                (3, BadCommitment::WrongDegree(4)),
+                (4, BadCommitment::InvalidProof)
+            ]
+        );
+        assert_eq!(rounds[0].state, States::Idle);
+        assert!(!rounds[0].commitments.contains_key(&4));
+
+        // No private shares are sent to the rogue key
+        assert!(rounds[0]
+            .process(MessageTypes::DkgPrivateBegin(dkg_begin(vec![])))
+            .is_err());
+    }
+
     #[test]
     fn dkg_private_shares() {
         let mut signing_round =
@@ -792,6 +2203,7 @@ mod test {
         let mut private_shares = DkgPrivateShares {
             dkg_id: 0,
             key_id: 0,
+            ephemeral_key: Point::default(),
             private_shares: HashMap::new(),
         };
         private_shares.private_shares.insert(1, Vec::new());
@@ -860,4 +2272,687 @@ mod test {
             _ => assert!(false),
         }
     }
+
+    /// Two signers with two key ids each and a threshold of three keys
+    fn signing_rounds() -> (Vec<Scalar>, Vec<SigningRound>) {
+        let private_keys: Vec<Scalar> = (0..2).map(|_| Scalar::random(&mut OsRng)).collect();
+        let mut public_keys = PublicKeys::default();
+        for (i, private_key) in private_keys.iter().enumerate() {
+            let public_key = ecdsa::PublicKey::new(private_key).unwrap();
+            let signer_id = i as u32 + 1;
+            public_keys.signers.insert(signer_id, public_key);
+            public_keys.key_ids.insert(2 * signer_id - 1, public_key);
+            public_keys.key_ids.insert(2 * signer_id, public_key);
+        }
+        let rounds = private_keys
+            .iter()
+            .enumerate()
+            .map(|(i, private_key)| {
+                let key_ids = vec![2 * i as u32, 2 * i as u32 + 1];
+                SigningRound::new(
+                    3,
+                    2,
+                    4,
+                    i as u32 + 1,
+                    key_ids,
+                    *private_key,
+                    public_keys.clone(),
+                )
+            })
+            .collect();
+        (private_keys, rounds)
+    }
+
+    fn dkg_begin(excluded_signers: Vec<u32>) -> DkgBegin {
+        DkgBegin {
+            dkg_id: 1,
+            expires_at: 0,
+            excluded_signers,
+        }
+    }
+
+    /// Deliver every message to every signer, as the relay does
+    fn broadcast(rounds: &mut [SigningRound], messages: &[MessageTypes]) -> Vec<MessageTypes> {
+        let mut out = vec![];
+        for round in rounds.iter_mut() {
+            for message in messages {
+                out.extend(round.process(message.clone()).unwrap());
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn bad_private_shares_are_complained_about() {
+        let (private_keys, mut rounds) = signing_rounds();
+        let public_shares = broadcast(&mut rounds, &[MessageTypes::DkgBegin(dkg_begin(vec![]))]);
+        broadcast(&mut rounds, &public_shares);
+        let mut private_shares = broadcast(
+            &mut rounds,
+            &[MessageTypes::DkgPrivateBegin(dkg_begin(vec![]))],
+        );
+
+        // Key id 2 of signer 2 sends key id 0 of signer 1 a share nobody can decrypt
+        let mut tampered = None;
+        for message in &mut private_shares {
+            if let MessageTypes::DkgPrivateShares(shares) = message {
+                if shares.key_id == 2 {
+                    let garbage = encrypt(&[7; 32], &[1; 32], &mut OsRng).unwrap();
+                    shares.private_shares.insert(0, garbage.clone());
+                    tampered = Some((shares.ephemeral_key, garbage));
+                }
+            }
+        }
+        let (ephemeral_key, garbage) = tampered.unwrap();
+        let ends = rounds[0].process_all(&private_shares);
+        let Some(MessageTypes::DkgEnd(dkg_end)) = ends.last() else {
+            panic!("expected DkgEnd, got {:?}", ends);
+        };
+        let DkgStatus::Failure(DkgFailure::BadPrivateShares(complaints)) = &dkg_end.status else {
+            panic!("expected complaints, got {:?}", dkg_end.status);
+        };
+        assert_eq!(complaints.len(), 1);
+        let complaint = &complaints[0];
+        assert_eq!(
+            (complaint.src_key_id, complaint.dst_key_id, complaint.reason),
+            (2, 0, ComplaintReason::Undecryptable)
+        );
+        assert_eq!(
+            complaint.judge(
+                &Point::from(private_keys[0]),
+                Some(&ephemeral_key),
+                Some(garbage.as_slice()),
+                &rounds[0].commitments[&3],
+            ),
+            Blame::Accused
+        );
+
+        // The untampered round completes for the other signer
+        let ends = rounds[1].process_all(&private_shares);
+        assert!(matches!(
+            ends.last(),
+            Some(MessageTypes::DkgEnd(dkg_end)) if matches!(dkg_end.status, DkgStatus::Success)
+        ));
+    }
+
+    #[test]
+    fn truncated_private_shares_are_complained_about() {
+        let (_, mut rounds) = signing_rounds();
+        let public_shares = broadcast(&mut rounds, &[MessageTypes::DkgBegin(dkg_begin(vec![]))]);
+        broadcast(&mut rounds, &public_shares);
+        let mut private_shares = broadcast(
+            &mut rounds,
+            &[MessageTypes::DkgPrivateBegin(dkg_begin(vec![]))],
+        );
+
+        // Key id 2 of signer 2 sends key id 0 of signer 1 a share shorter than a nonce
+        for message in &mut private_shares {
+            if let MessageTypes::DkgPrivateShares(shares) = message {
+                if shares.key_id == 2 {
+                    shares.private_shares.insert(0, vec![1; 5]);
+                }
+            }
+        }
+        let ends = rounds[0].process_all(&private_shares);
+        let Some(MessageTypes::DkgEnd(dkg_end)) = ends.last() else {
+            panic!("expected DkgEnd, got {:?}", ends);
+        };
+        let DkgStatus::Failure(DkgFailure::BadPrivateShares(complaints)) = &dkg_end.status else {
+            panic!("expected complaints, got {:?}", dkg_end.status);
+        };
+        assert_eq!(
+            complaints
+                .iter()
+                .map(|complaint| (complaint.src_key_id, complaint.dst_key_id, complaint.reason))
+                .collect::<Vec<_>>(),
+            vec![(2, 0, ComplaintReason::Undecryptable)]
+        );
+    }
+
+    #[test]
+    fn excluded_signers_take_no_part() {
+        let (_, mut rounds) = signing_rounds();
+        let begin = MessageTypes::DkgBegin(dkg_begin(vec![2]));
+
+        // The excluded signer stays idle
+        assert!(rounds[1].process(begin.clone()).unwrap().is_empty());
+        assert_eq!(rounds[1].state, States::Idle);
+
+        let public_shares = rounds[0].process(begin).unwrap();
+        assert_eq!(public_shares.len(), 2);
+        // Key ids 3 and 4 of the excluded signer are known to commit to zero
+        assert!(rounds[0].commitments[&3]
+            .A
+            .iter()
+            .all(|a| *a == Point::default()));
+        assert_eq!(
+            rounds[0].excluded_key_ids,
+            [2, 3].into_iter().collect::<hashbrown::HashSet<u32>>()
+        );
+
+        // Messages of the excluded signer are ignored
+        let excluded_shares = DkgPrivateShares {
+            dkg_id: 1,
+            key_id: 2,
+            ephemeral_key: Point::default(),
+            private_shares: HashMap::new(),
+        };
+        rounds[0]
+            .process(MessageTypes::DkgPrivateShares(excluded_shares))
+            .unwrap();
+        assert!(rounds[0].shares[&2].is_empty());
+
+        // Shares are only sent to included key ids
+        let public_end = rounds[0].process_all(&public_shares);
+        assert!(matches!(
+            public_end.last(),
+            Some(MessageTypes::DkgPublicEnd(_))
+        ));
+        let private_shares =
+            rounds[0].process_all(&[MessageTypes::DkgPrivateBegin(dkg_begin(vec![2]))]);
+        for message in &private_shares {
+            let MessageTypes::DkgPrivateShares(shares) = message else {
+                panic!("expected DkgPrivateShares");
+            };
+            let mut dst_key_ids: Vec<u32> = shares.private_shares.keys().copied().collect();
+            dst_key_ids.sort();
+            assert_eq!(dst_key_ids, vec![0, 1]);
+        }
+    }
+
+    fn run_dkg(rounds: &mut [SigningRound]) {
+        let public_shares = broadcast(rounds, &[MessageTypes::DkgBegin(dkg_begin(vec![]))]);
+        broadcast(rounds, &public_shares);
+        let private_shares = broadcast(rounds, &[MessageTypes::DkgPrivateBegin(dkg_begin(vec![]))]);
+        broadcast(rounds, &private_shares);
+    }
+
+    #[test]
+    fn batch_nonces_are_used_once() {
+        let (_, mut rounds) = signing_rounds();
+        run_dkg(&mut rounds);
+
+        let nonce_responses: Vec<NonceResponse> = broadcast(
+            &mut rounds,
+            &[MessageTypes::NonceRequest(NonceRequest {
+                dkg_id: 1,
+                sign_id: 1,
+                sign_nonce_id: 1,
+                expires_at: 0,
+                message_count: 3,
+            })],
+        )
+        .into_iter()
+        .map(|message| match message {
+            MessageTypes::NonceResponse(response) => response,
+            message => panic!("expected NonceResponse, got {:?}", message),
+        })
+        .collect();
+        // One nonce per key id for each message
+        assert!(nonce_responses.iter().all(|nr| nr.nonces.len() == 6));
+        assert_eq!(rounds[0].message_nonces.len(), 3);
+
+        let request = SignatureShareRequest {
+            dkg_id: 1,
+            sign_id: 1,
+            correlation_id: 0,
+            nonce_responses: nonce_responses.clone(),
+            message: vec![1],
+            signature_type: SignatureType::Frost,
+            expires_at: 0,
+            context: None,
+            nonce_index: 2,
+            batch: vec![BatchMessage {
+                message: vec![2],
+                context: None,
+                nonce_responses,
+                nonce_index: 0,
+            }],
+        };
+        let responses = rounds[0]
+            .process(MessageTypes::SignShareRequest(request.clone()))
+            .unwrap();
+        let [MessageTypes::SignShareResponse(response)] = responses.as_slice() else {
+            panic!("expected SignShareResponse, got {:?}", responses);
+        };
+        assert_eq!(response.signature_shares.len(), 4);
+        assert_eq!(rounds[0].message_nonces.len(), 1);
+
+        // Signing again with the same nonces would leak the key shares
+        assert!(rounds[0]
+            .process(MessageTypes::SignShareRequest(request))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn pool_nonces_are_used_once() {
+        let (_, mut rounds) = signing_rounds();
+        run_dkg(&mut rounds);
+
+        let pools: Vec<NoncePoolResponse> = broadcast(
+            &mut rounds,
+            &[MessageTypes::NoncePoolRequest(NoncePoolRequest {
+                dkg_id: 1,
+                pool_id: 1,
+                count: 3,
+                expires_at: 0,
+            })],
+        )
+        .into_iter()
+        .map(|message| match message {
+            MessageTypes::NoncePoolResponse(response) => response,
+            message => panic!("expected NoncePoolResponse, got {:?}", message),
+        })
+        .collect();
+        assert!(pools.iter().all(|pool| pool.nonces.len() == 6));
+        let nonce_responses = |pool_index: usize| -> Vec<NonceResponse> {
+            pools
+                .iter()
+                .map(|pool| pool.nonce_responses(2)[pool_index].clone())
+                .collect()
+        };
+        let request = |message: Vec<u8>, nonce_responses: Vec<NonceResponse>| {
+            MessageTypes::SignShareRequest(SignatureShareRequest {
+                dkg_id: 1,
+                sign_id: 2,
+                correlation_id: 0,
+                nonce_responses,
+                message,
+                signature_type: SignatureType::Frost,
+                expires_at: 0,
+                context: None,
+                nonce_index: 0,
+                batch: vec![],
+            })
+        };
+
+        let signed = |responses: Vec<MessageTypes>| {
+            matches!(responses.as_slice(), [MessageTypes::SignShareResponse(_)])
+        };
+        assert!(signed(
+            rounds[0]
+                .process(request(vec![1], nonce_responses(1)))
+                .unwrap()
+        ));
+        // Neither the used nonce nor one published before it signs another message
+        for pool_index in [1, 0] {
+            assert!(!signed(
+                rounds[0]
+                    .process(request(vec![2], nonce_responses(pool_index)))
+                    .unwrap()
+            ));
+        }
+        // Nor nonces the signer did not publish
+        let mut forged = nonce_responses(2);
+        forged[0].nonces.reverse();
+        assert!(!signed(
+            rounds[0].process(request(vec![2], forged)).unwrap()
+        ));
+        // The forged request burnt the last pool nonce
+        assert_eq!(rounds[0].nonce_pool.state().next_unused, 3);
+    }
+
+    #[test]
+    fn v2_signers_send_one_nonce_and_share_per_message() {
+        let (_, rounds) = signing_rounds();
+        let mut rounds: Vec<SigningRound> = rounds
+            .into_iter()
+            .map(|round| round.with_wsts_version(WstsVersion::V2))
+            .collect();
+
+        // One commitment and one set of private shares per signer
+        let public_shares = broadcast(&mut rounds, &[MessageTypes::DkgBegin(dkg_begin(vec![]))]);
+        assert_eq!(public_shares.len(), 2);
+        broadcast(&mut rounds, &public_shares);
+        let private_shares = broadcast(
+            &mut rounds,
+            &[MessageTypes::DkgPrivateBegin(dkg_begin(vec![]))],
+        );
+        assert_eq!(private_shares.len(), 2);
+        let ends = broadcast(&mut rounds, &private_shares);
+        assert_eq!(ends.len(), 2);
+        assert!(ends.iter().all(|end| matches!(
+            end,
+            MessageTypes::DkgEnd(dkg_end) if matches!(dkg_end.status, DkgStatus::Success)
+        )));
+
+        let nonce_responses: Vec<NonceResponse> = broadcast(
+            &mut rounds,
+            &[MessageTypes::NonceRequest(NonceRequest {
+                dkg_id: 1,
+                sign_id: 1,
+                sign_nonce_id: 1,
+                expires_at: 0,
+                message_count: 2,
+            })],
+        )
+        .into_iter()
+        .map(|message| match message {
+            MessageTypes::NonceResponse(response) => response,
+            message => panic!("expected NonceResponse, got {:?}", message),
+        })
+        .collect();
+        assert!(nonce_responses.iter().all(|nr| nr.nonces.len() == 2));
+
+        let messages = [vec![1], vec![2]];
+        let request = SignatureShareRequest {
+            dkg_id: 1,
+            sign_id: 1,
+            correlation_id: 0,
+            nonce_responses: nonce_responses.clone(),
+            message: messages[0].clone(),
+            signature_type: SignatureType::Frost,
+            expires_at: 0,
+            context: None,
+            nonce_index: 0,
+            batch: vec![BatchMessage {
+                message: messages[1].clone(),
+                context: None,
+                nonce_responses: nonce_responses.clone(),
+                nonce_index: 1,
+            }],
+        };
+        let shares: Vec<Vec<SignatureShare>> =
+            broadcast(&mut rounds, &[MessageTypes::SignShareRequest(request)])
+                .into_iter()
+                .map(|message| match message {
+                    MessageTypes::SignShareResponse(response) => response.signature_shares,
+                    message => panic!("expected SignShareResponse, got {:?}", message),
+                })
+                .collect();
+        assert!(shares.iter().all(|shares| shares.len() == 2));
+
+        let polys: Vec<PolyCommitment> = rounds[0].commitments.values().cloned().collect();
+        let aggregate_public_key = polys
+            .iter()
+            .fold(Point::default(), |key, poly| key + poly.A[0]);
+        let mut aggregator = WstsAggregator::new(WstsVersion::V2, 4, 3, polys).unwrap();
+        let key_ids: Vec<u32> = nonce_responses
+            .iter()
+            .flat_map(|nr| nr.key_ids.clone())
+            .collect();
+        for (index, message) in messages.iter().enumerate() {
+            let nonces: Vec<PublicNonce> = nonce_responses
+                .iter()
+                .flat_map(|nr| nr.message_nonces(index as u32).to_vec())
+                .collect();
+            let message_shares: Vec<SignatureShare> =
+                shares.iter().map(|shares| shares[index].clone()).collect();
+            let sig = aggregator
+                .sign(message, &nonces, &message_shares, &key_ids)
+                .unwrap();
+            assert!(sig.verify(&aggregate_public_key, message));
+        }
+    }
+
+    #[test]
+    fn stale_dkg_rounds_time_out_to_the_last_key_epoch() {
+        let (_, mut rounds) = signing_rounds();
+        run_dkg(&mut rounds);
+        assert_eq!(rounds[0].key_epoch, Some(1));
+
+        // The coordinator goes away after starting round #2
+        let begin = DkgBegin {
+            dkg_id: 2,
+            ..dkg_begin(vec![])
+        };
+        let public_shares = broadcast(&mut rounds, &[MessageTypes::DkgBegin(begin)]);
+        rounds[0].process_all(&public_shares);
+        assert_eq!(rounds[0].state, States::DkgPrivateDistribute);
+
+        let timeout = rounds[0].state_timeouts.dkg_private;
+        let entered_at = rounds[0].state_entered_at;
+        assert!(!rounds[0].expire_state(entered_at).unwrap());
+        assert!(rounds[0].expire_state(entered_at + timeout).unwrap());
+        assert_eq!(rounds[0].state, States::Idle);
+        assert_eq!(rounds[0].dkg_id, 1);
+        assert!(rounds[0].commitments.is_empty());
+
+        // The signer keeps handing out nonces for the keys of round #1
+        let pool = rounds[0]
+            .process(MessageTypes::NoncePoolRequest(NoncePoolRequest {
+                dkg_id: 1,
+                pool_id: 1,
+                count: 1,
+                expires_at: 0,
+            }))
+            .unwrap();
+        assert!(matches!(pool[..], [MessageTypes::NoncePoolResponse(_)]));
+    }
+
+    #[test]
+    fn newer_dkg_rounds_pre_empt_stale_ones() {
+        let (_, mut rounds) = signing_rounds();
+        let public_shares = broadcast(&mut rounds, &[MessageTypes::DkgBegin(dkg_begin(vec![]))]);
+        broadcast(&mut rounds, &public_shares);
+        broadcast(
+            &mut rounds,
+            &[MessageTypes::DkgPrivateBegin(dkg_begin(vec![]))],
+        );
+        assert_eq!(rounds[0].state, States::DkgPrivateGather);
+
+        // A replayed or older DkgBegin does not disturb the round
+        assert!(rounds[0]
+            .process(MessageTypes::DkgBegin(dkg_begin(vec![])))
+            .unwrap()
+            .is_empty());
+        assert_eq!(rounds[0].state, States::DkgPrivateGather);
+
+        // A newer one starts over from its public shares
+        let begin = DkgBegin {
+            dkg_id: 2,
+            ..dkg_begin(vec![])
+        };
+        let public_shares = broadcast(&mut rounds, &[MessageTypes::DkgBegin(begin.clone())]);
+        assert_eq!(public_shares.len(), 4);
+        assert!(rounds.iter().all(|round| round.dkg_id == 2));
+        broadcast(&mut rounds, &public_shares);
+        let private_shares = broadcast(&mut rounds, &[MessageTypes::DkgPrivateBegin(begin)]);
+        let ends = broadcast(&mut rounds, &private_shares);
+        assert_eq!(ends.len(), 2);
+        assert!(ends.iter().all(|end| matches!(
+            end,
+            MessageTypes::DkgEnd(dkg_end)
+                if dkg_end.dkg_id == 2 && matches!(dkg_end.status, DkgStatus::Success)
+        )));
+        assert!(rounds.iter().all(|round| round.key_epoch == Some(2)));
+    }
+
+    #[test]
+    fn key_ids_rebalance_at_the_next_dkg_round() {
+        let (_, mut rounds) = signing_rounds();
+        run_dkg(&mut rounds);
+
+        // Key id 2 moves from signer 1 to signer 2
+        let public_key = rounds[1].public_keys.signers[&2];
+        for round in rounds.iter_mut() {
+            round.public_keys.key_ids.insert(2, public_key);
+        }
+        // The keys of the completed round stay until the next one begins
+        assert_eq!(rounds[0].signer.frost_signer.get_key_ids(), vec![0, 1]);
+
+        let begin = DkgBegin {
+            dkg_id: 2,
+            ..dkg_begin(vec![])
+        };
+        let public_shares = broadcast(&mut rounds, &[MessageTypes::DkgBegin(begin.clone())]);
+        assert_eq!(rounds[0].signer.frost_signer.get_key_ids(), vec![0]);
+        let mut key_ids = rounds[1].signer.frost_signer.get_key_ids();
+        key_ids.sort();
+        assert_eq!(key_ids, vec![1, 2, 3]);
+
+        broadcast(&mut rounds, &public_shares);
+        let private_shares = broadcast(&mut rounds, &[MessageTypes::DkgPrivateBegin(begin)]);
+        let ends = broadcast(&mut rounds, &private_shares);
+        assert_eq!(ends.len(), 2);
+        assert!(ends.iter().all(|end| matches!(
+            end,
+            MessageTypes::DkgEnd(dkg_end) if matches!(dkg_end.status, DkgStatus::Success)
+        )));
+    }
+
+    #[test]
+    fn aborted_rounds_keep_the_key_ids_of_the_last_key_epoch() {
+        let (_, mut rounds) = signing_rounds();
+        run_dkg(&mut rounds);
+        let aggregate_public_key = rounds[0].signer.frost_signer.group_key();
+
+        // Key id 2 moves from signer 1 to signer 2, but the coordinator goes away after starting
+        // round #2
+        let public_key = rounds[1].public_keys.signers[&2];
+        for round in rounds.iter_mut() {
+            round.public_keys.key_ids.insert(2, public_key);
+        }
+        let begin = DkgBegin {
+            dkg_id: 2,
+            ..dkg_begin(vec![])
+        };
+        broadcast(&mut rounds, &[MessageTypes::DkgBegin(begin)]);
+        assert_eq!(rounds[0].signer.frost_signer.get_key_ids(), vec![0]);
+
+        let timeout = rounds[0].state_timeouts.dkg_public;
+        let entered_at = rounds[0].state_entered_at;
+        assert!(rounds[0].expire_state(entered_at + timeout).unwrap());
+        assert_eq!(rounds[0].dkg_id, 1);
+        assert_eq!(rounds[0].signer.frost_signer.get_key_ids(), vec![0, 1]);
+        assert_eq!(
+            rounds[0].signer.frost_signer.group_key(),
+            aggregate_public_key
+        );
+        assert!(rounds[0].epoch_signer.is_none());
+    }
+
+    #[test]
+    fn resharing_keeps_the_aggregate_key_for_the_new_key_ids() {
+        let (_, mut rounds) = signing_rounds();
+        run_dkg(&mut rounds);
+        let aggregate_public_key = rounds[0].signer.frost_signer.group_key();
+
+        // Signer 2 leaves, signer 1 takes three key ids with a threshold of two
+        let begin = ReshareBegin {
+            dkg_id: 2,
+            expires_at: 0,
+            threshold: 2,
+            signer_key_ids: BTreeMap::from([(1, vec![1, 2, 3]), (2, vec![])]),
+            dealers: BTreeMap::from([(1, vec![1, 2]), (2, vec![3])]),
+            aggregate_public_key,
+        };
+        let dealings = broadcast(&mut rounds, &[MessageTypes::ReshareBegin(begin)]);
+        assert_eq!(dealings.len(), 2);
+        let ends = broadcast(&mut rounds, &dealings);
+        assert_eq!(ends.len(), 2);
+        assert!(ends.iter().all(|end| matches!(
+            end,
+            MessageTypes::ReshareEnd(dkg_end)
+                if dkg_end.dkg_id == 2 && matches!(dkg_end.status, DkgStatus::Success)
+        )));
+        for round in &rounds {
+            assert_eq!(round.state, States::Idle);
+            assert_eq!(round.key_epoch, Some(2));
+            assert_eq!((round.threshold, round.total_keys), (2, 3));
+            assert_eq!(round.public_keys.signer_key_ids(1), vec![1, 2, 3]);
+        }
+        let mut key_ids = rounds[0].signer.frost_signer.get_key_ids();
+        key_ids.sort();
+        assert_eq!(key_ids, vec![0, 1, 2]);
+        assert_eq!(
+            rounds[0].signer.frost_signer.group_key(),
+            aggregate_public_key
+        );
+        // The removed signer keeps nothing of the key
+        assert!(rounds[1].signer.frost_signer.private_keys().is_empty());
+
+        // Signer 1 now signs for the same key on its own
+        let nonce_responses: Vec<NonceResponse> = rounds[0]
+            .process(MessageTypes::NonceRequest(NonceRequest {
+                dkg_id: 2,
+                sign_id: 1,
+                sign_nonce_id: 1,
+                expires_at: 0,
+                message_count: 1,
+            }))
+            .unwrap()
+            .into_iter()
+            .map(|message| match message {
+                MessageTypes::NonceResponse(response) => response,
+                message => panic!("expected NonceResponse, got {:?}", message),
+            })
+            .collect();
+        let message = vec![1, 3, 3, 7];
+        let request = SignatureShareRequest {
+            dkg_id: 2,
+            sign_id: 1,
+            correlation_id: 0,
+            nonce_responses: nonce_responses.clone(),
+            message: message.clone(),
+            signature_type: SignatureType::Frost,
+            expires_at: 0,
+            context: None,
+            nonce_index: 0,
+            batch: vec![],
+        };
+        let shares = match rounds[0]
+            .process(MessageTypes::SignShareRequest(request))
+            .unwrap()
+            .pop()
+        {
+            Some(MessageTypes::SignShareResponse(response)) => response.signature_shares,
+            message => panic!("expected SignShareResponse, got {:?}", message),
+        };
+
+        // The dealers' commitments stand in for those of the parties, padded to one per key id
+        let mut polys: Vec<PolyCommitment> = rounds[0].commitments.values().cloned().collect();
+        polys.push(zero_commitment(3, 2, &mut OsRng));
+        let mut aggregator = WstsAggregator::new(WstsVersion::V1, 3, 2, polys).unwrap();
+        let sig = aggregator
+            .sign(
+                &message,
+                &nonce_responses[0].nonces,
+                &shares,
+                &nonce_responses[0].key_ids,
+            )
+            .unwrap();
+        assert!(sig.verify(&aggregate_public_key, &message));
+    }
+
+    #[test]
+    fn dealers_without_the_key_shares_fail_resharing() {
+        let (_, mut rounds) = signing_rounds();
+        run_dkg(&mut rounds);
+        let aggregate_public_key = rounds[0].signer.frost_signer.group_key();
+
+        // Signer 1 is asked to deal key id 3, which signer 2 holds
+        let begin = ReshareBegin {
+            dkg_id: 2,
+            expires_at: 0,
+            threshold: 2,
+            signer_key_ids: BTreeMap::from([(1, vec![1, 2]), (2, vec![3])]),
+            dealers: BTreeMap::from([(1, vec![1, 2, 3])]),
+            aggregate_public_key,
+        };
+        let out = rounds[0]
+            .process(MessageTypes::ReshareBegin(begin))
+            .unwrap();
+        let [MessageTypes::ReshareEnd(dkg_end)] = &out[..] else {
+            panic!("expected ReshareEnd, got {:?}", out);
+        };
+        assert!(matches!(
+            &dkg_end.status,
+            DkgStatus::Failure(DkgFailure::MissingKeyShares(key_ids)) if key_ids == &vec![3]
+        ));
+        // The signer keeps the keys of the last DKG round
+        assert_eq!(rounds[0].state, States::Idle);
+        assert_eq!(rounds[0].dkg_id, 1);
+        assert_eq!(rounds[0].signer.frost_signer.get_key_ids(), vec![0, 1]);
+    }
+
+    impl SigningRound {
+        fn process_all(&mut self, messages: &[MessageTypes]) -> Vec<MessageTypes> {
+            messages
+                .iter()
+                .flat_map(|message| self.process(message.clone()).unwrap())
+                .collect()
+        }
+    }
 }
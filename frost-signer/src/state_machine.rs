@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 #[derive(Debug, PartialEq)]
 pub enum States {
     Idle,
@@ -5,10 +7,50 @@ pub enum States {
     DkgPublicGather,
     DkgPrivateDistribute,
     DkgPrivateGather,
+    /// Gathering the shares dealt while resharing the key to a new set of key ids
+    ReshareGather,
     SignGather,
     Signed,
 }
 
+impl States {
+    /// How long the signer may stay in the state before it gives up and returns to `Idle`.
+    /// The signer stays `Idle` until it is asked for something.
+    pub fn timeout(&self, timeouts: &StateTimeouts) -> Option<Duration> {
+        match self {
+            States::Idle => None,
+            States::DkgPublicDistribute | States::DkgPublicGather => Some(timeouts.dkg_public),
+            States::DkgPrivateDistribute | States::DkgPrivateGather | States::ReshareGather => {
+                Some(timeouts.dkg_private)
+            }
+            States::SignGather | States::Signed => Some(timeouts.sign),
+        }
+    }
+}
+
+/// Deadlines of the states a signer passes through while it waits for the coordinator and the
+/// other signers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateTimeouts {
+    /// Time to gather the commitments of all parties
+    pub dkg_public: Duration,
+    /// Time to be asked for private shares and to gather those of all parties, or those of all
+    /// dealers while resharing
+    pub dkg_private: Duration,
+    /// Time to complete a signing round
+    pub sign: Duration,
+}
+
+impl Default for StateTimeouts {
+    fn default() -> Self {
+        Self {
+            dkg_public: Duration::from_secs(120),
+            dkg_private: Duration::from_secs(120),
+            sign: Duration::from_secs(60),
+        }
+    }
+}
+
 pub trait StateMachine {
     fn move_to(&mut self, state: States) -> Result<(), Error>;
     fn can_move_to(&self, state: &States) -> Result<(), Error>;
@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{spawn, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use yarpc::http::{IoStream, Message, Method, Request, Response};
+
+use crate::signing_round::SigningRound;
+
+/// How many of the latest errors the status keeps
+pub const RECENT_ERRORS: usize = 16;
+
+/// How long the endpoint waits for a request, so that a stalled client cannot block it
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The signing round of one signer identity served by the process
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct IdentityStatus {
+    pub signer_id: u32,
+    /// The `States` value of the signing round
+    pub state: String,
+    /// Id of the DKG round in progress or last taken part in
+    pub dkg_id: u64,
+    /// Id of the DKG round which produced the key shares the identity holds, if any completed
+    pub key_epoch: Option<u64>,
+}
+
+impl From<&SigningRound> for IdentityStatus {
+    fn from(round: &SigningRound) -> Self {
+        Self {
+            signer_id: round.signer.signer_id,
+            state: format!("{:?}", round.state),
+            dkg_id: round.dkg_id,
+            key_epoch: round.key_epoch,
+        }
+    }
+}
+
+/// Counts of the protocol messages passing through the signer since it started
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MessageCounters {
+    /// Messages polled from the relay
+    pub received: u64,
+    /// Polled messages dropped for a bad signature or an unknown sender, or as replayed or expired
+    pub dropped: u64,
+    /// Messages sent to the relay
+    pub sent: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecentError {
+    /// When the error occurred, in seconds since the unix epoch
+    pub at: u64,
+    pub message: String,
+}
+
+/// The health of a running signer, as served by its status endpoint
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct StatusReport {
+    pub version: String,
+    pub uptime_secs: u64,
+    /// Whether the last poll of the relay succeeded
+    pub relay_connected: bool,
+    /// Seconds since the last message signed by the coordinator, if any was received
+    pub last_coordinator_message_secs_ago: Option<u64>,
+    pub identities: Vec<IdentityStatus>,
+    pub messages: MessageCounters,
+    /// The latest errors, oldest first
+    pub recent_errors: Vec<RecentError>,
+}
+
+impl StatusReport {
+    /// A signer is healthy while it reaches the relay
+    pub fn is_healthy(&self) -> bool {
+        self.relay_connected
+    }
+}
+
+struct Status {
+    started_at: Instant,
+    relay_connected: bool,
+    last_coordinator_message: Option<Instant>,
+    identities: Vec<IdentityStatus>,
+    messages: MessageCounters,
+    recent_errors: VecDeque<RecentError>,
+}
+
+/// Status of a signer process, shared between the threads which poll the relay, run the signing
+/// rounds and serve the status endpoint
+#[derive(Clone)]
+pub struct StatusHandle(Arc<Mutex<Status>>);
+
+impl Default for StatusHandle {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Status {
+            started_at: Instant::now(),
+            // Until the first poll of the relay succeeds
+            relay_connected: false,
+            last_coordinator_message: None,
+            identities: vec![],
+            messages: MessageCounters::default(),
+            recent_errors: VecDeque::new(),
+        })))
+    }
+}
+
+impl StatusHandle {
+    // A thread which panicked while holding the lock cannot have left the status inconsistent
+    fn lock(&self) -> MutexGuard<'_, Status> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_relay_connected(&self, connected: bool) {
+        self.lock().relay_connected = connected;
+    }
+
+    /// Count a polled message, and note the time if it is an accepted message of the coordinator
+    pub fn message_received(&self, from_coordinator: bool) {
+        let mut status = self.lock();
+        status.messages.received += 1;
+        if from_coordinator {
+            status.last_coordinator_message = Some(Instant::now());
+        }
+    }
+
+    pub fn message_dropped(&self) {
+        self.lock().messages.dropped += 1;
+    }
+
+    pub fn message_sent(&self) {
+        self.lock().messages.sent += 1;
+    }
+
+    /// Replace the identities served by the process
+    pub fn set_identities(&self, identities: Vec<IdentityStatus>) {
+        self.lock().identities = identities;
+    }
+
+    /// Update the status of the identity, which is added if it is not known yet
+    pub fn set_identity(&self, identity: IdentityStatus) {
+        let mut status = self.lock();
+        match status
+            .identities
+            .iter_mut()
+            .find(|known| known.signer_id == identity.signer_id)
+        {
+            Some(known) => *known = identity,
+            None => status.identities.push(identity),
+        }
+    }
+
+    /// Keep the error, forgetting the oldest one beyond `RECENT_ERRORS`
+    pub fn error(&self, message: impl ToString) {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let mut status = self.lock();
+        if status.recent_errors.len() == RECENT_ERRORS {
+            status.recent_errors.pop_front();
+        }
+        status.recent_errors.push_back(RecentError {
+            at,
+            message: message.to_string(),
+        });
+    }
+
+    pub fn report(&self) -> StatusReport {
+        let status = self.lock();
+        StatusReport {
+            version: crate::version(),
+            uptime_secs: status.started_at.elapsed().as_secs(),
+            relay_connected: status.relay_connected,
+            last_coordinator_message_secs_ago: status
+                .last_coordinator_message
+                .map(|at| at.elapsed().as_secs()),
+            identities: status.identities.clone(),
+            messages: status.messages.clone(),
+            recent_errors: status.recent_errors.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Serve the status over HTTP on the address, e.g. `127.0.0.1:9800`: `GET /status` answers the
+/// status report as JSON and `GET /health` answers 200 while the signer is healthy and 503
+/// otherwise
+pub fn serve(address: &str, status: StatusHandle) -> Result<JoinHandle<()>, io::Error> {
+    let listener = TcpListener::bind(address)?;
+    info!("Serving signer status on {}", address);
+    Ok(spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.and_then(|mut stream| {
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                respond(&mut stream, &status)
+            });
+            if let Err(e) = result {
+                warn!("Failed to serve signer status: {}", e);
+            }
+        }
+    }))
+}
+
+fn respond(io: &mut impl IoStream, status: &StatusHandle) -> Result<(), io::Error> {
+    let request = Request::read(io.istream())?;
+    let response = response(&request, &status.report());
+    let ostream = io.ostream();
+    response.write(ostream)?;
+    ostream.flush()
+}
+
+/// The JSON response to the request
+fn response(request: &Request, report: &StatusReport) -> Response {
+    let path = request.url.split('?').next().unwrap_or_default();
+    let (code, body) = match (request.method, path) {
+        (Method::GET, "/status") => (
+            200,
+            serde_json::to_string(report).expect("failed to serialize StatusReport"),
+        ),
+        (Method::GET, "/health") if report.is_healthy() => (200, r#"{"healthy":true}"#.to_string()),
+        (Method::GET, "/health") => (503, r#"{"healthy":false}"#.to_string()),
+        (Method::GET, _) => (404, r#"{"error":"not found"}"#.to_string()),
+        _ => (405, r#"{"error":"method not allowed"}"#.to_string()),
+    };
+    let phrase = match code {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    let headers = HashMap::from([("content-type".to_string(), "application/json".to_string())]);
+    Response::new(code, phrase.to_string(), headers, body.into_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use yarpc::http::MemIoStreamEx;
+
+    use super::*;
+
+    /// The status code and body answering the raw request
+    fn get(status: &StatusHandle, request: &str) -> (u16, Vec<u8>) {
+        let mut output = Vec::default();
+        let mut stream = request.as_bytes().mem_io_stream(&mut output);
+        respond(&mut stream, status).unwrap();
+        let response = Response::read(&mut output.as_slice()).unwrap();
+        (response.code, response.content)
+    }
+
+    #[test]
+    fn status_keeps_the_latest_errors() {
+        let status = StatusHandle::default();
+        for i in 0..RECENT_ERRORS + 2 {
+            status.error(format!("error {i}"));
+        }
+        let report = status.report();
+        assert_eq!(report.recent_errors.len(), RECENT_ERRORS);
+        assert_eq!(report.recent_errors[0].message, "error 2");
+        assert_eq!(
+            report.recent_errors[RECENT_ERRORS - 1].message,
+            format!("error {}", RECENT_ERRORS + 1)
+        );
+    }
+
+    #[test]
+    fn status_tracks_identities_and_messages() {
+        let status = StatusHandle::default();
+        assert_eq!(status.report().last_coordinator_message_secs_ago, None);
+
+        status.message_received(false);
+        status.message_received(true);
+        status.message_dropped();
+        status.message_sent();
+        let identity = IdentityStatus {
+            signer_id: 2,
+            state: "Idle".to_string(),
+            dkg_id: 1,
+            key_epoch: None,
+        };
+        status.set_identity(identity.clone());
+        status.set_identity(IdentityStatus {
+            key_epoch: Some(1),
+            ..identity
+        });
+
+        let report = status.report();
+        assert_eq!(
+            report.messages,
+            MessageCounters {
+                received: 2,
+                dropped: 1,
+                sent: 1
+            }
+        );
+        assert_eq!(report.last_coordinator_message_secs_ago, Some(0));
+        assert_eq!(report.identities.len(), 1);
+        assert_eq!(report.identities[0].key_epoch, Some(1));
+    }
+
+    #[test]
+    fn health_follows_the_relay_connection() {
+        let status = StatusHandle::default();
+        let health = "GET /health HTTP/1.1\r\n\r\n";
+        // Unhealthy until the relay was reached
+        assert_eq!(get(&status, health).0, 503);
+        status.set_relay_connected(true);
+        assert_eq!(get(&status, health).0, 200);
+        status.set_relay_connected(false);
+        assert_eq!(get(&status, health).0, 503);
+
+        let (code, body) = get(&status, "GET /status HTTP/1.1\r\n\r\n");
+        assert_eq!(code, 200);
+        let report: StatusReport = serde_json::from_slice(&body).unwrap();
+        assert!(!report.relay_connected);
+
+        assert_eq!(get(&status, "GET / HTTP/1.1\r\n\r\n").0, 404);
+        assert_eq!(
+            get(
+                &status,
+                "POST /status HTTP/1.1\r\nContent-Length: 0\r\n\r\n"
+            )
+            .0,
+            405
+        );
+    }
+}
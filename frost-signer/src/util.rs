@@ -2,6 +2,7 @@ use aes_gcm::{aead::Aead, Aes256Gcm, Error as AesGcmError, KeyInit, Nonce};
 use p256k1::{ecdsa, point::Point, scalar::Scalar};
 use rand_core::{CryptoRng, RngCore};
 use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub const AES_GCM_NONCE_SIZE: usize = 12;
 
@@ -12,8 +13,12 @@ pub fn parse_public_key(public_key: &str) -> Result<ecdsa::PublicKey, ecdsa::Key
 
 /// Do a Diffie-Hellman key exchange to create a shared secret from the passed private and public keys
 pub fn make_shared_secret(private_key: &Scalar, public_key: &Point) -> [u8; 32] {
+    make_shared_secret_from_key(&(private_key * public_key))
+}
+
+/// Create the shared secret from the Diffie-Hellman shared key, i.e. one private key times the other public key
+pub fn make_shared_secret_from_key(shared_key: &Point) -> [u8; 32] {
     let mut hasher = Sha256::new();
-    let shared_key = private_key * public_key;
 
     hasher.update("DH_SHARED_SECRET_KEY/".as_bytes());
     hasher.update(shared_key.compress().as_bytes());
@@ -47,8 +52,12 @@ pub fn encrypt<RNG: RngCore + CryptoRng>(
     Ok(bytes)
 }
 
-/// Decrypt the passed data using the key
+/// Decrypt the passed data using the key. Data too short to hold a nonce fails like any other
+/// data which does not decrypt, since it may come from a malicious sender.
 pub fn decrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, AesGcmError> {
+    if data.len() < AES_GCM_NONCE_SIZE {
+        return Err(AesGcmError);
+    }
     let nonce_vec = data[..AES_GCM_NONCE_SIZE].to_vec();
     let cipher_vec = data[AES_GCM_NONCE_SIZE..].to_vec();
     let nonce = Nonce::from_slice(&nonce_vec);
@@ -57,6 +66,14 @@ pub fn decrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, AesGcmError> {
     cipher.decrypt(nonce, cipher_vec.as_ref())
 }
 
+/// Seconds since the unix epoch
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod test {
     use p256k1::{point::Point, scalar::Scalar};
@@ -101,4 +118,12 @@ mod test {
 
         assert_eq!(msg.as_bytes(), &plain);
     }
+
+    #[test]
+    fn short_data_does_not_decrypt() {
+        let key = [7; 32];
+        assert!(decrypt(&key, &[]).is_err());
+        assert!(decrypt(&key, &[1; AES_GCM_NONCE_SIZE - 1]).is_err());
+        assert!(decrypt(&key, &[1; AES_GCM_NONCE_SIZE]).is_err());
+    }
 }
@@ -0,0 +1,189 @@
+use bitcoin::{
+    consensus::encode::{deserialize, Error as EncodeError},
+    hashes::Hash,
+    util::sighash::{Error as SighashError, Prevouts, SighashCache},
+    SchnorrSighashType, Transaction, TxOut,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::signing_round::SignatureShareRequest;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Signing request carries no transaction context")]
+    MissingContext,
+    #[error("Encode Error: {0}")]
+    EncodeError(#[from] EncodeError),
+    #[error("Sighash Error: {0}")]
+    SighashError(#[from] SighashError),
+    #[error("Transaction has {0} inputs but {1} prevouts were provided")]
+    PrevoutCountMismatch(usize, usize),
+    #[error("Message is not the taproot key spend sighash of input {0}")]
+    SighashMismatch(u32),
+    #[error("Transaction rejected: {0}")]
+    Rejected(String),
+}
+
+/// The transaction a signing request signs an input of, so that signers can check what they
+/// sign instead of trusting the coordinator with an opaque message
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct TransactionContext {
+    /// Consensus encoded unsigned transaction
+    pub transaction: Vec<u8>,
+    /// Consensus encoded outputs spent by the transaction, in input order
+    pub prevouts: Vec<Vec<u8>>,
+    /// Index of the input the message is the taproot key spend sighash of
+    pub input_index: u32,
+    /// Hex encoded txid of the peg out request op the transaction fulfills
+    pub peg_op_txid: String,
+    /// Burn block height the peg out request op was mined at
+    pub burn_block_height: u64,
+}
+
+impl TransactionContext {
+    pub fn new(
+        transaction: &Transaction,
+        prevouts: &[TxOut],
+        input_index: u32,
+        peg_op_txid: String,
+        burn_block_height: u64,
+    ) -> Self {
+        Self {
+            transaction: bitcoin::consensus::serialize(transaction),
+            prevouts: prevouts.iter().map(bitcoin::consensus::serialize).collect(),
+            input_index,
+            peg_op_txid,
+            burn_block_height,
+        }
+    }
+
+    pub fn hash(&self, hasher: &mut Sha256) {
+        hasher.update("TRANSACTION_CONTEXT".as_bytes());
+        hasher.update((self.transaction.len() as u64).to_be_bytes());
+        hasher.update(&self.transaction);
+        hasher.update((self.prevouts.len() as u64).to_be_bytes());
+        for prevout in &self.prevouts {
+            hasher.update((prevout.len() as u64).to_be_bytes());
+            hasher.update(prevout);
+        }
+        hasher.update(self.input_index.to_be_bytes());
+        hasher.update(self.peg_op_txid.as_bytes());
+        hasher.update(self.burn_block_height.to_be_bytes());
+    }
+
+    /// Decode the transaction and its prevouts
+    pub fn decode(&self) -> Result<(Transaction, Vec<TxOut>), Error> {
+        let transaction: Transaction = deserialize(&self.transaction)?;
+        let prevouts = self
+            .prevouts
+            .iter()
+            .map(|prevout| deserialize(prevout))
+            .collect::<Result<Vec<TxOut>, EncodeError>>()?;
+        if transaction.input.len() != prevouts.len() {
+            return Err(Error::PrevoutCountMismatch(
+                transaction.input.len(),
+                prevouts.len(),
+            ));
+        }
+        Ok((transaction, prevouts))
+    }
+}
+
+/// Decides whether a signer produces signature shares for a request
+pub trait SignRequestValidator: Send + Sync {
+    fn validate(&self, request: &SignatureShareRequest) -> Result<(), Error>;
+}
+
+/// Decode the transaction context of the request and check that the message is the taproot key
+/// spend sighash of the context's input, returning the decoded transaction and prevouts
+pub fn verify_sighash(
+    request: &SignatureShareRequest,
+) -> Result<(&TransactionContext, Transaction, Vec<TxOut>), Error> {
+    let context = request.context.as_ref().ok_or(Error::MissingContext)?;
+    let (transaction, prevouts) = context.decode()?;
+    let sighash = SighashCache::new(&transaction).taproot_key_spend_signature_hash(
+        context.input_index as usize,
+        &Prevouts::All(&prevouts),
+        SchnorrSighashType::Default,
+    )?;
+    if sighash.into_inner().as_slice() != request.message.as_slice() {
+        return Err(Error::SighashMismatch(context.input_index));
+    }
+    Ok((context, transaction, prevouts))
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::{OutPoint, PackedLockTime, Script, Sequence, TxIn, Witness};
+
+    use super::*;
+    use crate::signing_round::SignatureType;
+
+    fn transaction() -> (Transaction, Vec<TxOut>) {
+        let prevout = TxOut {
+            value: 10_000,
+            script_pubkey: Script::from(vec![0x51]),
+        };
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime(0),
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Script::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 9_000,
+                script_pubkey: prevout.script_pubkey.clone(),
+            }],
+        };
+        (tx, vec![prevout])
+    }
+
+    fn request(message: Vec<u8>, context: Option<TransactionContext>) -> SignatureShareRequest {
+        SignatureShareRequest {
+            dkg_id: 0,
+            sign_id: 0,
+            correlation_id: 0,
+            nonce_responses: vec![],
+            message,
+            signature_type: SignatureType::Taproot(None),
+            expires_at: 0,
+            context,
+            nonce_index: 0,
+            batch: vec![],
+        }
+    }
+
+    #[test]
+    fn sighash_is_recomputed_from_context() {
+        let (tx, prevouts) = transaction();
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&prevouts),
+                SchnorrSighashType::Default,
+            )
+            .unwrap()
+            .into_inner();
+        let context = TransactionContext::new(&tx, &prevouts, 0, "00".to_string(), 1);
+
+        assert!(verify_sighash(&request(sighash.to_vec(), Some(context.clone()))).is_ok());
+        assert!(matches!(
+            verify_sighash(&request(vec![0; 32], Some(context))),
+            Err(Error::SighashMismatch(0))
+        ));
+        assert!(matches!(
+            verify_sighash(&request(sighash.to_vec(), None)),
+            Err(Error::MissingContext)
+        ));
+
+        let mismatched = TransactionContext::new(&tx, &[], 0, "00".to_string(), 1);
+        assert!(matches!(
+            verify_sighash(&request(sighash.to_vec(), Some(mismatched))),
+            Err(Error::PrevoutCountMismatch(1, 0))
+        ));
+    }
+}
@@ -0,0 +1,395 @@
+use hashbrown::HashMap;
+use p256k1::{ecdsa, point::Point, scalar::Scalar};
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use wsts::{
+    common::{PolyCommitment, PublicNonce, Signature, SignatureShare},
+    errors::{AggregatorError, DkgError},
+    traits::{Signer as SignerTrait, SignerState},
+    v1, v2,
+};
+
+use crate::config::PublicKeys;
+
+/// The wsts protocol version signers and the coordinator run. All of them must run the same one.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum WstsVersion {
+    /// Each key id is a party, which sends its own commitment, private shares, nonces and
+    /// signature shares
+    #[default]
+    V1,
+    /// Each signer is a party, which sends one commitment, set of private shares, nonce and
+    /// signature share for all of its key ids
+    V2,
+}
+
+impl WstsVersion {
+    /// Number of parties sending commitments and private shares in a DKG round
+    pub fn total_parties(&self, total_signers: u32, total_keys: u32) -> u32 {
+        match self {
+            WstsVersion::V1 => total_keys,
+            WstsVersion::V2 => total_signers,
+        }
+    }
+
+    /// The party ids, counted from 1, of the signer: its key ids in v1 and its signer id in v2
+    pub fn party_ids(&self, public_keys: &PublicKeys, signer_id: u32) -> Vec<u32> {
+        match self {
+            WstsVersion::V1 => public_keys.signer_key_ids(signer_id),
+            WstsVersion::V2 if public_keys.signers.contains_key(&signer_id) => vec![signer_id],
+            WstsVersion::V2 => vec![],
+        }
+    }
+
+    /// The signer of the party id, counted from 1
+    pub fn party_signer(&self, public_keys: &PublicKeys, party_id: u32) -> Option<u32> {
+        public_keys
+            .signers
+            .keys()
+            .copied()
+            .find(|signer_id| self.party_ids(public_keys, *signer_id).contains(&party_id))
+    }
+
+    /// The network public key the party, counted from 1, signs its DKG messages with and its
+    /// private shares are encrypted for
+    pub fn party_public_key<'a>(
+        &self,
+        public_keys: &'a PublicKeys,
+        party_id: u32,
+    ) -> Option<&'a ecdsa::PublicKey> {
+        match self {
+            WstsVersion::V1 => public_keys.key_ids.get(&party_id),
+            WstsVersion::V2 => public_keys.signers.get(&party_id),
+        }
+    }
+}
+
+/// The wsts party state of a signer, for the protocol version it was created with. v2 parties
+/// have inherent methods of the same names for a single party, so their trait methods are called
+/// explicitly.
+pub enum WstsSigner {
+    V1(v1::Signer),
+    V2(v2::Party),
+}
+
+impl WstsSigner {
+    /// A signer holding the key ids, counted from 0
+    pub fn new<T: RngCore + CryptoRng>(
+        version: WstsVersion,
+        signer_id: u32,
+        key_ids: &[u32],
+        total_signers: u32,
+        total_keys: u32,
+        threshold: u32,
+        rng: &mut T,
+    ) -> Self {
+        match version {
+            WstsVersion::V1 => WstsSigner::V1(v1::Signer::new(
+                signer_id, key_ids, total_keys, threshold, rng,
+            )),
+            // v2 party ids are counted from 0
+            WstsVersion::V2 => WstsSigner::V2(v2::Party::new(
+                signer_id - 1,
+                key_ids,
+                total_signers,
+                total_keys,
+                threshold,
+                rng,
+            )),
+        }
+    }
+
+    /// Restore a saved party state of the given version
+    pub fn load(version: WstsVersion, state: &SignerState) -> Self {
+        match version {
+            WstsVersion::V1 => WstsSigner::V1(v1::Signer::load(state)),
+            WstsVersion::V2 => WstsSigner::V2(<v2::Party as SignerTrait>::load(state)),
+        }
+    }
+
+    /// A signer holding the private keys of its key ids, counted from 0, which were dealt to it
+    /// by resharing the secret of the group key instead of by a DKG round
+    #[allow(clippy::too_many_arguments)]
+    pub fn reshared<T: RngCore + CryptoRng>(
+        version: WstsVersion,
+        signer_id: u32,
+        key_ids: &[u32],
+        total_signers: u32,
+        total_keys: u32,
+        threshold: u32,
+        group_key: Point,
+        private_keys: &HashMap<u32, Scalar>,
+        rng: &mut T,
+    ) -> Self {
+        let signer = Self::new(
+            version,
+            signer_id,
+            key_ids,
+            total_signers,
+            total_keys,
+            threshold,
+            rng,
+        );
+        let mut state = signer.save();
+        state.group_key = group_key;
+        for (party_id, party) in state.parties.iter_mut() {
+            // A v1 party holds the key id it is named after, a v2 party all key ids of the signer
+            let party_key_ids = match version {
+                WstsVersion::V1 => vec![*party_id],
+                WstsVersion::V2 => key_ids.to_vec(),
+            };
+            party.private_keys = party_key_ids
+                .into_iter()
+                .filter_map(|key_id| Some((key_id, *private_keys.get(&key_id)?)))
+                .collect();
+        }
+        Self::load(version, &state)
+    }
+
+    pub fn version(&self) -> WstsVersion {
+        match self {
+            WstsSigner::V1(_) => WstsVersion::V1,
+            WstsSigner::V2(_) => WstsVersion::V2,
+        }
+    }
+
+    pub fn save(&self) -> SignerState {
+        match self {
+            WstsSigner::V1(signer) => signer.save(),
+            WstsSigner::V2(party) => SignerTrait::save(party),
+        }
+    }
+
+    pub fn get_id(&self) -> u32 {
+        match self {
+            WstsSigner::V1(signer) => signer.get_id(),
+            WstsSigner::V2(party) => SignerTrait::get_id(party),
+        }
+    }
+
+    /// The key ids of the signer, counted from 0
+    pub fn get_key_ids(&self) -> Vec<u32> {
+        match self {
+            WstsSigner::V1(signer) => signer.get_key_ids(),
+            WstsSigner::V2(party) => SignerTrait::get_key_ids(party),
+        }
+    }
+
+    /// The private keys of the signer's key ids, counted from 0, from the last DKG round
+    pub fn private_keys(&self) -> HashMap<u32, Scalar> {
+        self.save()
+            .parties
+            .into_iter()
+            .flat_map(|(_, party)| party.private_keys)
+            .collect()
+    }
+
+    /// The aggregate public key of the last DKG round
+    pub fn group_key(&self) -> Point {
+        self.save().group_key
+    }
+
+    /// One commitment per party of the signer
+    pub fn get_poly_commitments<T: RngCore + CryptoRng>(&self, rng: &mut T) -> Vec<PolyCommitment> {
+        match self {
+            WstsSigner::V1(signer) => signer.get_poly_commitments(rng),
+            WstsSigner::V2(party) => SignerTrait::get_poly_commitments(party, rng),
+        }
+    }
+
+    pub fn reset_polys<T: RngCore + CryptoRng>(&mut self, rng: &mut T) {
+        match self {
+            WstsSigner::V1(signer) => signer.reset_polys(rng),
+            WstsSigner::V2(party) => SignerTrait::reset_polys(party, rng),
+        }
+    }
+
+    /// The private shares of each party of the signer, counted from 0, for every key id
+    pub fn get_shares(&self) -> HashMap<u32, HashMap<u32, Scalar>> {
+        match self {
+            WstsSigner::V1(signer) => signer.get_shares(),
+            WstsSigner::V2(party) => SignerTrait::get_shares(party),
+        }
+    }
+
+    /// Compute the secrets of the signer's key ids from the private shares each party, counted
+    /// from 0, sent them
+    pub fn compute_secrets(
+        &mut self,
+        shares: &HashMap<u32, HashMap<u32, Scalar>>,
+        polys: &[PolyCommitment],
+    ) -> Result<(), HashMap<u32, DkgError>> {
+        match self {
+            WstsSigner::V1(signer) => signer.compute_secrets(shares, polys),
+            WstsSigner::V2(party) => SignerTrait::compute_secrets(party, shares, polys),
+        }
+    }
+
+    /// One nonce per party of the signer
+    pub fn gen_nonces<T: RngCore + CryptoRng>(&mut self, rng: &mut T) -> Vec<PublicNonce> {
+        match self {
+            WstsSigner::V1(signer) => signer.gen_nonces(rng),
+            WstsSigner::V2(party) => SignerTrait::gen_nonces(party, rng),
+        }
+    }
+
+    /// Sign with the nonces of the signers and the key ids, counted from 0, they hold
+    pub fn sign(
+        &self,
+        msg: &[u8],
+        signer_ids: &[u32],
+        key_ids: &[u32],
+        nonces: &[PublicNonce],
+    ) -> Vec<SignatureShare> {
+        match self {
+            WstsSigner::V1(signer) => signer.sign(msg, signer_ids, key_ids, nonces),
+            WstsSigner::V2(party) => {
+                SignerTrait::sign(party, msg, &party_ids(signer_ids), key_ids, nonces)
+            }
+        }
+    }
+
+    pub fn sign_taproot(
+        &self,
+        msg: &[u8],
+        signer_ids: &[u32],
+        key_ids: &[u32],
+        nonces: &[PublicNonce],
+        merkle_root: Option<[u8; 32]>,
+    ) -> Vec<SignatureShare> {
+        match self {
+            WstsSigner::V1(signer) => {
+                signer.sign_taproot(msg, signer_ids, key_ids, nonces, merkle_root)
+            }
+            WstsSigner::V2(party) => SignerTrait::sign_taproot(
+                party,
+                msg,
+                &party_ids(signer_ids),
+                key_ids,
+                nonces,
+                merkle_root,
+            ),
+        }
+    }
+}
+
+/// The v2 party ids, counted from 0, of the signers
+fn party_ids(signer_ids: &[u32]) -> Vec<u32> {
+    signer_ids.iter().map(|signer_id| signer_id - 1).collect()
+}
+
+/// Aggregates signature shares into signatures for the protocol version of the signers
+pub enum WstsAggregator {
+    V1(v1::SignatureAggregator),
+    V2(v2::SignatureAggregator),
+}
+
+impl WstsAggregator {
+    /// An aggregator for the commitments of all parties of a DKG round
+    pub fn new(
+        version: WstsVersion,
+        total_keys: u32,
+        threshold: u32,
+        polys: Vec<PolyCommitment>,
+    ) -> Result<Self, AggregatorError> {
+        Ok(match version {
+            WstsVersion::V1 => {
+                WstsAggregator::V1(v1::SignatureAggregator::new(total_keys, threshold, polys)?)
+            }
+            WstsVersion::V2 => {
+                WstsAggregator::V2(v2::SignatureAggregator::new(total_keys, threshold, polys)?)
+            }
+        })
+    }
+
+    /// Aggregate the shares of the signers holding the key ids, counted from 0. On failure, bad
+    /// shares are reported by party id counted from 0.
+    pub fn sign(
+        &mut self,
+        msg: &[u8],
+        nonces: &[PublicNonce],
+        shares: &[SignatureShare],
+        key_ids: &[u32],
+    ) -> Result<Signature, AggregatorError> {
+        match self {
+            WstsAggregator::V1(aggregator) => aggregator.sign(msg, nonces, shares),
+            WstsAggregator::V2(aggregator) => aggregator.sign(msg, nonces, shares, key_ids),
+        }
+    }
+
+    pub fn sign_taproot(
+        &mut self,
+        msg: &[u8],
+        nonces: &[PublicNonce],
+        shares: &[SignatureShare],
+        key_ids: &[u32],
+        merkle_root: Option<[u8; 32]>,
+    ) -> Result<Signature, AggregatorError> {
+        match self {
+            WstsAggregator::V1(aggregator) => {
+                aggregator.sign_taproot(msg, nonces, shares, merkle_root)
+            }
+            WstsAggregator::V2(aggregator) => {
+                aggregator.sign_taproot(msg, nonces, shares, key_ids, merkle_root)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use p256k1::ecdsa;
+    use rand_core::OsRng;
+
+    use super::*;
+
+    fn public_keys() -> PublicKeys {
+        let keys: Vec<ecdsa::PublicKey> = (0..2)
+            .map(|_| ecdsa::PublicKey::new(&Scalar::random(&mut OsRng)).unwrap())
+            .collect();
+        PublicKeys {
+            signers: HashMap::from([(1, keys[0]), (2, keys[1])]),
+            key_ids: HashMap::from([(1, keys[0]), (2, keys[0]), (3, keys[1])]),
+        }
+    }
+
+    #[test]
+    fn parties_are_key_ids_in_v1_and_signers_in_v2() {
+        let public_keys = public_keys();
+        let v1 = WstsVersion::V1;
+        assert_eq!(v1.total_parties(2, 3), 3);
+        assert_eq!(v1.party_ids(&public_keys, 1), vec![1, 2]);
+        assert_eq!(v1.party_signer(&public_keys, 3), Some(2));
+        assert_eq!(
+            v1.party_public_key(&public_keys, 2),
+            public_keys.signers.get(&1)
+        );
+
+        let v2 = WstsVersion::V2;
+        assert_eq!(v2.total_parties(2, 3), 2);
+        assert_eq!(v2.party_ids(&public_keys, 1), vec![1]);
+        assert_eq!(v2.party_ids(&public_keys, 3), Vec::<u32>::new());
+        assert_eq!(v2.party_signer(&public_keys, 2), Some(2));
+        assert_eq!(v2.party_signer(&public_keys, 3), None);
+        assert_eq!(
+            v2.party_public_key(&public_keys, 2),
+            public_keys.signers.get(&2)
+        );
+    }
+
+    #[test]
+    fn v2_signers_send_one_nonce_for_all_key_ids() {
+        let mut v1 = WstsSigner::new(WstsVersion::V1, 1, &[0, 1], 2, 3, 2, &mut OsRng);
+        let mut v2 = WstsSigner::new(WstsVersion::V2, 1, &[0, 1], 2, 3, 2, &mut OsRng);
+        assert_eq!(v1.gen_nonces(&mut OsRng).len(), 2);
+        assert_eq!(v2.gen_nonces(&mut OsRng).len(), 1);
+        assert_eq!(v1.get_poly_commitments(&mut OsRng).len(), 2);
+        assert_eq!(v2.get_poly_commitments(&mut OsRng).len(), 1);
+        assert_eq!(v2.get_key_ids(), vec![0, 1]);
+
+        let restored = WstsSigner::load(WstsVersion::V2, &v2.save());
+        assert_eq!(restored.version(), WstsVersion::V2);
+        assert_eq!(restored.get_key_ids(), v2.get_key_ids());
+    }
+}
@@ -19,7 +19,11 @@ fn dkg_begin() {
     let mut signer = setup_signer(total, total - 1);
     assert_eq!(signer.commitments.len(), 0);
 
-    let dkg_begin_msg = MessageTypes::DkgBegin(DkgBegin { dkg_id: 0 });
+    let dkg_begin_msg = MessageTypes::DkgBegin(DkgBegin {
+        dkg_id: 0,
+        expires_at: 0,
+        excluded_signers: vec![],
+    });
     let msgs = signer.process(dkg_begin_msg).unwrap();
     assert_eq!(msgs.len(), total);
 
@@ -47,8 +51,15 @@ fn signature_share() {
                 D: Default::default(),
                 E: Default::default(),
             }],
+            pool_index: None,
+            wsts_version: Default::default(),
         }],
         message: vec![],
+        signature_type: Default::default(),
+        expires_at: 0,
+        context: None,
+        nonce_index: 0,
+        batch: vec![],
     };
 
     let msg_share = MessageTypes::SignShareRequest(share);
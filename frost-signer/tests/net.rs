@@ -4,7 +4,11 @@ use frost_signer::signing_round::{DkgBegin, MessageTypes};
 #[test]
 fn receive_msg() {
     let m1 = Message {
-        msg: MessageTypes::DkgBegin(DkgBegin { dkg_id: 0 }),
+        msg: MessageTypes::DkgBegin(DkgBegin {
+            dkg_id: 0,
+            expires_at: 0,
+            excluded_signers: vec![],
+        }),
         sig: vec![0u8; 64],
     };
 
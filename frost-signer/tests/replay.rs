@@ -0,0 +1,245 @@
+use frost_signer::net::Message;
+use frost_signer::replay::{Error, ReplayGuard};
+use frost_signer::signing_round::{
+    DkgBegin, DkgEnd, DkgStatus, MessageTypes, NoncePoolRequest, NonceRequest, NonceResponse,
+    Signable, SignatureShareRequest,
+};
+use p256k1::scalar::Scalar;
+use rand_core::{OsRng, RngCore};
+
+const NOW: u64 = 1_700_000_000;
+const EXPIRES_AT: u64 = NOW + 60;
+
+fn sign(message: MessageTypes, private_key: &Scalar) -> Message {
+    let sig = match &message {
+        MessageTypes::DkgBegin(msg) | MessageTypes::DkgPrivateBegin(msg) => msg.sign(private_key),
+        MessageTypes::DkgEnd(msg) | MessageTypes::DkgPublicEnd(msg) => msg.sign(private_key),
+        MessageTypes::NonceRequest(msg) => msg.sign(private_key),
+        MessageTypes::NonceResponse(msg) => msg.sign(private_key),
+        MessageTypes::SignShareRequest(msg) => msg.sign(private_key),
+        MessageTypes::NoncePoolRequest(msg) => msg.sign(private_key),
+        message => panic!("unexpected message type {:?}", message),
+    }
+    .unwrap();
+    Message { msg: message, sig }
+}
+
+fn dkg_begin(dkg_id: u64, expires_at: u64) -> MessageTypes {
+    MessageTypes::DkgBegin(DkgBegin {
+        dkg_id,
+        expires_at,
+        excluded_signers: vec![],
+    })
+}
+
+fn dkg_private_begin(dkg_id: u64) -> MessageTypes {
+    MessageTypes::DkgPrivateBegin(DkgBegin {
+        dkg_id,
+        expires_at: EXPIRES_AT,
+        excluded_signers: vec![],
+    })
+}
+
+fn dkg_end(dkg_id: u64, signer_id: u32) -> MessageTypes {
+    MessageTypes::DkgEnd(DkgEnd {
+        dkg_id,
+        signer_id,
+        status: DkgStatus::Success,
+    })
+}
+
+fn nonce_request(dkg_id: u64, sign_id: u64, sign_nonce_id: u64) -> MessageTypes {
+    MessageTypes::NonceRequest(NonceRequest {
+        dkg_id,
+        sign_id,
+        sign_nonce_id,
+        expires_at: EXPIRES_AT,
+        message_count: 1,
+    })
+}
+
+fn nonce_response(dkg_id: u64, sign_id: u64, signer_id: u32) -> MessageTypes {
+    MessageTypes::NonceResponse(NonceResponse {
+        dkg_id,
+        sign_id,
+        sign_nonce_id: 1,
+        signer_id,
+        key_ids: vec![],
+        nonces: vec![],
+        pool_index: None,
+        wsts_version: Default::default(),
+    })
+}
+
+fn sign_share_request(dkg_id: u64, sign_id: u64) -> MessageTypes {
+    MessageTypes::SignShareRequest(SignatureShareRequest {
+        dkg_id,
+        sign_id,
+        correlation_id: 0,
+        nonce_responses: vec![],
+        message: vec![1, 3, 3, 7],
+        signature_type: Default::default(),
+        expires_at: EXPIRES_AT,
+        context: None,
+        nonce_index: 0,
+        batch: vec![],
+    })
+}
+
+/// Relay traffic of a DKG round followed by a signing round, as bincode encoded on the relay
+fn captured_traffic(coordinator_key: &Scalar, signer_key: &Scalar) -> Vec<Vec<u8>> {
+    [
+        sign(dkg_begin(10, EXPIRES_AT), coordinator_key),
+        sign(dkg_private_begin(10), coordinator_key),
+        sign(dkg_end(10, 1), signer_key),
+        sign(nonce_request(10, 20, 1), coordinator_key),
+        sign(nonce_response(10, 20, 1), signer_key),
+        sign(sign_share_request(10, 20), coordinator_key),
+    ]
+    .into_iter()
+    .map(|message| bincode::serialize(&message).unwrap())
+    .collect()
+}
+
+fn decode(bytes: &[u8]) -> Message {
+    bincode::deserialize(bytes).unwrap()
+}
+
+#[test]
+fn replayed_relay_traffic_is_rejected() {
+    let coordinator_key = Scalar::random(&mut OsRng);
+    let signer_key = Scalar::random(&mut OsRng);
+    let traffic = captured_traffic(&coordinator_key, &signer_key);
+
+    let mut guard = ReplayGuard::new();
+    for bytes in &traffic {
+        guard.check_at(&decode(bytes).msg, NOW).unwrap();
+    }
+
+    // Every captured message still carries a valid signature, but none is accepted again
+    for bytes in &traffic {
+        let message = decode(bytes);
+        assert!(matches!(
+            guard.check_at(&message.msg, NOW),
+            Err(Error::Stale(..))
+        ));
+    }
+
+    // Nor is the old DKG round accepted after a new one started
+    guard
+        .check_at(&sign(dkg_begin(11, EXPIRES_AT), &coordinator_key).msg, NOW)
+        .unwrap();
+    for bytes in &traffic {
+        assert!(guard.check_at(&decode(bytes).msg, NOW).is_err());
+    }
+}
+
+#[test]
+fn replay_protection_survives_restarts() {
+    let coordinator_key = Scalar::random(&mut OsRng);
+    let signer_key = Scalar::random(&mut OsRng);
+    let traffic = captured_traffic(&coordinator_key, &signer_key);
+    let path = std::env::temp_dir().join(format!("replay_{}.json", OsRng.next_u64()));
+
+    let mut guard = ReplayGuard::open(&path).unwrap();
+    for bytes in &traffic {
+        guard.check_at(&decode(bytes).msg, NOW).unwrap();
+    }
+
+    let mut restarted = ReplayGuard::open(&path).unwrap();
+    assert_eq!(restarted.seen(), guard.seen());
+    for bytes in &traffic {
+        assert!(restarted.check_at(&decode(bytes).msg, NOW).is_err());
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn expired_requests_are_rejected() {
+    let mut guard = ReplayGuard::new();
+    assert!(matches!(
+        guard.check_at(&dkg_begin(1, 0), NOW),
+        Err(Error::MissingExpiry("DkgBegin"))
+    ));
+    assert!(matches!(
+        guard.check_at(&dkg_begin(1, EXPIRES_AT), EXPIRES_AT + 1),
+        Err(Error::Expired("DkgBegin", EXPIRES_AT))
+    ));
+    // Rejected requests are not recorded
+    assert_eq!(guard.seen().dkg_begin, 0);
+    guard
+        .check_at(&dkg_begin(1, EXPIRES_AT), EXPIRES_AT)
+        .unwrap();
+}
+
+#[test]
+fn out_of_order_requests_are_rejected() {
+    let mut guard = ReplayGuard::new();
+    guard.check_at(&dkg_begin(5, EXPIRES_AT), NOW).unwrap();
+
+    // Private shares of a round the coordinator did not begin
+    assert!(matches!(
+        guard.check_at(&dkg_private_begin(6), NOW),
+        Err(Error::OutOfOrder(..))
+    ));
+    guard.check_at(&dkg_private_begin(5), NOW).unwrap();
+
+    // Signature shares without nonces for the signing round
+    assert!(matches!(
+        guard.check_at(&sign_share_request(5, 7), NOW),
+        Err(Error::OutOfOrder(..))
+    ));
+    guard.check_at(&nonce_request(5, 7, 1), NOW).unwrap();
+    // A new nonce attempt of the same signing round is accepted, an older one is not
+    guard.check_at(&nonce_request(5, 7, 2), NOW).unwrap();
+    assert!(matches!(
+        guard.check_at(&nonce_request(5, 7, 1), NOW),
+        Err(Error::Stale(..))
+    ));
+    guard.check_at(&sign_share_request(5, 7), NOW).unwrap();
+
+    // Signer messages of an older DKG round
+    assert!(matches!(
+        guard.check_at(&dkg_end(4, 2), NOW),
+        Err(Error::Stale("DkgEnd", _))
+    ));
+    guard.check_at(&dkg_end(5, 2), NOW).unwrap();
+}
+
+#[test]
+fn pool_nonces_need_no_nonce_request() {
+    let mut guard = ReplayGuard::new();
+    let nonce_pool_request = |pool_id| {
+        MessageTypes::NoncePoolRequest(NoncePoolRequest {
+            dkg_id: 5,
+            pool_id,
+            count: 10,
+            expires_at: EXPIRES_AT,
+        })
+    };
+    guard.check_at(&dkg_begin(5, EXPIRES_AT), NOW).unwrap();
+    guard.check_at(&nonce_pool_request(1), NOW).unwrap();
+    assert!(matches!(
+        guard.check_at(&nonce_pool_request(1), NOW),
+        Err(Error::Stale("NoncePoolRequest", _))
+    ));
+
+    // Signature shares for pool nonces follow no nonce request, others still do
+    let mut pooled = sign_share_request(5, 7);
+    if let MessageTypes::SignShareRequest(request) = &mut pooled {
+        let MessageTypes::NonceResponse(mut nonce_response) = nonce_response(5, 7, 1) else {
+            unreachable!()
+        };
+        nonce_response.pool_index = Some(3);
+        request.nonce_responses.push(nonce_response);
+    }
+    assert!(matches!(
+        guard.check_at(&sign_share_request(5, 7), NOW),
+        Err(Error::OutOfOrder(..))
+    ));
+    guard.check_at(&pooled, NOW).unwrap();
+    assert!(matches!(
+        guard.check_at(&pooled, NOW),
+        Err(Error::Stale(..))
+    ));
+}
@@ -1,5 +1,6 @@
 mod sync_test;
 mod v1;
+mod v2;
 
 // https://github.com/Trust-Machines/frost/blob/sbtc/src/v1.rs#L444
 
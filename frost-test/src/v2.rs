@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use wsts::{traits::Signer, v2};
+
+    #[test]
+    fn test() {
+        let mut rng = OsRng;
+
+        let ids = [1, 2, 3];
+        let n: u32 = 10;
+        let t: u32 = 7;
+
+        let mut signer = v2::Party::new(0, &ids, 4, n, t, &mut rng);
+
+        assert_eq!(Signer::get_key_ids(&signer).len(), ids.len());
+        Signer::gen_nonces(&mut signer, &mut rng);
+
+        // A v2 party sends a single nonce for all of its key ids
+        let nonces = Signer::gen_nonces(&mut signer, &mut rng);
+        assert_eq!(nonces.len(), 1);
+    }
+}
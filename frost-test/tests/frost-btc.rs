@@ -101,10 +101,17 @@ fn blog_post() {
 
 #[test]
 fn frost_btc() {
+    frost_btc_with_signer(SignerHelper::default());
+}
+
+#[test]
+fn frost_btc_v2() {
+    frost_btc_with_signer(SignerHelper::v2());
+}
+
+fn frost_btc_with_signer(mut signer: SignerHelper) {
     // Merkle root for taproot tweaks (null to prevent script spends)
     let merkle_root = [0u8; 32];
-    // Singer setup
-    let mut signer = SignerHelper::default();
     // DKG (Distributed Key Generation)
     let (public_commitments, deposit_wallet_public_key_point, deposit_wallet_public_key) =
         signer.run_distributed_key_generation(Some(merkle_root));
@@ -1,7 +1,8 @@
 use rand_core::OsRng;
 use wsts::taproot::test_helpers::{dkg, sign};
 use wsts::taproot::SchnorrProof;
-use wsts::v1::{self, SignatureAggregator};
+use wsts::traits::Signer;
+use wsts::{v1, v2};
 
 #[test]
 #[allow(non_snake_case)]
@@ -28,10 +29,46 @@ fn pure_frost_test() {
         // get nonces and shares
         let (nonces, shares) = sign(MSG, &mut signers, &mut rng, None);
 
-        SignatureAggregator::new(N, T, A.clone())
+        v1::SignatureAggregator::new(N, T, A.clone())
             .unwrap()
             .sign_taproot(&MSG, &nonces, &shares, None)
     };
 
     assert!(SchnorrProof::new(&result.unwrap()).is_ok());
 }
+
+#[test]
+#[allow(non_snake_case)]
+fn pure_frost_v2_test() {
+    let T = 3;
+    let N = 4;
+    let mut rng = OsRng;
+    let mut signers = [
+        v2::Party::new(0, &[0, 1], 3, N, T, &mut rng),
+        v2::Party::new(1, &[2], 3, N, T, &mut rng),
+        v2::Party::new(2, &[3], 3, N, T, &mut rng),
+    ];
+
+    // DKG (Distributed Key Generation), with one polynomial per signer
+    let A = dkg(&mut signers[..], &mut rng, None).unwrap();
+    assert_eq!(A.len(), 3);
+
+    // signing. Signers: 0 (keys: 0, 1) and 1 (keys: 2)
+    let result = {
+        let mut signers = [signers[0].clone(), signers[1].clone()];
+        let key_ids: Vec<u32> = signers.iter().flat_map(Signer::get_key_ids).collect();
+
+        const MSG: &[u8] = "It was many and many a year ago".as_bytes();
+
+        // get one nonce and share per signer
+        let (nonces, shares) = sign(MSG, &mut signers, &mut rng, None);
+        assert_eq!(nonces.len(), 2);
+        assert_eq!(shares.len(), 2);
+
+        v2::SignatureAggregator::new(N, T, A.clone())
+            .unwrap()
+            .sign_taproot(&MSG, &nonces, &shares, &key_ids, None)
+    };
+
+    assert!(SchnorrProof::new(&result.unwrap()).is_ok());
+}
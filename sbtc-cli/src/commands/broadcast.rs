@@ -4,23 +4,26 @@ use anyhow::anyhow;
 use bdk::blockchain::Blockchain;
 use bitcoin::{
     psbt::serialize::{Deserialize, Serialize},
-    Network, Transaction,
+    Transaction,
 };
 use clap::Parser;
 
-use crate::commands::utils;
+use crate::commands::utils::{self, Network};
 
 #[derive(Parser, Debug, Clone)]
 pub struct BroadcastArgs {
     /// The network to broadcast to
-    #[clap(short, long, default_value_t = Network::Testnet)]
+    #[clap(short, long, value_enum, default_value_t = Network::Testnet)]
     network: Network,
+    /// Electrum server to broadcast to. Default: the public server of the network
+    #[clap(long)]
+    electrum_url: Option<String>,
     /// The transaction to broadcast
     tx: String,
 }
 
 pub fn broadcast_tx(broadcast: &BroadcastArgs) -> anyhow::Result<()> {
-    let blockchain = utils::init_blockchain()?;
+    let blockchain = utils::init_blockchain(broadcast.network, broadcast.electrum_url.as_deref())?;
     let tx = Transaction::deserialize(
         &array_bytes::hex2bytes(&broadcast.tx).map_err(|e| anyhow!("{:?}", e))?,
     )?;
@@ -4,12 +4,12 @@ use anyhow::anyhow;
 use bdk::{database::MemoryDatabase, SignOptions, Wallet};
 use bitcoin::{
     psbt::{serialize::Serialize, PartiallySignedTransaction},
-    Address as BitcoinAddress, Network, PrivateKey,
+    Address as BitcoinAddress, PrivateKey,
 };
 use blockstack_lib::types::{chainstate::StacksAddress, Address};
 use clap::Parser;
 
-use crate::commands::utils;
+use crate::commands::utils::{self, Network};
 
 #[derive(Parser, Debug, Clone)]
 pub struct DepositArgs {
@@ -28,22 +28,33 @@ pub struct DepositArgs {
     /// Dkg wallet address
     #[clap(short, long)]
     dkg_wallet: String,
+
+    /// The network to create the transaction for. Default: the network of the WIF
+    #[clap(short, long, value_enum)]
+    network: Option<Network>,
+
+    /// Electrum server to sync the wallet with. Default: the public server of the network
+    #[clap(long)]
+    electrum_url: Option<String>,
 }
 
 pub fn build_deposit_tx(deposit: &DepositArgs) -> anyhow::Result<()> {
-    let private_key = PrivateKey::from_wif(&deposit.wif)?;
-    let wallet = utils::setup_wallet(private_key)?;
+    let mut private_key = PrivateKey::from_wif(&deposit.wif)?;
+    let network = utils::network_for_private_key(deposit.network, &mut private_key)?;
+    let wallet = utils::setup_wallet(private_key, network, deposit.electrum_url.as_deref())?;
     let recipient = StacksAddress::from_string(&deposit.recipient)
         .ok_or(anyhow::anyhow!("Could not parse recipient Stacks address"))?;
+    if recipient.version != utils::stacks_address_version(network) {
+        return Err(anyhow!(
+            "Recipient {} is not a {:?} Stacks address",
+            deposit.recipient,
+            network
+        ));
+    }
     let dkg_address = BitcoinAddress::from_str(&deposit.dkg_wallet)?;
+    utils::check_address(&dkg_address, network)?;
 
-    let mut psbt = deposit_psbt(
-        &wallet,
-        &recipient,
-        &dkg_address,
-        deposit.amount,
-        &private_key.network,
-    )?;
+    let mut psbt = deposit_psbt(&wallet, &recipient, &dkg_address, deposit.amount, network)?;
 
     wallet.sign(&mut psbt, SignOptions::default())?;
     let tx = psbt.extract_tx();
@@ -64,7 +75,7 @@ fn deposit_psbt(
     recipient: &StacksAddress,
     dkg_address: &BitcoinAddress,
     amount: u64,
-    network: &Network,
+    network: Network,
 ) -> anyhow::Result<PartiallySignedTransaction> {
     let mut tx_builder = wallet.build_tx();
 
@@ -93,8 +104,9 @@ fn deposit_psbt(
     Ok(partial_tx)
 }
 
-fn deposit_data(recipient: &StacksAddress, network: &Network) -> Vec<u8> {
-    utils::magic_bytes(network)
+fn deposit_data(recipient: &StacksAddress, network: Network) -> Vec<u8> {
+    network
+        .magic_bytes()
         .into_iter()
         .chain(once(b'<'))
         .chain(once(recipient.version))
@@ -9,23 +9,21 @@ use bdk::{
 use bitcoin::{
     schnorr::TweakedPublicKey,
     secp256k1::{rand::random, Secp256k1},
-    Address as BitcoinAddress, Network, PrivateKey,
-};
-use blockstack_lib::{
-    address::{C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG},
-    types::chainstate::StacksAddress,
-    util::hash::Hash160,
+    Address as BitcoinAddress, PrivateKey,
 };
+use blockstack_lib::{types::chainstate::StacksAddress, util::hash::Hash160};
 use clap::Parser;
 
+use crate::commands::utils::{self, Network};
+
 #[derive(Parser, Debug, Clone)]
 pub struct GenerateArgs {
     /// Specify how to generate the credentials
     #[command(subcommand)]
     subcommand: GenerateSubcommand,
-    /// The network to broadcast to
-    #[clap(short, long, default_value_t = Network::Testnet)]
-    network: Network,
+    /// The network to generate credentials for. Default: the network of the WIF, otherwise testnet
+    #[clap(short, long, value_enum)]
+    network: Option<Network>,
 }
 
 #[derive(clap::Subcommand, Debug, Clone)]
@@ -49,29 +47,37 @@ struct Credentials {
 }
 
 pub fn generate(generate_args: &GenerateArgs) -> anyhow::Result<()> {
-    let (private_key, maybe_mnemonic) = match &generate_args.subcommand {
+    let network = generate_args.network.unwrap_or(Network::Testnet);
+    let (private_key, network, maybe_mnemonic) = match &generate_args.subcommand {
         GenerateSubcommand::New => {
             let mnemonic = random_mnemonic()?;
             (
-                private_key_from_mnemonic(generate_args.network, mnemonic.clone())?,
+                private_key_from_mnemonic(network.bitcoin_network(), mnemonic.clone())?,
+                network,
                 Some(mnemonic),
             )
         }
-        GenerateSubcommand::Wif { wif } => (private_key_from_wif(wif)?, None),
+        GenerateSubcommand::Wif { wif } => {
+            let mut private_key = private_key_from_wif(wif)?;
+            let network = utils::network_for_private_key(generate_args.network, &mut private_key)?;
+            (private_key, network, None)
+        }
         GenerateSubcommand::PrivateKeyHex { private_key } => (
-            parse_private_key_from_hex(private_key, generate_args.network)?,
+            parse_private_key_from_hex(private_key, network.bitcoin_network())?,
+            network,
             None,
         ),
         GenerateSubcommand::Mnemonic { mnemonic } => {
             let mnemonic = Mnemonic::parse(mnemonic)?;
             (
-                private_key_from_mnemonic(generate_args.network, mnemonic.clone())?,
+                private_key_from_mnemonic(network.bitcoin_network(), mnemonic.clone())?,
+                network,
                 Some(mnemonic),
             )
         }
     };
 
-    let credentials = generate_credentials(&private_key, maybe_mnemonic)?;
+    let credentials = generate_credentials(&private_key, network, maybe_mnemonic)?;
 
     serde_json::to_writer_pretty(stdout(), &credentials)?;
 
@@ -87,13 +93,19 @@ fn private_key_from_wif(wif: &str) -> anyhow::Result<PrivateKey> {
     Ok(PrivateKey::from_wif(wif)?)
 }
 
-fn parse_private_key_from_hex(private_key: &str, network: Network) -> anyhow::Result<PrivateKey> {
+fn parse_private_key_from_hex(
+    private_key: &str,
+    network: bitcoin::Network,
+) -> anyhow::Result<PrivateKey> {
     let slice = array_bytes::hex2bytes(private_key)
         .map_err(|_| anyhow::anyhow!("Failed to parse hex string: {}", private_key,))?;
     Ok(PrivateKey::from_slice(&slice, network)?)
 }
 
-fn private_key_from_mnemonic(network: Network, mnemonic: Mnemonic) -> anyhow::Result<PrivateKey> {
+fn private_key_from_mnemonic(
+    network: bitcoin::Network,
+    mnemonic: Mnemonic,
+) -> anyhow::Result<PrivateKey> {
     let extended_key: ExtendedKey<BareCtx> = mnemonic.into_extended_key()?;
     let private_key = extended_key
         .into_xprv(network)
@@ -104,16 +116,13 @@ fn private_key_from_mnemonic(network: Network, mnemonic: Mnemonic) -> anyhow::Re
 
 fn generate_credentials(
     private_key: &PrivateKey,
+    network: Network,
     maybe_mnemonic: Option<Mnemonic>,
 ) -> anyhow::Result<Credentials> {
     let secp = Secp256k1::new();
     let public_key = private_key.public_key(&secp);
 
-    let stacks_address_version = match private_key.network {
-        Network::Testnet => C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
-        Network::Bitcoin => C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
-        _ => panic!("Not supported"),
-    };
+    let stacks_address_version = utils::stacks_address_version(network);
     let public_key_hash = Hash160::from_vec(&public_key.pubkey_hash().as_hash().to_vec()).unwrap();
     let stacks_address = StacksAddress::new(stacks_address_version, public_key_hash);
     let bitcoin_taproot_address_tweaked =
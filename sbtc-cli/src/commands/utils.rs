@@ -1,23 +1,76 @@
 use std::collections::{BTreeMap, HashMap};
 
+use anyhow::anyhow;
 use bdk::{
     blockchain::ElectrumBlockchain, database::MemoryDatabase, electrum_client::Client,
     template::P2Wpkh, SyncOptions, Wallet,
 };
 use bitcoin::{
     blockdata::{opcodes, script::Builder},
-    Network, PrivateKey, Script, TxOut,
+    Address, PrivateKey, Script, TxOut,
+};
+use blockstack_lib::address::{
+    C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
 use serde::Serialize;
 
-pub fn init_blockchain() -> anyhow::Result<ElectrumBlockchain> {
-    let client = Client::new("ssl://blockstream.info:993")?;
+pub use sbtc_core::Network;
+
+pub fn stacks_address_version(network: Network) -> u8 {
+    match network {
+        Network::Mainnet => C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+        _ => C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+    }
+}
+
+/// The public electrum server of the network, if there is one
+fn default_electrum_url(network: Network) -> Option<&'static str> {
+    match network {
+        Network::Mainnet => Some("ssl://blockstream.info:700"),
+        Network::Testnet => Some("ssl://blockstream.info:993"),
+        _ => None,
+    }
+}
+
+/// Resolve the network of a private key. WIF only distinguishes mainnet from the test networks,
+/// so an explicit test network takes precedence over the one encoded in the key.
+pub fn network_for_private_key(
+    network: Option<Network>,
+    private_key: &mut PrivateKey,
+) -> anyhow::Result<Network> {
+    let key_network = match private_key.network {
+        bitcoin::Network::Bitcoin => Network::Mainnet,
+        _ => Network::Testnet,
+    };
+    let network = network.unwrap_or(key_network);
+    if (network == Network::Mainnet) != (key_network == Network::Mainnet) {
+        return Err(anyhow!(
+            "Private key is not valid for network {:?}",
+            network
+        ));
+    }
+    private_key.network = network.bitcoin_network();
+    Ok(network)
+}
+
+pub fn init_blockchain(
+    network: Network,
+    electrum_url: Option<&str>,
+) -> anyhow::Result<ElectrumBlockchain> {
+    let electrum_url = electrum_url
+        .or(default_electrum_url(network))
+        .ok_or_else(|| anyhow!("An electrum url is required for network {:?}", network))?;
+    let client = Client::new(electrum_url)?;
     let blockchain = ElectrumBlockchain::from(client);
     Ok(blockchain)
 }
 
-pub fn setup_wallet(private_key: PrivateKey) -> anyhow::Result<Wallet<MemoryDatabase>> {
-    let blockchain = init_blockchain()?;
+pub fn setup_wallet(
+    private_key: PrivateKey,
+    network: Network,
+    electrum_url: Option<&str>,
+) -> anyhow::Result<Wallet<MemoryDatabase>> {
+    let blockchain = init_blockchain(network, electrum_url)?;
     let wallet = Wallet::new(
         P2Wpkh(private_key),
         Some(P2Wpkh(private_key)),
@@ -62,12 +115,16 @@ pub fn reorder_outputs(
     outputs_ordered.into_values().collect()
 }
 
-pub fn magic_bytes(network: &Network) -> [u8; 2] {
-    match network {
-        Network::Bitcoin => [b'X', b'2'],
-        Network::Testnet => [b'T', b'2'],
-        _ => [b'i', b'd'],
+/// Check that an address belongs to the given network
+pub fn check_address(address: &Address, network: Network) -> anyhow::Result<()> {
+    if !address.is_valid_for_network(network.bitcoin_network()) {
+        return Err(anyhow!(
+            "Address {} is not valid for network {:?}",
+            address,
+            network
+        ));
     }
+    Ok(())
 }
 
 #[derive(Serialize)]
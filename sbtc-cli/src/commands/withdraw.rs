@@ -5,12 +5,14 @@ use bdk::{database::MemoryDatabase, SignOptions, Wallet};
 use bitcoin::{
     psbt::{serialize::Serialize, PartiallySignedTransaction},
     secp256k1::{Message, Secp256k1},
-    Address as BitcoinAddress, Network, PrivateKey,
+    Address as BitcoinAddress, PrivateKey,
 };
 use clap::Parser;
 
-use crate::commands::utils::TransactionData;
-use crate::commands::utils::{build_op_return_script, magic_bytes, reorder_outputs, setup_wallet};
+use crate::commands::utils::{self, TransactionData};
+use crate::commands::utils::{
+    build_op_return_script, check_address, reorder_outputs, setup_wallet, Network,
+};
 
 #[derive(Parser, Debug, Clone)]
 pub struct WithdrawalArgs {
@@ -37,16 +39,28 @@ pub struct WithdrawalArgs {
     /// Dkg wallet address
     #[clap(short, long)]
     dkg_wallet: String,
+
+    /// The network to create the transaction for. Default: the network of the WIF
+    #[clap(short, long, value_enum)]
+    network: Option<Network>,
+
+    /// Electrum server to sync the wallet with. Default: the public server of the network
+    #[clap(long)]
+    electrum_url: Option<String>,
 }
 
 pub fn build_withdrawal_tx(withdrawal: &WithdrawalArgs) -> anyhow::Result<()> {
-    let private_key = PrivateKey::from_wif(&withdrawal.wif)?;
+    let mut private_key = PrivateKey::from_wif(&withdrawal.wif)?;
+    let network = utils::network_for_private_key(withdrawal.network, &mut private_key)?;
 
-    let wallet = setup_wallet(private_key)?;
+    let wallet = setup_wallet(private_key, network, withdrawal.electrum_url.as_deref())?;
 
-    let sender_private_key = PrivateKey::from_wif(&withdrawal.sender_wif)?;
+    let mut sender_private_key = PrivateKey::from_wif(&withdrawal.sender_wif)?;
+    utils::network_for_private_key(Some(network), &mut sender_private_key)?;
     let recipient = BitcoinAddress::from_str(&withdrawal.recipient)?;
+    check_address(&recipient, network)?;
     let dkg_address = BitcoinAddress::from_str(&withdrawal.dkg_wallet)?;
+    check_address(&dkg_address, network)?;
 
     let mut psbt = withdrawal_psbt(
         &wallet,
@@ -55,7 +69,7 @@ pub fn build_withdrawal_tx(withdrawal: &WithdrawalArgs) -> anyhow::Result<()> {
         &dkg_address,
         withdrawal.amount,
         withdrawal.fulfillment_fee,
-        &private_key.network,
+        network,
     )?;
 
     wallet.sign(&mut psbt, SignOptions::default())?;
@@ -79,7 +93,7 @@ fn withdrawal_psbt(
     dkg_address: &BitcoinAddress,
     amount: u64,
     fulfillment_fee: u64,
-    network: &Network,
+    network: Network,
 ) -> anyhow::Result<PartiallySignedTransaction> {
     let recipient_script = recipient.script_pubkey();
     let dkg_wallet_script = dkg_address.script_pubkey();
@@ -126,7 +140,7 @@ fn withdrawal_data(
     recipient: &BitcoinAddress,
     amount: u64,
     sender_private_key: &PrivateKey,
-    network: &Network,
+    network: Network,
 ) -> Vec<u8> {
     let mut msg = amount.to_be_bytes().to_vec();
     msg.extend_from_slice(recipient.script_pubkey().as_bytes());
@@ -139,7 +153,8 @@ fn withdrawal_data(
         .sign_ecdsa_recoverable(&msg_ecdsa, &sender_private_key.inner)
         .serialize_compact();
 
-    magic_bytes(network)
+    network
+        .magic_bytes()
         .into_iter()
         .chain(once(b'>'))
         .chain(amount.to_be_bytes())
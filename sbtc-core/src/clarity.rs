@@ -0,0 +1,366 @@
+use std::collections::BTreeMap;
+
+use bitcoin::XOnlyPublicKey;
+pub use blockstack_lib::vm::Value as ClarityValue;
+use blockstack_lib::vm::{
+    types::{CharType, SequenceData},
+    ClarityName,
+};
+use wsts::ecdsa::PublicKey;
+
+/// Errors which occur decoding a clarity value into a rust type. `path` locates the
+/// offending value within the decoded result, e.g. `.key-ids[2]`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("result{path}: expected {expected}, found {found}")]
+    UnexpectedType {
+        path: String,
+        expected: &'static str,
+        found: String,
+    },
+    #[error("result{path}: missing tuple field")]
+    MissingField { path: String },
+    #[error("result{path}: {reason}")]
+    InvalidValue { path: String, reason: String },
+}
+
+impl Error {
+    fn unexpected(expected: &'static str, found: &ClarityValue) -> Self {
+        Self::UnexpectedType {
+            path: String::new(),
+            expected,
+            found: found.to_string(),
+        }
+    }
+
+    fn invalid(reason: impl ToString) -> Self {
+        Self::InvalidValue {
+            path: String::new(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Prefix the error path with the segment of the enclosing value
+    fn at(mut self, segment: &str) -> Self {
+        let path = match &mut self {
+            Self::UnexpectedType { path, .. }
+            | Self::MissingField { path }
+            | Self::InvalidValue { path, .. } => path,
+        };
+        path.insert_str(0, segment);
+        self
+    }
+}
+
+/// Types which can be decoded from the clarity value returned by a read-only contract call
+pub trait FromClarityValue: Sized {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error>;
+}
+
+impl FromClarityValue for ClarityValue {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        Ok(value)
+    }
+}
+
+impl FromClarityValue for u128 {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::UInt(value) => Ok(value),
+            value => Err(Error::unexpected("uint", &value)),
+        }
+    }
+}
+
+impl FromClarityValue for u64 {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        u128::from_clarity_value(value)?
+            .try_into()
+            .map_err(Error::invalid)
+    }
+}
+
+impl FromClarityValue for u32 {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        u128::from_clarity_value(value)?
+            .try_into()
+            .map_err(Error::invalid)
+    }
+}
+
+impl FromClarityValue for i128 {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::Int(value) => Ok(value),
+            value => Err(Error::unexpected("int", &value)),
+        }
+    }
+}
+
+impl FromClarityValue for bool {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::Bool(value) => Ok(value),
+            value => Err(Error::unexpected("bool", &value)),
+        }
+    }
+}
+
+impl FromClarityValue for String {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::Sequence(SequenceData::String(CharType::ASCII(string))) => {
+                String::from_utf8(string.data).map_err(Error::invalid)
+            }
+            ClarityValue::Sequence(SequenceData::String(CharType::UTF8(string))) => {
+                String::from_utf8(string.data.concat()).map_err(Error::invalid)
+            }
+            value => Err(Error::unexpected("string", &value)),
+        }
+    }
+}
+
+/// The contents of a clarity `buff`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Buffer(pub Vec<u8>);
+
+impl FromClarityValue for Buffer {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::Sequence(SequenceData::Buffer(buffer)) => Ok(Buffer(buffer.data)),
+            value => Err(Error::unexpected("buff", &value)),
+        }
+    }
+}
+
+/// A compressed secp256k1 public key stored in a `(buff 33)`
+impl FromClarityValue for PublicKey {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        let Buffer(bytes) = Buffer::from_clarity_value(value)?;
+        PublicKey::try_from(bytes.as_slice())
+            .map_err(|_| Error::invalid("invalid compressed public key"))
+    }
+}
+
+/// An x-only public key stored in a `(buff 32)`
+impl FromClarityValue for XOnlyPublicKey {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        let Buffer(bytes) = Buffer::from_clarity_value(value)?;
+        XOnlyPublicKey::from_slice(&bytes).map_err(Error::invalid)
+    }
+}
+
+impl<T: FromClarityValue> FromClarityValue for Option<T> {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::Optional(optional) => optional
+                .data
+                .map(|value| T::from_clarity_value(*value).map_err(|e| e.at(".some")))
+                .transpose(),
+            value => Err(Error::unexpected("optional", &value)),
+        }
+    }
+}
+
+/// A clarity `response`, decoded as `Ok` when committed and `Err` otherwise
+impl<T: FromClarityValue, E: FromClarityValue> FromClarityValue for Result<T, E> {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::Response(response) if response.committed => Ok(Ok(
+                T::from_clarity_value(*response.data).map_err(|e| e.at(".ok"))?,
+            )),
+            ClarityValue::Response(response) => Ok(Err(
+                E::from_clarity_value(*response.data).map_err(|e| e.at(".err"))?
+            )),
+            value => Err(Error::unexpected("response", &value)),
+        }
+    }
+}
+
+impl<T: FromClarityValue> FromClarityValue for Vec<T> {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::Sequence(SequenceData::List(list)) => list
+                .data
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| T::from_clarity_value(value).map_err(|e| e.at(&format!("[{i}]"))))
+                .collect(),
+            value => Err(Error::unexpected("list", &value)),
+        }
+    }
+}
+
+/// The fields of a clarity tuple, which are taken by name as they are decoded
+#[derive(Debug)]
+pub struct Tuple {
+    fields: BTreeMap<ClarityName, ClarityValue>,
+}
+
+impl Tuple {
+    pub fn field<T: FromClarityValue>(&mut self, name: &str) -> Result<T, Error> {
+        let segment = format!(".{name}");
+        let value = self
+            .fields
+            .remove(&ClarityName::from(name))
+            .ok_or_else(|| Error::MissingField {
+                path: segment.clone(),
+            })?;
+        T::from_clarity_value(value).map_err(|e| e.at(&segment))
+    }
+}
+
+impl FromClarityValue for Tuple {
+    fn from_clarity_value(value: ClarityValue) -> Result<Self, Error> {
+        match value {
+            ClarityValue::Tuple(tuple) => Ok(Tuple {
+                fields: tuple.data_map,
+            }),
+            value => Err(Error::unexpected("tuple", &value)),
+        }
+    }
+}
+
+/// Implement [`FromClarityValue`] for a struct by mapping each of its fields to a tuple field:
+///
+/// ```ignore
+/// clarity_tuple!(SignerData {
+///     public_key: "public-key",
+///     key_ids: "key-ids",
+/// });
+/// ```
+#[macro_export]
+macro_rules! clarity_tuple {
+    ($name:ident { $($field:ident: $key:literal),* $(,)? }) => {
+        impl $crate::clarity::FromClarityValue for $name {
+            fn from_clarity_value(
+                value: $crate::clarity::ClarityValue,
+            ) -> Result<Self, $crate::clarity::Error> {
+                let mut tuple = <$crate::clarity::Tuple as $crate::clarity::FromClarityValue>::from_clarity_value(value)?;
+                Ok(Self {
+                    $($field: tuple.field($key)?,)*
+                })
+            }
+        }
+    };
+}
+
+/// A signer registered in the sBTC contract
+#[derive(Clone, Debug)]
+pub struct SignerData {
+    pub public_key: PublicKey,
+    pub key_ids: Vec<u32>,
+}
+
+clarity_tuple!(SignerData {
+    public_key: "public-key",
+    key_ids: "key-ids",
+});
+
+/// The coordinator registered in the sBTC contract
+#[derive(Clone, Debug)]
+pub struct CoordinatorData {
+    pub key: PublicKey,
+}
+
+clarity_tuple!(CoordinatorData { key: "key" });
+
+#[cfg(test)]
+mod tests {
+    use blockstack_lib::vm::types::TupleData;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Wallet {
+        version: Buffer,
+        hashbytes: Buffer,
+        cycles: Vec<u32>,
+    }
+
+    clarity_tuple!(Wallet {
+        version: "version",
+        hashbytes: "hashbytes",
+        cycles: "cycles",
+    });
+
+    fn wallet(cycles: Vec<ClarityValue>) -> ClarityValue {
+        ClarityValue::Tuple(
+            TupleData::from_data(vec![
+                (
+                    ClarityName::from("version"),
+                    ClarityValue::buff_from(vec![4]).unwrap(),
+                ),
+                (
+                    ClarityName::from("hashbytes"),
+                    ClarityValue::buff_from(vec![1; 32]).unwrap(),
+                ),
+                (
+                    ClarityName::from("cycles"),
+                    ClarityValue::list_from(cycles).unwrap(),
+                ),
+            ])
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn decode_tuple() {
+        let value = ClarityValue::some(wallet(vec![ClarityValue::UInt(1)])).unwrap();
+        assert_eq!(
+            Option::<Wallet>::from_clarity_value(value).unwrap(),
+            Some(Wallet {
+                version: Buffer(vec![4]),
+                hashbytes: Buffer(vec![1; 32]),
+                cycles: vec![1],
+            })
+        );
+        assert_eq!(
+            Option::<Wallet>::from_clarity_value(ClarityValue::none()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn decode_errors_locate_the_value() {
+        let value = ClarityValue::some(wallet(vec![
+            ClarityValue::UInt(1),
+            ClarityValue::Bool(true),
+        ]))
+        .unwrap();
+        let error = Option::<Wallet>::from_clarity_value(value).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "result.some.cycles[1]: expected uint, found true"
+        );
+
+        let value = wallet(vec![ClarityValue::UInt(u128::MAX)]);
+        assert!(matches!(
+            Wallet::from_clarity_value(value),
+            Err(Error::InvalidValue { path, .. }) if path == ".cycles[0]"
+        ));
+
+        let mut tuple = Tuple::from_clarity_value(wallet(vec![])).unwrap();
+        assert_eq!(
+            tuple.field::<Buffer>("key"),
+            Err(Error::MissingField {
+                path: ".key".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn decode_response() {
+        let ok = ClarityValue::okay(ClarityValue::UInt(6)).unwrap();
+        assert_eq!(Result::<u128, u128>::from_clarity_value(ok), Ok(Ok(6)));
+        let err = ClarityValue::error(ClarityValue::UInt(1000)).unwrap();
+        assert_eq!(Result::<u128, u128>::from_clarity_value(err), Ok(Err(1000)));
+        assert!(matches!(
+            Result::<u128, u128>::from_clarity_value(ClarityValue::UInt(6)),
+            Err(Error::UnexpectedType {
+                expected: "response",
+                ..
+            })
+        ));
+    }
+}
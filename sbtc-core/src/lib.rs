@@ -0,0 +1,5 @@
+//! Definitions shared by the sBTC binaries
+pub mod clarity;
+mod network;
+
+pub use network::Network;
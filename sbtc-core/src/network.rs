@@ -0,0 +1,43 @@
+/// The network sBTC runs on
+#[derive(clap::ValueEnum, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[value(alias = "bitcoin")]
+    Mainnet,
+    Testnet,
+    Signet,
+    Regtest,
+    /// A local development network on top of bitcoin regtest
+    Devnet,
+}
+
+impl Network {
+    pub fn bitcoin_network(&self) -> bitcoin::Network {
+        match self {
+            Network::Mainnet => bitcoin::Network::Bitcoin,
+            Network::Testnet => bitcoin::Network::Testnet,
+            Network::Signet => bitcoin::Network::Signet,
+            Network::Regtest | Network::Devnet => bitcoin::Network::Regtest,
+        }
+    }
+
+    /// The magic bytes prefixing sBTC op return data
+    pub fn magic_bytes(&self) -> [u8; 2] {
+        match self {
+            Network::Mainnet => [b'X', b'2'],
+            Network::Testnet | Network::Signet => [b'T', b'2'],
+            Network::Regtest | Network::Devnet => [b'i', b'd'],
+        }
+    }
+}
+
+impl From<bitcoin::Network> for Network {
+    fn from(network: bitcoin::Network) -> Self {
+        match network {
+            bitcoin::Network::Bitcoin => Network::Mainnet,
+            bitcoin::Network::Testnet => Network::Testnet,
+            bitcoin::Network::Signet => Network::Signet,
+            bitcoin::Network::Regtest => Network::Regtest,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    io::{Error as IoError, Write},
+    net::{SocketAddr, TcpListener},
+    thread,
+};
+
+use serde::Serialize;
+use tracing::{info, warn};
+use yarpc::http::{IoStream, Method, Request, Response};
+
+use crate::control::{Command, Controller};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    IOError(#[from] IoError),
+    #[error("Admin address {0} is not a loopback address")]
+    NotLoopback(SocketAddr),
+}
+
+/// HTTP endpoint serving the admin routes `POST /admin/pause`, `POST /admin/resume`,
+/// `POST /admin/stop` and `GET /admin/status`. The routes are unauthenticated, so the endpoint
+/// only listens on loopback addresses, and refuses requests with an `Origin` header, which
+/// browsers send along with requests a web page makes to another site.
+pub struct AdminEndpoint {
+    address: SocketAddr,
+}
+
+impl AdminEndpoint {
+    pub fn spawn(address: &str, controller: Controller) -> Result<Self, Error> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        if !address.ip().is_loopback() {
+            return Err(Error::NotLoopback(address));
+        }
+        thread::spawn(move || listen(listener, controller));
+        info!("Listening for admin commands on {}", address);
+        Ok(Self { address })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    paused: bool,
+    stopping: bool,
+}
+
+enum AdminRequest {
+    Command(Command),
+    Status,
+}
+
+fn listen(listener: TcpListener, controller: Controller) {
+    for stream in listener.incoming() {
+        if let Err(e) = stream.and_then(|mut stream| handle(&mut stream, &controller)) {
+            warn!("Failed to handle admin request: {}", e);
+        }
+    }
+}
+
+fn handle(io: &mut impl IoStream, controller: &Controller) -> Result<(), IoError> {
+    let request = Request::read(io.istream())?;
+    let (code, content) = if request.headers.contains_key("origin") {
+        warn!("Refused admin request {} from a web page", request.url);
+        (403, Vec::default())
+    } else {
+        match parse_admin_request(&request) {
+            Some(AdminRequest::Command(command)) => {
+                info!("Received admin command {:?}", command);
+                controller.send(command);
+                (200, Vec::default())
+            }
+            Some(AdminRequest::Status) => {
+                let status = Status {
+                    paused: controller.is_paused(),
+                    stopping: controller.is_stopping(),
+                };
+                (200, serde_json::to_vec(&status)?)
+            }
+            None => (404, Vec::default()),
+        }
+    };
+    let phrase = match code {
+        200 => "OK",
+        403 => "Forbidden",
+        _ => "Not Found",
+    };
+    let response = Response::new(code, phrase.to_string(), Default::default(), content);
+    let ostream = io.ostream();
+    response.write(ostream)?;
+    ostream.flush()?;
+    Ok(())
+}
+
+fn parse_admin_request(request: &Request) -> Option<AdminRequest> {
+    let path = request.url.split('?').next().unwrap_or_default();
+    match (&request.method, path) {
+        (Method::POST, "/admin/pause") => Some(AdminRequest::Command(Command::Pause)),
+        (Method::POST, "/admin/resume") => Some(AdminRequest::Command(Command::Resume)),
+        (Method::POST, "/admin/stop") => Some(AdminRequest::Command(Command::Stop)),
+        (Method::GET, "/admin/status") => Some(AdminRequest::Status),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpStream, str::from_utf8, time::Instant};
+
+    use yarpc::http::{Call, MemIoStreamEx};
+
+    use super::*;
+    use crate::control::Control;
+
+    #[test]
+    fn should_forward_admin_commands() {
+        let control = Control::default();
+        let request = "POST /admin/pause HTTP/1.1\r\nContent-Length: 0\r\n\r\n";
+        let mut output = Vec::default();
+        let mut stream = request.as_bytes().mem_io_stream(&mut output);
+        handle(&mut stream, control.controller()).unwrap();
+        assert_eq!(control.next_command(Instant::now()), Command::Pause);
+
+        let request = "GET /admin/status HTTP/1.1\r\nContent-Length: 0\r\n\r\n";
+        let mut output = Vec::default();
+        let mut stream = request.as_bytes().mem_io_stream(&mut output);
+        handle(&mut stream, control.controller()).unwrap();
+        let output = from_utf8(&output).unwrap();
+        assert!(output.ends_with(r#"{"paused":true,"stopping":false}"#));
+
+        let request = "POST /admin/unknown HTTP/1.1\r\nContent-Length: 0\r\n\r\n";
+        let mut output = Vec::default();
+        let mut stream = request.as_bytes().mem_io_stream(&mut output);
+        handle(&mut stream, control.controller()).unwrap();
+        assert!(from_utf8(&output).unwrap().starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn should_refuse_requests_from_web_pages() {
+        let control = Control::default();
+        let request =
+            "POST /admin/stop HTTP/1.1\r\nOrigin: https://example.com\r\nContent-Length: 0\r\n\r\n";
+        let mut output = Vec::default();
+        let mut stream = request.as_bytes().mem_io_stream(&mut output);
+        handle(&mut stream, control.controller()).unwrap();
+        assert!(from_utf8(&output).unwrap().starts_with("HTTP/1.1 403"));
+        assert!(!control.controller().is_stopping());
+    }
+
+    #[test]
+    fn should_only_listen_on_loopback_addresses() {
+        let control = Control::default();
+        assert!(matches!(
+            AdminEndpoint::spawn("0.0.0.0:0", control.controller().clone()),
+            Err(Error::NotLoopback(_))
+        ));
+
+        let endpoint = AdminEndpoint::spawn("127.0.0.1:0", control.controller().clone()).unwrap();
+        let mut stream = TcpStream::connect(endpoint.local_addr()).unwrap();
+        let request = Request::new(
+            Method::POST,
+            "/admin/stop".to_string(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(stream.call(request).unwrap().code, 200);
+        assert!(control.controller().is_stopping());
+    }
+}
@@ -1,7 +1,12 @@
 use std::{borrow::Cow, str::FromStr};
 
 use bdk::descriptor::calc_checksum;
-use bitcoin::{consensus::Encodable, hashes::sha256d::Hash, util::amount::Amount, Txid};
+use bitcoin::{
+    consensus::{deserialize, Encodable},
+    hashes::sha256d::Hash,
+    util::amount::Amount,
+    Txid,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tracing::{debug, info, warn};
@@ -14,6 +19,10 @@ pub trait BitcoinNode {
     fn load_wallet(&self, address: &bitcoin::Address) -> Result<(), Error>;
     /// Get all utxos from the given address
     fn list_unspent(&self, address: &bitcoin::Address) -> Result<Vec<UTXO>, Error>;
+    /// Get a transaction known to the wallet. Returns None if the wallet has never seen it
+    fn get_transaction(&self, txid: &Txid) -> Result<Option<BitcoinTransaction>, Error>;
+    /// List the ids of all transactions spending from the wallet
+    fn list_sent_transactions(&self) -> Result<Vec<Txid>, Error>;
 }
 
 pub type BitcoinTransaction = bitcoin::Transaction;
@@ -124,6 +133,58 @@ impl BitcoinNode for LocalhostBitcoinNode {
 
         result
     }
+
+    fn get_transaction(&self, txid: &Txid) -> Result<Option<BitcoinTransaction>, Error> {
+        debug!("Retrieving transaction {}...", txid);
+        let include_watchonly = true;
+        let response =
+            match self.call_wallet("gettransaction", (txid.to_string(), include_watchonly)) {
+                Ok(response) => response,
+                Err(Error::RPCError(message))
+                    if message.contains("Invalid or non-wallet transaction id") =>
+                {
+                    return Ok(None)
+                }
+                Err(e) => return Err(e),
+            };
+        let raw_tx = response["hex"].as_str().ok_or(Error::InvalidResponseJSON(
+            "No transaction hex in gettransaction response".to_string(),
+        ))?;
+        let tx_bytes =
+            hex::decode(raw_tx).map_err(|e| Error::InvalidResponseJSON(e.to_string()))?;
+        let tx = deserialize(&tx_bytes).map_err(|e| Error::InvalidResponseJSON(e.to_string()))?;
+        Ok(Some(tx))
+    }
+
+    fn list_sent_transactions(&self) -> Result<Vec<Txid>, Error> {
+        debug!("Retrieving sent transactions...");
+        // Construct the params using defaults found at https://developer.bitcoin.org/reference/rpc/listtransactions.html
+        let label = "*";
+        let count = 9999999i64;
+        let skip = 0i64;
+        let include_watchonly = true;
+        let params = (label, count, skip, include_watchonly);
+
+        let response = self.call_wallet("listtransactions", params)?;
+
+        let mut txids = vec![];
+        for raw in response.as_array().ok_or(Error::InvalidResponseJSON(
+            "Listtransactions response is not an array".to_string(),
+        ))? {
+            if raw["category"].as_str() != Some("send") {
+                continue;
+            }
+            let txid = raw["txid"].as_str().ok_or(Error::InvalidResponseJSON(
+                "Could not parse txid".to_string(),
+            ))?;
+            let txid = Txid::from_str(txid).map_err(|_| Error::InvalidTxHash)?;
+            // A transaction with several outputs is listed once per output
+            if !txids.contains(&txid) {
+                txids.push(txid);
+            }
+        }
+        Ok(txids)
+    }
 }
 
 impl LocalhostBitcoinNode {
@@ -134,6 +195,21 @@ impl LocalhostBitcoinNode {
         }
     }
 
+    /// The network the bitcoin node is running on
+    pub fn network(&self) -> Result<bitcoin::Network, Error> {
+        let response = self.call("getblockchaininfo", ())?;
+        match response["chain"].as_str() {
+            Some("main") => Ok(bitcoin::Network::Bitcoin),
+            Some("test") => Ok(bitcoin::Network::Testnet),
+            Some("signet") => Ok(bitcoin::Network::Signet),
+            Some("regtest") => Ok(bitcoin::Network::Regtest),
+            _ => Err(Error::InvalidResponseJSON(format!(
+                "Unknown chain: {}",
+                response["chain"]
+            ))),
+        }
+    }
+
     /// Make the Bitcoin RPC method call with the corresponding paramenters
     fn call(
         &self,
@@ -1,4 +1,4 @@
-use std::iter::repeat;
+use std::iter::{once, repeat};
 
 use crate::bitcoin_node::UTXO;
 use crate::peg_wallet::{BitcoinWallet as BitcoinWalletTrait, Error as PegWalletError};
@@ -6,9 +6,15 @@ use crate::stacks_node::PegOutRequestOp;
 use bitcoin::blockdata::opcodes;
 use bitcoin::TxOut;
 use bitcoin::{
-    blockdata::script, hashes::hex::FromHex, schnorr::TweakedPublicKey, Address, Network, OutPoint,
-    Script, Transaction, TxIn, XOnlyPublicKey,
+    blockdata::script,
+    hashes::{hex::FromHex, Hash},
+    schnorr::TweakedPublicKey,
+    secp256k1::Secp256k1,
+    util::taproot::TapBranchHash,
+    Address, Network, OutPoint, Script, Transaction, TxIn, XOnlyPublicKey,
 };
+use frost_signer::signing_round::SignatureType;
+use stacks_signer::peg_out::recipient_script;
 use tracing::{debug, warn};
 
 #[derive(thiserror::Error, Debug, PartialEq)]
@@ -28,17 +34,60 @@ pub enum Error {
 pub struct BitcoinWallet {
     address: Address,
     public_key: XOnlyPublicKey,
+    signature_type: SignatureType,
+    magic_bytes: [u8; 2],
 }
 
 impl BitcoinWallet {
+    /// A wallet which pays to the untweaked aggregate public key. It can not be spent through script paths.
     pub fn new(public_key: XOnlyPublicKey, network: Network) -> Self {
         let tweaked_public_key = TweakedPublicKey::dangerous_assume_tweaked(public_key);
         let address = Address::p2tr_tweaked(tweaked_public_key, network);
         Self {
             address,
             public_key,
+            signature_type: SignatureType::Frost,
+            magic_bytes: sbtc_core::Network::from(network).magic_bytes(),
         }
     }
+
+    /// A BIP341 wallet which pays to the aggregate public key tweaked to commit to the script tree with the given merkle root
+    pub fn new_taproot(
+        internal_key: XOnlyPublicKey,
+        merkle_root: Option<TapBranchHash>,
+        network: Network,
+    ) -> Self {
+        let secp = Secp256k1::verification_only();
+        let address = Address::p2tr(&secp, internal_key, merkle_root, network);
+        Self {
+            address,
+            public_key: internal_key,
+            signature_type: SignatureType::Taproot(merkle_root.map(|root| root.into_inner())),
+            magic_bytes: sbtc_core::Network::from(network).magic_bytes(),
+        }
+    }
+
+    /// A wallet which is spent with signatures of the given type
+    pub fn from_signature_type(
+        public_key: XOnlyPublicKey,
+        signature_type: SignatureType,
+        network: Network,
+    ) -> Self {
+        match signature_type {
+            SignatureType::Frost => Self::new(public_key, network),
+            SignatureType::Taproot(merkle_root) => Self::new_taproot(
+                public_key,
+                merkle_root.map(TapBranchHash::from_inner),
+                network,
+            ),
+        }
+    }
+
+    /// Use custom magic bytes instead of the ones of the network
+    pub fn with_magic_bytes(mut self, magic_bytes: [u8; 2]) -> Self {
+        self.magic_bytes = magic_bytes;
+        self
+    }
 }
 
 impl BitcoinWalletTrait for BitcoinWallet {
@@ -101,15 +150,14 @@ impl BitcoinWalletTrait for BitcoinWallet {
             "change_amount: {:?}, total_consumed: {:?}, op.amount: {:?}",
             change_amount, total_consumed, op.amount
         );
-        // Do not want to use Script::new_v1_p2tr because it will tweak our key when we don't want it to
-        let public_key_tweaked = TweakedPublicKey::dangerous_assume_tweaked(self.public_key);
-        let script_pubkey = Script::new_v1_p2tr_tweaked(public_key_tweaked);
+        // Return change to the wallet address, whose output key already includes any taproot tweak
+        let script_pubkey = self.address.script_pubkey();
 
-        tx.output.push(withdrawal_data_output());
+        tx.output.push(withdrawal_data_output(self.magic_bytes));
 
         let withdrawal_output = bitcoin::TxOut {
             value: op.amount,
-            script_pubkey: script_pubkey.clone(),
+            script_pubkey: recipient_script(&op.recipient),
         };
         tx.output.push(withdrawal_output);
 
@@ -134,11 +182,16 @@ impl BitcoinWalletTrait for BitcoinWallet {
     fn x_only_pub_key(&self) -> &XOnlyPublicKey {
         &self.public_key
     }
+
+    fn signature_type(&self) -> SignatureType {
+        self.signature_type
+    }
 }
 
-fn withdrawal_data_output() -> TxOut {
-    let data: Vec<u8> = [b'T', b'2', b'!']
+fn withdrawal_data_output(magic_bytes: [u8; 2]) -> TxOut {
+    let data: Vec<u8> = magic_bytes
         .into_iter()
+        .chain(once(b'!'))
         .chain(repeat(b'.'))
         .take(35)
         .collect();
@@ -187,9 +240,14 @@ mod tests {
     use crate::bitcoin_node::UTXO;
     use crate::peg_wallet::{BitcoinWallet as BitcoinWalletTrait, Error as PegWalletError};
     use crate::util::test::{build_peg_out_request_op, PRIVATE_KEY_HEX};
-    use bitcoin::XOnlyPublicKey;
+    use bitcoin::{
+        hashes::Hash, schnorr::TapTweak, secp256k1::Secp256k1, util::taproot::TapBranchHash,
+        Address, XOnlyPublicKey,
+    };
+    use frost_signer::signing_round::SignatureType;
     use hex::encode;
     use rand::Rng;
+    use stacks_signer::peg_out::recipient_script;
     use std::str::FromStr;
 
     /// Helper function to build a valid bitcoin wallet
@@ -268,6 +326,76 @@ mod tests {
         assert_eq!(btc_tx.output[0].value, 0);
         assert_eq!(btc_tx.output[1].value, amount);
         assert_eq!(btc_tx.output[2].value, 10000);
+        // The withdrawal pays the recipient and the change returns to the wallet
+        assert_eq!(
+            btc_tx.output[1].script_pubkey,
+            recipient_script(&op.recipient)
+        );
+        assert_eq!(
+            btc_tx.output[2].script_pubkey,
+            wallet.address().script_pubkey()
+        );
+    }
+
+    #[test]
+    fn fulfill_peg_out_uses_network_magic_bytes() {
+        let public_key = *bitcoin_wallet().x_only_pub_key();
+        let op = build_peg_out_request_op(PRIVATE_KEY_HEX, 10000, 1, 1);
+        let mut txouts = build_utxos(1);
+        txouts.push(build_utxo(op.txid.to_string(), 2, 1));
+
+        let wallets = [
+            (bitcoin_wallet(), b"T2"),
+            (
+                BitcoinWallet::new(public_key, bitcoin::Network::Bitcoin),
+                b"X2",
+            ),
+            (
+                BitcoinWallet::new(public_key, bitcoin::Network::Regtest),
+                b"id",
+            ),
+            (
+                BitcoinWallet::new(public_key, bitcoin::Network::Regtest).with_magic_bytes(*b"dv"),
+                b"dv",
+            ),
+        ];
+        for (wallet, magic_bytes) in wallets {
+            let (btc_tx, _) = wallet.fulfill_peg_out(&op, txouts.clone()).unwrap();
+            // OP_RETURN OP_PUSHBYTES_35 <magic bytes> '!'
+            let data = &btc_tx.output[0].script_pubkey.as_bytes()[2..];
+            assert_eq!(&data[..2], magic_bytes);
+            assert_eq!(data[2], b'!');
+        }
+    }
+
+    #[test]
+    fn taproot_wallet_pays_to_tweaked_key() {
+        let public_key = *bitcoin_wallet().x_only_pub_key();
+        let merkle_root = TapBranchHash::from_inner([1; 32]);
+        let wallet =
+            BitcoinWallet::new_taproot(public_key, Some(merkle_root), bitcoin::Network::Testnet);
+
+        let secp = Secp256k1::verification_only();
+        let (output_key, _) = public_key.tap_tweak(&secp, Some(merkle_root));
+        assert_eq!(
+            wallet.address(),
+            &Address::p2tr_tweaked(output_key, bitcoin::Network::Testnet)
+        );
+        assert_ne!(wallet.address(), bitcoin_wallet().address());
+        assert_eq!(wallet.x_only_pub_key(), &public_key);
+        assert_eq!(
+            wallet.signature_type(),
+            SignatureType::Taproot(Some([1; 32]))
+        );
+
+        let mut txouts = build_utxos(6);
+        let op = build_peg_out_request_op(PRIVATE_KEY_HEX, 200000, 1, 1);
+        txouts.push(build_utxo(op.txid.to_string(), 2, 1));
+        let (btc_tx, _) = wallet.fulfill_peg_out(&op, txouts).unwrap();
+        assert_eq!(
+            btc_tx.output[2].script_pubkey,
+            wallet.address().script_pubkey()
+        );
     }
 
     #[test]
@@ -1,4 +1,5 @@
 use clap::Parser;
+use frost_signer::{config_loader::ConfigOverrides, keystore::KeystoreCommand};
 
 ///Command line interface for stacks coordinator
 #[derive(Parser)]
@@ -15,10 +16,17 @@ pub struct Cli {
     #[arg(short = 'b', long)]
     pub start_block_height: Option<u64>,
 
-    /// Signer Config file path
+    /// Signer Config file path. Overrides signer_config_path within the config file
     /// TODO: this should not be a seperate option really
     #[arg(short, long)]
-    pub signer_config: String,
+    pub signer_config: Option<String>,
+
+    /// Print the output as JSON for scripting
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
 
     /// Subcommand to perform
     #[clap(subcommand)]
@@ -33,4 +41,123 @@ pub enum Command {
     Dkg,
     // Run distributed key generation round then sign a message
     DkgSign,
+    // Verify the signing journal and cross-check it against broadcast bitcoin transactions
+    VerifyJournal {
+        /// Journal file path. Defaults to the journal in the configured data directory
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+    // Inspect and manipulate the peg queue in the data directory
+    Queue {
+        #[clap(subcommand)]
+        command: QueueCommand,
+    },
+    // Inspect the peg wallet
+    Wallet {
+        #[clap(subcommand)]
+        command: WalletCommand,
+    },
+    // Recover the peg wallet through its timelocked recovery leaf
+    Recovery {
+        #[clap(subcommand)]
+        command: RecoveryCommand,
+    },
+    // Print or check the configuration after environment and command line overrides
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommand,
+    },
+    // Create, import or inspect an encrypted keystore of private keys
+    Keystore {
+        #[clap(subcommand)]
+        command: KeystoreCommand,
+    },
+    // Run a signing round over a message
+    SignMessage {
+        /// The message to sign
+        message: String,
+        /// Interpret the message as hex encoded bytes
+        #[arg(long)]
+        hex: bool,
+    },
+}
+
+/// Peg queue subcommands. These work offline and should not be run while the coordinator is running.
+#[derive(clap::Subcommand, Debug)]
+pub enum QueueCommand {
+    // List the ops in the queue
+    List {
+        /// Only list ops with this status
+        #[arg(long, value_parser = ["new", "pending", "awaiting_review", "acknowledged", "skipped"])]
+        status: Option<String>,
+        /// Only list ops of this type
+        #[arg(long = "type", value_parser = ["peg_in", "peg_out_request"])]
+        op_type: Option<String>,
+    },
+    // Show an op and the fulfillment transaction it awaits review of, if any
+    Show {
+        txid: String,
+    },
+    // Return a pending or parked op to the queue so that it is processed again
+    Retry {
+        txid: String,
+    },
+    // Remove an op from the queue without processing it
+    Skip {
+        txid: String,
+    },
+    // Set the burn block height the next poll starts at
+    ResetHeight {
+        height: u64,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum ConfigCommand {
+    // Print the configuration with secrets redacted
+    Show,
+    // Validate the configuration and connect to every configured endpoint
+    Check,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum WalletCommand {
+    // Print the peg wallet address derived from the DKG public shares in the data directory
+    Address,
+    // List the unspent outputs of the peg wallet
+    Utxos,
+}
+
+/// Recovery sweep subcommands. The sweep is a PSBT which each recovery key holder signs in turn.
+#[derive(clap::Subcommand, Debug)]
+pub enum RecoveryCommand {
+    // Print the recovery leaf and the peg wallet address committing to it
+    Show,
+    // Write an unsigned PSBT sweeping all peg wallet utxos past the timelock to the destination
+    Build {
+        /// The bitcoin address to sweep the peg wallet to
+        destination: String,
+        /// The absolute transaction fee in sats
+        #[arg(long)]
+        fee: u64,
+        /// Path to write the PSBT to
+        #[arg(short, long)]
+        output: String,
+    },
+    // Sign the sweep PSBT in place with a recovery key
+    Sign {
+        /// Path of the sweep PSBT
+        psbt: String,
+        /// File holding the recovery secret key in WIF or hex
+        #[arg(long)]
+        key_file: String,
+    },
+    // Assemble the recovery signatures into the final witness, optionally broadcasting the sweep
+    Finalize {
+        /// Path of the sweep PSBT
+        psbt: String,
+        /// Broadcast the sweep to the bitcoin node
+        #[arg(long)]
+        broadcast: bool,
+    },
 }
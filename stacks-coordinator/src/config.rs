@@ -1,3 +1,4 @@
+use bitcoin::{hashes::Hash, XOnlyPublicKey};
 use blockstack_lib::{
     address::AddressHashMode,
     burnchains::Address,
@@ -5,9 +6,18 @@ use blockstack_lib::{
     types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey},
     vm::ContractName,
 };
+use frost_signer::{
+    config_loader::{self, ConfigOverrides},
+    signing_round::SignatureType,
+    wsts_version::WstsVersion,
+};
+use std::{path::PathBuf, str::FromStr};
 use url::Url;
+use zeroize::Zeroizing;
 
-use crate::util::address_version;
+use crate::psbt::PsbtReview;
+use crate::recovery::{Recovery, Timelock};
+use crate::util::{address_version, chain_id};
 
 /// Default polling interval in seconds
 const DEFAULT_POLLING_INTERVAL: u64 = 5;
@@ -25,41 +35,97 @@ pub enum Error {
     InvalidContract(String),
     #[error("Failed to parse stacks_private_key: {0}")]
     InvalidPrivateKey(String),
+    #[error("Config Loader Error: {0}")]
+    LoaderError(#[from] config_loader::Error),
 }
 
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum Network {
-    Mainnet,
-    Testnet,
+pub use sbtc_core::Network;
+
+fn stacks_version(network: Network) -> TransactionVersion {
+    match network {
+        Network::Mainnet => TransactionVersion::Mainnet,
+        _ => TransactionVersion::Testnet,
+    }
 }
 
 #[derive(serde::Deserialize, Default)]
 pub struct RawConfig {
     pub sbtc_contract: String,
-    pub stacks_private_key: String,
+    pub stacks_private_key: Zeroizing<String>,
     pub stacks_node_rpc_url: String,
     pub bitcoin_node_rpc_url: String,
     pub frost_dkg_round_id: u64,
     pub signer_config_path: Option<String>,
     pub start_block_height: Option<u64>,
     pub data_directory: Option<String>,
-    /// The network version we are using ('mainnet' or 'testnet'). Default: 'mainnet'
+    /// The network we are using ('mainnet', 'testnet', 'signet', 'regtest' or 'devnet'). Default: 'mainnet'
     pub network: Option<Network>,
+    /// Custom network parameters. Only valid if the network is 'devnet'
+    pub devnet: Option<RawDevnet>,
     /// The transaction fee in Satoshis used to broadcast transactions to the stacks node
     pub transaction_fee: u64,
     /// Frost specific config options. Must be specified if signer_config_path is not used
     pub http_relay_url: Option<String>,
     pub frost_state_file: Option<String>,
-    pub network_private_key: Option<String>,
+    pub network_private_key: Option<Zeroizing<String>>,
     /// Controls how many seconds to wait between polls
     pub polling_interval: Option<u64>,
+    /// Directory to write unsigned peg out fulfillment PSBTs to for manual review
+    pub psbt_outbox_directory: Option<String>,
+    /// Peg outs of at least this many sats require review. Default: 0 (review all peg outs)
+    pub psbt_review_threshold: Option<u64>,
+    /// Address to receive stacks node events on, e.g. "127.0.0.1:30445". Polling is used to fill gaps
+    pub event_observer_address: Option<String>,
+    /// Loopback address to serve the admin routes on, e.g. "127.0.0.1:30446". Unset disables them
+    pub admin_address: Option<String>,
+    /// Hex encoded merkle root of the peg wallet's taproot script tree. If set, the wallet pays to the
+    /// BIP341 tweaked aggregate public key. An empty string commits to no scripts. If unset, the wallet
+    /// pays to the untweaked aggregate public key and can not be spent through script paths.
+    pub wallet_merkle_root: Option<String>,
+    /// Timelocked recovery leaf of the peg wallet's taproot script tree
+    pub recovery: Option<RawRecovery>,
+    /// Number of nonces each signer publishes in advance, so that signing a peg out fulfillment
+    /// takes a single round trip to the signers. Default: 0 (request nonces for every signature)
+    pub nonce_pool_size: Option<u32>,
+    /// The wsts protocol version ('v1' or 'v2') of the signers, if their config is read from the
+    /// sbtc contract. Default: 'v1'
+    pub wsts_version: Option<WstsVersion>,
+}
+
+/// A recovery key set which can spend the peg wallet once a timelock has passed.
+/// Exactly one of `csv` and `cltv` must be set.
+#[derive(serde::Deserialize, Default)]
+pub struct RawRecovery {
+    /// Hex encoded x-only public keys of the recovery key set
+    pub keys: Vec<String>,
+    /// The number of recovery keys required to spend. Default: all keys
+    pub threshold: Option<usize>,
+    /// The number of blocks a wallet utxo must be confirmed for before it can be recovered
+    pub csv: Option<u16>,
+    /// The block height from which the wallet can be recovered
+    pub cltv: Option<u32>,
+}
+
+/// Network parameters of a devnet which differ from those of regtest
+#[derive(serde::Deserialize, Default)]
+pub struct RawDevnet {
+    /// The two magic bytes prefixing sBTC op return data. Default: 'id'
+    pub magic_bytes: Option<String>,
+    /// The chain id of stacks transactions. Default: the testnet chain id
+    pub chain_id: Option<u32>,
 }
 
 impl RawConfig {
     pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
-        let config: RawConfig = toml::from_str(&std::fs::read_to_string(path)?)?;
-        Ok(config)
+        Ok(config_loader::deserialize(config_loader::read(path)?)?)
+    }
+
+    /// Load the config file with SBTC_* environment and command line overrides
+    pub fn load(
+        path: impl AsRef<std::path::Path>,
+        overrides: &ConfigOverrides,
+    ) -> Result<Self, Error> {
+        Ok(config_loader::load(Some(path), overrides)?)
     }
 
     pub fn parse_contract(&self) -> Result<(ContractName, StacksAddress), Error> {
@@ -100,9 +166,85 @@ impl RawConfig {
 
     pub fn parse_version(&self) -> (TransactionVersion, bitcoin::Network) {
         // Determine what network we are running on
-        match self.network.as_ref().unwrap_or(&Network::Mainnet) {
-            Network::Mainnet => (TransactionVersion::Mainnet, bitcoin::Network::Bitcoin),
-            Network::Testnet => (TransactionVersion::Testnet, bitcoin::Network::Testnet),
+        let network = self.network.unwrap_or(Network::Mainnet);
+        (stacks_version(network), network.bitcoin_network())
+    }
+
+    /// Parse the magic bytes and stacks chain id, which may only be customized on a devnet
+    pub fn parse_network_params(&self) -> Result<([u8; 2], u32), Error> {
+        let (stacks_version, _) = self.parse_version();
+        let magic_bytes = self.network.unwrap_or(Network::Mainnet).magic_bytes();
+        let chain_id = chain_id(&stacks_version);
+        let Some(devnet) = &self.devnet else {
+            return Ok((magic_bytes, chain_id));
+        };
+        if self.network != Some(Network::Devnet) {
+            return Err(Error::InvalidConfig(
+                "devnet parameters require network 'devnet'.".to_string(),
+            ));
+        }
+        let magic_bytes = match devnet.magic_bytes.as_deref() {
+            Some(custom) => <[u8; 2]>::try_from(custom.as_bytes()).map_err(|_| {
+                Error::InvalidConfig("devnet.magic_bytes must be two characters.".to_string())
+            })?,
+            None => magic_bytes,
+        };
+        Ok((magic_bytes, devnet.chain_id.unwrap_or(chain_id)))
+    }
+
+    pub fn parse_recovery(&self) -> Result<Option<Recovery>, Error> {
+        let Some(recovery) = &self.recovery else {
+            return Ok(None);
+        };
+        let keys = recovery
+            .keys
+            .iter()
+            .map(|key| {
+                XOnlyPublicKey::from_str(key).map_err(|e| {
+                    Error::InvalidConfig(format!("Invalid recovery key {}: {}", key, e))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let timelock = match (recovery.csv, recovery.cltv) {
+            (Some(blocks), None) => Timelock::Relative(blocks),
+            (None, Some(height)) => Timelock::Absolute(height),
+            _ => {
+                return Err(Error::InvalidConfig(
+                    "Must specify exactly one of recovery.csv and recovery.cltv.".to_string(),
+                ))
+            }
+        };
+        let threshold = recovery.threshold.unwrap_or(keys.len());
+        Recovery::new(keys, threshold, timelock)
+            .map(Some)
+            .map_err(|e| Error::InvalidConfig(e.to_string()))
+    }
+
+    pub fn parse_wallet_signature_type(&self) -> Result<SignatureType, Error> {
+        if let Some(recovery) = self.parse_recovery()? {
+            if self.wallet_merkle_root.is_some() {
+                return Err(Error::InvalidConfig(
+                    "wallet_merkle_root can not be combined with a recovery leaf.".to_string(),
+                ));
+            }
+            return Ok(SignatureType::Taproot(Some(
+                recovery.merkle_root().into_inner(),
+            )));
+        }
+        match self.wallet_merkle_root.as_deref() {
+            None => Ok(SignatureType::Frost),
+            Some("") => Ok(SignatureType::Taproot(None)),
+            Some(merkle_root) => {
+                let merkle_root = hex::decode(merkle_root.trim_start_matches("0x"))
+                    .ok()
+                    .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                    .ok_or_else(|| {
+                        Error::InvalidConfig(
+                            "wallet_merkle_root must be 32 hex encoded bytes.".to_string(),
+                        )
+                    })?;
+                Ok(SignatureType::Taproot(Some(merkle_root)))
+            }
         }
     }
 }
@@ -120,13 +262,31 @@ pub struct Config {
     pub data_directory: Option<String>,
     pub bitcoin_network: bitcoin::Network,
     pub stacks_version: TransactionVersion,
+    /// The magic bytes prefixing sBTC op return data
+    pub magic_bytes: [u8; 2],
+    /// The chain id of stacks transactions
+    pub chain_id: u32,
     /// The transaction fee in Satoshis used to broadcast transactions to the stacks node
     pub transaction_fee: u64,
     /// Frost specific config options. Must be specified if signer_config_path is not used
     pub http_relay_url: Option<String>,
-    pub network_private_key: Option<String>,
+    pub network_private_key: Option<Zeroizing<String>>,
     /// Controls how many seconds to wait between polls
     pub polling_interval: u64,
+    /// Manual review of peg out fulfillments. Disabled if no outbox directory is configured
+    pub psbt_review: Option<PsbtReview>,
+    /// Address to receive stacks node events on
+    pub event_observer_address: Option<String>,
+    /// Loopback address to serve the admin routes on
+    pub admin_address: Option<String>,
+    /// Signatures spending the peg wallet, which determine its address
+    pub wallet_signature_type: SignatureType,
+    /// Timelocked recovery leaf of the peg wallet
+    pub recovery: Option<Recovery>,
+    /// Number of nonces each signer publishes in advance. Zero disables nonce pools
+    pub nonce_pool_size: u32,
+    /// The wsts protocol version of the signers, if their config is read from the sbtc contract
+    pub wsts_version: WstsVersion,
 }
 
 impl TryFrom<RawConfig> for Config {
@@ -153,7 +313,10 @@ impl TryFrom<RawConfig> for Config {
         }
         let (contract_name, contract_address) = config.parse_contract()?;
         let (stacks_version, bitcoin_network) = config.parse_version();
+        let (magic_bytes, chain_id) = config.parse_network_params()?;
         let (stacks_private_key, stacks_address) = config.parse_stacks_private_key()?;
+        let wallet_signature_type = config.parse_wallet_signature_type()?;
+        let recovery = config.parse_recovery()?;
 
         Ok(Self {
             contract_name,
@@ -171,19 +334,39 @@ impl TryFrom<RawConfig> for Config {
             data_directory: config.data_directory,
             bitcoin_network,
             stacks_version,
+            magic_bytes,
+            chain_id,
             transaction_fee: config.transaction_fee,
             http_relay_url: config.http_relay_url,
             network_private_key: config.network_private_key,
             polling_interval: config.polling_interval.unwrap_or(DEFAULT_POLLING_INTERVAL),
+            psbt_review: config
+                .psbt_outbox_directory
+                .map(|outbox_directory| PsbtReview {
+                    outbox_directory: PathBuf::from(outbox_directory),
+                    threshold: config.psbt_review_threshold.unwrap_or(0),
+                }),
+            event_observer_address: config.event_observer_address,
+            admin_address: config.admin_address,
+            wallet_signature_type,
+            recovery,
+            nonce_pool_size: config.nonce_pool_size.unwrap_or(0),
+            wsts_version: config.wsts_version.unwrap_or_default(),
         })
     }
 }
 
 impl Config {
     pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
-        let raw_config: RawConfig = toml::from_str(&std::fs::read_to_string(path)?)?;
-        let config = Config::try_from(raw_config)?;
-        Ok(config)
+        Config::try_from(RawConfig::from_path(path)?)
+    }
+
+    /// Load the config file with SBTC_* environment and command line overrides
+    pub fn load(
+        path: impl AsRef<std::path::Path>,
+        overrides: &ConfigOverrides,
+    ) -> Result<Self, Error> {
+        Config::try_from(RawConfig::load(path, overrides)?)
     }
 }
 
@@ -193,7 +376,10 @@ mod tests {
 
     use super::*;
     use bitcoin::Network as BitcoinNetwork;
-    use blockstack_lib::chainstate::stacks::TransactionVersion;
+    use blockstack_lib::{
+        chainstate::stacks::TransactionVersion,
+        core::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET},
+    };
     use std::io::Write;
     use tempdir::TempDir;
 
@@ -237,6 +423,58 @@ transaction_fee = 2000
         Config::from_path(file_path)
     }
 
+    #[test]
+    fn config_load_applies_overrides() {
+        let dir = TempDir::new("").unwrap();
+        let file_path = dir.path().join("coordinator.toml");
+        std::fs::write(
+            &file_path,
+            format!(
+                r#"
+sbtc_contract = "ST3N4AJFZZYC4BK99H53XP8KDGXFGQ2PRSPNET8TN.sbtc-alpha"
+stacks_private_key = "{PRIVATE_KEY_HEX}"
+stacks_node_rpc_url = "http://localhost:20443"
+bitcoin_node_rpc_url = "http://localhost:18445"
+frost_dkg_round_id = 0
+transaction_fee = 2000
+signer_config_path = "signer.toml"
+polling_interval = 5
+"#
+            ),
+        )
+        .unwrap();
+        let overrides = ConfigOverrides {
+            set: vec![
+                "network=testnet".to_string(),
+                "polling_interval=9".to_string(),
+                "recovery.keys=[]".to_string(),
+            ],
+        };
+
+        let raw_config = RawConfig::load(&file_path, &overrides).unwrap();
+        assert!(raw_config.recovery.is_some());
+
+        let config = Config::load(
+            &file_path,
+            &ConfigOverrides {
+                set: overrides.set[..2].to_vec(),
+            },
+        )
+        .unwrap();
+        assert_eq!(config.bitcoin_network, BitcoinNetwork::Testnet);
+        assert_eq!(config.polling_interval, 9);
+        assert_eq!(config.transaction_fee, 2000);
+
+        // Overrides are type checked against the config
+        let overrides = ConfigOverrides {
+            set: vec!["polling_interval=often".to_string()],
+        };
+        assert!(matches!(
+            Config::load(&file_path, &overrides),
+            Err(Error::LoaderError(_))
+        ));
+    }
+
     #[test]
     fn config_from_raw() {
         let sbtc_contract_address = "ST3N4AJFZZYC4BK99H53XP8KDGXFGQ2PRSPNET8TN";
@@ -385,7 +623,7 @@ transaction_fee = 2000
         ));
 
         // An invalid key shoudl fail
-        config.stacks_private_key = "This is an invalid private key...".to_string();
+        config.stacks_private_key = "This is an invalid private key...".to_string().into();
         assert!(matches!(
             config.parse_stacks_private_key(),
             Err(Error::InvalidPrivateKey(_))
@@ -393,10 +631,12 @@ transaction_fee = 2000
 
         // A valid key should succeed
         config.stacks_private_key =
-            "d655b2523bcd65e34889725c73064feb17ceb796831c0e111ba1a552b0f31b3901".to_string();
+            "d655b2523bcd65e34889725c73064feb17ceb796831c0e111ba1a552b0f31b3901"
+                .to_string()
+                .into();
         assert_eq!(
             config.parse_stacks_private_key().unwrap().0.to_hex(),
-            config.stacks_private_key
+            *config.stacks_private_key
         );
     }
 
@@ -419,7 +659,63 @@ transaction_fee = 2000
         let (stacks_version, bitcoin_network) = config.parse_version();
         assert_eq!(stacks_version, TransactionVersion::Mainnet);
         assert_eq!(bitcoin_network, BitcoinNetwork::Bitcoin);
+
+        // Test networks use the stacks testnet
+        for (network, expected_bitcoin_network) in [
+            (Network::Signet, BitcoinNetwork::Signet),
+            (Network::Regtest, BitcoinNetwork::Regtest),
+            (Network::Devnet, BitcoinNetwork::Regtest),
+        ] {
+            config.network = Some(network);
+            let (stacks_version, bitcoin_network) = config.parse_version();
+            assert_eq!(stacks_version, TransactionVersion::Testnet);
+            assert_eq!(bitcoin_network, expected_bitcoin_network);
+        }
     }
+
+    #[test]
+    fn parse_network_params_test() {
+        let mut config = RawConfig::default();
+        assert_eq!(
+            config.parse_network_params().unwrap(),
+            (*b"X2", CHAIN_ID_MAINNET)
+        );
+
+        config.network = Some(Network::Signet);
+        assert_eq!(
+            config.parse_network_params().unwrap(),
+            (*b"T2", CHAIN_ID_TESTNET)
+        );
+
+        config.network = Some(Network::Regtest);
+        assert_eq!(
+            config.parse_network_params().unwrap(),
+            (*b"id", CHAIN_ID_TESTNET)
+        );
+
+        // Only a devnet can be customized
+        config.devnet = Some(RawDevnet {
+            magic_bytes: Some("dv".to_string()),
+            chain_id: Some(0x80000042),
+        });
+        assert!(config.parse_network_params().is_err());
+
+        config.network = Some(Network::Devnet);
+        assert_eq!(config.parse_network_params().unwrap(), (*b"dv", 0x80000042));
+
+        config.devnet = Some(RawDevnet {
+            magic_bytes: Some("dev".to_string()),
+            chain_id: None,
+        });
+        assert!(config.parse_network_params().is_err());
+
+        config.devnet = Some(RawDevnet::default());
+        assert_eq!(
+            config.parse_network_params().unwrap(),
+            (*b"id", CHAIN_ID_TESTNET)
+        );
+    }
+
     #[test]
     fn parse_contract_test() {
         let mut config = RawConfig::default();
@@ -452,4 +748,68 @@ transaction_fee = 2000
             Err(Error::InvalidContract(_))
         ));
     }
+
+    #[test]
+    fn parse_wallet_signature_type_test() {
+        let mut config = RawConfig::default();
+        // Defaults to the untweaked aggregate key
+        assert_eq!(
+            config.parse_wallet_signature_type().unwrap(),
+            SignatureType::Frost
+        );
+
+        config.wallet_merkle_root = Some(String::new());
+        assert_eq!(
+            config.parse_wallet_signature_type().unwrap(),
+            SignatureType::Taproot(None)
+        );
+
+        config.wallet_merkle_root = Some("07".repeat(32));
+        assert_eq!(
+            config.parse_wallet_signature_type().unwrap(),
+            SignatureType::Taproot(Some([7; 32]))
+        );
+
+        // Must be exactly 32 bytes
+        config.wallet_merkle_root = Some("07".repeat(31));
+        assert!(matches!(
+            config.parse_wallet_signature_type(),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn parse_recovery_test() {
+        let key = "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115";
+        let mut config = RawConfig {
+            recovery: Some(RawRecovery {
+                keys: vec![key.to_string()],
+                csv: Some(144),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let recovery = config.parse_recovery().unwrap().unwrap();
+        assert_eq!(recovery.threshold(), 1);
+        assert_eq!(recovery.timelock(), Timelock::Relative(144));
+        // The wallet commits to the recovery leaf
+        assert_eq!(
+            config.parse_wallet_signature_type().unwrap(),
+            SignatureType::Taproot(Some(recovery.merkle_root().into_inner()))
+        );
+
+        config.wallet_merkle_root = Some("07".repeat(32));
+        assert!(matches!(
+            config.parse_wallet_signature_type(),
+            Err(Error::InvalidConfig(_))
+        ));
+
+        // Exactly one timelock must be given
+        let raw_recovery = config.recovery.as_mut().unwrap();
+        raw_recovery.cltv = Some(800_000);
+        assert!(matches!(
+            config.parse_recovery(),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
 }
@@ -0,0 +1,172 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    time::Instant,
+};
+
+use tracing::{info, warn};
+
+use crate::event_observer::Event;
+
+/// Commands which control the coordinator loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Finish the op in flight, then shut down
+    Stop,
+    /// No command arrived before the next poll was due
+    Timeout,
+    /// Keep polling for ops but stop processing them
+    Pause,
+    /// Resume processing ops
+    Resume,
+    /// The stacks node pushed an event which may contain new ops
+    Event(Event),
+}
+
+/// Exit status of the coordinator, telling a supervisor whether a restart is safe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// Stopped on request with no op in flight
+    Stopped = 0,
+    /// An op was interrupted and may be partially complete. It must be reconciled,
+    /// e.g. with `verify-journal`, before the coordinator is restarted.
+    RestartUnsafe = 1,
+    /// Failed while no op was in flight, e.g. because a node was unreachable. Restarting is safe.
+    RestartSafe = 75,
+}
+
+impl ExitStatus {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    stopping: AtomicBool,
+    paused: AtomicBool,
+}
+
+/// Handle used by signal handlers and the admin endpoint to control the coordinator loop
+#[derive(Debug, Clone)]
+pub struct Controller {
+    state: Arc<State>,
+    sender: Sender<Command>,
+}
+
+impl Controller {
+    pub fn send(&self, command: Command) {
+        match command {
+            Command::Stop => self.state.stopping.store(true, Ordering::SeqCst),
+            Command::Pause => self.state.paused.store(true, Ordering::SeqCst),
+            Command::Resume => self.state.paused.store(false, Ordering::SeqCst),
+            Command::Timeout | Command::Event(_) => {}
+        }
+        // The flags above hold the state, the command only wakes up the coordinator loop
+        let _ = self.sender.send(command);
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        self.state.stopping.load(Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::SeqCst)
+    }
+
+    /// Stop on SIGINT, SIGTERM and SIGHUP. A second signal exits immediately, without
+    /// waiting for the op in flight.
+    pub fn handle_signals(&self) -> Result<(), ctrlc::Error> {
+        let controller = self.clone();
+        ctrlc::set_handler(move || {
+            if controller.is_stopping() {
+                warn!("Received a second shutdown signal. Exiting immediately.");
+                std::process::exit(ExitStatus::RestartUnsafe.code());
+            }
+            info!("Received shutdown signal. Stopping after the op in flight...");
+            controller.send(Command::Stop);
+        })
+    }
+}
+
+/// The receiving end of the control channel, owned by the coordinator
+#[derive(Debug)]
+pub struct Control {
+    controller: Controller,
+    receiver: Receiver<Command>,
+}
+
+impl Default for Control {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            controller: Controller {
+                state: Arc::default(),
+                sender,
+            },
+            receiver,
+        }
+    }
+}
+
+impl Control {
+    pub fn controller(&self) -> &Controller {
+        &self.controller
+    }
+
+    /// Wait until the next command arrives or the deadline passes
+    pub fn next_command(&self, deadline: Instant) -> Command {
+        if self.controller.is_stopping() {
+            return Command::Stop;
+        }
+        match self
+            .receiver
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            Ok(command) => command,
+            Err(RecvTimeoutError::Timeout) => Command::Timeout,
+            // Unreachable while we hold a controller, but there is nothing left to wait for
+            Err(RecvTimeoutError::Disconnected) => Command::Stop,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn commands_update_state_and_wake_the_loop() {
+        let control = Control::default();
+        let controller = control.controller().clone();
+
+        controller.send(Command::Pause);
+        assert!(controller.is_paused());
+        assert_eq!(control.next_command(Instant::now()), Command::Pause);
+
+        controller.send(Command::Resume);
+        assert!(!controller.is_paused());
+        assert_eq!(control.next_command(Instant::now()), Command::Resume);
+
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert_eq!(control.next_command(deadline), Command::Timeout);
+    }
+
+    #[test]
+    fn stop_takes_precedence_over_queued_commands() {
+        let control = Control::default();
+        let controller = control.controller().clone();
+
+        controller.send(Command::Event(Event::NewBurnBlock {
+            burn_block_height: 1,
+        }));
+        controller.send(Command::Stop);
+        assert!(controller.is_stopping());
+        assert_eq!(control.next_command(Instant::now()), Command::Stop);
+    }
+}
@@ -1,32 +1,42 @@
 use bitcoin::{
-    psbt::Prevouts,
+    hashes::Hash,
+    psbt::{PartiallySignedTransaction, Prevouts},
     util::{
         base58,
         sighash::{Error as SighashError, SighashCache},
     },
-    SchnorrSighashType, XOnlyPublicKey,
+    SchnorrSighashType, TxOut, XOnlyPublicKey,
 };
 use blockstack_lib::{types::chainstate::StacksAddress, util::secp256k1::Secp256k1PublicKey};
 use frost_coordinator::{
-    coordinator::Error as FrostCoordinatorError, create_coordinator, create_coordinator_from_path,
+    coordinator::{Error as FrostCoordinatorError, MessageToSign},
+    create_coordinator, create_coordinator_from_path,
+    dkg_report::DkgReport,
 };
 use frost_signer::{
     config::Config as SignerConfig,
     net::{Error as HttpNetError, HttpNetListen},
     signing_round::DkgPublicShare,
+    validation::TransactionContext,
 };
 use std::{
     collections::BTreeMap,
-    fs::File,
+    fs::{self, File},
     path::{Path, PathBuf},
     sync::mpsc::RecvError,
-    thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tracing::{debug, info, warn};
 use wsts::{common::Signature, field::Element, taproot::SchnorrProof, Point, Scalar};
 
+use crate::admin::{AdminEndpoint, Error as AdminError};
 use crate::bitcoin_wallet::BitcoinWallet;
+pub use crate::control::Command;
+use crate::control::{Control, Controller, ExitStatus};
+use crate::event_observer::{Error as EventObserverError, EventObserver};
+use crate::journal::{Journal, JournalError, PegContext, SIGNING_JOURNAL_FILE};
+use crate::operator::PEG_QUEUE_FILE;
+use crate::psbt::{self, Error as PsbtError, PsbtReview};
 use crate::stacks_node::{self, Error as StacksNodeError};
 use crate::stacks_wallet::StacksWallet;
 use crate::{config::Config, stacks_node::client::BroadcastError};
@@ -96,6 +106,27 @@ pub enum Error {
     MaxNonceRetriesExceeded,
     #[error("Point error: {0}")]
     PointError(String),
+    #[error("Journal Error: {0}")]
+    JournalError(#[from] JournalError),
+    #[error("PSBT Error: {0}")]
+    PsbtError(#[from] PsbtError),
+    #[error("Event Observer Error: {0}")]
+    EventObserverError(#[from] EventObserverError),
+    #[error("Admin Error: {0}")]
+    AdminError(#[from] AdminError),
+    /// Error occurred while an op was in flight, which may have been partially processed
+    #[error("Failed to process op {0}: {1}")]
+    OpFailed(String, Box<Error>),
+}
+
+impl Error {
+    /// Whether the coordinator can be restarted without reconciling an interrupted op first
+    pub fn exit_status(&self) -> ExitStatus {
+        match self {
+            Self::OpFailed(..) => ExitStatus::RestartUnsafe,
+            _ => ExitStatus::RestartSafe,
+        }
+    }
 }
 
 pub trait Coordinator: Sized {
@@ -113,43 +144,118 @@ pub trait Coordinator: Sized {
     fn stacks_node(&self) -> &Self::StacksNode;
     fn stacks_node_mut(&mut self) -> &mut Self::StacksNode;
     fn bitcoin_node(&self) -> &Self::BitcoinNode;
+    fn psbt_review(&self) -> Option<&PsbtReview>;
+    fn control(&self) -> &Control;
 
     // Provided methods
+    /// Poll for and process ops until a stop command is received. Processing can be paused
+    /// and resumed through the control channel without stopping polling.
     fn run(mut self, polling_interval: u64) -> Result<()> {
+        let polling_interval = Duration::from_secs(polling_interval);
         loop {
             info!("Polling for withdrawal and deposit requests to process...");
             self.peg_queue().poll(self.stacks_node())?;
             self.process_queue()?;
-
-            sleep(Duration::from_secs(polling_interval));
+            self.process_approved_psbts()?;
+
+            let next_poll = Instant::now() + polling_interval;
+            loop {
+                match self.control().next_command(next_poll) {
+                    Command::Timeout => break,
+                    Command::Stop => {
+                        info!("Coordinator stopped with no op in flight");
+                        return Ok(());
+                    }
+                    Command::Pause => info!("Paused processing of peg ops"),
+                    Command::Resume => {
+                        info!("Resumed processing of peg ops");
+                        self.process_queue()?;
+                        self.process_approved_psbts()?;
+                    }
+                    Command::Event(event) => {
+                        debug!("Processing stacks node event {:?}", event);
+                        self.peg_queue()
+                            .ingest(self.stacks_node(), event.burn_block_height())?;
+                        self.process_queue()?;
+                    }
+                }
+            }
         }
     }
 
     fn process_queue(&mut self) -> Result<()> {
-        loop {
-            match self.peg_queue().sbtc_op()? {
+        while !self.holding_ops() {
+            let result = match self.peg_queue().sbtc_op()? {
                 Some(SbtcOp::PegIn(op)) => {
                     debug!("Processing peg in request: {:?}", op);
-                    self.peg_in(op)?;
+                    let txid = op.txid.to_string();
+                    self.peg_in(op)
+                        .map_err(|e| Error::OpFailed(txid, Box::new(e)))
                 }
                 Some(SbtcOp::PegOutRequest(op)) => {
                     debug!("Processing peg out request: {:?}", op);
-                    self.peg_out(op)?;
+                    let txid = op.txid.to_string();
+                    self.peg_out(op)
+                        .map_err(|e| Error::OpFailed(txid, Box::new(e)))
                 }
                 None => return Ok(()),
+            };
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Sign, broadcast and acknowledge the peg outs whose fulfillment PSBT has been approved
+    fn process_approved_psbts(&mut self) -> Result<()> {
+        let Some(review) = self.psbt_review().cloned() else {
+            return Ok(());
+        };
+        for path in review.approved_psbts()? {
+            if self.holding_ops() {
+                return Ok(());
             }
+            let (psbt, op, sighashes) = match self.read_approved_psbt(&path) {
+                Ok(approved) => approved,
+                Err(e) => {
+                    // Set the PSBT aside so that it is not retried on every poll
+                    warn!("Rejecting approved PSBT {}: {}", path.display(), e);
+                    fs::rename(&path, path.with_extension("rejected")).map_err(PsbtError::from)?;
+                    continue;
+                }
+            };
+            info!("Processing approved PSBT {}", path.display());
+            let txid = op.txid.to_string();
+            self.fulfill_reviewed_peg_out(&review, psbt, op, sighashes)
+                .map_err(|e| Error::OpFailed(txid, Box::new(e)))?;
+            fs::remove_file(&path).map_err(PsbtError::from)?;
         }
+        Ok(())
     }
 }
 
 // Private helper functions
 trait CoordinatorHelpers: Coordinator {
+    /// True if no further ops should be started, because processing is paused or stopping
+    fn holding_ops(&self) -> bool {
+        let controller = self.control().controller();
+        controller.is_paused() || controller.is_stopping()
+    }
+
     fn peg_in(&mut self, op: stacks_node::PegInOp) -> Result<()> {
         // Build a transaction from the peg in op and broadcast it to the node with reattempts
-        self.try_broadcast_transaction(&op)
+        self.try_broadcast_transaction(&op)?;
+        self.peg_queue()
+            .acknowledge(&op.txid, &op.burn_header_hash)?;
+        Ok(())
     }
 
     fn peg_out(&mut self, op: stacks_node::PegOutRequestOp) -> Result<()> {
+        if let Some(review) = self.psbt_review().cloned() {
+            if review.requires_review(&op) {
+                return self.export_for_review(&review, op);
+            }
+        }
+
         // First build both the sBTC and BTC transactions before attempting to broadcast either of them
         // This ensures that if either of the transactions fail to build, neither of them will be broadcast
 
@@ -165,6 +271,8 @@ trait CoordinatorHelpers: Coordinator {
             "Broadcasted fulfilled BTC transaction: {}",
             fulfill_tx.txid()
         );
+        self.peg_queue()
+            .acknowledge(&op.txid, &op.burn_header_hash)?;
         Ok(())
     }
 
@@ -178,19 +286,22 @@ trait CoordinatorHelpers: Coordinator {
         let (mut tx, prevouts) = self.fee_wallet().bitcoin().fulfill_peg_out(op, utxos)?;
         let sighash_tx = tx.clone();
         let mut sighash_cache = SighashCache::new(&sighash_tx);
-        // Sign the transaction
-        for index in 0..tx.input.len() {
-            let taproot_sighash = sighash_cache
-                .taproot_key_spend_signature_hash(
-                    index,
-                    &Prevouts::All(&prevouts),
-                    SchnorrSighashType::Default,
-                )
-                .map_err(Error::SigningError)?;
-            let (_frost_sig, schnorr_proof) = self
-                .frost_coordinator_mut()
-                .sign_message(&taproot_sighash.as_hash())?;
-
+        let sighashes = (0..tx.input.len())
+            .map(|index| {
+                sighash_cache
+                    .taproot_key_spend_signature_hash(
+                        index,
+                        &Prevouts::All(&prevouts),
+                        SchnorrSighashType::Default,
+                    )
+                    .map(|sighash| sighash.into_inner())
+                    .map_err(Error::SigningError)
+            })
+            .collect::<Result<Vec<[u8; 32]>>>()?;
+        // Sign all inputs of the transaction in one signing round
+        let schnorr_proofs =
+            self.sign_fulfillment_inputs(op, &sighash_tx, &prevouts, &sighashes)?;
+        for (index, schnorr_proof) in schnorr_proofs.into_iter().enumerate() {
             debug!(
                 "Fulfill Tx {:?} SchnorrProof ({},{})",
                 &tx, schnorr_proof.r, schnorr_proof.s
@@ -206,6 +317,149 @@ trait CoordinatorHelpers: Coordinator {
         Ok(tx)
     }
 
+    /// Sign the taproot key spend sighashes of all inputs of the fulfillment transaction
+    fn sign_fulfillment_inputs(
+        &mut self,
+        op: &stacks_node::PegOutRequestOp,
+        tx: &BitcoinTransaction,
+        prevouts: &[TxOut],
+        sighashes: &[[u8; 32]],
+    ) -> Result<Vec<SchnorrProof>> {
+        // The witness is not part of the txid, so it is already final
+        let txid = tx.txid();
+        let messages: Vec<MessageToSign> = sighashes
+            .iter()
+            .enumerate()
+            .map(|(index, sighash)| MessageToSign {
+                message: sighash.to_vec(),
+                context: Some(PegContext {
+                    peg_op: format!("peg-out-request:{}", op.txid),
+                    txid: txid.to_string(),
+                    input_index: index.try_into().unwrap(),
+                }),
+                transaction: Some(TransactionContext::new(
+                    tx,
+                    prevouts,
+                    index.try_into().unwrap(),
+                    op.txid.to_string(),
+                    op.block_height,
+                )),
+            })
+            .collect();
+        let signature_type = self.fee_wallet().bitcoin().signature_type();
+        let signatures = self
+            .frost_coordinator_mut()
+            .sign_batch(&messages, signature_type)?;
+        Ok(signatures
+            .into_iter()
+            .map(|(_frost_sig, schnorr_proof)| schnorr_proof)
+            .collect())
+    }
+
+    /// Write the unsigned fulfillment transaction to the outbox and park the op until the PSBT is approved
+    fn export_for_review(
+        &mut self,
+        review: &PsbtReview,
+        op: stacks_node::PegOutRequestOp,
+    ) -> Result<()> {
+        let utxos = self
+            .bitcoin_node()
+            .list_unspent(self.fee_wallet().bitcoin().address())?;
+        let (tx, prevouts) = self.fee_wallet().bitcoin().fulfill_peg_out(&op, utxos)?;
+        let txid = tx.txid();
+
+        let psbt = psbt::build_review_psbt(tx, prevouts, &SbtcOp::PegOutRequest(op.clone()))?;
+        let path = review.outbox_path(&txid);
+        psbt::write_psbt(&path, &psbt)?;
+        self.peg_queue()
+            .await_review(&op.txid, &op.burn_header_hash, &txid.to_string())?;
+        info!(
+            "Peg out request {} awaiting review of fulfillment PSBT {}",
+            op.txid,
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// Read an approved PSBT, making sure it is exactly the transaction which was exported for review
+    fn read_approved_psbt(
+        &self,
+        path: &Path,
+    ) -> Result<(
+        PartiallySignedTransaction,
+        stacks_node::PegOutRequestOp,
+        Vec<[u8; 32]>,
+    )> {
+        let psbt = psbt::read_psbt(path)?;
+        let txid = psbt.unsigned_tx.txid();
+        // Trust neither the peg op nor the witness utxos of the approved file
+        let Some(SbtcOp::PegOutRequest(op)) =
+            self.peg_queue().op_under_review(&txid.to_string())?
+        else {
+            return Err(PsbtError::UnexpectedTransaction(txid).into());
+        };
+        let prevouts = self.wallet_prevouts(&psbt.unsigned_tx)?;
+        let sighashes = psbt::reviewed_sighashes(&psbt, &prevouts)?;
+        Ok((psbt, op, sighashes))
+    }
+
+    /// The outputs spent by the inputs of the transaction, as known to the wallet of the bitcoin node
+    fn wallet_prevouts(&self, tx: &BitcoinTransaction) -> Result<Vec<TxOut>> {
+        tx.input
+            .iter()
+            .enumerate()
+            .map(|(index, input)| {
+                let outpoint = input.previous_output;
+                self.bitcoin_node()
+                    .get_transaction(&outpoint.txid)?
+                    .and_then(|prev_tx| prev_tx.output.get(outpoint.vout as usize).cloned())
+                    .ok_or_else(|| PsbtError::UnknownPrevout(index).into())
+            })
+            .collect()
+    }
+
+    fn fulfill_reviewed_peg_out(
+        &mut self,
+        review: &PsbtReview,
+        mut psbt: PartiallySignedTransaction,
+        op: stacks_node::PegOutRequestOp,
+        sighashes: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let txid = psbt.unsigned_tx.txid();
+        // The BTC transaction goes out first and is recorded, so that a retry after a failed
+        // broadcast neither sends the sBTC transaction without it nor signs it again
+        let broadcast_path = review.broadcast_path(&txid);
+        if broadcast_path.exists() {
+            info!(
+                "Reviewed fulfilled BTC transaction {} was broadcast before",
+                txid
+            );
+        } else {
+            let prevouts = psbt::prevouts(&psbt)?;
+            let unsigned_tx = psbt.unsigned_tx.clone();
+            let schnorr_proofs =
+                self.sign_fulfillment_inputs(&op, &unsigned_tx, &prevouts, &sighashes)?;
+            for (index, schnorr_proof) in schnorr_proofs.into_iter().enumerate() {
+                psbt::finalize_input(&mut psbt, index, &schnorr_proof.to_bytes())?;
+            }
+            // Keep the finalized PSBT so that the witness can be inspected by external tools
+            psbt::write_psbt(review.signed_path(&txid), &psbt)?;
+
+            let fulfill_tx = psbt.extract_tx();
+            self.bitcoin_node().broadcast_transaction(&fulfill_tx)?;
+            info!(
+                "Broadcasted reviewed fulfilled BTC transaction: {}",
+                fulfill_tx.txid()
+            );
+            fs::write(&broadcast_path, txid.to_string()).map_err(PsbtError::from)?;
+        }
+
+        self.try_broadcast_transaction(&op)?;
+        self.peg_queue()
+            .acknowledge(&op.txid, &op.burn_header_hash)?;
+        Ok(())
+    }
+
     /// Broadcast a transaction to the stacks node, retrying if the nonce is rejected or the fee set too low until a retry limit is reached
     fn try_broadcast_transaction<T: BuildStacksTransaction>(&mut self, op: &T) -> Result<()> {
         // Retrieve the nonce from the stacks node using the sBTC wallet address
@@ -255,17 +509,18 @@ trait CoordinatorHelpers: Coordinator {
 
 impl<T: Coordinator> CoordinatorHelpers for T {}
 
-pub enum Command {
-    Stop,
-    Timeout,
-}
-
 pub struct StacksCoordinator {
     frost_coordinator: FrostCoordinator,
     local_peg_queue: SqlitePegQueue,
     local_stacks_node: NodeClient,
     local_bitcoin_node: LocalhostBitcoinNode,
     pub local_fee_wallet: WrapPegWallet,
+    psbt_review: Option<PsbtReview>,
+    control: Control,
+    // Kept alive so that the observer keeps forwarding events to the control channel
+    _event_observer: Option<EventObserver>,
+    // Kept alive so that admin commands keep reaching the control channel
+    _admin: Option<AdminEndpoint>,
 }
 
 impl StacksCoordinator {
@@ -275,8 +530,13 @@ impl StacksCoordinator {
             .map_err(|e| Error::InvalidPublicKey(e.to_string()))
     }
 
-    pub fn sign_message(&mut self, message: &str) -> Result<(Signature, SchnorrProof)> {
-        Ok(self.frost_coordinator.sign_message(message.as_bytes())?)
+    /// Handle used to stop, pause and resume the coordinator loop
+    pub fn controller(&self) -> Controller {
+        self.control.controller().clone()
+    }
+
+    pub fn sign_message(&mut self, message: &[u8]) -> Result<(Signature, SchnorrProof)> {
+        Ok(self.frost_coordinator.sign_message(message)?)
     }
 }
 
@@ -335,21 +595,22 @@ fn create_frost_coordinator_from_contract(
     let network_private_key = Scalar::try_from(
         config
             .network_private_key
-            .clone()
-            .unwrap_or(String::new())
-            .as_bytes(),
+            .as_ref()
+            .map(|key| key.as_bytes())
+            .unwrap_or_default(),
     )
     .map_err(|_| Error::ConfigError("Invalid network_private_key.".to_string()))?;
     let http_relay_url = config.http_relay_url.clone().unwrap_or(String::new());
-    create_coordinator(&SignerConfig::new(
+    let mut signer_config = SignerConfig::new(
         keys_threshold.try_into().unwrap(),
         coordinator_public_key,
         public_keys,
         signer_key_ids,
-        network_private_key,
+        network_private_key.into(),
         http_relay_url,
-    ))
-    .map_err(|e| Error::ConfigError(e.to_string()))
+    );
+    signer_config.wsts_version = config.wsts_version;
+    create_coordinator(&signer_config).map_err(|e| Error::ConfigError(e.to_string()))
 }
 
 fn create_frost_coordinator(
@@ -367,7 +628,9 @@ fn create_frost_coordinator(
     }
 }
 
-fn read_dkg_public_shares(path: impl AsRef<Path>) -> Result<BTreeMap<u32, DkgPublicShare>> {
+pub(crate) fn read_dkg_public_shares(
+    path: impl AsRef<Path>,
+) -> Result<BTreeMap<u32, DkgPublicShare>> {
     let dkg_public_shares_path = path.as_ref().join("dkg_public_shares.json");
 
     serde_json::from_reader(File::open(&dkg_public_shares_path).map_err(|err| {
@@ -409,6 +672,51 @@ fn write_dkg_public_shares(
     Ok(())
 }
 
+/// Read the report of the DKG run which produced the aggregate public key, if one was written
+fn read_dkg_report(path: impl AsRef<Path>) -> Result<Option<DkgReport>> {
+    let dkg_report_path = path.as_ref().join("dkg_report.json");
+    if !dkg_report_path.exists() {
+        return Ok(None);
+    }
+    let file = File::open(&dkg_report_path).map_err(|err| {
+        Error::ConfigError(format!(
+            "Unable to open DKG report file {}: {}",
+            dkg_report_path.to_str().unwrap_or("Invalid path"),
+            err
+        ))
+    })?;
+    serde_json::from_reader(file)
+        .map(Some)
+        .map_err(|err| Error::ConfigError(format!("Unable to parse DKG report JSON: {}", err)))
+}
+
+fn write_dkg_report(path: impl AsRef<Path>, dkg_report: &DkgReport) -> Result<()> {
+    let dkg_report_path = path.as_ref().join("dkg_report.json");
+
+    let dkg_report_file = File::options()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&dkg_report_path)
+        .map_err(|err| {
+            Error::ConfigError(format!(
+                "Unable to open DKG report file {}: {}",
+                dkg_report_path.to_str().unwrap_or("Invalid path"),
+                err
+            ))
+        })?;
+
+    serde_json::to_writer_pretty(dkg_report_file, dkg_report).map_err(|err| {
+        Error::ConfigError(format!(
+            "Unable to write DKG report to file {}: {}",
+            dkg_report_path.to_str().unwrap_or("Invalid path"),
+            err
+        ))
+    })?;
+
+    Ok(())
+}
+
 fn load_dkg_data(
     data_directory: Option<&str>,
     frost_coordinator: &mut FrostCoordinator,
@@ -420,6 +728,10 @@ fn load_dkg_data(
     if let Some(xonly_pubkey) = stacks_node.bitcoin_wallet_public_key(address)? {
         if let Some(data_directory) = data_directory {
             frost_coordinator.set_dkg_public_shares(read_dkg_public_shares(data_directory)?);
+            // Signers excluded from the DKG run hold no valid shares of the key
+            if let Some(dkg_report) = read_dkg_report(data_directory)? {
+                frost_coordinator.set_excluded_signers(dkg_report.excluded_signers);
+            }
         }
         // We have to set the frost_coordinator aggregate key
         frost_coordinator.set_aggregate_public_key(
@@ -433,6 +745,9 @@ fn load_dkg_data(
 
         if let Some(data_directory) = data_directory {
             write_dkg_public_shares(data_directory, frost_coordinator.get_dkg_public_shares())?;
+            if let Some(dkg_report) = frost_coordinator.get_dkg_report() {
+                write_dkg_report(data_directory, dkg_report)?;
+            }
         }
 
         let xonly_pubkey = XOnlyPublicKey::from_slice(&point.x().to_bytes())
@@ -447,6 +762,29 @@ fn load_dkg_data(
     }
 }
 
+/// Ensure both nodes run on the configured network, so that we do not pay to or sign for the wrong chain
+fn check_node_networks(
+    config: &Config,
+    stacks_node: &NodeClient,
+    bitcoin_node: &LocalhostBitcoinNode,
+) -> Result<()> {
+    let bitcoin_network = bitcoin_node.network()?;
+    if bitcoin_network != config.bitcoin_network {
+        return Err(Error::ConfigError(format!(
+            "Bitcoin node is running on {}, but the configured network is {}.",
+            bitcoin_network, config.bitcoin_network
+        )));
+    }
+    let chain_id = stacks_node.network_id()?;
+    if chain_id != config.chain_id {
+        return Err(Error::ConfigError(format!(
+            "Stacks node has chain id {:#x}, but the configured chain id is {:#x}.",
+            chain_id, config.chain_id
+        )));
+    }
+    Ok(())
+}
+
 impl TryFrom<&Config> for StacksCoordinator {
     type Error = Error;
     fn try_from(config: &Config) -> Result<Self> {
@@ -456,6 +794,8 @@ impl TryFrom<&Config> for StacksCoordinator {
             config.contract_name.clone(),
             config.contract_address,
         );
+        let local_bitcoin_node = LocalhostBitcoinNode::new(config.bitcoin_node_rpc_url.clone());
+        check_node_networks(config, &local_stacks_node, &local_bitcoin_node)?;
 
         let stacks_wallet = StacksWallet::new(
             config.contract_name.clone(),
@@ -464,7 +804,8 @@ impl TryFrom<&Config> for StacksCoordinator {
             config.stacks_address,
             config.stacks_version,
             config.transaction_fee,
-        );
+        )
+        .with_chain_id(config.chain_id);
 
         let mut frost_coordinator =
             create_frost_coordinator(config, &mut local_stacks_node, &stacks_wallet)?;
@@ -477,17 +818,39 @@ impl TryFrom<&Config> for StacksCoordinator {
             &stacks_wallet,
             &config.stacks_address,
         )?;
-        let bitcoin_wallet = BitcoinWallet::new(xonly_pubkey, config.bitcoin_network);
+        let bitcoin_wallet = BitcoinWallet::from_signature_type(
+            xonly_pubkey,
+            config.wallet_signature_type,
+            config.bitcoin_network,
+        )
+        .with_magic_bytes(config.magic_bytes);
+
+        if let Some(data_directory) = &config.data_directory {
+            let journal_path = PathBuf::from(data_directory).join(SIGNING_JOURNAL_FILE);
+            frost_coordinator.set_journal(Journal::open(journal_path)?);
+        }
+        frost_coordinator.set_nonce_pool_size(config.nonce_pool_size);
 
         // Load the bitcoin wallet
-        let local_bitcoin_node = LocalhostBitcoinNode::new(config.bitcoin_node_rpc_url.clone());
         local_bitcoin_node.load_wallet(bitcoin_wallet.address())?;
 
         // If a user has not specified a start block height, begin from the current burn block height by default
         let start_block_height = config.start_block_height;
         let current_block_height = local_stacks_node.burn_block_height()?;
+        let control = Control::default();
+        let event_observer = config
+            .event_observer_address
+            .as_deref()
+            .map(|address| EventObserver::spawn(address, control.controller().clone()))
+            .transpose()?;
+        let admin = config
+            .admin_address
+            .as_deref()
+            .map(|address| AdminEndpoint::spawn(address, control.controller().clone()))
+            .transpose()?;
+
         let local_peg_queue = if let Some(path) = &config.data_directory {
-            let db_path = PathBuf::from(path).join("peg_queue.sqlite");
+            let db_path = PathBuf::from(path).join(PEG_QUEUE_FILE);
             SqlitePegQueue::new(db_path, start_block_height, current_block_height)
         } else {
             SqlitePegQueue::in_memory(start_block_height, current_block_height)
@@ -502,6 +865,10 @@ impl TryFrom<&Config> for StacksCoordinator {
                 bitcoin_wallet,
                 stacks_wallet,
             },
+            psbt_review: config.psbt_review.clone(),
+            control,
+            _event_observer: event_observer,
+            _admin: admin,
         })
     }
 }
@@ -543,6 +910,14 @@ impl Coordinator for StacksCoordinator {
     fn bitcoin_node(&self) -> &Self::BitcoinNode {
         &self.local_bitcoin_node
     }
+
+    fn psbt_review(&self) -> Option<&PsbtReview> {
+        self.psbt_review.as_ref()
+    }
+
+    fn control(&self) -> &Control {
+        &self.control
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,206 @@
+use std::{
+    io::{Error as IoError, Write},
+    net::{SocketAddr, TcpListener},
+    thread,
+};
+
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+use yarpc::http::{IoStream, Message, Method, Request, Response};
+
+use crate::control::{Command, Controller};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    IOError(#[from] IoError),
+}
+
+/// Events pushed by the stacks node which can contain new sBTC ops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    NewBurnBlock { burn_block_height: u64 },
+    NewBlock { burn_block_height: u64 },
+}
+
+impl Event {
+    /// The burn block height whose ops should be ingested
+    pub fn burn_block_height(&self) -> u64 {
+        match self {
+            Self::NewBurnBlock { burn_block_height } | Self::NewBlock { burn_block_height } => {
+                *burn_block_height
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BlockPayload {
+    burn_block_height: u64,
+}
+
+/// HTTP endpoint which the stacks node can be configured to push events to. Events are received
+/// on a background thread and forwarded to the coordinator loop over its control channel.
+pub struct EventObserver {
+    address: SocketAddr,
+}
+
+impl EventObserver {
+    pub fn spawn(address: &str, controller: Controller) -> Result<Self, Error> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        thread::spawn(move || listen(listener, controller));
+        info!("Listening for stacks node events on {}", address);
+        Ok(Self { address })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+fn listen(listener: TcpListener, controller: Controller) {
+    for stream in listener.incoming() {
+        if let Err(e) = stream.and_then(|mut stream| handle(&mut stream, &controller)) {
+            warn!("Failed to handle stacks node event: {}", e);
+        }
+    }
+}
+
+fn handle(io: &mut impl IoStream, controller: &Controller) -> Result<(), IoError> {
+    let request = Request::read(io.istream())?;
+    if let Some(event) = parse_event(&request) {
+        debug!("Received stacks node event {:?}", event);
+        controller.send(Command::Event(event));
+    }
+    // Always acknowledge the event, otherwise the stacks node keeps retrying it
+    let response = Response::new(200, "OK".to_string(), Default::default(), Vec::default());
+    let ostream = io.ostream();
+    response.write(ostream)?;
+    ostream.flush()?;
+    Ok(())
+}
+
+/// Parse the events relevant to the peg queue. All other events are ignored.
+pub fn parse_event(request: &Request) -> Option<Event> {
+    if request.method != Method::POST {
+        return None;
+    }
+    let path = request.url.split('?').next().unwrap_or_default();
+    let payload = || match serde_json::from_slice::<BlockPayload>(&request.content) {
+        Ok(payload) => Some(payload),
+        Err(e) => {
+            warn!("Malformed {} event: {}", path, e);
+            None
+        }
+    };
+    match path {
+        "/new_burn_block" => payload().map(|p| Event::NewBurnBlock {
+            burn_block_height: p.burn_block_height,
+        }),
+        "/new_block" => payload().map(|p| Event::NewBlock {
+            burn_block_height: p.burn_block_height,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::TcpStream,
+        str::from_utf8,
+        time::{Duration, Instant},
+    };
+
+    use yarpc::http::{Call, MemIoStreamEx};
+
+    use super::*;
+    use crate::control::Control;
+
+    fn post(url: &str, content: &str) -> Request {
+        Request::new(
+            Method::POST,
+            url.to_string(),
+            Default::default(),
+            content.as_bytes().to_vec(),
+        )
+    }
+
+    #[test]
+    fn should_parse_block_events() {
+        let burn_block = post(
+            "/new_burn_block",
+            r#"{"burn_block_hash":"0x00","burn_block_height":2425540,"reward_recipients":[]}"#,
+        );
+        assert_eq!(
+            parse_event(&burn_block),
+            Some(Event::NewBurnBlock {
+                burn_block_height: 2425540
+            })
+        );
+
+        let block = post(
+            "/new_block",
+            r#"{"block_height":10,"burn_block_height":2425541,"transactions":[]}"#,
+        );
+        assert_eq!(
+            parse_event(&block),
+            Some(Event::NewBlock {
+                burn_block_height: 2425541
+            })
+        );
+    }
+
+    #[test]
+    fn should_ignore_other_events() {
+        assert_eq!(parse_event(&post("/new_mempool_tx", "[]")), None);
+        assert_eq!(parse_event(&post("/new_burn_block", "{}")), None);
+        let get = Request::new(
+            Method::GET,
+            "/new_burn_block".to_string(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(parse_event(&get), None);
+    }
+
+    #[test]
+    fn should_acknowledge_every_event() {
+        let control = Control::default();
+        let request = "POST /drop_mempool_tx HTTP/1.1\r\nContent-Length: 2\r\n\r\n[]";
+        let mut output = Vec::default();
+        let mut stream = request.as_bytes().mem_io_stream(&mut output);
+        handle(&mut stream, control.controller()).unwrap();
+        assert!(from_utf8(&output).unwrap().starts_with("HTTP/1.1 200 OK"));
+        assert_eq!(control.next_command(Instant::now()), Command::Timeout);
+    }
+
+    #[test]
+    fn should_not_serve_admin_commands() {
+        let control = Control::default();
+        let request = "POST /admin/stop HTTP/1.1\r\nContent-Length: 0\r\n\r\n";
+        let mut output = Vec::default();
+        let mut stream = request.as_bytes().mem_io_stream(&mut output);
+        handle(&mut stream, control.controller()).unwrap();
+        assert!(!control.controller().is_stopping());
+        assert_eq!(control.next_command(Instant::now()), Command::Timeout);
+    }
+
+    #[test]
+    fn should_receive_events_over_http() {
+        let control = Control::default();
+        let observer = EventObserver::spawn("127.0.0.1:0", control.controller().clone()).unwrap();
+        let mut stream = TcpStream::connect(observer.local_addr()).unwrap();
+        let response = stream
+            .call(post("/new_burn_block", r#"{"burn_block_height":7}"#))
+            .unwrap();
+        assert_eq!(response.code, 200);
+        assert_eq!(
+            control.next_command(Instant::now() + Duration::from_secs(5)),
+            Command::Event(Event::NewBurnBlock {
+                burn_block_height: 7
+            })
+        );
+    }
+}
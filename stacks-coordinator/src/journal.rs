@@ -0,0 +1,318 @@
+use std::{collections::HashSet, path::Path, str::FromStr};
+
+use bitcoin::{Script, Transaction, TxIn, Txid};
+pub use frost_signer::journal::{Error as JournalError, Journal, JournalEntry, PegContext};
+use tracing::{debug, warn};
+
+use crate::bitcoin_node::{BitcoinNode, Error as BitcoinNodeError};
+
+/// Name of the signing journal file within the data directory
+pub const SIGNING_JOURNAL_FILE: &str = "signing_journal.jsonl";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Journal Error: {0}")]
+    JournalError(#[from] JournalError),
+    #[error("Bitcoin Node Error: {0}")]
+    BitcoinNodeError(#[from] BitcoinNodeError),
+    #[error("Journal entry {0} has an invalid txid")]
+    InvalidTxid(u64),
+}
+
+/// The result of checking a signing journal against the bitcoin node
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct JournalReport {
+    /// Number of entries in the journal
+    pub entries: usize,
+    /// Journal entries whose signature was found in a wallet transaction
+    pub confirmed: usize,
+    /// Journal entries whose transaction the wallet has never seen
+    pub unbroadcast: Vec<u64>,
+    /// Journal entries whose signature does not match the witness of the broadcast transaction
+    pub mismatched: Vec<u64>,
+    /// Key path spends of the peg wallet whose signature does not appear in the journal
+    pub unjournaled: Vec<(Txid, usize)>,
+}
+
+impl JournalReport {
+    /// Unbroadcast entries are expected when a peg out failed after signing and do not
+    /// make the journal inconsistent.
+    pub fn is_consistent(&self) -> bool {
+        self.mismatched.is_empty() && self.unjournaled.is_empty()
+    }
+}
+
+/// Verify the hash chain of the journal at the given path, then cross-check every
+/// journaled signature and every key path spend of the peg wallet against each other
+pub fn verify_journal(
+    path: impl AsRef<Path>,
+    bitcoin_node: &impl BitcoinNode,
+    peg_script: &Script,
+) -> Result<JournalReport, Error> {
+    let entries = Journal::read(path)?;
+    Journal::verify(&entries)?;
+    cross_check(&entries, bitcoin_node, peg_script)
+}
+
+fn cross_check(
+    entries: &[JournalEntry],
+    bitcoin_node: &impl BitcoinNode,
+    peg_script: &Script,
+) -> Result<JournalReport, Error> {
+    let mut report = JournalReport {
+        entries: entries.len(),
+        ..Default::default()
+    };
+    let mut journaled_proofs = HashSet::new();
+
+    for entry in entries {
+        let (Some(context), Some(proof)) = (&entry.context, &entry.proof) else {
+            continue;
+        };
+        journaled_proofs.insert(proof.clone());
+        let txid = Txid::from_str(&context.txid).map_err(|_| Error::InvalidTxid(entry.sequence))?;
+        match bitcoin_node.get_transaction(&txid)? {
+            None => {
+                debug!("Journal entry {} was never broadcast", entry.sequence);
+                report.unbroadcast.push(entry.sequence);
+            }
+            Some(tx) => {
+                let witness = tx
+                    .input
+                    .get(context.input_index as usize)
+                    .and_then(|input| input.witness.to_vec().first().map(hex::encode));
+                if witness.as_ref() == Some(proof) {
+                    report.confirmed += 1;
+                } else {
+                    warn!(
+                        "Journal entry {} does not match input {} of transaction {}",
+                        entry.sequence, context.input_index, txid
+                    );
+                    report.mismatched.push(entry.sequence);
+                }
+            }
+        }
+    }
+
+    for txid in bitcoin_node.list_sent_transactions()? {
+        let Some(tx) = bitcoin_node.get_transaction(&txid)? else {
+            continue;
+        };
+        for (index, input) in tx.input.iter().enumerate() {
+            // Only key path spends are signed by the signers. Script path spends, e.g. recovery
+            // sweeps, and spends of other wallet outputs are not journaled.
+            if !is_key_path_spend(input) || !spends_script(bitcoin_node, input, peg_script)? {
+                continue;
+            }
+            let signature = input.witness.to_vec().first().map(hex::encode);
+            if !signature.map_or(false, |s| journaled_proofs.contains(&s)) {
+                warn!("Input {} of transaction {} was not journaled", index, txid);
+                report.unjournaled.push((txid, index));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// A taproot key path spend carries a single schnorr signature, with a sighash type byte unless
+/// it signs for all of the transaction
+fn is_key_path_spend(input: &TxIn) -> bool {
+    let witness = input.witness.to_vec();
+    witness.len() == 1 && matches!(witness[0].len(), 64 | 65)
+}
+
+/// Whether the input spends an output locked by the script. Outputs of transactions unknown to
+/// the wallet do not pay to it.
+fn spends_script(
+    bitcoin_node: &impl BitcoinNode,
+    input: &TxIn,
+    script: &Script,
+) -> Result<bool, Error> {
+    let outpoint = input.previous_output;
+    let prevout = bitcoin_node
+        .get_transaction(&outpoint.txid)?
+        .and_then(|tx: Transaction| tx.output.get(outpoint.vout as usize).cloned());
+    Ok(prevout.map_or(false, |output| &output.script_pubkey == script))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bitcoin::{OutPoint, PackedLockTime, Script, Transaction, TxIn, TxOut, Witness};
+
+    use super::*;
+    use crate::bitcoin_node::{BitcoinTransaction, UTXO};
+
+    #[derive(Default)]
+    struct WalletNode {
+        transactions: HashMap<Txid, BitcoinTransaction>,
+    }
+
+    impl BitcoinNode for WalletNode {
+        fn broadcast_transaction(&self, tx: &BitcoinTransaction) -> Result<Txid, BitcoinNodeError> {
+            Ok(tx.txid())
+        }
+
+        fn load_wallet(&self, _address: &bitcoin::Address) -> Result<(), BitcoinNodeError> {
+            Ok(())
+        }
+
+        fn list_unspent(&self, _address: &bitcoin::Address) -> Result<Vec<UTXO>, BitcoinNodeError> {
+            Ok(vec![])
+        }
+
+        fn get_transaction(
+            &self,
+            txid: &Txid,
+        ) -> Result<Option<BitcoinTransaction>, BitcoinNodeError> {
+            Ok(self.transactions.get(txid).cloned())
+        }
+
+        fn list_sent_transactions(&self) -> Result<Vec<Txid>, BitcoinNodeError> {
+            Ok(self.transactions.keys().cloned().collect())
+        }
+    }
+
+    fn peg_script() -> Script {
+        Script::new_witness_program(bitcoin::util::address::WitnessVersion::V1, &[2; 32])
+    }
+
+    /// A transaction paying one output of the script per witness
+    fn funding_transaction(script: &Script, count: usize) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime(0),
+            input: vec![],
+            output: (0..count)
+                .map(|_| TxOut {
+                    value: 1000,
+                    script_pubkey: script.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// A transaction spending the outputs of the funding transaction with the witnesses
+    fn spending_transaction(funding: &Transaction, witnesses: Vec<Vec<Vec<u8>>>) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime(0),
+            input: witnesses
+                .into_iter()
+                .enumerate()
+                .map(|(vout, witness)| TxIn {
+                    previous_output: OutPoint::new(funding.txid(), vout as u32),
+                    script_sig: Script::new(),
+                    sequence: bitcoin::Sequence(0xFFFFFFFD),
+                    witness: Witness::from_vec(witness),
+                })
+                .collect(),
+            output: vec![],
+        }
+    }
+
+    /// A key path spend of peg wallet outputs, and the node knowing both transactions
+    fn signed_transaction(signatures: &[[u8; 64]]) -> (Transaction, WalletNode) {
+        let funding = funding_transaction(&peg_script(), signatures.len());
+        let tx = spending_transaction(
+            &funding,
+            signatures
+                .iter()
+                .map(|signature| vec![signature.to_vec()])
+                .collect(),
+        );
+        let node = WalletNode {
+            transactions: HashMap::from([(funding.txid(), funding), (tx.txid(), tx.clone())]),
+        };
+        (tx, node)
+    }
+
+    fn journal_entries(tx: &Transaction, signatures: &[[u8; 64]]) -> Vec<JournalEntry> {
+        let path =
+            std::env::temp_dir().join(format!("verify_journal_{}.jsonl", rand::random::<u64>()));
+        let mut journal = Journal::open(&path).unwrap();
+        for (index, signature) in signatures.iter().enumerate() {
+            let context = PegContext {
+                peg_op: "peg-out-request:00".to_string(),
+                txid: tx.txid().to_string(),
+                input_index: index as u32,
+            };
+            journal
+                .append(
+                    &[index as u8; 32],
+                    Some(context),
+                    vec![1, 2],
+                    Some(signature),
+                )
+                .unwrap();
+        }
+        let entries = Journal::read(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        entries
+    }
+
+    #[test]
+    fn journal_matching_broadcast_transactions_is_consistent() {
+        let signatures = [[1; 64], [2; 64]];
+        let (tx, node) = signed_transaction(&signatures);
+        let entries = journal_entries(&tx, &signatures);
+
+        let report = cross_check(&entries, &node, &peg_script()).unwrap();
+        assert_eq!(report.confirmed, 2);
+        assert!(report.is_consistent());
+    }
+
+    #[test]
+    fn unbroadcast_entries_are_reported() {
+        let signatures = [[1; 64]];
+        let (tx, _) = signed_transaction(&signatures);
+        let entries = journal_entries(&tx, &signatures);
+
+        let report = cross_check(&entries, &WalletNode::default(), &peg_script()).unwrap();
+        assert_eq!(report.unbroadcast, vec![0]);
+        assert!(report.is_consistent());
+    }
+
+    #[test]
+    fn unjournaled_and_mismatched_signatures_are_reported() {
+        let (tx, node) = signed_transaction(&[[1; 64], [2; 64]]);
+        // Only the first input was journaled, and with a different signature
+        let entries = journal_entries(&tx, &[[3; 64]]);
+        let txid = tx.txid();
+
+        let report = cross_check(&entries, &node, &peg_script()).unwrap();
+        assert_eq!(report.mismatched, vec![0]);
+        assert_eq!(report.unjournaled, vec![(txid, 0), (txid, 1)]);
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn script_path_and_other_wallet_spends_are_not_journaled() {
+        // A recovery sweep spends the peg wallet through its script path
+        let peg_funding = funding_transaction(&peg_script(), 1);
+        let sweep = spending_transaction(
+            &peg_funding,
+            vec![vec![vec![1; 64], vec![0x51], vec![0xc0; 33]]],
+        );
+        // A key path spend of another wallet output
+        let other_funding = funding_transaction(
+            &Script::new_witness_program(bitcoin::util::address::WitnessVersion::V1, &[3; 32]),
+            1,
+        );
+        let other = spending_transaction(&other_funding, vec![vec![vec![2; 64]]]);
+        let node = WalletNode {
+            transactions: HashMap::from([
+                (peg_funding.txid(), peg_funding),
+                (sweep.txid(), sweep),
+                (other_funding.txid(), other_funding),
+                (other.txid(), other),
+            ]),
+        };
+
+        let report = cross_check(&[], &node, &peg_script()).unwrap();
+        assert!(report.unjournaled.is_empty());
+        assert!(report.is_consistent());
+    }
+}
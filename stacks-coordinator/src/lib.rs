@@ -1,10 +1,17 @@
+pub mod admin;
 pub mod bitcoin_node;
 pub mod bitcoin_wallet;
 pub mod cli;
 pub mod config;
+pub mod control;
 pub mod coordinator;
+pub mod event_observer;
+pub mod journal;
+pub mod operator;
 pub mod peg_queue;
 pub mod peg_wallet;
+pub mod psbt;
+pub mod recovery;
 pub mod stacks_node;
 pub mod stacks_wallet;
 mod util;
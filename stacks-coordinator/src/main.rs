@@ -1,33 +1,344 @@
 use clap::Parser;
+use frost_signer::config_loader::ConfigOverrides;
+use frost_signer::keystore::{self, KeystoreCommand};
 use frost_signer::logging;
-use stacks_coordinator::cli::{Cli, Command};
+use serde::Serialize;
+use serde_json::json;
+use stacks_coordinator::bitcoin_node::LocalhostBitcoinNode;
+use stacks_coordinator::cli::{
+    Cli, Command, ConfigCommand, QueueCommand, RecoveryCommand, WalletCommand,
+};
 use stacks_coordinator::config::Config;
+use stacks_coordinator::control::ExitStatus;
 use stacks_coordinator::coordinator::{Coordinator, StacksCoordinator};
+use stacks_coordinator::journal::{self, SIGNING_JOURNAL_FILE};
+use stacks_coordinator::operator::{self, Error as OperatorError};
+use stacks_coordinator::peg_queue::Entry;
+use std::path::PathBuf;
 use tracing::{error, info, warn};
 
+/// Print the value as JSON, or in human readable form using the given formatter
+fn print_output<T: Serialize>(json: bool, value: &T, text: impl FnOnce(&T)) {
+    if json {
+        match serde_json::to_string_pretty(value) {
+            Ok(output) => println!("{}", output),
+            Err(e) => error!("Failed to serialize output: {}", e),
+        }
+    } else {
+        text(value);
+    }
+}
+
+fn print_entries(entries: &[Entry]) {
+    for entry in entries {
+        println!(
+            "{} {} {} {}",
+            entry.txid,
+            entry.block_height,
+            entry.op.type_name(),
+            entry.status.as_str()
+        );
+    }
+}
+
+/// Returns true if the journal chain is intact and consistent with the bitcoin wallet
+fn verify_journal(config: &Config, path: Option<&str>, json: bool) -> bool {
+    let path = match (path, &config.data_directory) {
+        (Some(path), _) => PathBuf::from(path),
+        (None, Some(data_directory)) => PathBuf::from(data_directory).join(SIGNING_JOURNAL_FILE),
+        (None, None) => {
+            error!("No journal path given and no data_directory configured.");
+            return false;
+        }
+    };
+    let peg_address = match operator::wallet_address(config) {
+        Ok(address) => address,
+        Err(e) => {
+            error!("Unable to determine the peg wallet address: {}", e);
+            return false;
+        }
+    };
+    let bitcoin_node = LocalhostBitcoinNode::new(config.bitcoin_node_rpc_url.clone());
+    match journal::verify_journal(&path, &bitcoin_node, &peg_address.script_pubkey()) {
+        Ok(report) => {
+            let output = json!({
+                "entries": report.entries,
+                "confirmed": report.confirmed,
+                "unbroadcast": report.unbroadcast,
+                "mismatched": report.mismatched,
+                "unjournaled": report
+                    .unjournaled
+                    .iter()
+                    .map(|(txid, index)| json!({"txid": txid.to_string(), "input": index}))
+                    .collect::<Vec<_>>(),
+                "consistent": report.is_consistent(),
+            });
+            print_output(json, &output, |_| {
+                info!(
+                    "Verified {} journal entries: {} confirmed, {} unbroadcast",
+                    report.entries,
+                    report.confirmed,
+                    report.unbroadcast.len()
+                );
+                for sequence in &report.mismatched {
+                    error!(
+                        "Journal entry {} does not match the broadcast transaction",
+                        sequence
+                    );
+                }
+                for (txid, index) in &report.unjournaled {
+                    error!("Input {} of transaction {} is not journaled", index, txid);
+                }
+            });
+            report.is_consistent()
+        }
+        Err(e) => {
+            error!("Journal {} failed verification: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+fn queue(config: &Config, command: &QueueCommand, json: bool) -> Result<(), OperatorError> {
+    let peg_queue = operator::open_peg_queue(config)?;
+    match command {
+        QueueCommand::List { status, op_type } => {
+            let entries = operator::queue_list(&peg_queue, status.as_deref(), op_type.as_deref())?;
+            print_output(json, &entries, |entries| print_entries(entries));
+        }
+        QueueCommand::Show { txid } => {
+            let entries = operator::queue_show(&peg_queue, &operator::parse_txid(txid)?)?;
+            print_output(json, &entries, |entries| {
+                for entry in entries {
+                    println!("txid: {}", entry.entry.txid);
+                    println!("burn header hash: {}", entry.entry.burn_header_hash);
+                    println!("block height: {}", entry.entry.block_height);
+                    println!("status: {}", entry.entry.status.as_str());
+                    if let Some(fulfillment_txid) = &entry.fulfillment_txid {
+                        println!("fulfillment txid: {}", fulfillment_txid);
+                    }
+                    println!("op: {:#?}", entry.entry.op);
+                }
+            });
+        }
+        QueueCommand::Retry { txid } => {
+            let entries = peg_queue.retry(&operator::parse_txid(txid)?)?;
+            print_output(json, &entries, |entries| print_entries(entries));
+        }
+        QueueCommand::Skip { txid } => {
+            let entries = peg_queue.skip(&operator::parse_txid(txid)?)?;
+            print_output(json, &entries, |entries| print_entries(entries));
+        }
+        QueueCommand::ResetHeight { height } => {
+            peg_queue.reset_block_height(*height)?;
+            let output = json!({ "next_block_height": peg_queue.next_block_height()? });
+            print_output(json, &output, |output| {
+                println!(
+                    "Next poll starts at block height {}",
+                    output["next_block_height"]
+                )
+            });
+        }
+    }
+    Ok(())
+}
+
+fn wallet(config: &Config, command: &WalletCommand, json: bool) -> Result<(), OperatorError> {
+    match command {
+        WalletCommand::Address => {
+            let address = operator::wallet_address(config)?;
+            let output = json!({ "address": address.to_string() });
+            print_output(json, &output, |_| println!("{}", address));
+        }
+        WalletCommand::Utxos => {
+            let utxos = operator::wallet_utxos(config)?;
+            print_output(json, &utxos, |utxos| {
+                for utxo in utxos {
+                    println!(
+                        "{}:{} {} sats, {} confirmations",
+                        utxo.txid, utxo.vout, utxo.amount, utxo.confirmations
+                    );
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+fn recovery(config: &Config, command: &RecoveryCommand, json: bool) -> Result<(), OperatorError> {
+    match command {
+        RecoveryCommand::Show => {
+            let info = operator::recovery_info(config)?;
+            print_output(json, &info, |info| {
+                println!("wallet address: {}", info.address);
+                println!("internal key: {}", info.internal_key);
+                println!("recovery keys: {}", info.keys.join(", "));
+                println!("threshold: {} of {}", info.threshold, info.keys.len());
+                if let Some(csv) = info.csv {
+                    println!("spendable {} blocks after a utxo confirms", csv);
+                }
+                if let Some(cltv) = info.cltv {
+                    println!("spendable from block height {}", cltv);
+                }
+                println!("script: {}", info.script);
+            });
+        }
+        RecoveryCommand::Build {
+            destination,
+            fee,
+            output: path,
+        } => {
+            let tx = operator::recovery_build(config, destination, *fee, path)?;
+            let amount: u64 = tx.output.iter().map(|output| output.value).sum();
+            let output = json!({
+                "path": path,
+                "txid": tx.txid().to_string(),
+                "inputs": tx.input.len(),
+                "amount": amount,
+            });
+            print_output(json, &output, |_| {
+                println!(
+                    "Wrote sweep {} of {} utxos ({} sats) to {}",
+                    tx.txid(),
+                    tx.input.len(),
+                    amount,
+                    path
+                )
+            });
+        }
+        RecoveryCommand::Sign { psbt, key_file } => {
+            let signed = operator::recovery_sign(config, psbt, key_file)?;
+            let output = json!({ "path": psbt, "inputs_signed": signed });
+            print_output(json, &output, |_| {
+                println!("Signed {} inputs of {}", signed, psbt)
+            });
+        }
+        RecoveryCommand::Finalize { psbt, broadcast } => {
+            let (tx, txid) = operator::recovery_finalize(config, psbt, *broadcast)?;
+            let tx_hex = bitcoin::consensus::encode::serialize_hex(&tx);
+            let output = json!({
+                "txid": tx.txid().to_string(),
+                "tx": tx_hex,
+                "broadcast": txid.is_some(),
+            });
+            print_output(json, &output, |_| {
+                println!("{}", tx_hex);
+                if let Some(txid) = txid {
+                    println!("Broadcasted recovery sweep {}", txid);
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if the configuration passed every check
+fn config(path: &str, overrides: &ConfigOverrides, command: &ConfigCommand, json: bool) -> bool {
+    match command {
+        ConfigCommand::Show => match operator::config_show(path, overrides) {
+            Ok(value) => {
+                print_output(json, &value, |value| print!("{}", value));
+                true
+            }
+            Err(e) => {
+                error!("An error occurred reading config file {}: {}", path, e);
+                false
+            }
+        },
+        ConfigCommand::Check => {
+            let checks = operator::config_check(path, overrides);
+            print_output(json, &checks, |checks| {
+                for check in checks {
+                    let status = if check.ok { "ok" } else { "FAILED" };
+                    println!("{} {}: {}", status, check.name, check.message);
+                }
+            });
+            checks.iter().all(|check| check.ok)
+        }
+    }
+}
+
+/// Returns true if the keystore command succeeded
+fn keystore(command: &KeystoreCommand, json: bool) -> bool {
+    match keystore::execute(command) {
+        Ok(public_keys) => {
+            print_output(json, &public_keys, |public_keys| {
+                for (name, public_key) in public_keys {
+                    println!("{}: {}", name, public_key);
+                }
+            });
+            true
+        }
+        Err(e) => {
+            error!("An error occurred running the keystore command: {}", e);
+            false
+        }
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
     logging::initiate_tracing_subscriber();
 
+    if let Command::Config { command } = &cli.command {
+        if !config(&cli.config, &cli.overrides, command, cli.json) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    if let Command::Keystore { command } = &cli.command {
+        if !keystore(command, cli.json) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
     //TODO: get configs from sBTC contract
-    match Config::from_path(&cli.config) {
+    match Config::load(&cli.config, &cli.overrides) {
         Ok(mut config) => {
-            config.signer_config_path = Some(cli.signer_config);
+            if let Some(signer_config) = cli.signer_config {
+                config.signer_config_path = Some(signer_config);
+            }
             if cli.start_block_height == Some(0) {
                 error!("Invalid start block height. Must specify a value greater than 0.",);
                 return;
             }
             config.start_block_height = cli.start_block_height;
+            // These commands work offline and do not need a running coordinator
+            let offline_result = match &cli.command {
+                Command::VerifyJournal { path } => {
+                    if !verify_journal(&config, path.as_deref(), cli.json) {
+                        std::process::exit(1);
+                    }
+                    return;
+                }
+                Command::Queue { command } => Some(queue(&config, command, cli.json)),
+                Command::Wallet { command } => Some(wallet(&config, command, cli.json)),
+                Command::Recovery { command } => Some(recovery(&config, command, cli.json)),
+                _ => None,
+            };
+            if let Some(result) = offline_result {
+                if let Err(e) = result {
+                    error!("{}", e);
+                    std::process::exit(1);
+                }
+                return;
+            }
             match StacksCoordinator::try_from(&config) {
                 Ok(mut coordinator) => {
                     // Determine what action the caller wishes to perform
                     match cli.command {
                         Command::Run => {
                             info!("Running Coordinator");
+                            if let Err(e) = coordinator.controller().handle_signals() {
+                                error!("Failed to install shutdown signal handler: {}", e);
+                                std::process::exit(ExitStatus::RestartSafe.code());
+                            }
                             //TODO: set up coordination with the stacks node
                             if let Err(e) = coordinator.run(config.polling_interval) {
                                 error!("An error occurred running the coordinator: {}", e);
+                                std::process::exit(e.exit_status().code());
                             }
                         }
                         Command::Dkg => {
@@ -43,7 +354,7 @@ fn main() {
                             };
                             info!("Running Signing Round");
                             let (signature, schnorr_proof) =
-                                match coordinator.sign_message("Hello, world!") {
+                                match coordinator.sign_message(b"Hello, world!") {
                                     Ok((sig, proof)) => (sig, proof),
                                     Err(e) => {
                                         panic!("signing message failed: {e}");
@@ -54,6 +365,48 @@ fn main() {
                                 &signature.R, &signature.z, &schnorr_proof.r, &schnorr_proof.s
                             );
                         }
+                        Command::SignMessage { message, hex } => {
+                            let message = if hex {
+                                match hex::decode(message.trim_start_matches("0x")) {
+                                    Ok(message) => message,
+                                    Err(e) => {
+                                        error!("Invalid hex message: {}", e);
+                                        std::process::exit(1);
+                                    }
+                                }
+                            } else {
+                                message.into_bytes()
+                            };
+                            match coordinator.sign_message(&message) {
+                                Ok((signature, schnorr_proof)) => {
+                                    let output = json!({
+                                        "message": hex::encode(&message),
+                                        "signature": {
+                                            "R": signature.R.to_string(),
+                                            "z": signature.z.to_string(),
+                                        },
+                                        "schnorr_proof": hex::encode(schnorr_proof.to_bytes()),
+                                    });
+                                    print_output(cli.json, &output, |_| {
+                                        println!("signature: ({},{})", &signature.R, &signature.z);
+                                        println!(
+                                            "schnorr proof: {}",
+                                            hex::encode(schnorr_proof.to_bytes())
+                                        );
+                                    });
+                                }
+                                Err(e) => {
+                                    error!("An error occurred during signing round: {}", e);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        Command::VerifyJournal { .. }
+                        | Command::Config { .. }
+                        | Command::Keystore { .. }
+                        | Command::Queue { .. }
+                        | Command::Wallet { .. }
+                        | Command::Recovery { .. } => unreachable!(),
                     };
                 }
                 Err(e) => {
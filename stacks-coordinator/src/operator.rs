@@ -0,0 +1,408 @@
+use std::{
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use bitcoin::{
+    secp256k1::SecretKey, Address as BitcoinAddress, PrivateKey, Transaction, Txid as BitcoinTxid,
+    XOnlyPublicKey,
+};
+use blockstack_lib::burnchains::Txid;
+use frost_signer::{
+    config::Config as SignerConfig,
+    config_loader::{self, ConfigCheck, ConfigOverrides},
+};
+use wsts::{ecdsa, Point};
+
+use crate::bitcoin_node::{BitcoinNode, Error as BitcoinNodeError, LocalhostBitcoinNode, UTXO};
+use crate::bitcoin_wallet::BitcoinWallet;
+use crate::config::Config;
+use crate::coordinator::{read_dkg_public_shares, Error as CoordinatorError};
+use crate::peg_queue::{Entry, PegQueue, SqlitePegQueue, SqlitePegQueueError, Status};
+use crate::peg_wallet::BitcoinWallet as BitcoinWalletTrait;
+use crate::psbt::{self, Error as PsbtError};
+use crate::recovery::{Error as RecoveryError, Recovery, Timelock};
+use crate::stacks_node::client::NodeClient;
+
+/// Name of the peg queue database within the data directory
+pub const PEG_QUEUE_FILE: &str = "peg_queue.sqlite";
+
+/// Errors of the operator subcommands, which work offline against the data directory
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("No data_directory configured")]
+    MissingDataDirectory,
+    #[error("Sqlite Peg Queue Error: {0}")]
+    SqlitePegQueueError(#[from] SqlitePegQueueError),
+    #[error("Peg Queue Error: {0}")]
+    PegQueueError(#[from] crate::peg_queue::Error),
+    #[error("Coordinator Error: {0}")]
+    CoordinatorError(#[from] CoordinatorError),
+    #[error("Bitcoin Node Error: {0}")]
+    BitcoinNodeError(#[from] BitcoinNodeError),
+    #[error("Invalid txid: {0}")]
+    InvalidTxid(String),
+    #[error("No DKG public shares found in the data directory")]
+    NoDkgPublicShares,
+    #[error("Invalid bitcoin wallet public key: {0}")]
+    InvalidPublicKey(String),
+    #[error("No recovery leaf configured")]
+    MissingRecovery,
+    #[error("Recovery Error: {0}")]
+    RecoveryError(#[from] RecoveryError),
+    #[error("PSBT Error: {0}")]
+    PsbtError(#[from] PsbtError),
+    #[error("Invalid destination address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid recovery secret key in {0}")]
+    InvalidSecretKey(String),
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+}
+
+/// A peg queue entry together with the fulfillment transaction it awaits review of, if any
+#[derive(Debug, serde::Serialize)]
+pub struct QueueEntry {
+    #[serde(flatten)]
+    pub entry: Entry,
+    pub fulfillment_txid: Option<String>,
+}
+
+pub fn data_directory(config: &Config) -> Result<PathBuf, Error> {
+    config
+        .data_directory
+        .as_ref()
+        .map(PathBuf::from)
+        .ok_or(Error::MissingDataDirectory)
+}
+
+pub fn open_peg_queue(config: &Config) -> Result<SqlitePegQueue, Error> {
+    Ok(SqlitePegQueue::open(
+        data_directory(config)?.join(PEG_QUEUE_FILE),
+    )?)
+}
+
+pub fn parse_txid(txid: &str) -> Result<Txid, Error> {
+    Txid::from_hex(txid.trim_start_matches("0x")).map_err(|_| Error::InvalidTxid(txid.to_string()))
+}
+
+/// Queue entries, optionally filtered by status and op type
+pub fn queue_list(
+    peg_queue: &SqlitePegQueue,
+    status: Option<&str>,
+    op_type: Option<&str>,
+) -> Result<Vec<Entry>, Error> {
+    let status: Option<Status> = status.map(str::parse).transpose()?;
+    Ok(peg_queue
+        .entries()?
+        .into_iter()
+        .filter(|entry| {
+            status
+                .as_ref()
+                .map_or(true, |status| &entry.status == status)
+        })
+        .filter(|entry| op_type.map_or(true, |op_type| entry.op.type_name() == op_type))
+        .collect())
+}
+
+pub fn queue_show(peg_queue: &SqlitePegQueue, txid: &Txid) -> Result<Vec<QueueEntry>, Error> {
+    peg_queue
+        .entries_with_txid(txid)?
+        .into_iter()
+        .map(|entry| -> Result<QueueEntry, Error> {
+            let fulfillment_txid =
+                peg_queue.fulfillment_under_review(&entry.txid, &entry.burn_header_hash)?;
+            Ok(QueueEntry {
+                entry,
+                fulfillment_txid,
+            })
+        })
+        .collect()
+}
+
+/// The peg wallet public key, aggregated from the DKG public shares in the data directory
+pub fn wallet_public_key(config: &Config) -> Result<XOnlyPublicKey, Error> {
+    let dkg_public_shares = read_dkg_public_shares(data_directory(config)?)?;
+    if dkg_public_shares.is_empty() {
+        return Err(Error::NoDkgPublicShares);
+    }
+    let aggregate_public_key = dkg_public_shares
+        .values()
+        .fold(Point::default(), |sum, share| sum + share.public_share.A[0]);
+    XOnlyPublicKey::from_slice(&aggregate_public_key.x().to_bytes())
+        .map_err(|e| Error::InvalidPublicKey(e.to_string()))
+}
+
+pub fn wallet_address(config: &Config) -> Result<BitcoinAddress, Error> {
+    let wallet = BitcoinWallet::from_signature_type(
+        wallet_public_key(config)?,
+        config.wallet_signature_type,
+        config.bitcoin_network,
+    );
+    Ok(wallet.address().clone())
+}
+
+pub fn wallet_utxos(config: &Config) -> Result<Vec<UTXO>, Error> {
+    let address = wallet_address(config)?;
+    let bitcoin_node = LocalhostBitcoinNode::new(config.bitcoin_node_rpc_url.clone());
+    bitcoin_node.load_wallet(&address)?;
+    Ok(bitcoin_node.list_unspent(&address)?)
+}
+
+/// The recovery leaf of the peg wallet
+#[derive(Debug, serde::Serialize)]
+pub struct RecoveryInfo {
+    pub address: String,
+    pub internal_key: String,
+    pub keys: Vec<String>,
+    pub threshold: usize,
+    pub csv: Option<u16>,
+    pub cltv: Option<u32>,
+    pub script: String,
+}
+
+fn recovery(config: &Config) -> Result<&Recovery, Error> {
+    config.recovery.as_ref().ok_or(Error::MissingRecovery)
+}
+
+pub fn recovery_info(config: &Config) -> Result<RecoveryInfo, Error> {
+    let recovery = recovery(config)?;
+    let (csv, cltv) = match recovery.timelock() {
+        Timelock::Relative(blocks) => (Some(blocks), None),
+        Timelock::Absolute(height) => (None, Some(height)),
+    };
+    Ok(RecoveryInfo {
+        address: wallet_address(config)?.to_string(),
+        internal_key: wallet_public_key(config)?.to_string(),
+        keys: recovery.keys().iter().map(ToString::to_string).collect(),
+        threshold: recovery.threshold(),
+        csv,
+        cltv,
+        script: hex::encode(recovery.script().as_bytes()),
+    })
+}
+
+/// Write an unsigned PSBT sweeping all recoverable peg wallet utxos to the destination
+pub fn recovery_build(
+    config: &Config,
+    destination: &str,
+    fee: u64,
+    path: impl AsRef<Path>,
+) -> Result<Transaction, Error> {
+    let recovery = recovery(config)?;
+    let destination = BitcoinAddress::from_str(destination)
+        .ok()
+        .filter(|address| address.is_valid_for_network(config.bitcoin_network))
+        .ok_or_else(|| Error::InvalidAddress(destination.to_string()))?;
+    let psbt = recovery.build_sweep(
+        wallet_public_key(config)?,
+        wallet_utxos(config)?,
+        &destination,
+        fee,
+    )?;
+    psbt::write_psbt(path, &psbt)?;
+    Ok(psbt.unsigned_tx)
+}
+
+/// Add the signatures of the recovery key in `key_file` to the sweep PSBT. Returns the number of inputs signed.
+pub fn recovery_sign(
+    config: &Config,
+    path: impl AsRef<Path>,
+    key_file: impl AsRef<Path>,
+) -> Result<usize, Error> {
+    let secret_key = read_secret_key(key_file)?;
+    let mut psbt = psbt::read_psbt(&path)?;
+    let signed = recovery(config)?.sign_sweep(&mut psbt, &secret_key)?;
+    psbt::write_psbt(path, &psbt)?;
+    Ok(signed)
+}
+
+/// Finalize the sweep PSBT and optionally broadcast the sweep transaction
+pub fn recovery_finalize(
+    config: &Config,
+    path: impl AsRef<Path>,
+    broadcast: bool,
+) -> Result<(Transaction, Option<BitcoinTxid>), Error> {
+    let mut psbt = psbt::read_psbt(&path)?;
+    let tx = recovery(config)?.finalize_sweep(&mut psbt)?;
+    psbt::write_psbt(path, &psbt)?;
+    let txid = if broadcast {
+        let bitcoin_node = LocalhostBitcoinNode::new(config.bitcoin_node_rpc_url.clone());
+        Some(bitcoin_node.broadcast_transaction(&tx)?)
+    } else {
+        None
+    };
+    Ok((tx, txid))
+}
+
+/// The layered config with secrets redacted
+pub fn config_show(
+    path: impl AsRef<Path>,
+    overrides: &ConfigOverrides,
+) -> Result<toml::Value, config_loader::Error> {
+    Ok(config_loader::redact(config_loader::load_value(
+        Some(path),
+        overrides,
+    )?))
+}
+
+/// Validate the layered config and connect to every configured endpoint
+pub fn config_check(path: impl AsRef<Path>, overrides: &ConfigOverrides) -> Vec<ConfigCheck> {
+    let config = match Config::load(path, overrides) {
+        Ok(config) => config,
+        Err(e) => return vec![ConfigCheck::new("config", Err(e.to_string()))],
+    };
+    let mut checks = vec![ConfigCheck::new("config", Ok("valid".to_string()))];
+
+    let mut http_relay_url = config.http_relay_url.clone();
+    if let Some(signer_config_path) = &config.signer_config_path {
+        let signer_config = SignerConfig::from_path(signer_config_path).and_then(|signer_config| {
+            signer_config.validate()?;
+            Ok(signer_config)
+        });
+        match signer_config {
+            Ok(signer_config) => {
+                checks.push(ConfigCheck::new(
+                    "signer_config",
+                    Ok(format!(
+                        "{} signers, {} of {} keys",
+                        signer_config.total_signers,
+                        signer_config.keys_threshold,
+                        signer_config.total_keys
+                    )),
+                ));
+                checks.push(ConfigCheck::new(
+                    "coordinator_public_key",
+                    check_coordinator_key(&signer_config),
+                ));
+                http_relay_url = Some(signer_config.http_relay_url);
+            }
+            Err(e) => checks.push(ConfigCheck::new("signer_config", Err(e.to_string()))),
+        }
+    }
+
+    if let Some(http_relay_url) = http_relay_url {
+        checks.push(ConfigCheck::new(
+            "http_relay_url",
+            config_loader::check_reachable(&http_relay_url),
+        ));
+    }
+
+    let stacks_node = NodeClient::new(
+        config.stacks_node_rpc_url.clone(),
+        config.contract_name.clone(),
+        config.contract_address,
+    );
+    let stacks_node_check = match stacks_node.network_id() {
+        Ok(chain_id) if chain_id == config.chain_id => Ok(format!("chain id {:#x}", chain_id)),
+        Ok(chain_id) => Err(format!(
+            "Stacks node has chain id {:#x}, but the configured chain id is {:#x}",
+            chain_id, config.chain_id
+        )),
+        Err(e) => Err(config_loader::redact_urls(&e.to_string())),
+    };
+    checks.push(ConfigCheck::new("stacks_node_rpc_url", stacks_node_check));
+
+    let bitcoin_node = LocalhostBitcoinNode::new(config.bitcoin_node_rpc_url.clone());
+    let bitcoin_node_check = match bitcoin_node.network() {
+        Ok(network) if network == config.bitcoin_network => Ok(format!("network {}", network)),
+        Ok(network) => Err(format!(
+            "Bitcoin node is running on {}, but the configured network is {}",
+            network, config.bitcoin_network
+        )),
+        Err(e) => Err(config_loader::redact_urls(&e.to_string())),
+    };
+    checks.push(ConfigCheck::new("bitcoin_node_rpc_url", bitcoin_node_check));
+
+    if let Some(data_directory) = &config.data_directory {
+        let path = Path::new(data_directory);
+        let check = if !path.exists() || path.is_dir() {
+            Ok(path.display().to_string())
+        } else {
+            Err(format!("{} is not a directory", path.display()))
+        };
+        checks.push(ConfigCheck::new("data_directory", check));
+    }
+
+    if let Some(address) = &config.event_observer_address {
+        let check = address
+            .parse::<SocketAddr>()
+            .map(|address| address.to_string())
+            .map_err(|e| format!("Invalid address {}: {}", address, e));
+        checks.push(ConfigCheck::new("event_observer_address", check));
+    }
+
+    if let Some(address) = &config.admin_address {
+        let check = match address.parse::<SocketAddr>() {
+            Ok(address) if address.ip().is_loopback() => Ok(address.to_string()),
+            Ok(address) => Err(format!("{} is not a loopback address", address)),
+            Err(e) => Err(format!("Invalid address {}: {}", address, e)),
+        };
+        checks.push(ConfigCheck::new("admin_address", check));
+    }
+    checks
+}
+
+/// The coordinator signs its messages with the network private key of the signer config,
+/// which signers verify against the coordinator public key
+fn check_coordinator_key(signer_config: &SignerConfig) -> Result<String, String> {
+    let public_key = signer_config
+        .network_private_key
+        .with_scalar(ecdsa::PublicKey::new)
+        .map_err(|e| format!("Invalid network_private_key: {:?}", e))?;
+    if public_key.to_bytes() == signer_config.coordinator_public_key.to_bytes() {
+        Ok("matches network_private_key".to_string())
+    } else {
+        Err(
+            "Does not match network_private_key, so signers will reject the coordinator"
+                .to_string(),
+        )
+    }
+}
+
+/// Read a secret key stored in WIF or as 32 hex encoded bytes
+fn read_secret_key(path: impl AsRef<Path>) -> Result<SecretKey, Error> {
+    let invalid = || Error::InvalidSecretKey(path.as_ref().display().to_string());
+    let contents = fs::read_to_string(path.as_ref())?;
+    let contents = contents.trim();
+    if let Ok(private_key) = PrivateKey::from_wif(contents) {
+        return Ok(private_key.inner);
+    }
+    let bytes = hex::decode(contents).map_err(|_| invalid())?;
+    SecretKey::from_slice(&bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn txids_parse_with_or_without_prefix() {
+        let hex = "11".repeat(32);
+        assert_eq!(parse_txid(&hex).unwrap(), Txid([0x11; 32]));
+        assert_eq!(parse_txid(&format!("0x{hex}")).unwrap(), Txid([0x11; 32]));
+        assert!(matches!(parse_txid("11"), Err(Error::InvalidTxid(_))));
+    }
+
+    #[test]
+    fn secret_keys_are_read_as_wif_or_hex() {
+        let dir = tempdir::TempDir::new("operator").unwrap();
+        let path = dir.path().join("recovery.key");
+        let secret_key = SecretKey::from_slice(&[7; 32]).unwrap();
+
+        fs::write(&path, format!("{}\n", hex::encode([7; 32]))).unwrap();
+        assert_eq!(read_secret_key(&path).unwrap(), secret_key);
+
+        let wif = PrivateKey::new(secret_key, bitcoin::Network::Regtest).to_wif();
+        fs::write(&path, wif).unwrap();
+        assert_eq!(read_secret_key(&path).unwrap(), secret_key);
+
+        fs::write(&path, "garbage").unwrap();
+        assert!(matches!(
+            read_secret_key(&path),
+            Err(Error::InvalidSecretKey(_))
+        ));
+    }
+}
@@ -5,7 +5,7 @@ use crate::stacks_node;
 use crate::stacks_node::Error as StacksNodeError;
 mod sqlite_peg_queue;
 
-pub use sqlite_peg_queue::{Error as SqlitePegQueueError, SqlitePegQueue};
+pub use sqlite_peg_queue::{Entry, Error as SqlitePegQueueError, SqlitePegQueue, Status};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -19,8 +19,35 @@ pub trait PegQueue {
     fn sbtc_op(&self) -> Result<Option<SbtcOp>, Error>;
     fn poll<N: stacks_node::StacksNode>(&self, stacks_node: &N) -> Result<(), Error>;
 
+    /// Ingest the ops of a single burn block, e.g. when notified of it by the stacks node.
+    /// The last processed block height only advances if this leaves no gap, otherwise
+    /// the gap is filled by the next poll.
+    fn ingest<N: stacks_node::StacksNode>(
+        &self,
+        stacks_node: &N,
+        block_height: u64,
+    ) -> Result<(), Error>;
+
     fn acknowledge(&self, txid: &Txid, burn_header_hash: &BurnchainHeaderHash)
         -> Result<(), Error>;
+
+    /// Park an op until the fulfillment transaction with the given txid has been reviewed
+    fn await_review(
+        &self,
+        txid: &Txid,
+        burn_header_hash: &BurnchainHeaderHash,
+        fulfillment_txid: &str,
+    ) -> Result<(), Error>;
+
+    /// The txid of the fulfillment transaction exported for review, if the op is awaiting review
+    fn fulfillment_under_review(
+        &self,
+        txid: &Txid,
+        burn_header_hash: &BurnchainHeaderHash,
+    ) -> Result<Option<String>, Error>;
+
+    /// The op awaiting review of the fulfillment transaction with the given txid, if any
+    fn op_under_review(&self, fulfillment_txid: &str) -> Result<Option<SbtcOp>, Error>;
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -30,6 +57,14 @@ pub enum SbtcOp {
 }
 
 impl SbtcOp {
+    /// Name of the op type as used by the operator CLI
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::PegIn(_) => "peg_in",
+            Self::PegOutRequest(_) => "peg_out_request",
+        }
+    }
+
     pub fn as_peg_in(&self) -> Option<&stacks_node::PegInOp> {
         match self {
             Self::PegIn(op) => Some(op),
@@ -1,4 +1,6 @@
-use rusqlite::{Connection as RusqliteConnection, Error as RusqliteError, Row as SqliteRow};
+use rusqlite::{
+    Connection as RusqliteConnection, Error as RusqliteError, OpenFlags, Row as SqliteRow,
+};
 use std::path::Path;
 use std::str::FromStr;
 
@@ -21,6 +23,10 @@ pub enum Error {
     HexError(#[from] HexError),
     #[error("Did not recognize status: {0}")]
     InvalidStatusError(String),
+    #[error("No op found with txid {0}")]
+    UnknownOp(String),
+    #[error("Op {0} is {1} and can not be {2}")]
+    InvalidStatusTransition(String, &'static str, &'static str),
 }
 
 // Workaround to allow non-perfect conversions in `Entry::from_row`
@@ -58,6 +64,90 @@ impl SqlitePegQueue {
         )
     }
 
+    /// Open an existing peg queue without a stacks node, e.g. to inspect it while the
+    /// coordinator is not running
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let conn = RusqliteConnection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        let this = Self { conn };
+        this.conn
+            .execute(Self::create_psbt_reviews_table(), rusqlite::params![])?;
+        Ok(this)
+    }
+
+    /// All entries ordered by block height
+    pub fn entries(&self) -> Result<Vec<Entry>, Error> {
+        let mut statement = self.conn.prepare(Self::sql_select_all())?;
+        let entries = statement
+            .query_map(rusqlite::params![], Entry::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// All entries of the op with the given txid. An op is only found under more than one
+    /// burn header hash if it was mined again after a reorg.
+    pub fn entries_with_txid(&self, txid: &Txid) -> Result<Vec<Entry>, Error> {
+        let mut statement = self.conn.prepare(Self::sql_select_txid())?;
+        let entries = statement
+            .query_map(rusqlite::params![txid.to_hex()], Entry::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        if entries.is_empty() {
+            return Err(Error::UnknownOp(txid.to_hex()));
+        }
+        Ok(entries)
+    }
+
+    /// Return a pending or parked op to the queue so that it is processed again
+    pub fn retry(&self, txid: &Txid) -> Result<Vec<Entry>, Error> {
+        self.transition(txid, Status::New, "retried", |status| {
+            matches!(status, Status::Pending | Status::AwaitingReview)
+        })
+    }
+
+    /// Remove an unfinished op from the queue without processing it
+    pub fn skip(&self, txid: &Txid) -> Result<Vec<Entry>, Error> {
+        self.transition(txid, Status::Skipped, "skipped", |status| {
+            matches!(
+                status,
+                Status::New | Status::Pending | Status::AwaitingReview
+            )
+        })
+    }
+
+    /// Set the block height the next poll starts at
+    pub fn reset_block_height(&self, block_height: u64) -> Result<(), Error> {
+        self.insert_last_processed_block_height(block_height.saturating_sub(1))
+    }
+
+    /// The block height the next poll starts at
+    pub fn next_block_height(&self) -> Result<u64, Error> {
+        Ok(self.last_processed_block_height()? + 1)
+    }
+
+    fn transition(
+        &self,
+        txid: &Txid,
+        status: Status,
+        action: &'static str,
+        allowed: impl Fn(&Status) -> bool,
+    ) -> Result<Vec<Entry>, Error> {
+        let mut entries = self.entries_with_txid(txid)?;
+        if let Some(entry) = entries.iter().find(|entry| !allowed(&entry.status)) {
+            return Err(Error::InvalidStatusTransition(
+                txid.to_hex(),
+                entry.status.as_str(),
+                action,
+            ));
+        }
+        for entry in &mut entries {
+            entry.status = status.clone();
+            self.insert(entry)?;
+        }
+        Ok(entries)
+    }
+
     fn from_connection(
         conn: RusqliteConnection,
         start_block_height: Option<u64>,
@@ -68,6 +158,8 @@ impl SqlitePegQueue {
             .execute(Self::create_sbtc_ops_table(), rusqlite::params![])?;
         this.conn
             .execute(Self::create_metadata_table(), rusqlite::params![])?;
+        this.conn
+            .execute(Self::create_psbt_reviews_table(), rusqlite::params![])?;
 
         // Prevent overflow by calling saturating sub to ensure we don't go below 0
         if let Some(start_block_height) = start_block_height {
@@ -92,7 +184,7 @@ impl SqlitePegQueue {
             Ok(peg_in_ops) => {
                 for peg_in_op in peg_in_ops {
                     let entry = Entry::from(peg_in_op);
-                    self.insert(&entry)?;
+                    self.insert_new(&entry)?;
                 }
             }
         }
@@ -112,7 +204,7 @@ impl SqlitePegQueue {
             Ok(peg_out_request_ops) => {
                 for peg_out_request_op in peg_out_request_ops {
                     let entry = Entry::from(peg_out_request_op);
-                    self.insert(&entry)?;
+                    self.insert_new(&entry)?;
                 }
             }
         }
@@ -133,6 +225,23 @@ impl SqlitePegQueue {
         Ok(())
     }
 
+    /// Insert a newly observed entry, leaving any existing entry for the same op untouched
+    /// so that ops which are observed more than once are only processed once
+    fn insert_new(&self, entry: &Entry) -> Result<(), Error> {
+        self.conn.execute(
+            Self::sql_insert_new(),
+            rusqlite::params![
+                entry.txid.to_hex(),
+                entry.burn_header_hash.to_hex(),
+                entry.block_height as i64,
+                serde_json::to_string(&entry.op)?,
+                entry.status.as_str(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
     fn get_single_entry_with_status(&self, status: &Status) -> Result<Option<Entry>, Error> {
         Ok(self
             .conn
@@ -153,6 +262,72 @@ impl SqlitePegQueue {
         )?)
     }
 
+    fn get_entry_if_exists(
+        &self,
+        txid: &Txid,
+        burn_header_hash: &BurnchainHeaderHash,
+    ) -> Result<Option<Entry>, Error> {
+        Ok(self
+            .conn
+            .prepare(Self::sql_select_pk())?
+            .query_map(
+                rusqlite::params![txid.to_hex(), burn_header_hash.to_hex()],
+                Entry::from_row,
+            )?
+            .next()
+            .transpose()?)
+    }
+
+    fn insert_psbt_review(&self, entry: &Entry, fulfillment_txid: &str) -> Result<(), Error> {
+        self.conn.execute(
+            Self::sql_insert_psbt_review(),
+            rusqlite::params![
+                entry.txid.to_hex(),
+                entry.burn_header_hash.to_hex(),
+                fulfillment_txid,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn psbt_review(
+        &self,
+        txid: &Txid,
+        burn_header_hash: &BurnchainHeaderHash,
+    ) -> Result<Option<String>, Error> {
+        Ok(self
+            .conn
+            .prepare(Self::sql_select_psbt_review())?
+            .query_map(
+                rusqlite::params![txid.to_hex(), burn_header_hash.to_hex()],
+                |row| row.get::<_, String>(0),
+            )?
+            .next()
+            .transpose()?)
+    }
+
+    fn psbt_review_by_fulfillment(
+        &self,
+        fulfillment_txid: &str,
+    ) -> Result<Option<(Txid, BurnchainHeaderHash)>, Error> {
+        let review = self
+            .conn
+            .prepare(Self::sql_select_psbt_review_by_fulfillment())?
+            .query_map(rusqlite::params![fulfillment_txid], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .next()
+            .transpose()?;
+        let Some((txid, burn_header_hash)) = review else {
+            return Ok(None);
+        };
+        Ok(Some((
+            Txid::from_hex(&txid)?,
+            BurnchainHeaderHash::from_hex(&burn_header_hash)?,
+        )))
+    }
+
     fn last_processed_block_height(&self) -> Result<u64, Error> {
         Ok(self
             .conn
@@ -199,24 +374,72 @@ impl SqlitePegQueue {
         "#
     }
 
+    const fn create_psbt_reviews_table() -> &'static str {
+        r#"
+        CREATE TABLE IF NOT EXISTS psbt_reviews (
+            txid TEXT NOT NULL,
+            burn_header_hash TEXT NOT NULL,
+            fulfillment_txid TEXT NOT NULL,
+
+            PRIMARY KEY(txid, burn_header_hash)
+        )
+        "#
+    }
+
     const fn sql_insert() -> &'static str {
         r#"
         REPLACE INTO sbtc_ops (txid, burn_header_hash, block_height, op, status) VALUES (?1, ?2, ?3, ?4, ?5)
         "#
     }
 
+    const fn sql_insert_new() -> &'static str {
+        r#"
+        INSERT OR IGNORE INTO sbtc_ops (txid, burn_header_hash, block_height, op, status) VALUES (?1, ?2, ?3, ?4, ?5)
+        "#
+    }
+
     const fn sql_select_status() -> &'static str {
         r#"
         SELECT txid, burn_header_hash, block_height, op, status FROM sbtc_ops WHERE status=?1 ORDER BY block_height, op ASC
         "#
     }
 
+    const fn sql_select_all() -> &'static str {
+        r#"
+        SELECT txid, burn_header_hash, block_height, op, status FROM sbtc_ops ORDER BY block_height, op ASC
+        "#
+    }
+
+    const fn sql_select_txid() -> &'static str {
+        r#"
+        SELECT txid, burn_header_hash, block_height, op, status FROM sbtc_ops WHERE txid=?1 ORDER BY block_height ASC
+        "#
+    }
+
     const fn sql_select_pk() -> &'static str {
         r#"
         SELECT txid, burn_header_hash, block_height, op, status FROM sbtc_ops WHERE txid=?1 AND burn_header_hash=?2
         "#
     }
 
+    const fn sql_insert_psbt_review() -> &'static str {
+        r#"
+        REPLACE INTO psbt_reviews (txid, burn_header_hash, fulfillment_txid) VALUES (?1, ?2, ?3)
+        "#
+    }
+
+    const fn sql_select_psbt_review() -> &'static str {
+        r#"
+        SELECT fulfillment_txid FROM psbt_reviews WHERE txid=?1 AND burn_header_hash=?2
+        "#
+    }
+
+    const fn sql_select_psbt_review_by_fulfillment() -> &'static str {
+        r#"
+        SELECT txid, burn_header_hash FROM psbt_reviews WHERE fulfillment_txid=?1
+        "#
+    }
+
     const fn sql_select_last_processed_block_height() -> &'static str {
         r#"
             SELECT last_processed_block_height FROM peg_queue_metadata WHERE id='peg_queue'
@@ -267,6 +490,20 @@ impl PegQueue for SqlitePegQueue {
         Ok(())
     }
 
+    fn ingest<N: StacksNode>(
+        &self,
+        stacks_node: &N,
+        block_height: u64,
+    ) -> Result<(), PegQueueError> {
+        self.poll_peg_in_ops(stacks_node, block_height)?;
+        self.poll_peg_out_request_ops(stacks_node, block_height)?;
+        if self.last_processed_block_height()? + 1 == block_height {
+            self.insert_last_processed_block_height(block_height)?;
+        }
+        info!("Ingested block height {}", block_height);
+        Ok(())
+    }
+
     fn acknowledge(
         &self,
         txid: &Txid,
@@ -279,15 +516,53 @@ impl PegQueue for SqlitePegQueue {
 
         Ok(())
     }
+
+    fn await_review(
+        &self,
+        txid: &Txid,
+        burn_header_hash: &BurnchainHeaderHash,
+        fulfillment_txid: &str,
+    ) -> Result<(), PegQueueError> {
+        let mut entry = self.get_entry(txid, burn_header_hash)?;
+
+        entry.status = Status::AwaitingReview;
+        self.insert(&entry)?;
+        self.insert_psbt_review(&entry, fulfillment_txid)?;
+
+        Ok(())
+    }
+
+    fn fulfillment_under_review(
+        &self,
+        txid: &Txid,
+        burn_header_hash: &BurnchainHeaderHash,
+    ) -> Result<Option<String>, PegQueueError> {
+        match self.get_entry_if_exists(txid, burn_header_hash)? {
+            Some(entry) if entry.status == Status::AwaitingReview => {}
+            _ => return Ok(None),
+        }
+        Ok(self.psbt_review(txid, burn_header_hash)?)
+    }
+
+    fn op_under_review(&self, fulfillment_txid: &str) -> Result<Option<SbtcOp>, PegQueueError> {
+        let Some((txid, burn_header_hash)) = self.psbt_review_by_fulfillment(fulfillment_txid)?
+        else {
+            return Ok(None);
+        };
+        match self.get_entry_if_exists(&txid, &burn_header_hash)? {
+            Some(entry) if entry.status == Status::AwaitingReview => Ok(Some(entry.op)),
+            _ => Ok(None),
+        }
+    }
 }
 
-#[derive(Debug)]
-struct Entry {
-    burn_header_hash: BurnchainHeaderHash,
-    txid: Txid,
-    block_height: u64,
-    op: SbtcOp,
-    status: Status,
+#[derive(Debug, serde::Serialize)]
+pub struct Entry {
+    pub burn_header_hash: BurnchainHeaderHash,
+    pub txid: Txid,
+    pub block_height: u64,
+    pub op: SbtcOp,
+    pub status: Status,
 }
 
 impl Entry {
@@ -337,19 +612,25 @@ impl From<PegOutRequestOp> for Entry {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Status {
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
     New,
     Pending,
+    AwaitingReview,
     Acknowledged,
+    /// Removed from the queue by an operator without being processed
+    Skipped,
 }
 
 impl Status {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             Self::New => "new",
             Self::Pending => "pending",
+            Self::AwaitingReview => "awaiting_review",
             Self::Acknowledged => "acknowledged",
+            Self::Skipped => "skipped",
         }
     }
 }
@@ -360,7 +641,9 @@ impl FromStr for Status {
         Ok(match s {
             "new" => Self::New,
             "pending" => Self::Pending,
+            "awaiting_review" => Self::AwaitingReview,
             "acknowledged" => Self::Acknowledged,
+            "skipped" => Self::Skipped,
             other => return Err(Error::InvalidStatusError(other.to_owned())),
         })
     }
@@ -481,6 +764,114 @@ mod tests {
         assert_eq!(entry.status, Status::Acknowledged);
     }
 
+    #[test]
+    fn entries_awaiting_review_should_record_fulfillment_txid() {
+        let peg_queue = SqlitePegQueue::in_memory(Some(1), 2).unwrap();
+        let number_of_simulated_blocks: u64 = 1;
+
+        let stacks_node_mock = default_stacks_node_mock(number_of_simulated_blocks);
+        peg_queue.poll(&stacks_node_mock).unwrap();
+
+        peg_queue.sbtc_op().unwrap().unwrap();
+        let next_op = peg_queue.sbtc_op().unwrap().unwrap();
+        let op = next_op.as_peg_out_request().unwrap();
+        assert_eq!(
+            peg_queue
+                .fulfillment_under_review(&op.txid, &op.burn_header_hash)
+                .unwrap(),
+            None
+        );
+
+        peg_queue
+            .await_review(&op.txid, &op.burn_header_hash, "aa")
+            .unwrap();
+        let entry = peg_queue.get_entry(&op.txid, &op.burn_header_hash).unwrap();
+        assert_eq!(entry.status, Status::AwaitingReview);
+        assert_eq!(
+            peg_queue
+                .fulfillment_under_review(&op.txid, &op.burn_header_hash)
+                .unwrap(),
+            Some("aa".to_string())
+        );
+        let under_review = peg_queue.op_under_review("aa").unwrap().unwrap();
+        assert_eq!(under_review.as_peg_out_request().unwrap().txid, op.txid);
+        assert!(peg_queue.op_under_review("bb").unwrap().is_none());
+
+        // Reviewed ops are no longer awaiting review once acknowledged
+        peg_queue
+            .acknowledge(&op.txid, &op.burn_header_hash)
+            .unwrap();
+        assert_eq!(
+            peg_queue
+                .fulfillment_under_review(&op.txid, &op.burn_header_hash)
+                .unwrap(),
+            None
+        );
+        assert!(peg_queue.op_under_review("aa").unwrap().is_none());
+    }
+
+    #[test]
+    fn operators_can_retry_and_skip_ops() {
+        let peg_queue = SqlitePegQueue::in_memory(Some(1), 2).unwrap();
+        let stacks_node_mock = default_stacks_node_mock(1);
+        peg_queue.poll(&stacks_node_mock).unwrap();
+        assert_eq!(peg_queue.entries().unwrap().len(), 2);
+
+        let op = peg_queue.sbtc_op().unwrap().unwrap();
+        let txid = op.as_peg_in().unwrap().txid;
+
+        // New ops can not be retried, pending ops can
+        let entries = peg_queue.retry(&txid).unwrap();
+        assert_eq!(entries[0].status, Status::New);
+        assert!(matches!(
+            peg_queue.retry(&txid),
+            Err(Error::InvalidStatusTransition(..))
+        ));
+
+        let entries = peg_queue.skip(&txid).unwrap();
+        assert_eq!(entries[0].status, Status::Skipped);
+        assert_eq!(
+            peg_queue.entries_with_txid(&txid).unwrap()[0].status,
+            Status::Skipped
+        );
+        // Skipped ops are not processed
+        let next_op = peg_queue.sbtc_op().unwrap().unwrap();
+        assert!(next_op.as_peg_out_request().is_some());
+
+        assert!(matches!(
+            peg_queue.entries_with_txid(&Txid([0xff; 32])),
+            Err(Error::UnknownOp(_))
+        ));
+
+        peg_queue.reset_block_height(1).unwrap();
+        assert_eq!(peg_queue.next_block_height().unwrap(), 1);
+    }
+
+    #[test]
+    fn ingesting_should_only_advance_block_height_without_gaps() {
+        let peg_queue = SqlitePegQueue::in_memory(Some(1), 2).unwrap();
+        let stacks_node_mock = default_stacks_node_mock(3);
+
+        peg_queue.ingest(&stacks_node_mock, 1).unwrap();
+        assert_eq!(peg_queue.last_processed_block_height().unwrap(), 1);
+
+        // Height 2 was missed, so polling has to fill the gap
+        peg_queue.ingest(&stacks_node_mock, 3).unwrap();
+        assert_eq!(peg_queue.last_processed_block_height().unwrap(), 1);
+
+        peg_queue.poll(&stacks_node_mock).unwrap();
+        assert_eq!(peg_queue.last_processed_block_height().unwrap(), 3);
+
+        // Ops of ingested blocks are not queued a second time by the gap-filling poll
+        for height in 1..=3 {
+            let next_op = peg_queue.sbtc_op().unwrap().unwrap();
+            assert_eq!(next_op.as_peg_in().unwrap().block_height, height);
+            let next_op = peg_queue.sbtc_op().unwrap().unwrap();
+            assert_eq!(next_op.as_peg_out_request().unwrap().block_height, height);
+        }
+        assert!(peg_queue.sbtc_op().unwrap().is_none());
+    }
+
     #[test]
     fn should_start_at_last_observed_block_height_when_polling() {
         let start_block_height: u64 = 10;
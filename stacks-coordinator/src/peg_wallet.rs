@@ -10,6 +10,7 @@ use blockstack_lib::{
     chainstate::stacks::StacksTransaction,
     types::chainstate::{StacksAddress, StacksPublicKey},
 };
+use frost_signer::signing_round::SignatureType;
 use std::fmt::Debug;
 
 #[derive(thiserror::Error, Debug, PartialEq)]
@@ -61,7 +62,11 @@ pub trait BitcoinWallet {
     /// Returns the BTC address for the wallet
     fn address(&self) -> &BitcoinAddress;
 
+    /// Returns the internal public key of the wallet, i.e. the aggregate public key before any taproot tweak
     fn x_only_pub_key(&self) -> &XOnlyPublicKey;
+
+    /// Returns the kind of signature which spends the wallet through its key path
+    fn signature_type(&self) -> SignatureType;
 }
 
 pub trait PegWallet {
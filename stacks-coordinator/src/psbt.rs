@@ -0,0 +1,311 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bitcoin::{
+    consensus::encode::{deserialize, serialize, Error as EncodeError},
+    hashes::Hash,
+    psbt::{raw::ProprietaryKey, Error as PsbtError, PartiallySignedTransaction, Prevouts},
+    util::{
+        schnorr::{SchnorrSig, SchnorrSigError},
+        sighash::{Error as SighashError, SighashCache},
+    },
+    SchnorrSighashType, Transaction, TxOut, Txid, Witness,
+};
+
+use crate::{peg_queue::SbtcOp, stacks_node::PegOutRequestOp};
+
+/// Prefix of all proprietary PSBT fields written by the coordinator
+pub const PROPRIETARY_PREFIX: &[u8] = b"sbtc";
+/// Global field holding the JSON encoded peg op the transaction fulfills
+pub const PROPRIETARY_PEG_OP: u8 = 0x00;
+/// Input field holding the taproot key-spend sighash of the input
+pub const PROPRIETARY_SIGHASH: u8 = 0x00;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("PSBT encoding error: {0}")]
+    EncodeError(#[from] EncodeError),
+    #[error("PSBT error: {0}")]
+    PsbtError(#[from] PsbtError),
+    #[error("Sighash error: {0}")]
+    SighashError(#[from] SighashError),
+    #[error("Invalid schnorr signature: {0}")]
+    SchnorrSigError(#[from] SchnorrSigError),
+    #[error("PSBT is missing the sbtc peg op")]
+    MissingPegOp,
+    #[error("PSBT peg op is invalid: {0}")]
+    InvalidPegOp(#[from] serde_json::Error),
+    #[error("PSBT input {0} is missing its witness utxo")]
+    MissingWitnessUtxo(usize),
+    #[error("PSBT input {0} witness utxo is not the output it spends")]
+    PrevoutMismatch(usize),
+    #[error("PSBT input {0} spends an output unknown to the wallet")]
+    UnknownPrevout(usize),
+    #[error("PSBT input {0} sighash does not match the reviewed sighash")]
+    SighashMismatch(usize),
+    #[error("PSBT transaction {0} is not the transaction which was exported for review")]
+    UnexpectedTransaction(Txid),
+}
+
+/// Peg outs of at least `threshold` sats are written to `outbox_directory` as unsigned PSBTs
+/// and are only signed once the approved PSBT is placed in the `approved` subdirectory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PsbtReview {
+    pub outbox_directory: PathBuf,
+    pub threshold: u64,
+}
+
+impl PsbtReview {
+    pub fn requires_review(&self, op: &PegOutRequestOp) -> bool {
+        op.amount >= self.threshold
+    }
+
+    pub fn outbox_path(&self, txid: &Txid) -> PathBuf {
+        self.outbox_directory.join(format!("{}.psbt", txid))
+    }
+
+    pub fn approved_directory(&self) -> PathBuf {
+        self.outbox_directory.join("approved")
+    }
+
+    pub fn signed_path(&self, txid: &Txid) -> PathBuf {
+        self.outbox_directory
+            .join("signed")
+            .join(format!("{}.psbt", txid))
+    }
+
+    /// Marks a signed transaction as broadcast, so that a retry only broadcasts the sBTC transaction
+    pub fn broadcast_path(&self, txid: &Txid) -> PathBuf {
+        self.outbox_directory
+            .join("signed")
+            .join(format!("{}.broadcast", txid))
+    }
+
+    /// All PSBTs currently waiting in the approved directory
+    pub fn approved_psbts(&self) -> Result<Vec<PathBuf>, Error> {
+        let approved_directory = self.approved_directory();
+        if !approved_directory.exists() {
+            return Ok(vec![]);
+        }
+        let mut paths = vec![];
+        for entry in fs::read_dir(approved_directory)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "psbt") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+/// Build an unsigned PSBT for the fulfillment transaction which carries the peg op and the
+/// taproot key-spend sighash of every input in proprietary fields
+pub fn build_review_psbt(
+    tx: Transaction,
+    prevouts: Vec<TxOut>,
+    op: &SbtcOp,
+) -> Result<PartiallySignedTransaction, Error> {
+    let mut psbt = PartiallySignedTransaction::from_unsigned_tx(tx)?;
+    psbt.proprietary
+        .insert(proprietary_key(PROPRIETARY_PEG_OP), serde_json::to_vec(op)?);
+    for (input, prevout) in psbt.inputs.iter_mut().zip(prevouts) {
+        input.witness_utxo = Some(prevout);
+    }
+    for (index, sighash) in sighashes(&psbt.unsigned_tx, &prevouts(&psbt)?)?
+        .into_iter()
+        .enumerate()
+    {
+        psbt.inputs[index]
+            .proprietary
+            .insert(proprietary_key(PROPRIETARY_SIGHASH), sighash.to_vec());
+    }
+    Ok(psbt)
+}
+
+/// The peg op the PSBT claims to fulfill. Only informative for reviewers, the op to fulfill is
+/// taken from the peg queue.
+pub fn peg_op(psbt: &PartiallySignedTransaction) -> Result<SbtcOp, Error> {
+    let op = psbt
+        .proprietary
+        .get(&proprietary_key(PROPRIETARY_PEG_OP))
+        .ok_or(Error::MissingPegOp)?;
+    Ok(serde_json::from_slice(op)?)
+}
+
+/// Check the PSBT witness utxos against the outputs the inputs actually spend, then compute the
+/// sighash of every input from them and check it against the sighash which was exported for review
+pub fn reviewed_sighashes(
+    psbt: &PartiallySignedTransaction,
+    prevouts: &[TxOut],
+) -> Result<Vec<[u8; 32]>, Error> {
+    for (index, input) in psbt.inputs.iter().enumerate() {
+        if input.witness_utxo.as_ref() != prevouts.get(index) {
+            return Err(Error::PrevoutMismatch(index));
+        }
+    }
+    let sighashes = sighashes(&psbt.unsigned_tx, prevouts)?;
+    for (index, (input, sighash)) in psbt.inputs.iter().zip(&sighashes).enumerate() {
+        let reviewed = input.proprietary.get(&proprietary_key(PROPRIETARY_SIGHASH));
+        if reviewed.map(Vec::as_slice) != Some(sighash.as_slice()) {
+            return Err(Error::SighashMismatch(index));
+        }
+    }
+    Ok(sighashes)
+}
+
+/// Write the schnorr signature of a key-spend into the PSBT input as both the taproot key
+/// signature and the final witness
+pub fn finalize_input(
+    psbt: &mut PartiallySignedTransaction,
+    index: usize,
+    signature: &[u8],
+) -> Result<(), Error> {
+    let input = &mut psbt.inputs[index];
+    input.tap_key_sig = Some(SchnorrSig::from_slice(signature)?);
+    input.final_script_witness = Some(Witness::from_vec(vec![signature.to_vec()]));
+    Ok(())
+}
+
+pub fn read_psbt(path: impl AsRef<Path>) -> Result<PartiallySignedTransaction, Error> {
+    Ok(deserialize(&fs::read(path)?)?)
+}
+
+pub fn write_psbt(path: impl AsRef<Path>, psbt: &PartiallySignedTransaction) -> Result<(), Error> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(fs::write(path, serialize(psbt))?)
+}
+
+/// The outputs spent by the PSBT inputs, taken from their witness utxos
+pub fn prevouts(psbt: &PartiallySignedTransaction) -> Result<Vec<TxOut>, Error> {
+    psbt.inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| {
+            input
+                .witness_utxo
+                .clone()
+                .ok_or(Error::MissingWitnessUtxo(index))
+        })
+        .collect()
+}
+
+fn sighashes(tx: &Transaction, prevouts: &[TxOut]) -> Result<Vec<[u8; 32]>, Error> {
+    let mut sighash_cache = SighashCache::new(tx);
+    (0..tx.input.len())
+        .map(|index| {
+            let sighash = sighash_cache.taproot_key_spend_signature_hash(
+                index,
+                &Prevouts::All(prevouts),
+                SchnorrSighashType::Default,
+            )?;
+            Ok(sighash.into_inner())
+        })
+        .collect()
+}
+
+fn proprietary_key(subtype: u8) -> ProprietaryKey {
+    ProprietaryKey {
+        prefix: PROPRIETARY_PREFIX.to_vec(),
+        subtype,
+        key: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{OutPoint, PackedLockTime, Script, Sequence, TxIn};
+
+    use super::*;
+    use crate::util::test::{build_peg_out_request_op, PRIVATE_KEY_HEX};
+
+    fn unsigned_transaction() -> (Transaction, Vec<TxOut>) {
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime(0),
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Script::new(),
+                sequence: Sequence(0xFFFFFFFD),
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 1000,
+                script_pubkey: Script::new(),
+            }],
+        };
+        let prevouts = vec![TxOut {
+            value: 2000,
+            script_pubkey: Script::new(),
+        }];
+        (tx, prevouts)
+    }
+
+    #[test]
+    fn review_psbt_round_trip() {
+        let (tx, prevouts) = unsigned_transaction();
+        let op = build_peg_out_request_op(PRIVATE_KEY_HEX, 1000, 10, 3);
+        let psbt =
+            build_review_psbt(tx.clone(), prevouts.clone(), &SbtcOp::PegOutRequest(op)).unwrap();
+
+        let decoded: PartiallySignedTransaction = deserialize(&serialize(&psbt)).unwrap();
+        assert_eq!(decoded.unsigned_tx.txid(), tx.txid());
+        let decoded_op = peg_op(&decoded).unwrap();
+        assert_eq!(decoded_op.as_peg_out_request().unwrap().amount, 1000);
+        assert_eq!(reviewed_sighashes(&decoded, &prevouts).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn modified_prevouts_fail_review() {
+        let (tx, prevouts) = unsigned_transaction();
+        let op = build_peg_out_request_op(PRIVATE_KEY_HEX, 1000, 10, 3);
+        let mut psbt = build_review_psbt(tx, prevouts.clone(), &SbtcOp::PegOutRequest(op)).unwrap();
+
+        // Modifying the witness utxo and the reviewed sighash consistently does not help
+        psbt.inputs[0].witness_utxo.as_mut().unwrap().value = 1_000_000;
+        let forged = sighashes(&psbt.unsigned_tx, &prevouts(&psbt).unwrap()).unwrap();
+        psbt.inputs[0]
+            .proprietary
+            .insert(proprietary_key(PROPRIETARY_SIGHASH), forged[0].to_vec());
+        assert!(matches!(
+            reviewed_sighashes(&psbt, &prevouts),
+            Err(Error::PrevoutMismatch(0))
+        ));
+    }
+
+    #[test]
+    fn modified_sighashes_fail_review() {
+        let (tx, prevouts) = unsigned_transaction();
+        let op = build_peg_out_request_op(PRIVATE_KEY_HEX, 1000, 10, 3);
+        let mut psbt = build_review_psbt(tx, prevouts.clone(), &SbtcOp::PegOutRequest(op)).unwrap();
+
+        psbt.inputs[0]
+            .proprietary
+            .insert(proprietary_key(PROPRIETARY_SIGHASH), vec![0; 32]);
+        assert!(matches!(
+            reviewed_sighashes(&psbt, &prevouts),
+            Err(Error::SighashMismatch(0))
+        ));
+    }
+
+    #[test]
+    fn finalized_psbt_extracts_signed_transaction() {
+        let (tx, prevouts) = unsigned_transaction();
+        let op = build_peg_out_request_op(PRIVATE_KEY_HEX, 1000, 10, 3);
+        let mut psbt = build_review_psbt(tx, prevouts, &SbtcOp::PegOutRequest(op)).unwrap();
+
+        let signature = [1u8; 64];
+        finalize_input(&mut psbt, 0, &signature).unwrap();
+        let signed_tx = psbt.extract_tx();
+        assert_eq!(
+            signed_tx.input[0].witness.to_vec(),
+            vec![signature.to_vec()]
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use bitcoin::{
+    blockdata::{opcodes::all::*, script::Builder},
+    hashes::{hex::FromHex, Hash},
+    psbt::{Input, PartiallySignedTransaction, Prevouts},
+    secp256k1::{KeyPair, Message, Secp256k1, SecretKey},
+    util::{
+        schnorr::SchnorrSig,
+        sighash::{Error as SighashError, SighashCache},
+        taproot::{
+            LeafVersion, TapBranchHash, TapLeafHash, TaprootBuilder, TaprootBuilderError,
+            TaprootSpendInfo,
+        },
+    },
+    Address, OutPoint, PackedLockTime, SchnorrSighashType, Script, Sequence, Transaction, TxIn,
+    TxOut, Txid, Witness, XOnlyPublicKey,
+};
+use tracing::debug;
+
+use crate::bitcoin_node::UTXO;
+use crate::psbt::Error as PsbtError;
+
+/// Block heights at or above this value are interpreted as timestamps by CLTV
+const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Invalid recovery config: {0}")]
+    InvalidConfig(String),
+    #[error("PSBT Error: {0}")]
+    PsbtError(#[from] PsbtError),
+    #[error("Taproot builder error: {0}")]
+    TaprootBuilderError(#[from] TaprootBuilderError),
+    #[error("Sighash error: {0}")]
+    SighashError(#[from] SighashError),
+    #[error("Invalid utxo: {0}")]
+    InvalidUtxo(String),
+    #[error("No peg wallet utxos are past the recovery timelock")]
+    NoRecoverableUtxos,
+    #[error("Fee of {fee} sats leaves less than the dust limit of the {amount} sats swept")]
+    InsufficientFunds { amount: u64, fee: u64 },
+    #[error("PSBT input {0} does not spend the recovery leaf")]
+    NotRecoveryInput(usize),
+    #[error("Key {0} is not a recovery key")]
+    UnknownKey(XOnlyPublicKey),
+    #[error("PSBT input {index} has {signatures} of the {threshold} required recovery signatures")]
+    InsufficientSignatures {
+        index: usize,
+        signatures: usize,
+        threshold: usize,
+    },
+}
+
+/// When the recovery leaf becomes spendable
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timelock {
+    /// Once the wallet utxo has been confirmed for this many blocks (OP_CHECKSEQUENCEVERIFY)
+    Relative(u16),
+    /// From this block height on (OP_CHECKLOCKTIMEVERIFY)
+    Absolute(u32),
+}
+
+/// A timelocked taproot leaf which lets `threshold` of the recovery `keys` spend the peg wallet
+/// in case the signers lose their threshold
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recovery {
+    keys: Vec<XOnlyPublicKey>,
+    threshold: usize,
+    timelock: Timelock,
+}
+
+impl Recovery {
+    pub fn new(
+        keys: Vec<XOnlyPublicKey>,
+        threshold: usize,
+        timelock: Timelock,
+    ) -> Result<Self, Error> {
+        if threshold == 0 || threshold > keys.len() {
+            return Err(Error::InvalidConfig(format!(
+                "threshold must be between 1 and the {} recovery keys",
+                keys.len()
+            )));
+        }
+        if keys
+            .iter()
+            .enumerate()
+            .any(|(i, key)| keys[..i].contains(key))
+        {
+            return Err(Error::InvalidConfig("duplicate recovery key".to_string()));
+        }
+        match timelock {
+            Timelock::Relative(0) | Timelock::Absolute(0) => {
+                return Err(Error::InvalidConfig("timelock must not be 0".to_string()))
+            }
+            Timelock::Absolute(height) if height >= LOCKTIME_THRESHOLD => {
+                return Err(Error::InvalidConfig(format!(
+                    "lock height {} would be interpreted as a timestamp",
+                    height
+                )))
+            }
+            _ => {}
+        }
+        Ok(Self {
+            keys,
+            threshold,
+            timelock,
+        })
+    }
+
+    pub fn keys(&self) -> &[XOnlyPublicKey] {
+        &self.keys
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn timelock(&self) -> Timelock {
+        self.timelock
+    }
+
+    /// `<timelock> CSV|CLTV DROP <key 1> CHECKSIG <key 2> CHECKSIGADD ... <threshold> NUMEQUAL`
+    pub fn script(&self) -> Script {
+        let builder = match self.timelock {
+            Timelock::Relative(blocks) => {
+                Builder::new().push_int(blocks.into()).push_opcode(OP_CSV)
+            }
+            Timelock::Absolute(height) => {
+                Builder::new().push_int(height.into()).push_opcode(OP_CLTV)
+            }
+        };
+        let builder =
+            self.keys
+                .iter()
+                .enumerate()
+                .fold(builder.push_opcode(OP_DROP), |builder, (i, key)| {
+                    let opcode = if i == 0 { OP_CHECKSIG } else { OP_CHECKSIGADD };
+                    builder.push_x_only_key(key).push_opcode(opcode)
+                });
+        builder
+            .push_int(self.threshold as i64)
+            .push_opcode(OP_NUMEQUAL)
+            .into_script()
+    }
+
+    pub fn leaf_hash(&self) -> TapLeafHash {
+        TapLeafHash::from_script(&self.script(), LeafVersion::TapScript)
+    }
+
+    /// The merkle root of the wallet's script tree, which consists of the recovery leaf only
+    pub fn merkle_root(&self) -> TapBranchHash {
+        TapBranchHash::from_inner(self.leaf_hash().into_inner())
+    }
+
+    pub fn spend_info(&self, internal_key: XOnlyPublicKey) -> Result<TaprootSpendInfo, Error> {
+        let secp = Secp256k1::verification_only();
+        Ok(TaprootBuilder::new()
+            .add_leaf(0, self.script())?
+            .finalize(&secp, internal_key)
+            .map_err(|_| Error::InvalidConfig("incomplete script tree".to_string()))?)
+    }
+
+    /// Build an unsigned PSBT which sweeps the recoverable utxos to the destination
+    pub fn build_sweep(
+        &self,
+        internal_key: XOnlyPublicKey,
+        utxos: Vec<UTXO>,
+        destination: &Address,
+        fee: u64,
+    ) -> Result<PartiallySignedTransaction, Error> {
+        let (lock_time, sequence) = match self.timelock {
+            Timelock::Relative(blocks) => (PackedLockTime(0), Sequence::from_height(blocks)),
+            Timelock::Absolute(height) => {
+                (PackedLockTime(height), Sequence::ENABLE_LOCKTIME_NO_RBF)
+            }
+        };
+        let mut tx = Transaction {
+            version: 2,
+            lock_time,
+            input: vec![],
+            output: vec![],
+        };
+        let mut prevouts = vec![];
+        for utxo in utxos {
+            if let Timelock::Relative(blocks) = self.timelock {
+                if utxo.confirmations < blocks.into() {
+                    debug!(
+                        "Skipping utxo {}:{} with {} confirmations",
+                        utxo.txid, utxo.vout, utxo.confirmations
+                    );
+                    continue;
+                }
+            }
+            tx.input.push(TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_hex(&utxo.txid)
+                        .map_err(|_| Error::InvalidUtxo(utxo.txid.clone()))?,
+                    vout: utxo.vout,
+                },
+                script_sig: Default::default(),
+                sequence,
+                witness: Default::default(),
+            });
+            prevouts.push(TxOut {
+                value: utxo.amount,
+                script_pubkey: Script::from(
+                    hex::decode(&utxo.scriptPubKey)
+                        .map_err(|_| Error::InvalidUtxo(utxo.scriptPubKey.clone()))?,
+                ),
+            });
+        }
+        if prevouts.is_empty() {
+            return Err(Error::NoRecoverableUtxos);
+        }
+
+        let amount = prevouts.iter().map(|prevout| prevout.value).sum();
+        let script_pubkey = destination.script_pubkey();
+        if amount < fee + script_pubkey.dust_value().to_sat() {
+            return Err(Error::InsufficientFunds { amount, fee });
+        }
+        tx.output.push(TxOut {
+            value: amount - fee,
+            script_pubkey,
+        });
+
+        let spend_info = self.spend_info(internal_key)?;
+        let script_ver = (self.script(), LeafVersion::TapScript);
+        let control_block = spend_info
+            .control_block(&script_ver)
+            .ok_or_else(|| Error::InvalidConfig("missing recovery leaf".to_string()))?;
+
+        let mut psbt = PartiallySignedTransaction::from_unsigned_tx(tx).map_err(PsbtError::from)?;
+        for (input, prevout) in psbt.inputs.iter_mut().zip(prevouts) {
+            input.witness_utxo = Some(prevout);
+            input.tap_internal_key = Some(internal_key);
+            input.tap_merkle_root = spend_info.merkle_root();
+            input
+                .tap_scripts
+                .insert(control_block.clone(), script_ver.clone());
+        }
+        Ok(psbt)
+    }
+
+    /// Add the signatures of the recovery key to every input of the sweep. Returns the number of inputs signed.
+    pub fn sign_sweep(
+        &self,
+        psbt: &mut PartiallySignedTransaction,
+        secret_key: &SecretKey,
+    ) -> Result<usize, Error> {
+        let secp = Secp256k1::new();
+        let keypair = KeyPair::from_secret_key(&secp, secret_key);
+        let (public_key, _) = keypair.x_only_public_key();
+        if !self.keys.contains(&public_key) {
+            return Err(Error::UnknownKey(public_key));
+        }
+
+        let leaf_hash = self.leaf_hash();
+        let prevouts = psbt
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| {
+                input
+                    .witness_utxo
+                    .clone()
+                    .ok_or(PsbtError::MissingWitnessUtxo(index))
+            })
+            .collect::<Result<Vec<TxOut>, PsbtError>>()?;
+        let mut sighash_cache = SighashCache::new(&psbt.unsigned_tx);
+        for (index, input) in psbt.inputs.iter_mut().enumerate() {
+            self.check_recovery_input(index, input)?;
+            let sighash = sighash_cache.taproot_script_spend_signature_hash(
+                index,
+                &Prevouts::All(&prevouts),
+                leaf_hash,
+                SchnorrSighashType::Default,
+            )?;
+            let message =
+                Message::from_slice(&sighash.into_inner()).expect("sighashes are 32 bytes long");
+            input.tap_script_sigs.insert(
+                (public_key, leaf_hash),
+                SchnorrSig {
+                    sig: secp.sign_schnorr(&message, &keypair),
+                    hash_ty: SchnorrSighashType::Default,
+                },
+            );
+        }
+        Ok(psbt.inputs.len())
+    }
+
+    /// Assemble the recovery signatures into the final witness of every input and extract the sweep
+    pub fn finalize_sweep(
+        &self,
+        psbt: &mut PartiallySignedTransaction,
+    ) -> Result<Transaction, Error> {
+        let leaf_hash = self.leaf_hash();
+        for (index, input) in psbt.inputs.iter_mut().enumerate() {
+            self.check_recovery_input(index, input)?;
+            let (control_block, (script, _)) = input
+                .tap_scripts
+                .iter()
+                .next()
+                .ok_or(Error::NotRecoveryInput(index))?;
+
+            // The script requires exactly `threshold` valid signatures, so only the signatures
+            // of the first `threshold` signing keys are used. The others are left empty.
+            let mut count = 0;
+            let mut signatures = self
+                .keys
+                .iter()
+                .map(|key| match input.tap_script_sigs.get(&(*key, leaf_hash)) {
+                    Some(signature) if count < self.threshold => {
+                        count += 1;
+                        signature.to_vec()
+                    }
+                    _ => vec![],
+                })
+                .collect::<Vec<Vec<u8>>>();
+            if count < self.threshold {
+                return Err(Error::InsufficientSignatures {
+                    index,
+                    signatures: count,
+                    threshold: self.threshold,
+                });
+            }
+
+            // The script checks the first key against the top of the stack, so the
+            // signatures are pushed in reverse key order
+            signatures.reverse();
+            let mut witness = signatures;
+            witness.push(script.to_bytes());
+            witness.push(control_block.serialize());
+            input.final_script_witness = Some(Witness::from_vec(witness));
+        }
+        Ok(psbt.clone().extract_tx())
+    }
+
+    fn check_recovery_input(&self, index: usize, input: &Input) -> Result<(), Error> {
+        let script = self.script();
+        if input.tap_scripts.values().any(|(leaf, _)| leaf == &script) {
+            Ok(())
+        } else {
+            Err(Error::NotRecoveryInput(index))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::Network;
+
+    use super::*;
+
+    fn keypair(byte: u8) -> (SecretKey, XOnlyPublicKey) {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[byte; 32]).unwrap();
+        let (public_key, _) = KeyPair::from_secret_key(&secp, &secret_key).x_only_public_key();
+        (secret_key, public_key)
+    }
+
+    fn recovery(timelock: Timelock) -> Recovery {
+        let keys = (1..=3).map(|byte| keypair(byte).1).collect();
+        Recovery::new(keys, 2, timelock).unwrap()
+    }
+
+    fn utxo(confirmations: u64, script_pubkey: &Script) -> UTXO {
+        UTXO {
+            txid: "11".repeat(32),
+            vout: 0,
+            amount: 100_000,
+            scriptPubKey: hex::encode(script_pubkey.as_bytes()),
+            confirmations,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recovery_config_is_validated() {
+        let keys = vec![keypair(1).1, keypair(2).1];
+        assert!(Recovery::new(keys.clone(), 0, Timelock::Relative(10)).is_err());
+        assert!(Recovery::new(keys.clone(), 3, Timelock::Relative(10)).is_err());
+        assert!(Recovery::new(keys.clone(), 2, Timelock::Relative(0)).is_err());
+        assert!(Recovery::new(keys.clone(), 2, Timelock::Absolute(LOCKTIME_THRESHOLD)).is_err());
+        assert!(Recovery::new(vec![keys[0], keys[0]], 1, Timelock::Relative(10)).is_err());
+        assert!(Recovery::new(keys, 2, Timelock::Absolute(1000)).is_ok());
+    }
+
+    #[test]
+    fn recovery_script_is_a_timelocked_multisig() {
+        let recovery = recovery(Timelock::Relative(144));
+        let keys = recovery
+            .keys()
+            .iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            recovery.script().asm(),
+            format!(
+                "OP_PUSHBYTES_2 9000 OP_CSV OP_DROP OP_PUSHBYTES_32 {} OP_CHECKSIG OP_PUSHBYTES_32 {} OP_CHECKSIGADD OP_PUSHBYTES_32 {} OP_CHECKSIGADD OP_PUSHNUM_2 OP_NUMEQUAL",
+                keys[0], keys[1], keys[2]
+            )
+        );
+    }
+
+    #[test]
+    fn sweep_requires_threshold_signatures() {
+        let recovery = recovery(Timelock::Relative(10));
+        let (_, internal_key) = keypair(9);
+        let secp = Secp256k1::verification_only();
+        let wallet = Address::p2tr(
+            &secp,
+            internal_key,
+            Some(recovery.merkle_root()),
+            Network::Regtest,
+        );
+        let destination = Address::p2tr(&secp, keypair(8).1, None, Network::Regtest);
+
+        // Utxos which are still timelocked are not swept
+        let utxos = vec![utxo(9, &wallet.script_pubkey())];
+        assert!(matches!(
+            recovery.build_sweep(internal_key, utxos, &destination, 1000),
+            Err(Error::NoRecoverableUtxos)
+        ));
+
+        let utxos = vec![
+            utxo(9, &wallet.script_pubkey()),
+            utxo(10, &wallet.script_pubkey()),
+        ];
+        let mut psbt = recovery
+            .build_sweep(internal_key, utxos, &destination, 1000)
+            .unwrap();
+        assert_eq!(psbt.unsigned_tx.input.len(), 1);
+        assert_eq!(
+            psbt.unsigned_tx.input[0].sequence,
+            Sequence::from_height(10)
+        );
+        assert_eq!(psbt.unsigned_tx.output[0].value, 99_000);
+
+        assert!(matches!(
+            recovery.sign_sweep(&mut psbt, &keypair(4).0),
+            Err(Error::UnknownKey(_))
+        ));
+        recovery.sign_sweep(&mut psbt, &keypair(3).0).unwrap();
+        assert!(matches!(
+            recovery.finalize_sweep(&mut psbt),
+            Err(Error::InsufficientSignatures {
+                signatures: 1,
+                threshold: 2,
+                ..
+            })
+        ));
+
+        recovery.sign_sweep(&mut psbt, &keypair(1).0).unwrap();
+        let tx = recovery.finalize_sweep(&mut psbt).unwrap();
+        let witness = tx.input[0].witness.to_vec();
+        // Signatures in reverse key order, then the script and the control block
+        assert_eq!(witness.len(), 5);
+        assert_eq!(witness[0].len(), 64);
+        assert!(witness[1].is_empty());
+        assert_eq!(witness[2].len(), 64);
+        assert_eq!(witness[3], recovery.script().to_bytes());
+
+        // With every key signing, only the first threshold signatures are pushed
+        recovery.sign_sweep(&mut psbt, &keypair(2).0).unwrap();
+        let tx = recovery.finalize_sweep(&mut psbt).unwrap();
+        let witness = tx.input[0].witness.to_vec();
+        assert_eq!(witness.len(), 5);
+        assert!(witness[0].is_empty());
+        assert_eq!(witness[1].len(), 64);
+        assert_eq!(witness[2].len(), 64);
+    }
+}
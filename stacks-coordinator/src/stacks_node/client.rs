@@ -6,13 +6,14 @@ use blockstack_lib::{
     chainstate::stacks::StacksTransaction,
     codec::StacksMessageCodec,
     types::chainstate::StacksAddress,
-    vm::{types::SequenceData, ClarityName, ContractName, Value as ClarityValue},
+    vm::{ContractName, Value as ClarityValue},
 };
 use frost_signer::config::{PublicKeys, SignerKeyIds};
 use reqwest::{
     blocking::{Client, Response},
     StatusCode,
 };
+use sbtc_core::clarity::{CoordinatorData, FromClarityValue, SignerData};
 use serde_json::{json, Value};
 use tracing::debug;
 use url::Url;
@@ -114,6 +115,17 @@ impl NodeClient {
         Ok(response)
     }
 
+    /// The chain id of the network the stacks node is running on
+    pub fn network_id(&self) -> Result<u32, StacksNodeError> {
+        debug!("Retrieving network id...");
+        let json = self.get_response("/v2/info")?.json::<Value>()?;
+        let entry = "network_id";
+        json[entry]
+            .as_u64()
+            .and_then(|network_id| u32::try_from(network_id).ok())
+            .ok_or_else(|| StacksNodeError::InvalidJsonEntry(entry.to_string()))
+    }
+
     fn get_burn_ops<T>(&self, block_height: u64, op: &str) -> Result<Vec<T>, StacksNodeError>
     where
         T: serde::de::DeserializeOwned,
@@ -125,96 +137,102 @@ impl NodeClient {
         Ok(serde_json::from_value(json[op].clone())?)
     }
 
-    fn num_signers(&self, sender: &StacksAddress) -> Result<u128, StacksNodeError> {
-        let function_name = "get-num-signers";
-        let total_signers_hex = self.call_read(sender, function_name, &[])?;
-        let total_signers = ClarityValue::try_deserialize_hex_untyped(&total_signers_hex)?;
-        if let ClarityValue::UInt(total_signers) = total_signers {
-            Ok(total_signers)
-        } else {
-            Err(StacksNodeError::MalformedClarityValue(
-                function_name.to_string(),
-                total_signers,
-            ))
+    /// The signers registered in the sBTC contract, keyed by signer ID
+    fn signers(
+        &self,
+        sender: &StacksAddress,
+    ) -> Result<(PublicKeys, SignerKeyIds), StacksNodeError> {
+        let total_signers = self.num_signers(sender)?;
+        let mut public_keys = PublicKeys::default();
+        let mut signer_key_ids = SignerKeyIds::default();
+        for id in 1..=total_signers {
+            let signer_data = self.signer_data(sender, id)?;
+            let signer_id = u32::try_from(id).map_err(|_| StacksNodeError::NoSignerData(id))?;
+            for key_id in &signer_data.key_ids {
+                public_keys.key_ids.insert(*key_id, signer_data.public_key);
+            }
+            public_keys
+                .signers
+                .insert(signer_id, signer_data.public_key);
+            signer_key_ids.insert(signer_id, signer_data.key_ids);
         }
+        Ok((public_keys, signer_key_ids))
+    }
+
+    fn num_signers(&self, sender: &StacksAddress) -> Result<u128, StacksNodeError> {
+        self.call_read_function(sender, "get-num-signers", &[])
+    }
+
+    fn signer_data(&self, sender: &StacksAddress, id: u128) -> Result<SignerData, StacksNodeError> {
+        self.call_read_function::<Option<SignerData>>(
+            sender,
+            "get-signer-data",
+            &[ClarityValue::UInt(id)],
+        )?
+        .ok_or(StacksNodeError::NoSignerData(id))
+    }
+
+    /// Call a read-only function of the sBTC contract and decode its result
+    pub fn call_read_function<T: FromClarityValue>(
+        &self,
+        sender: &StacksAddress,
+        function_name: &str,
+        function_args: &[ClarityValue],
+    ) -> Result<T, StacksNodeError> {
+        self.call_read_contract(
+            sender,
+            &self.contract_address,
+            &self.contract_name,
+            function_name,
+            function_args,
+        )
     }
 
-    fn signer_data(
+    /// Call a read-only function of any contract, e.g. one of the sbtc-mini contracts,
+    /// and decode its result
+    pub fn call_read_contract<T: FromClarityValue>(
         &self,
         sender: &StacksAddress,
-        id: u128,
-        public_keys: &mut PublicKeys,
-        signer_key_ids: &mut SignerKeyIds,
-    ) -> Result<(), StacksNodeError> {
-        let function_name = "get-signer-data";
-        let signer_data_hex = self.call_read(
+        contract_address: &StacksAddress,
+        contract_name: &ContractName,
+        function_name: &str,
+        function_args: &[ClarityValue],
+    ) -> Result<T, StacksNodeError> {
+        let result_hex = self.call_read(
             sender,
+            contract_address,
+            contract_name,
             function_name,
-            &[&ClarityValue::UInt(id).to_string()],
+            function_args,
         )?;
-        let signer_data = ClarityValue::try_deserialize_hex_untyped(&signer_data_hex)?;
-        if let ClarityValue::Optional(optional_data) = signer_data.clone() {
-            if let Some(ClarityValue::Tuple(tuple_data)) = optional_data.data.map(|boxed| *boxed) {
-                let public_key =
-                    if let Some(ClarityValue::Sequence(SequenceData::Buffer(public_key))) =
-                        tuple_data.data_map.get(&ClarityName::from("public-key"))
-                    {
-                        PublicKey::try_from(public_key.data.as_slice()).map_err(|_| {
-                            StacksNodeError::MalformedClarityValue(
-                                function_name.to_string(),
-                                signer_data.clone(),
-                            )
-                        })?
-                    } else {
-                        return Err(StacksNodeError::MalformedClarityValue(
-                            function_name.to_string(),
-                            signer_data,
-                        ));
-                    };
-                public_keys
-                    .signers
-                    .insert(id.try_into().unwrap(), public_key);
-                if let Some(ClarityValue::Sequence(SequenceData::List(keys_ids))) =
-                    tuple_data.data_map.get(&ClarityName::from("key-ids"))
-                {
-                    let mut this_signer_key_ids = Vec::new();
-                    for key_id in &keys_ids.data {
-                        if let ClarityValue::UInt(key_id) = key_id {
-                            public_keys
-                                .key_ids
-                                .insert((*key_id).try_into().unwrap(), public_key);
-                            this_signer_key_ids.push((*key_id).try_into().unwrap());
-                        } else {
-                            return Err(StacksNodeError::MalformedClarityValue(
-                                function_name.to_string(),
-                                signer_data,
-                            ));
-                        }
-                    }
-                    signer_key_ids.insert(id.try_into().unwrap(), this_signer_key_ids);
-                }
-            } else {
-                return Err(StacksNodeError::NoSignerData(id));
-            }
-        }
-        Err(StacksNodeError::MalformedClarityValue(
-            function_name.to_string(),
-            signer_data,
-        ))
+        let result = ClarityValue::try_deserialize_hex_untyped(&result_hex)?;
+        T::from_clarity_value(result)
+            .map_err(|e| StacksNodeError::MalformedClarityValue(function_name.to_string(), e))
     }
 
     fn call_read(
         &self,
         sender: &StacksAddress,
+        contract_address: &StacksAddress,
+        contract_name: &ContractName,
         function_name: &str,
-        function_args: &[&str],
+        function_args: &[ClarityValue],
     ) -> Result<String, StacksNodeError> {
-        debug!("Calling read-only function {}...", function_name);
-        let body = json!({"sender": sender.to_string(), "arguments": function_args}).to_string();
+        debug!(
+            "Calling read-only function {}.{}...",
+            contract_name.as_str(),
+            function_name
+        );
+        // Arguments are passed to the node as hex encoded consensus serialized values
+        let arguments: Vec<String> = function_args
+            .iter()
+            .map(|arg| format!("0x{}", hex::encode(arg.serialize_to_vec())))
+            .collect();
+        let body = json!({"sender": sender.to_string(), "arguments": arguments}).to_string();
         let url = self.build_url(&format!(
             "/v2/contracts/call-read/{}/{}/{function_name}",
-            self.contract_address,
-            self.contract_name.as_str()
+            contract_address,
+            contract_name.as_str()
         ))?;
         let response = self
             .client
@@ -318,112 +336,31 @@ impl StacksNode for NodeClient {
     }
 
     fn keys_threshold(&self, sender: &StacksAddress) -> Result<u128, StacksNodeError> {
-        let function_name = "get-threshold";
-        let threshold_hex = self.call_read(sender, function_name, &[])?;
-        let threshold = ClarityValue::try_deserialize_hex_untyped(&threshold_hex)?;
-        if let ClarityValue::UInt(keys_threshold) = threshold {
-            Ok(keys_threshold)
-        } else {
-            Err(StacksNodeError::MalformedClarityValue(
-                function_name.to_string(),
-                threshold,
-            ))
-        }
+        self.call_read_function(sender, "get-threshold", &[])
     }
 
     fn public_keys(&self, sender: &StacksAddress) -> Result<PublicKeys, StacksNodeError> {
-        let total_signers = self.num_signers(sender)?;
-        // Retrieve all the signers
-        let mut public_keys = PublicKeys::default();
-        let mut signer_key_ids = SignerKeyIds::default();
-        for id in 1..=total_signers {
-            self.signer_data(sender, id, &mut public_keys, &mut signer_key_ids)?;
-        }
-        Ok(public_keys)
+        Ok(self.signers(sender)?.0)
     }
 
     fn signer_key_ids(&self, sender: &StacksAddress) -> Result<SignerKeyIds, StacksNodeError> {
-        let total_signers = self.num_signers(sender)?;
-        // Retrieve all the signers
-        let mut public_keys = PublicKeys::default();
-        let mut signer_key_ids = SignerKeyIds::default();
-        for id in 1..=total_signers {
-            self.signer_data(sender, id, &mut public_keys, &mut signer_key_ids)?;
-        }
-        Ok(signer_key_ids)
+        Ok(self.signers(sender)?.1)
     }
 
     fn coordinator_public_key(
         &self,
         sender: &StacksAddress,
     ) -> Result<Option<PublicKey>, StacksNodeError> {
-        let function_name = "get-coordinator-data";
-        let coordinator_data_hex = self.call_read(sender, function_name, &[])?;
-        let coordinator_data = ClarityValue::try_deserialize_hex_untyped(&coordinator_data_hex)?;
-        if let ClarityValue::Optional(optional_data) = coordinator_data.clone() {
-            if let Some(ClarityValue::Tuple(tuple_data)) = optional_data.data.map(|boxed| *boxed) {
-                let value = tuple_data
-                    .data_map
-                    .get(&ClarityName::from("key"))
-                    .ok_or_else(|| {
-                        StacksNodeError::MalformedClarityValue(
-                            function_name.to_string(),
-                            coordinator_data.clone(),
-                        )
-                    })?;
-                if let ClarityValue::Sequence(SequenceData::Buffer(coordinator_public_key)) = value
-                {
-                    let public_key = PublicKey::try_from(coordinator_public_key.data.as_slice())
-                        .map_err(|_| {
-                            StacksNodeError::MalformedClarityValue(
-                                function_name.to_string(),
-                                coordinator_data,
-                            )
-                        })?;
-                    return Ok(Some(public_key));
-                } else {
-                    return Err(StacksNodeError::MalformedClarityValue(
-                        function_name.to_string(),
-                        coordinator_data,
-                    ));
-                }
-            }
-            Err(StacksNodeError::MalformedClarityValue(
-                function_name.to_string(),
-                coordinator_data,
-            ))
-        } else {
-            Ok(None)
-        }
+        let coordinator_data: Option<CoordinatorData> =
+            self.call_read_function(sender, "get-coordinator-data", &[])?;
+        Ok(coordinator_data.map(|data| data.key))
     }
 
     fn bitcoin_wallet_public_key(
         &self,
         sender: &StacksAddress,
     ) -> Result<Option<XOnlyPublicKey>, StacksNodeError> {
-        let function_name = "get-bitcoin-wallet-public-key";
-        let bitcoin_wallet_public_key_hex = self.call_read(sender, function_name, &[])?;
-        let bitcoin_wallet_public_key =
-            ClarityValue::try_deserialize_hex_untyped(&bitcoin_wallet_public_key_hex)?;
-        if let ClarityValue::Optional(optional_data) = bitcoin_wallet_public_key.clone() {
-            if let Some(ClarityValue::Sequence(SequenceData::Buffer(public_key))) =
-                optional_data.data.map(|boxed| *boxed)
-            {
-                let xonly_pubkey = XOnlyPublicKey::from_slice(&public_key.data).map_err(|_| {
-                    StacksNodeError::MalformedClarityValue(
-                        function_name.to_string(),
-                        bitcoin_wallet_public_key,
-                    )
-                })?;
-                return Ok(Some(xonly_pubkey));
-            } else {
-                return Ok(None);
-            }
-        }
-        Err(StacksNodeError::MalformedClarityValue(
-            function_name.to_string(),
-            bitcoin_wallet_public_key,
-        ))
+        self.call_read_function(sender, "get-bitcoin-wallet-public-key", &[])
     }
 }
 
@@ -443,6 +380,7 @@ mod tests {
             TransactionAuth, TransactionPayload, TransactionPostConditionMode,
             TransactionPublicKeyEncoding, TransactionSpendingCondition, TransactionVersion,
         },
+        core::CHAIN_ID_TESTNET,
         types::chainstate::{StacksPrivateKey, StacksPublicKey},
         util::{hash::Hash160, secp256k1::MessageSignature},
     };
@@ -450,6 +388,14 @@ mod tests {
     use crate::util::test::PRIVATE_KEY_HEX;
 
     use super::*;
+    use sbtc_core::clarity::Error as ClarityDecodeError;
+
+    /// Compressed secp256k1 generator point, used as a known valid public key
+    const GENERATOR_HEX: &str =
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+    /// Recorded `get-signer-data` result: (some {key-ids: (list u1 u2), public-key: G})
+    const SIGNER_DATA_HEX: &str = "0x0a0c00000002076b65792d6964730b00000002010000000000000000000000000000000101000000000000000000000000000000020a7075626c69632d6b657902000000210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
 
     struct TestConfig {
         sender: StacksAddress,
@@ -505,9 +451,14 @@ mod tests {
     fn call_read_success_test() {
         let config = TestConfig::new();
         let h = spawn(move || {
-            config
-                .client
-                .call_read(&config.sender, "function-name", &[])
+            let client = &config.client;
+            client.call_read(
+                &config.sender,
+                &client.contract_address,
+                &client.contract_name,
+                "function-name",
+                &[],
+            )
         });
         write_response(
             config.mock_server,
@@ -521,9 +472,14 @@ mod tests {
     fn call_read_failure_test() {
         let config = TestConfig::new();
         let h = spawn(move || {
-            config
-                .client
-                .call_read(&config.sender, "function-name", &[])
+            let client = &config.client;
+            client.call_read(
+                &config.sender,
+                &client.contract_address,
+                &client.contract_name,
+                "function-name",
+                &[],
+            )
         });
         write_response(
             config.mock_server,
@@ -537,13 +493,7 @@ mod tests {
     fn signer_data_none_test() {
         let config = TestConfig::new();
 
-        let h = spawn(move || {
-            let mut public_keys = PublicKeys::default();
-            let mut signer_key_ids = SignerKeyIds::default();
-            config
-                .client
-                .signer_data(&config.sender, 1u128, &mut public_keys, &mut signer_key_ids)
-        });
+        let h = spawn(move || config.client.signer_data(&config.sender, 1u128));
         write_response(
             config.mock_server,
             b"HTTP/1.1 200 OK\n\n{\"okay\":true,\"result\":\"0x09\"}",
@@ -552,6 +502,88 @@ mod tests {
         assert!(matches!(result, Err(StacksNodeError::NoSignerData(_))));
     }
 
+    #[test]
+    fn signer_data_test() {
+        let config = TestConfig::new();
+
+        let h = spawn(move || config.client.signer_data(&config.sender, 1u128));
+        let request_bytes = write_response(
+            config.mock_server,
+            format!("HTTP/1.1 200 OK\n\n{{\"okay\":true,\"result\":\"{SIGNER_DATA_HEX}\"}}")
+                .as_bytes(),
+        );
+        let signer_data = h.join().unwrap().unwrap();
+        assert_eq!(signer_data.key_ids, vec![1, 2]);
+
+        // The signer ID is passed as a hex encoded clarity uint
+        let request = String::from_utf8_lossy(&request_bytes);
+        assert!(request.contains("0x0100000000000000000000000000000001"));
+    }
+
+    #[test]
+    fn signer_data_invalid_public_key_test() {
+        let config = TestConfig::new();
+
+        let h = spawn(move || config.client.signer_data(&config.sender, 1u128));
+        write_response(config.mock_server, b"HTTP/1.1 200 OK\n\n{\"okay\":true,\"result\":\"0x0a0c00000002076b65792d6964730b0000000101000000000000000000000000000000010a7075626c69632d6b6579020000002079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\"}");
+        let result = h.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(StacksNodeError::MalformedClarityValue(
+                _,
+                ClarityDecodeError::InvalidValue { path, .. }
+            )) if path == ".some.public-key"
+        ));
+    }
+
+    #[test]
+    fn coordinator_public_key_test() {
+        let config = TestConfig::new();
+
+        let h = spawn(move || config.client.coordinator_public_key(&config.sender));
+        write_response(config.mock_server, b"HTTP/1.1 200 OK\n\n{\"okay\":true,\"result\":\"0x0a0c00000001036b657902000000210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\"}");
+        let result = h.join().unwrap().unwrap();
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn bitcoin_wallet_public_key_test() {
+        let config = TestConfig::new();
+
+        let h = spawn(move || config.client.bitcoin_wallet_public_key(&config.sender));
+        write_response(config.mock_server, b"HTTP/1.1 200 OK\n\n{\"okay\":true,\"result\":\"0x0a020000002079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\"}");
+        let result = h.join().unwrap().unwrap();
+        assert_eq!(
+            result,
+            Some(XOnlyPublicKey::from_slice(&hex::decode(GENERATOR_HEX).unwrap()[1..]).unwrap())
+        );
+    }
+
+    #[test]
+    fn call_read_contract_test() {
+        let config = TestConfig::new();
+
+        let h = spawn(move || {
+            let client = &config.client;
+            client.call_read_contract::<Result<u128, u128>>(
+                &config.sender,
+                &client.contract_address,
+                &ContractName::from("sbtc-registry"),
+                "get-burnchain-confirmations-required",
+                &[],
+            )
+        });
+        let request_bytes = write_response(
+            config.mock_server,
+            b"HTTP/1.1 200 OK\n\n{\"okay\":true,\"result\":\"0x070100000000000000000000000000000006\"}",
+        );
+        let result = h.join().unwrap().unwrap();
+        assert_eq!(result, Ok(6));
+
+        let request = String::from_utf8_lossy(&request_bytes);
+        assert!(request.contains("/sbtc-registry/get-burnchain-confirmations-required"));
+    }
+
     #[test]
     fn keys_threshold_test() {
         let config = TestConfig::new();
@@ -663,6 +695,19 @@ mod tests {
         assert!(matches!(result, Err(StacksNodeError::InvalidJsonEntry(_))));
     }
 
+    #[test]
+    fn network_id_test() {
+        let config = TestConfig::new();
+
+        let h = spawn(move || config.client.network_id());
+        write_response(
+            config.mock_server,
+            b"HTTP/1.1 200 OK\n\n{\"peer_version\":4207599105,\"network_id\":2147483648}",
+        );
+        let result = h.join().unwrap().unwrap();
+        assert_eq!(result, CHAIN_ID_TESTNET);
+    }
+
     #[test]
     fn should_send_tx_bytes_to_node() {
         let config = TestConfig::new();
@@ -5,9 +5,10 @@ use blockstack_lib::{
     chainstate::{burn::operations as burn_ops, stacks::StacksTransaction},
     codec::Error as CodecError,
     types::chainstate::StacksAddress,
-    vm::{types::serialization::SerializationError, Value as ClarityValue},
+    vm::types::serialization::SerializationError,
 };
 use frost_signer::config::{PublicKeys, SignerKeyIds};
+use sbtc_core::clarity::Error as ClarityDecodeError;
 use wsts::ecdsa::PublicKey;
 
 use self::client::BroadcastError;
@@ -41,8 +42,8 @@ pub enum Error {
     NoCoordinatorData,
     #[error("No signer data found for signer ID {0}")]
     NoSignerData(u128),
-    #[error("Recieved a malformed clarity value from {0} contract call: {1}")]
-    MalformedClarityValue(String, ClarityValue),
+    #[error("Received a malformed clarity value from {0} contract call: {1}")]
+    MalformedClarityValue(String, ClarityDecodeError),
     #[error("Error occurred deserializing clarity value: {0}")]
     Utf8Error(#[from] std::str::Utf8Error),
     #[error("URL Parse Error: {0}")]
@@ -1,7 +1,7 @@
 use crate::{
     peg_wallet::{Error as PegWalletError, StacksWallet as StacksWalletTrait},
     stacks_node::{PegInOp, PegOutRequestOp},
-    util::address_version,
+    util::{address_version, chain_id},
 };
 use bitcoin::XOnlyPublicKey;
 use blockstack_lib::{
@@ -10,7 +10,6 @@ use blockstack_lib::{
         TransactionContractCall, TransactionPayload, TransactionPostConditionMode,
         TransactionSpendingCondition, TransactionVersion,
     },
-    core::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET},
     types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey},
     vm::{
         errors::RuntimeErrorType,
@@ -45,6 +44,7 @@ pub struct StacksWallet {
     public_key: StacksPublicKey,
     address: StacksAddress,
     version: TransactionVersion,
+    chain_id: u32,
     fee: u64,
 }
 
@@ -65,10 +65,17 @@ impl StacksWallet {
             public_key,
             address,
             version,
+            chain_id: chain_id(&version),
             fee,
         }
     }
 
+    /// Use a custom chain id instead of the one of the transaction version
+    pub fn with_chain_id(mut self, chain_id: u32) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
     fn build_transaction_signed(
         &self,
         function_name: impl Into<String>,
@@ -117,11 +124,7 @@ impl StacksWallet {
         let mut unsigned_tx = StacksTransaction::new(self.version, auth, payload);
         unsigned_tx.anchor_mode = TransactionAnchorMode::Any;
         unsigned_tx.post_condition_mode = TransactionPostConditionMode::Allow;
-        unsigned_tx.chain_id = if self.version == TransactionVersion::Testnet {
-            CHAIN_ID_TESTNET
-        } else {
-            CHAIN_ID_MAINNET
-        };
+        unsigned_tx.chain_id = self.chain_id;
 
         Ok(unsigned_tx)
     }
@@ -1,6 +1,7 @@
 use blockstack_lib::{
     address::{C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG},
     chainstate::stacks::TransactionVersion,
+    core::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET},
 };
 
 pub fn address_version(version: &TransactionVersion) -> u8 {
@@ -10,6 +11,13 @@ pub fn address_version(version: &TransactionVersion) -> u8 {
     }
 }
 
+pub fn chain_id(version: &TransactionVersion) -> u32 {
+    match version {
+        TransactionVersion::Mainnet => CHAIN_ID_MAINNET,
+        TransactionVersion::Testnet => CHAIN_ID_TESTNET,
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use blockstack_lib::{
@@ -1,6 +1,16 @@
 use std::str::FromStr;
 
-use bitcoin::{Address, Network, OutPoint};
+use bitcoin::{
+    blockdata::{opcodes::all::*, script::Builder},
+    hashes::Hash,
+    schnorr::TweakedPublicKey,
+    secp256k1::{KeyPair, Message, Secp256k1},
+    util::{
+        sighash::{Prevouts, SighashCache},
+        taproot::{LeafVersion, TapBranchHash, TapLeafHash, TaprootBuilder},
+    },
+    Address, Network, OutPoint, SchnorrSighashType, Script, Sequence, XOnlyPublicKey,
+};
 use stacks_coordinator::{
     bitcoin_node::{BitcoinNode, LocalhostBitcoinNode},
     bitcoin_wallet::BitcoinWallet,
@@ -11,6 +21,14 @@ use test_utils::{
     mine_and_get_coinbase_txid, sign_transaction_ecdsa, sign_transaction_taproot, BitcoinProcess,
     SignerHelper,
 };
+use wsts::Point;
+
+#[test]
+fn should_report_regtest_network() {
+    let btcd = BitcoinProcess::new();
+    let local_btc_node = LocalhostBitcoinNode::new(btcd.url().clone());
+    assert_eq!(local_btc_node.network().unwrap(), Network::Regtest);
+}
 
 #[test]
 fn should_broadcast_transaction() {
@@ -155,3 +173,115 @@ fn should_list_unspent() {
 
     assert!(!utxos.is_empty());
 }
+
+#[test]
+fn should_spend_tweaked_wallet_through_key_and_script_paths() {
+    let btcd = BitcoinProcess::new();
+    let local_btc_node = LocalhostBitcoinNode::new(btcd.url().clone());
+    let secp = Secp256k1::new();
+
+    // A recovery leaf which lets a single recovery key spend after a relative timelock
+    let recovery_blocks: u16 = 10;
+    let (recovery_secret_key, _, _, recovery_public_key, _, _) = generate_wallet(true);
+    let recovery_script = Builder::new()
+        .push_int(recovery_blocks.into())
+        .push_opcode(OP_CSV)
+        .push_opcode(OP_DROP)
+        .push_x_only_key(&recovery_public_key)
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+    let recovery_leaf = TapLeafHash::from_script(&recovery_script, LeafVersion::TapScript);
+    // The merkle root of a single leaf tree is the leaf hash
+    let merkle_root = TapBranchHash::from_inner(recovery_leaf.into_inner());
+
+    let mut signer = SignerHelper::default();
+    let (public_commitments, tweaked_public_key_point, _) =
+        signer.run_distributed_key_generation(Some(merkle_root.into_inner()));
+    let aggregate_public_key = public_commitments
+        .iter()
+        .fold(Point::default(), |sum, poly| sum + poly.A[0]);
+    let internal_key = XOnlyPublicKey::from_slice(&aggregate_public_key.x().to_bytes()).unwrap();
+
+    // The wallet address commits to the same output key the signers sign for
+    let wallet = BitcoinWallet::new_taproot(internal_key, Some(merkle_root), Network::Regtest);
+    let output_key = XOnlyPublicKey::from_slice(&tweaked_public_key_point.x().to_bytes()).unwrap();
+    assert_eq!(
+        wallet.address().script_pubkey(),
+        Script::new_v1_p2tr_tweaked(TweakedPublicKey::dangerous_assume_tweaked(output_key))
+    );
+
+    // Fund the wallet twice, once for each spend path
+    let (key_path_txid, key_path_blockhash) = mine_and_get_coinbase_txid(&btcd, wallet.address());
+    let (script_path_txid, script_path_blockhash) =
+        mine_and_get_coinbase_txid(&btcd, wallet.address());
+    let (_, _, destination_public_key, _, _, _) = generate_wallet(false);
+
+    // Key path: the signers sign with the tweaked aggregate key
+    let key_path_tx = get_raw_transaction(&btcd, &key_path_txid, Some(key_path_blockhash)).unwrap();
+    let key_path_utxo = &key_path_tx.output[0];
+    let mut withdrawal_tx = build_transaction_withdrawal(
+        key_path_utxo.value - 1000,
+        destination_public_key,
+        OutPoint {
+            txid: key_path_txid,
+            vout: 0,
+        },
+    );
+    sign_transaction_taproot(
+        &mut withdrawal_tx,
+        key_path_utxo,
+        &mut signer,
+        &tweaked_public_key_point,
+        public_commitments,
+        Some(merkle_root.into_inner()),
+    );
+    let withdrawal_txid = local_btc_node
+        .broadcast_transaction(&withdrawal_tx)
+        .unwrap();
+    assert!(get_raw_transaction(&btcd, &withdrawal_txid, None).is_ok());
+
+    // Script path: the recovery key spends once the timelock has passed
+    let script_path_tx =
+        get_raw_transaction(&btcd, &script_path_txid, Some(script_path_blockhash)).unwrap();
+    let script_path_utxo = &script_path_tx.output[0];
+    let mut recovery_tx = build_transaction_withdrawal(
+        script_path_utxo.value - 1000,
+        destination_public_key,
+        OutPoint {
+            txid: script_path_txid,
+            vout: 0,
+        },
+    );
+    recovery_tx.input[0].sequence = Sequence::from_height(recovery_blocks);
+
+    let sighash = SighashCache::new(&recovery_tx)
+        .taproot_script_spend_signature_hash(
+            0,
+            &Prevouts::All(&[script_path_utxo]),
+            recovery_leaf,
+            SchnorrSighashType::Default,
+        )
+        .unwrap();
+    let signature = secp.sign_schnorr_no_aux_rand(
+        &Message::from_slice(&sighash.into_inner()).unwrap(),
+        &KeyPair::from_secret_key(&secp, &recovery_secret_key),
+    );
+    let spend_info = TaprootBuilder::new()
+        .add_leaf(0, recovery_script.clone())
+        .unwrap()
+        .finalize(&secp, internal_key)
+        .unwrap();
+    assert_eq!(spend_info.merkle_root(), Some(merkle_root));
+    let control_block = spend_info
+        .control_block(&(recovery_script.clone(), LeafVersion::TapScript))
+        .unwrap();
+
+    recovery_tx.input[0].witness.push(&signature[..]);
+    recovery_tx.input[0]
+        .witness
+        .push(recovery_script.as_bytes());
+    recovery_tx.input[0].witness.push(control_block.serialize());
+
+    let recovery_txid = local_btc_node.broadcast_transaction(&recovery_tx).unwrap();
+    assert!(get_raw_transaction(&btcd, &recovery_txid, None).is_ok());
+}
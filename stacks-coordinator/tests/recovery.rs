@@ -0,0 +1,130 @@
+use bitcoin::{secp256k1::SecretKey, Address, Network, XOnlyPublicKey};
+use stacks_coordinator::{
+    bitcoin_node::{BitcoinNode, LocalhostBitcoinNode},
+    bitcoin_wallet::BitcoinWallet,
+    peg_wallet::BitcoinWallet as BitcoinWalletTrait,
+    recovery::{Error as RecoveryError, Recovery, Timelock},
+};
+use test_utils::{generate_wallet, get_raw_transaction, BitcoinProcess};
+
+fn recovery_keys() -> Vec<(SecretKey, XOnlyPublicKey)> {
+    (0..3)
+        .map(|_| {
+            let (secret_key, _, _, public_key, _, _) = generate_wallet(true);
+            (secret_key, public_key)
+        })
+        .collect()
+}
+
+fn mine(btcd: &BitcoinProcess, blocks: u64, address: &Address) {
+    btcd.rpc("generatetoaddress", (blocks, address.to_string()));
+}
+
+/// Fund a peg wallet committing to the recovery leaf with mature coinbase utxos.
+/// Its internal key belongs to a signer set which has lost its threshold.
+fn funded_wallet(btcd: &BitcoinProcess, recovery: &Recovery) -> (BitcoinWallet, Address) {
+    let (_, _, _, internal_key, _, _) = generate_wallet(true);
+    let wallet =
+        BitcoinWallet::new_taproot(internal_key, Some(recovery.merkle_root()), Network::Regtest);
+    let (_, _, _, _, miner_address, _) = generate_wallet(false);
+
+    let local_btc_node = LocalhostBitcoinNode::new(btcd.url().clone());
+    local_btc_node.load_wallet(wallet.address()).unwrap();
+    mine(btcd, 100, wallet.address());
+    mine(btcd, 100, &miner_address);
+    (wallet, miner_address)
+}
+
+#[test]
+fn should_sweep_wallet_through_csv_recovery_leaf() {
+    let btcd = BitcoinProcess::new();
+    let local_btc_node = LocalhostBitcoinNode::new(btcd.url().clone());
+    let keys = recovery_keys();
+    let recovery = Recovery::new(
+        keys.iter().map(|(_, public_key)| *public_key).collect(),
+        2,
+        Timelock::Relative(250),
+    )
+    .unwrap();
+    let (wallet, miner_address) = funded_wallet(&btcd, &recovery);
+    let (_, _, _, _, destination, _) = generate_wallet(false);
+
+    // The oldest utxo has 200 confirmations, so nothing is recoverable yet
+    let mut utxos = local_btc_node.list_unspent(wallet.address()).unwrap();
+    assert!(!utxos.is_empty());
+    assert!(matches!(
+        recovery.build_sweep(*wallet.x_only_pub_key(), utxos.clone(), &destination, 1000),
+        Err(RecoveryError::NoRecoverableUtxos)
+    ));
+
+    // Consensus rejects a sweep before the delay has passed
+    utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.confirmations));
+    let mut early_utxo = utxos[0].clone();
+    early_utxo.confirmations = 250;
+    let mut psbt = recovery
+        .build_sweep(
+            *wallet.x_only_pub_key(),
+            vec![early_utxo],
+            &destination,
+            1000,
+        )
+        .unwrap();
+    recovery.sign_sweep(&mut psbt, &keys[0].0).unwrap();
+    recovery.sign_sweep(&mut psbt, &keys[2].0).unwrap();
+    let early_sweep = recovery.finalize_sweep(&mut psbt).unwrap();
+    assert!(local_btc_node.broadcast_transaction(&early_sweep).is_err());
+
+    // Mine past the delay of the oldest utxo
+    mine(&btcd, 50, &miner_address);
+    let utxos = local_btc_node.list_unspent(wallet.address()).unwrap();
+    let mut psbt = recovery
+        .build_sweep(*wallet.x_only_pub_key(), utxos, &destination, 1000)
+        .unwrap();
+    assert_eq!(psbt.unsigned_tx.input.len(), 1);
+
+    // A single recovery key is not enough
+    recovery.sign_sweep(&mut psbt, &keys[1].0).unwrap();
+    assert!(matches!(
+        recovery.finalize_sweep(&mut psbt.clone()),
+        Err(RecoveryError::InsufficientSignatures { .. })
+    ));
+    recovery.sign_sweep(&mut psbt, &keys[2].0).unwrap();
+    // Signatures beyond the threshold are not used, so every recovery key may sign
+    recovery.sign_sweep(&mut psbt, &keys[0].0).unwrap();
+    let sweep = recovery.finalize_sweep(&mut psbt).unwrap();
+
+    let sweep_txid = local_btc_node.broadcast_transaction(&sweep).unwrap();
+    assert!(get_raw_transaction(&btcd, &sweep_txid, None).is_ok());
+    assert_eq!(sweep.output[0].script_pubkey, destination.script_pubkey());
+}
+
+#[test]
+fn should_sweep_wallet_through_cltv_recovery_leaf() {
+    let btcd = BitcoinProcess::new();
+    let local_btc_node = LocalhostBitcoinNode::new(btcd.url().clone());
+    let keys = recovery_keys();
+    // The wallet is funded up to block height 200
+    let recovery = Recovery::new(
+        keys.iter().map(|(_, public_key)| *public_key).collect(),
+        3,
+        Timelock::Absolute(210),
+    )
+    .unwrap();
+    let (wallet, miner_address) = funded_wallet(&btcd, &recovery);
+    let (_, _, _, _, destination, _) = generate_wallet(false);
+
+    let utxos = local_btc_node.list_unspent(wallet.address()).unwrap();
+    let mut psbt = recovery
+        .build_sweep(*wallet.x_only_pub_key(), utxos, &destination, 100_000)
+        .unwrap();
+    for (secret_key, _) in &keys {
+        recovery.sign_sweep(&mut psbt, secret_key).unwrap();
+    }
+    let sweep = recovery.finalize_sweep(&mut psbt).unwrap();
+
+    // The sweep is not final until the lock height is reached
+    assert!(local_btc_node.broadcast_transaction(&sweep).is_err());
+    mine(&btcd, 10, &miner_address);
+    let sweep_txid = local_btc_node.broadcast_transaction(&sweep).unwrap();
+    assert!(get_raw_transaction(&btcd, &sweep_txid, None).is_ok());
+}
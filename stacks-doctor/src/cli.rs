@@ -1,33 +1,15 @@
-use core::fmt::{self, Formatter};
-use std::{
-    fmt::{Debug, Display},
-    path::PathBuf,
-    str::FromStr,
-};
+use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 
-#[derive(Clone, Debug)]
-pub enum Network {
-    Mainnet,
-    Testnet,
-}
-
-impl Display for Network {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-impl FromStr for Network {
-    type Err = String;
+pub use sbtc_core::Network;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_ascii_lowercase().as_str() {
-            "mainnet" => Ok(Network::Mainnet),
-            "testnet" => Ok(Network::Testnet),
-            _ => Err(format!("Could not parse Network: {}", s)),
-        }
+/// The directory the node keeps the network's databases in, named after its burnchain mode
+pub fn mode_dir(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => "mainnet/",
+        Network::Testnet | Network::Signet => "xenon/",
+        Network::Regtest | Network::Devnet => "krypton/",
     }
 }
 
@@ -55,7 +37,14 @@ pub enum Commands {
 #[command(author, version, about)]
 pub struct Args {
     /// Which network to analyze
-    #[arg(short, long, default_value_t = Network::Mainnet, env = "DOCTOR_NETWORK")]
+    #[arg(
+        short,
+        long,
+        value_enum,
+        ignore_case = true,
+        default_value_t = Network::Mainnet,
+        env = "DOCTOR_NETWORK"
+    )]
     pub network: Network,
 
     /// URL to the node RPC API
@@ -3,14 +3,12 @@ use std::path::Path;
 use anyhow::{anyhow, Context, Result};
 use rusqlite::OpenFlags;
 
-use crate::cli::{BlocksArgs, Network};
+use crate::cli::{mode_dir, BlocksArgs, Network};
 
 pub fn burns(network: Network, db_dir: &Path, args: &BlocksArgs) -> Result<()> {
-    let mode = match network {
-        Network::Mainnet => "mainnet/",
-        Network::Testnet => "xenon/",
-    };
-    let db_file = db_dir.join(mode).join("burnchain/burnchain.sqlite");
+    let db_file = db_dir
+        .join(mode_dir(network))
+        .join("burnchain/burnchain.sqlite");
     let conn = rusqlite::Connection::open_with_flags(db_file, OpenFlags::SQLITE_OPEN_READ_ONLY)
         .context("Could not open database connection")?;
 
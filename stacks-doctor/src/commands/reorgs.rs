@@ -5,7 +5,7 @@ use rusqlite::OpenFlags;
 use serde::Serialize;
 use serde_json::to_string_pretty;
 
-use crate::cli::{BlocksArgs, Network};
+use crate::cli::{mode_dir, BlocksArgs, Network};
 
 #[derive(Serialize)]
 struct Item {
@@ -25,11 +25,9 @@ struct Response {
 }
 
 pub fn reorgs(network: Network, db_dir: &Path, args: &BlocksArgs) -> Result<()> {
-    let mode = match network {
-        Network::Mainnet => "mainnet/",
-        Network::Testnet => "xenon/",
-    };
-    let db_file = db_dir.join(mode).join("chainstate/vm/index.sqlite");
+    let db_file = db_dir
+        .join(mode_dir(network))
+        .join("chainstate/vm/index.sqlite");
     let conn = rusqlite::Connection::open_with_flags(db_file, OpenFlags::SQLITE_OPEN_READ_ONLY)
         .context("Could not open database connection")?;
 
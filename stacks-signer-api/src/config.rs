@@ -1,3 +1,4 @@
+use frost_signer::config_loader::{self, ConfigOverrides};
 use secp256k1::{PublicKey, Secp256k1, SecretKey};
 use serde::{Deserialize, Serialize};
 use utoipa::{ToResponse, ToSchema};
@@ -19,6 +20,9 @@ pub enum Error {
     /// Config error due to toml deserialization error
     #[error("Toml Error: {0}")]
     TomlError(#[from] toml::de::Error),
+    /// Config error due to invalid environment or command line overrides
+    #[error("Config Loader Error: {0}")]
+    LoaderError(#[from] config_loader::Error),
 }
 
 #[derive(serde::Deserialize)]
@@ -39,16 +43,25 @@ pub struct RawConfig {
 impl RawConfig {
     /// Try to create a raw configuration from a given path to a TOML file.
     pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
-        let raw_config: RawConfig = toml::from_str(&std::fs::read_to_string(path)?)?;
-        Ok(raw_config)
+        Ok(config_loader::deserialize(config_loader::read(path)?)?)
+    }
+
+    /// Try to create a raw configuration from a given path to a TOML file, with SBTC_* environment
+    /// and command line overrides.
+    pub fn load(
+        path: impl AsRef<std::path::Path>,
+        overrides: &ConfigOverrides,
+    ) -> Result<Self, Error> {
+        Ok(config_loader::load(Some(path), overrides)?)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize, ToResponse, ToSchema)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, ToResponse, ToSchema)]
 /// A signer configuration.
 pub struct Config {
-    /// The signer's secret key.
-    #[schema(value_type = String)]
+    /// The signer's secret key. Never included in responses.
+    #[serde(skip_serializing)]
+    #[schema(value_type = String, write_only)]
     pub secret_key: SecretKey,
     /// The maximum dollar amount of a transaction that will be auto approved
     pub auto_approve_max_amount: u64,
@@ -62,6 +75,40 @@ pub struct Config {
     pub auto_deny_addresses: Vec<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+/// An update of the signer configuration.
+pub struct ConfigUpdate {
+    /// The signer's new secret key. The current secret key is kept if not provided.
+    #[schema(value_type = Option<String>, write_only)]
+    pub secret_key: Option<SecretKey>,
+    /// The maximum dollar amount of a transaction that will be auto approved
+    pub auto_approve_max_amount: u64,
+    /// The public key of the signer being delegated to
+    #[schema(value_type = String)]
+    pub delegate_public_key: PublicKey,
+    /// The public keys of signers that this signer has agreed to sign on behalf of
+    #[schema(value_type = Vec<String>)]
+    pub delegator_public_keys: Vec<PublicKey>,
+    /// The addresses to be auto denied
+    pub auto_deny_addresses: Vec<String>,
+}
+
+impl ConfigUpdate {
+    /// Apply the update to the configuration.
+    pub fn apply(self, config: Option<Config>) -> Option<Config> {
+        let secret_key = self
+            .secret_key
+            .or_else(|| config.map(|config| config.secret_key))?;
+        Some(Config {
+            secret_key,
+            auto_approve_max_amount: self.auto_approve_max_amount,
+            delegate_public_key: self.delegate_public_key,
+            delegator_public_keys: self.delegator_public_keys,
+            auto_deny_addresses: self.auto_deny_addresses,
+        })
+    }
+}
+
 impl Config {
     /// Create a new signer configuration with a given secret key.
     pub fn new(secret_key: SecretKey) -> Self {
@@ -88,6 +135,15 @@ impl Config {
         let raw_config = RawConfig::from_path(path)?;
         Config::try_from(raw_config)
     }
+
+    /// Try to create a configuration from a given path to a TOML file, with SBTC_* environment
+    /// and command line overrides.
+    pub fn load(
+        path: impl AsRef<std::path::Path>,
+        overrides: &ConfigOverrides,
+    ) -> Result<Self, Error> {
+        Config::try_from(RawConfig::load(path, overrides)?)
+    }
 }
 
 impl TryFrom<RawConfig> for Config {
@@ -1,8 +1,9 @@
 use clap::Parser;
+use frost_signer::config_loader::{self, ConfigCheck, ConfigOverrides};
 use rand::Rng;
 use sqlx::SqlitePool;
 use stacks_signer_api::{
-    config::Config,
+    config::{Config, ConfigUpdate},
     db::{self, transaction::add_transaction, vote::add_vote},
     error::{ErrorCode, ErrorResponse},
     routes::all_routes,
@@ -51,7 +52,8 @@ const TEST_SECRET_KEY: &str = "26F85CE8B2C635AD92F6148E4443FE415F512F3F29F44AB0E
             VoteTally,
             ErrorCode,
             ErrorResponse,
-            Config
+            Config,
+            ConfigUpdate
         ),
         responses(TransactionResponse, VoteResponse, Config, ErrorResponse)
     )
@@ -72,6 +74,7 @@ enum Command {
     Swagger(SwaggerArgs),
     Simulator(SimulatorArgs),
     Run(RunArgs),
+    Config(ConfigArgs),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -114,29 +117,60 @@ struct RunArgs {
     #[command(flatten)]
     pub server: ServerArgs,
     /// Optional path to a signer configuration file
-    /// Required if env DATABASE_URL is not set or no config is found in the database
+    /// Required if env SBTC_DATABASE_URL or DATABASE_URL is not set or no config is found in the database
     #[arg(short, long)]
     pub config: Option<String>,
 }
 
+#[derive(Parser, Debug, Clone)]
+struct ConfigArgs {
+    /// Path to a signer configuration file
+    #[arg(short, long)]
+    pub config: String,
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+/// Configuration subcommands
+#[derive(clap::Subcommand, Debug, Clone)]
+enum ConfigCommand {
+    /// Print the configuration with secrets redacted
+    Show,
+    /// Validate the configuration and connect to the database
+    Check,
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     /// Subcommand action to take
     #[command(subcommand)]
     command: Command,
+
+    #[command(flatten)]
+    overrides: ConfigOverrides,
 }
 
-async fn verify_config(pool: &SqlitePool, config_path: Option<String>) -> anyhow::Result<()> {
-    let database_url = env::var("DATABASE_URL").ok();
-    if database_url.is_none() && config_path.is_none() {
+/// The database URL from SBTC_DATABASE_URL, falling back to DATABASE_URL
+fn database_url() -> Option<String> {
+    env::var("SBTC_DATABASE_URL")
+        .or_else(|_| env::var("DATABASE_URL"))
+        .ok()
+}
+
+async fn verify_config(
+    pool: &SqlitePool,
+    config_path: Option<String>,
+    overrides: &ConfigOverrides,
+) -> anyhow::Result<()> {
+    if database_url().is_none() && config_path.is_none() {
         return Err(anyhow::anyhow!(
-            "No DATABASE_URL env variable set or config path provided"
+            "No SBTC_DATABASE_URL or DATABASE_URL env variable set or config path provided"
         ));
     }
     // If we have a config path, try to load the config from the file
     if let Some(path) = config_path {
-        let config = Config::from_path(path)
+        let config = Config::load(path, overrides)
             .map_err(|e| anyhow::anyhow!("Failed to load config from file: {}", e))?;
         db::config::update_config(pool, &config).await?;
     } else {
@@ -152,12 +186,50 @@ async fn verify_config(pool: &SqlitePool, config_path: Option<String>) -> anyhow
 async fn init_pool() -> anyhow::Result<SqlitePool> {
     let _ = dotenv::dotenv();
     // Initialize the connection pool__
-    let pool = db::init_pool(env::var("DATABASE_URL").ok())
+    let pool = db::init_pool(database_url())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to initialize database connection pool: {}", e))?;
     Ok(pool)
 }
 
+/// Print or check the signer configuration
+async fn config(args: &ConfigArgs, overrides: &ConfigOverrides) -> anyhow::Result<()> {
+    match args.command {
+        ConfigCommand::Show => {
+            let value = config_loader::load_value(Some(&args.config), overrides)
+                .map_err(|e| anyhow::anyhow!("Failed to load config from file: {}", e))?;
+            print!("{}", config_loader::redact(value));
+            Ok(())
+        }
+        ConfigCommand::Check => {
+            let checks = vec![
+                ConfigCheck::new(
+                    "config",
+                    Config::load(&args.config, overrides)
+                        .map(|config| format!("delegate {}", config.delegate_public_key))
+                        .map_err(|e| e.to_string()),
+                ),
+                ConfigCheck::new(
+                    "database_url",
+                    init_pool()
+                        .await
+                        .map(|_| "connected".to_string())
+                        .map_err(|e| config_loader::redact_urls(&e.to_string())),
+                ),
+            ];
+            for check in &checks {
+                let status = if check.ok { "ok" } else { "FAILED" };
+                println!("{} {}: {}", status, check.name, check.message);
+            }
+            if checks.iter().all(|check| check.ok) {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("Configuration check failed"))
+            }
+        }
+    }
+}
+
 /// Run the Signer API server on the provided port and address
 async fn run(pool: SqlitePool, server_args: ServerArgs) -> anyhow::Result<()> {
     // Create the routes
@@ -288,11 +360,12 @@ async fn main() {
         Command::Docs(args) => generate_docs(&args.output),
         Command::Swagger(args) => run_swagger(&args).await,
         Command::Simulator(args) => run_simulator(args).await,
+        Command::Config(args) => config(&args, &cli.overrides).await,
         Command::Run(args) => {
             // Initialize the connection pool
             match init_pool().await {
                 Ok(pool) => {
-                    if let Err(e) = verify_config(&pool, args.config).await {
+                    if let Err(e) = verify_config(&pool, args.config, &cli.overrides).await {
                         println!("Error occurred running API server: {}", e);
                         return;
                     }
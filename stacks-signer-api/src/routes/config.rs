@@ -1,7 +1,7 @@
 use std::convert::Infallible;
 
 use crate::{
-    config::Config,
+    config::ConfigUpdate,
     db,
     routes::{json_body, with_pool},
 };
@@ -22,7 +22,7 @@ pub fn update_config_route(
     warp::post()
         .and(warp::path!("v1" / "config"))
         .and(warp::path::end())
-        .and(json_body::<Config>())
+        .and(json_body::<ConfigUpdate>())
         .and(with_pool(pool))
         .and_then(update_config)
 }
@@ -49,17 +49,23 @@ pub fn get_config_route(
 #[utoipa::path(
     post,
     path = "/v1/config",
-    request_body = Config,
+    request_body = ConfigUpdate,
     responses(
         (status = OK, description = "Config updated successfully."),
         (status = INTERNAL_SERVER_ERROR, description = "Internal server error occurred.", body = ErrorResponse)
     ),
 )]
-pub async fn update_config(config: Config, pool: SqlitePool) -> Result<Box<dyn Reply>, Infallible> {
-    if db::config::update_config(&pool, &config).await.is_ok() {
-        Ok(Box::new(StatusCode::OK))
-    } else {
-        Ok(Box::new(StatusCode::NOT_FOUND))
+pub async fn update_config(
+    update: ConfigUpdate,
+    pool: SqlitePool,
+) -> Result<Box<dyn Reply>, Infallible> {
+    // Without a secret key in the update, keep the current one
+    let current = db::config::get_config(&pool).await.ok();
+    match update.apply(current) {
+        Some(config) if db::config::update_config(&pool, &config).await.is_ok() => {
+            Ok(Box::new(StatusCode::OK))
+        }
+        _ => Ok(Box::new(StatusCode::NOT_FOUND)),
     }
 }
 
@@ -86,7 +92,7 @@ pub async fn get_config(pool: SqlitePool) -> Result<Box<dyn Reply>, Infallible>
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
+    use crate::config::{Config, ConfigUpdate};
     use crate::db::init_pool;
     use secp256k1::PublicKey;
     use std::str::FromStr;
@@ -172,9 +178,16 @@ mod tests {
             .reply(&get_config_route(pool))
             .await;
         let body = api.body();
-        let config: Config = serde_json::from_slice(body).expect("failed to deserialize config");
-
-        assert_eq!(config.secret_key, expected_config.secret_key);
+        let response: serde_json::Value =
+            serde_json::from_slice(body).expect("failed to deserialize config");
+        assert!(response.get("secret_key").is_none());
+        assert!(!String::from_utf8_lossy(body)
+            .to_lowercase()
+            .contains(&TEST_SECRET_KEY_1.to_lowercase()));
+        let config: ConfigUpdate =
+            serde_json::from_value(response).expect("failed to deserialize config");
+
+        assert!(config.secret_key.is_none());
         assert_eq!(
             config.delegate_public_key,
             expected_config.delegate_public_key
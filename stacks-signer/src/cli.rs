@@ -1,5 +1,6 @@
 use crate::secp256k1::Secp256k1;
 use clap::{Parser, Subcommand};
+use frost_signer::{config_loader::ConfigOverrides, keystore::KeystoreCommand};
 
 ///Command line interface for stacks signer
 #[derive(Parser)]
@@ -8,6 +9,9 @@ pub struct Cli {
     /// Subcommand action to take
     #[clap(subcommand)]
     pub command: Command,
+
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
 }
 
 /// Possible actions that stacks signer can perform
@@ -21,6 +25,10 @@ pub enum Command {
         /// Config file path
         #[arg(short, long)]
         config: String,
+        /// Read the signers, key ids, threshold and coordinator from the sBTC contract instead
+        /// of the config file, and roll over to new signer sets as they are registered
+        #[arg(long)]
+        from_contract: bool,
     },
     /// Generate Secp256k1 Private Key
     PrivateKey(Secp256k1),
@@ -30,4 +38,32 @@ pub enum Command {
         #[arg(short, long)]
         config: String,
     },
+    /// Print or check the configuration after environment and command line overrides
+    Config {
+        /// Config file path
+        #[arg(short, long)]
+        config: String,
+        #[clap(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Create, import or inspect an encrypted keystore of private keys
+    Keystore {
+        #[clap(subcommand)]
+        command: KeystoreCommand,
+    },
+    /// Query the status endpoint of a running signer, failing if it is unhealthy
+    Status {
+        /// Address the signer serves its status on, i.e. its configured status_listen
+        #[arg(short, long)]
+        address: String,
+    },
+}
+
+/// Configuration subcommands
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the configuration with secrets redacted
+    Show,
+    /// Validate the configuration and connect to the relay
+    Check,
 }
@@ -1,9 +1,15 @@
 /// Module for defining the CLI and its operations
 pub mod cli;
+/// Module for validating peg out fulfillment signing requests
+pub mod peg_out;
 /// Module for secp256k1 operations
 pub mod secp256k1;
 /// Module for signer operations
 pub mod signer;
+/// Module for following the signer set registered in the sBTC contract
+pub mod signer_set;
+/// Module for reading from the signer's own stacks node
+pub mod stacks_node;
 
 // set via _compile-time_ envars
 const GIT_BRANCH: Option<&'static str> = option_env!("GIT_BRANCH");
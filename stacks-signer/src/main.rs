@@ -1,10 +1,13 @@
 use clap::Parser;
 use frost_signer::config::Config;
+use frost_signer::config_loader::{self, ConfigCheck, ConfigOverrides};
+use frost_signer::keystore;
 use frost_signer::logging;
-use stacks_signer::cli::{Cli, Command};
+use frost_signer::status::StatusReport;
+use stacks_signer::cli::{Cli, Command, ConfigCommand};
 use stacks_signer::signer::Signer;
-use tracing::info;
-use wsts::Point;
+use stacks_signer::stacks_node::StacksNode;
+use tracing::{error, info};
 
 fn main() {
     let cli = Cli::parse();
@@ -14,12 +17,35 @@ fn main() {
 
     // Determine what action the caller wishes to perform
     match cli.command {
-        Command::Run { id, config } => {
-            //TODO: getConf from sBTC contract instead
-            match Config::from_path(&config) {
+        Command::Run {
+            id,
+            config,
+            from_contract,
+        } => {
+            let loaded = if from_contract {
+                Config::load_with_signer_set(&config, &cli.overrides, |rpc_url, contract| {
+                    StacksNode::new(rpc_url).signer_set(contract)
+                })
+            } else {
+                Config::load(&config, &cli.overrides)
+            };
+            match loaded {
                 Ok(config) => {
-                    let mut signer = Signer::new(config, id);
-                    info!("{} signer id #{}", stacks_signer::version(), id); // sign-on message
+                    if let Err(e) = config.validate_signer_id(id) {
+                        panic!("Invalid signer id #{}: {}", id, e);
+                    }
+                    let mut signer_ids = vec![id];
+                    signer_ids.extend(config.identities.iter().map(|identity| identity.signer_id));
+                    let signer = if from_contract {
+                        Signer::from_contract(config, id)
+                    } else {
+                        Signer::new(config, id)
+                    };
+                    let mut signer = match signer {
+                        Ok(signer) => signer,
+                        Err(e) => panic!("An error occurred setting up the signer: {}", e),
+                    };
+                    info!("{} signer ids {:?}", stacks_signer::version(), signer_ids); // sign-on message
                     if let Err(e) = signer.start_p2p_sync() {
                         panic!("An error occurred on the P2P Network: {}", e);
                     }
@@ -34,14 +60,89 @@ fn main() {
                 panic!("An error occurred generating private key: {}", e);
             }
         }
-        Command::PublicKey { config } => match Config::from_path(&config) {
+        Command::PublicKey { config } => match Config::load(&config, &cli.overrides) {
             Ok(config) => {
-                let public_key = Point::from(&config.network_private_key);
+                let public_key = config.network_private_key.public_key();
                 println!("{public_key}")
             }
             Err(e) => {
                 panic!("An error occurred reading config file {}: {}", config, e);
             }
         },
+        Command::Config { config, command } => match command {
+            ConfigCommand::Show => match config_loader::load_value(Some(&config), &cli.overrides) {
+                Ok(value) => print!("{}", config_loader::redact(value)),
+                Err(e) => {
+                    panic!("An error occurred reading config file {}: {}", config, e);
+                }
+            },
+            ConfigCommand::Check => {
+                let checks = check_config(&config, &cli.overrides);
+                for check in &checks {
+                    let status = if check.ok { "ok" } else { "FAILED" };
+                    println!("{} {}: {}", status, check.name, check.message);
+                }
+                if !checks.iter().all(|check| check.ok) {
+                    std::process::exit(1);
+                }
+            }
+        },
+        Command::Keystore { command } => match keystore::execute(&command) {
+            Ok(public_keys) => {
+                for (name, public_key) in public_keys {
+                    println!("{name}: {public_key}");
+                }
+            }
+            Err(e) => {
+                error!("An error occurred running the keystore command: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Command::Status { address } => match query_status(&address) {
+            Ok(report) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).expect("failed to serialize status")
+                );
+                if !report.is_healthy() {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                panic!(
+                    "An error occurred querying the signer status at {}: {}",
+                    address, e
+                );
+            }
+        },
     };
 }
+
+/// Fetch the status report served by a running signer
+fn query_status(address: &str) -> Result<StatusReport, String> {
+    ureq::get(&format!("http://{address}/status"))
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())
+}
+
+/// Validate the configuration and connect to the relay
+fn check_config(path: &str, overrides: &ConfigOverrides) -> Vec<ConfigCheck> {
+    match Config::load(path, overrides) {
+        Ok(config) => vec![
+            ConfigCheck::new(
+                "config",
+                Ok(format!(
+                    "{} signers, {} of {} keys",
+                    config.total_signers, config.keys_threshold, config.total_keys
+                )),
+            ),
+            ConfigCheck::new(
+                "http_relay_url",
+                config_loader::check_reachable(&config.http_relay_url),
+            ),
+        ],
+        Err(e) => vec![ConfigCheck::new("config", Err(e.to_string()))],
+    }
+}
@@ -0,0 +1,288 @@
+use bitcoin::{
+    hashes::Hash, util::address::WitnessVersion, PubkeyHash, Script, ScriptHash, Transaction, TxOut,
+};
+use blockstack_lib::{
+    address::{C32_ADDRESS_VERSION_MAINNET_MULTISIG, C32_ADDRESS_VERSION_TESTNET_MULTISIG},
+    chainstate::{
+        burn::operations::PegOutRequestOp,
+        stacks::address::{PoxAddress, PoxAddressType20, PoxAddressType32},
+    },
+};
+use frost_signer::{
+    signing_round::SignatureShareRequest,
+    validation::{verify_sighash, Error as ValidationError, SignRequestValidator},
+};
+
+use crate::stacks_node::StacksNode;
+
+/// The output script paying the recipient of a peg out request
+pub fn recipient_script(recipient: &PoxAddress) -> Script {
+    match recipient {
+        PoxAddress::Standard(address, _) => {
+            let hash = address.bytes.0;
+            if address.version == C32_ADDRESS_VERSION_MAINNET_MULTISIG
+                || address.version == C32_ADDRESS_VERSION_TESTNET_MULTISIG
+            {
+                Script::new_p2sh(&ScriptHash::from_inner(hash))
+            } else {
+                Script::new_p2pkh(&PubkeyHash::from_inner(hash))
+            }
+        }
+        PoxAddress::Addr20(_, PoxAddressType20::P2WPKH, hash) => {
+            Script::new_witness_program(WitnessVersion::V0, hash)
+        }
+        PoxAddress::Addr32(_, PoxAddressType32::P2WSH, hash) => {
+            Script::new_witness_program(WitnessVersion::V0, hash)
+        }
+        PoxAddress::Addr32(_, PoxAddressType32::P2TR, key) => {
+            Script::new_witness_program(WitnessVersion::V1, key)
+        }
+    }
+}
+
+/// Only signs transactions which fulfill a peg out request observed by the signer's own stacks node
+pub struct PegOutValidator {
+    stacks_node: StacksNode,
+}
+
+impl PegOutValidator {
+    pub fn new(stacks_node_rpc_url: &str) -> Self {
+        Self {
+            stacks_node: StacksNode::new(stacks_node_rpc_url),
+        }
+    }
+}
+
+impl SignRequestValidator for PegOutValidator {
+    fn validate(&self, request: &SignatureShareRequest) -> Result<(), ValidationError> {
+        let (context, tx, prevouts) = verify_sighash(request)?;
+        let ops = self
+            .stacks_node
+            .peg_out_request_ops(context.burn_block_height)
+            .map_err(|e| ValidationError::Rejected(e.to_string()))?;
+        let op = ops
+            .iter()
+            .find(|op| op.txid.to_string() == context.peg_op_txid)
+            .ok_or_else(|| {
+                ValidationError::Rejected(format!(
+                    "peg out request {} was not observed at burn block height {}",
+                    context.peg_op_txid, context.burn_block_height
+                ))
+            })?;
+        check_fulfillment(op, &tx, &prevouts)
+    }
+}
+
+/// Check that the transaction only spends peg wallet outputs including the fulfillment output of
+/// the peg out request, pays exactly the requested amount to the recipient, returns everything
+/// else to the peg wallet and pays no more fee than the fulfillment fee covers
+pub fn check_fulfillment(
+    op: &PegOutRequestOp,
+    tx: &Transaction,
+    prevouts: &[TxOut],
+) -> Result<(), ValidationError> {
+    let reject = |reason: String| Err(ValidationError::Rejected(reason));
+    let Some(wallet_script) = prevouts.first().map(|prevout| &prevout.script_pubkey) else {
+        return reject("transaction spends no outputs".to_string());
+    };
+    if prevouts
+        .iter()
+        .any(|prevout| &prevout.script_pubkey != wallet_script)
+    {
+        return reject("transaction spends outputs of more than one wallet".to_string());
+    }
+    let spends_fulfillment_output = tx.input.iter().any(|input| {
+        input.previous_output.txid.to_string() == op.txid.to_string()
+            && input.previous_output.vout == 2
+    });
+    if !spends_fulfillment_output {
+        return reject(format!(
+            "transaction does not spend the fulfillment output of peg out request {}",
+            op.txid
+        ));
+    }
+
+    let recipient = recipient_script(&op.recipient);
+    let mut paid_recipient = false;
+    for output in &tx.output {
+        if output.script_pubkey.is_op_return() && output.value == 0 {
+            continue;
+        }
+        if !paid_recipient && output.script_pubkey == recipient && output.value == op.amount {
+            paid_recipient = true;
+        } else if &output.script_pubkey != wallet_script {
+            return reject(format!(
+                "transaction pays {} sats to {}, which is neither the recipient nor the peg wallet",
+                output.value, output.script_pubkey
+            ));
+        }
+    }
+    if !paid_recipient {
+        return reject(format!(
+            "transaction does not pay {} sats to the recipient {}",
+            op.amount, recipient
+        ));
+    }
+
+    let spent: u64 = prevouts.iter().map(|prevout| prevout.value).sum();
+    let paid: u64 = tx.output.iter().map(|output| output.value).sum();
+    let max_fee = op.fulfillment_fee + wallet_script.dust_value().to_sat();
+    match spent.checked_sub(paid) {
+        Some(fee) if fee <= max_fee => Ok(()),
+        Some(fee) => reject(format!(
+            "transaction fee of {} sats exceeds the fulfillment fee {} plus dust",
+            fee, op.fulfillment_fee
+        )),
+        None => reject("transaction pays more than it spends".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::{OutPoint, PackedLockTime, Sequence, TxIn, Txid, Witness};
+    use blockstack_lib::{
+        burnchains::Txid as StacksTxid, types::chainstate::BurnchainHeaderHash,
+        util::secp256k1::MessageSignature,
+    };
+
+    use super::*;
+
+    const AMOUNT: u64 = 50_000;
+    const FULFILLMENT_FEE: u64 = 2_000;
+
+    fn peg_out_request_op() -> PegOutRequestOp {
+        PegOutRequestOp {
+            amount: AMOUNT,
+            recipient: PoxAddress::Addr32(true, PoxAddressType32::P2TR, [1; 32]),
+            signature: MessageSignature([0; 65]),
+            peg_wallet_address: PoxAddress::Addr32(true, PoxAddressType32::P2TR, [2; 32]),
+            fulfillment_fee: FULFILLMENT_FEE,
+            memo: vec![],
+            // Uniform bytes read the same in stacks and bitcoin byte order
+            txid: StacksTxid([3; 32]),
+            vtxindex: 0,
+            block_height: 100,
+            burn_header_hash: BurnchainHeaderHash([0; 32]),
+        }
+    }
+
+    fn wallet_script() -> Script {
+        Script::new_witness_program(WitnessVersion::V1, &[2; 32])
+    }
+
+    fn input(txid: [u8; 32], vout: u32) -> TxIn {
+        TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_inner(txid),
+                vout,
+            },
+            script_sig: Script::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }
+    }
+
+    /// A fulfillment spending the fulfillment output and a 100000 sat wallet output
+    fn fulfillment(op: &PegOutRequestOp) -> (Transaction, Vec<TxOut>) {
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime(0),
+            input: vec![input([3; 32], 2), input([4; 32], 0)],
+            output: vec![
+                TxOut {
+                    value: 0,
+                    script_pubkey: Script::new_op_return(b"T2!"),
+                },
+                TxOut {
+                    value: op.amount,
+                    script_pubkey: recipient_script(&op.recipient),
+                },
+                TxOut {
+                    value: 100_000 - op.amount,
+                    script_pubkey: wallet_script(),
+                },
+            ],
+        };
+        let prevouts = vec![
+            TxOut {
+                value: FULFILLMENT_FEE,
+                script_pubkey: wallet_script(),
+            },
+            TxOut {
+                value: 100_000,
+                script_pubkey: wallet_script(),
+            },
+        ];
+        (tx, prevouts)
+    }
+
+    #[test]
+    fn recipient_scripts() {
+        assert_eq!(
+            recipient_script(&PoxAddress::Addr20(
+                false,
+                PoxAddressType20::P2WPKH,
+                [0; 20]
+            )),
+            Script::new_v0_p2wpkh(&bitcoin::WPubkeyHash::from_inner([0; 20]))
+        );
+        assert!(
+            recipient_script(&PoxAddress::Addr32(false, PoxAddressType32::P2WSH, [0; 32]))
+                .is_v0_p2wsh()
+        );
+        assert!(
+            recipient_script(&PoxAddress::Addr32(false, PoxAddressType32::P2TR, [0; 32]))
+                .is_v1_p2tr()
+        );
+    }
+
+    #[test]
+    fn valid_fulfillment_is_accepted() {
+        let op = peg_out_request_op();
+        let (tx, prevouts) = fulfillment(&op);
+        assert!(check_fulfillment(&op, &tx, &prevouts).is_ok());
+
+        // Change below dust goes to the miner along with the fulfillment fee
+        let mut tx = tx;
+        tx.output.pop();
+        let prevouts = vec![
+            prevouts[0].clone(),
+            TxOut {
+                value: op.amount + 100,
+                script_pubkey: wallet_script(),
+            },
+        ];
+        assert!(check_fulfillment(&op, &tx, &prevouts).is_ok());
+    }
+
+    #[test]
+    fn diverted_funds_are_rejected() {
+        let op = peg_out_request_op();
+
+        // Pays someone other than the recipient
+        let (mut tx, prevouts) = fulfillment(&op);
+        tx.output[1].script_pubkey = Script::new_witness_program(WitnessVersion::V1, &[9; 32]);
+        assert!(check_fulfillment(&op, &tx, &prevouts).is_err());
+
+        // Pays the recipient more than requested
+        let (mut tx, prevouts) = fulfillment(&op);
+        tx.output[1].value += 1;
+        tx.output[2].value -= 1;
+        assert!(check_fulfillment(&op, &tx, &prevouts).is_err());
+
+        // Takes the change as fee
+        let (mut tx, prevouts) = fulfillment(&op);
+        tx.output.pop();
+        assert!(check_fulfillment(&op, &tx, &prevouts).is_err());
+
+        // Does not spend the fulfillment output of the op
+        let (mut tx, prevouts) = fulfillment(&op);
+        tx.input[0] = input([5; 32], 2);
+        assert!(check_fulfillment(&op, &tx, &prevouts).is_err());
+
+        // Spends outputs of another wallet
+        let (tx, mut prevouts) = fulfillment(&op);
+        prevouts[1].script_pubkey = Script::new_witness_program(WitnessVersion::V1, &[9; 32]);
+        assert!(check_fulfillment(&op, &tx, &prevouts).is_err());
+    }
+}
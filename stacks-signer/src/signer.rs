@@ -1,5 +1,21 @@
 use frost_signer::config::Config;
 use frost_signer::signer::{Error as SignerError, Signer as FrostSigner};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::peg_out::PegOutValidator;
+use crate::signer_set::ContractSignerSet;
+use crate::stacks_node::{Error as StacksNodeError, StacksNode};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Stacks Node Error: {0}")]
+    StacksNodeError(#[from] StacksNodeError),
+    #[error("sbtc_contract is set but stacks_node_rpc_url is not")]
+    MissingStacksNode,
+    #[error("Following the signer set of the sBTC contract requires sbtc_contract and stacks_node_rpc_url")]
+    MissingContract,
+}
 
 #[derive(Clone)]
 pub struct Signer {
@@ -8,10 +24,48 @@ pub struct Signer {
 }
 
 impl Signer {
-    pub fn new(config: Config, id: u32) -> Self {
-        Self {
-            frost_signer: FrostSigner::new(config, id),
+    /// Signing requests are only validated against peg out requests if the config sets the
+    /// signer's own stacks node. Saved DKG state is only checked against the on-chain aggregate
+    /// public key if the config also sets the sBTC contract.
+    pub fn new(config: Config, id: u32) -> Result<Self, Error> {
+        let stacks_node = config.stacks_node_rpc_url.as_deref().map(StacksNode::new);
+        let on_chain_public_key = match (&config.sbtc_contract, &stacks_node) {
+            (Some(contract), Some(stacks_node)) => {
+                let public_key = stacks_node.bitcoin_wallet_public_key(contract)?;
+                match &public_key {
+                    Some(public_key) => info!(
+                        "sBTC contract bitcoin wallet public key {}",
+                        hex::encode(public_key)
+                    ),
+                    None => info!("sBTC contract has no bitcoin wallet public key yet"),
+                }
+                public_key
+            }
+            (Some(_), None) => return Err(Error::MissingStacksNode),
+            (None, _) => None,
+        };
+        let validator = config
+            .stacks_node_rpc_url
+            .as_deref()
+            .map(|url| Arc::new(PegOutValidator::new(url)));
+
+        let mut frost_signer = FrostSigner::new(config, id);
+        if let Some(validator) = validator {
+            frost_signer = frost_signer.with_validator(validator);
         }
+        if let Some(public_key) = on_chain_public_key {
+            frost_signer = frost_signer.with_on_chain_public_key(public_key);
+        }
+        Ok(Self { frost_signer })
+    }
+
+    /// Like `new`, but the signer rolls over to the signer sets registered in the sBTC contract as
+    /// their cycle begins
+    pub fn from_contract(config: Config, id: u32) -> Result<Self, Error> {
+        let source = ContractSignerSet::new(&config).ok_or(Error::MissingContract)?;
+        let mut signer = Self::new(config, id)?;
+        signer.frost_signer = signer.frost_signer.with_signer_set_source(Arc::new(source));
+        Ok(signer)
     }
 
     pub fn start_p2p_sync(&mut self) -> Result<(), SignerError> {
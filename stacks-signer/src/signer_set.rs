@@ -0,0 +1,188 @@
+use frost_signer::config::{Config, SignerSet};
+use frost_signer::signer_set::{Error as SignerSetError, Rollover, SignerSetSource};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::stacks_node::StacksNode;
+
+/// Decides when to roll over to a signer set registered in the sBTC contract: at the start of the
+/// cycle after the one it was first seen in
+#[derive(Debug)]
+pub struct Schedule {
+    current: SignerSet,
+    /// The registered signer set waiting for its cycle, and the burn block height it starts at
+    pending: Option<(SignerSet, u64)>,
+    cycle_length: Option<u64>,
+}
+
+impl Schedule {
+    pub fn new(current: SignerSet, cycle_length: Option<u64>) -> Self {
+        Self {
+            current,
+            pending: None,
+            cycle_length: cycle_length.filter(|length| *length > 0),
+        }
+    }
+
+    /// Note the signer set registered at the burn block height, and hand it out once its cycle
+    /// begins, until it is rolled over to. A registration which is undone before then is never
+    /// rolled over to.
+    pub fn update(&mut self, registered: SignerSet, burn_block_height: u64) -> Option<SignerSet> {
+        if registered == self.current {
+            self.pending = None;
+            return None;
+        }
+        let starts_at = match &self.pending {
+            Some((pending, starts_at)) if *pending == registered => *starts_at,
+            _ => {
+                let starts_at = self.cycle_length.map_or(burn_block_height, |length| {
+                    (burn_block_height / length + 1) * length
+                });
+                info!("New signer set registered, rolling over at burn block height {starts_at}");
+                starts_at
+            }
+        };
+        self.pending = Some((registered.clone(), starts_at));
+        (burn_block_height >= starts_at).then_some(registered)
+    }
+
+    /// Note that the signer runs with the signer set now
+    pub fn rolled_over(&mut self, signer_set: &SignerSet) {
+        if matches!(&self.pending, Some((pending, _)) if pending == signer_set) {
+            self.pending = None;
+        }
+        self.current = signer_set.clone();
+    }
+}
+
+/// Follows the signer set registered in the sBTC contract, reading it at the configured poll
+/// interval
+pub struct ContractSignerSet {
+    stacks_node: StacksNode,
+    sbtc_contract: String,
+    poll_interval: Duration,
+    // When the signer set was last read, and the rollover schedule
+    state: Mutex<(Instant, Schedule)>,
+}
+
+impl ContractSignerSet {
+    /// Follow the contract of the config, starting from its signer set. None if the config does
+    /// not set both `sbtc_contract` and `stacks_node_rpc_url`.
+    pub fn new(config: &Config) -> Option<Self> {
+        let (Some(stacks_node_rpc_url), Some(sbtc_contract)) =
+            (&config.stacks_node_rpc_url, &config.sbtc_contract)
+        else {
+            return None;
+        };
+        Some(Self {
+            stacks_node: StacksNode::new(stacks_node_rpc_url),
+            sbtc_contract: sbtc_contract.clone(),
+            poll_interval: config.signer_set_poll_interval,
+            state: Mutex::new((
+                Instant::now(),
+                Schedule::new(config.signer_set(), config.signer_set_cycle_length),
+            )),
+        })
+    }
+}
+
+impl SignerSetSource for ContractSignerSet {
+    fn rollover(&self) -> Result<Option<Rollover>, SignerSetError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (last_poll, schedule) = &mut *state;
+        if last_poll.elapsed() < self.poll_interval {
+            return Ok(None);
+        }
+        *last_poll = Instant::now();
+        let unavailable = |e: crate::stacks_node::Error| SignerSetError::Unavailable(e.to_string());
+        let registered = self
+            .stacks_node
+            .signer_set(&self.sbtc_contract)
+            .map_err(unavailable)?;
+        let burn_block_height = self.stacks_node.burn_block_height().map_err(unavailable)?;
+        let on_chain_public_key = self
+            .stacks_node
+            .bitcoin_wallet_public_key(&self.sbtc_contract)
+            .map_err(unavailable)?;
+        Ok(schedule
+            .update(registered, burn_block_height)
+            .map(|signer_set| Rollover {
+                signer_set,
+                on_chain_public_key,
+            }))
+    }
+
+    fn rolled_over(&self, signer_set: &SignerSet) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.1.rolled_over(signer_set);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use frost_signer::config::{PublicKeys, SignerKeyIds};
+    use wsts::{ecdsa, Scalar};
+
+    use super::*;
+
+    fn signer_set(keys_threshold: u32) -> SignerSet {
+        SignerSet {
+            keys_threshold,
+            coordinator_public_key: ecdsa::PublicKey::new(&Scalar::from(1u32)).unwrap(),
+            public_keys: PublicKeys::default(),
+            signer_key_ids: SignerKeyIds::default(),
+        }
+    }
+
+    #[test]
+    fn rolls_over_at_the_next_cycle() {
+        let mut schedule = Schedule::new(signer_set(2), Some(100));
+        assert_eq!(schedule.update(signer_set(2), 150), None);
+
+        assert_eq!(schedule.update(signer_set(3), 150), None);
+        assert_eq!(schedule.update(signer_set(3), 199), None);
+        assert_eq!(schedule.update(signer_set(3), 200), Some(signer_set(3)));
+        schedule.rolled_over(&signer_set(3));
+        assert_eq!(schedule.update(signer_set(3), 201), None);
+
+        // A set registered at the start of a cycle waits for the next one
+        assert_eq!(schedule.update(signer_set(4), 300), None);
+        assert_eq!(schedule.update(signer_set(4), 400), Some(signer_set(4)));
+    }
+
+    #[test]
+    fn rolls_over_right_away_without_cycles() {
+        let mut schedule = Schedule::new(signer_set(2), None);
+        assert_eq!(schedule.update(signer_set(3), 150), Some(signer_set(3)));
+        schedule.rolled_over(&signer_set(3));
+        assert_eq!(schedule.update(signer_set(3), 151), None);
+    }
+
+    #[test]
+    fn failed_rollovers_are_retried() {
+        let mut schedule = Schedule::new(signer_set(2), Some(100));
+        assert_eq!(schedule.update(signer_set(3), 150), None);
+        assert_eq!(schedule.update(signer_set(3), 200), Some(signer_set(3)));
+
+        // The signer failed to roll over, so the set is handed out again
+        assert_eq!(schedule.update(signer_set(3), 201), Some(signer_set(3)));
+        assert_eq!(schedule.update(signer_set(3), 350), Some(signer_set(3)));
+
+        schedule.rolled_over(&signer_set(3));
+        assert_eq!(schedule.update(signer_set(3), 351), None);
+    }
+
+    #[test]
+    fn undone_registrations_are_not_rolled_over_to() {
+        let mut schedule = Schedule::new(signer_set(2), Some(100));
+        assert_eq!(schedule.update(signer_set(3), 150), None);
+        assert_eq!(schedule.update(signer_set(2), 160), None);
+        assert_eq!(schedule.update(signer_set(2), 200), None);
+
+        // Registering another set restarts the wait
+        assert_eq!(schedule.update(signer_set(3), 210), None);
+        assert_eq!(schedule.update(signer_set(4), 290), None);
+        assert_eq!(schedule.update(signer_set(4), 300), Some(signer_set(4)));
+    }
+}
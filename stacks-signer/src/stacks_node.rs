@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use bitcoin::XOnlyPublicKey;
+use blockstack_lib::{chainstate::burn::operations::PegOutRequestOp, vm::Value as ClarityValue};
+use frost_signer::config::{PublicKeys, SignerKeyIds, SignerSet};
+use sbtc_core::clarity::{
+    CoordinatorData, Error as ClarityDecodeError, FromClarityValue, SignerData,
+};
+use serde_json::{json, Value};
+use tracing::debug;
+
+/// How long to wait for the stacks node to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Stacks node request failed: {0}")]
+    RequestError(#[from] Box<ureq::Error>),
+    #[error("IO Error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("JSON Error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Invalid sbtc_contract {0}. Expected <address>.<name>")]
+    InvalidContract(String),
+    #[error("Read-only call {0} failed: {1}")]
+    ReadOnlyFailure(String, String),
+    #[error("Read-only call {0} returned an unexpected value: {1}")]
+    UnexpectedValue(String, String),
+    #[error("Read-only call {0} returned a malformed clarity value: {1}")]
+    MalformedClarityValue(String, ClarityDecodeError),
+    #[error("Stacks node info is missing {0}")]
+    MissingInfo(String),
+    #[error("No coordinator is registered in the sBTC contract")]
+    NoCoordinator,
+    #[error("No signer {0} is registered in the sBTC contract")]
+    NoSignerData(u128),
+}
+
+/// The few stacks node endpoints the signer reads to check what the coordinator asks of it
+pub struct StacksNode {
+    rpc_url: String,
+}
+
+impl StacksNode {
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The peg out request ops the node observed at the burn block height
+    pub fn peg_out_request_ops(&self, block_height: u64) -> Result<Vec<PegOutRequestOp>, Error> {
+        debug!("Retrieving peg out request ops at burn block height {block_height}");
+        let json: Value = ureq::get(&format!(
+            "{}/v2/burn_ops/{block_height}/peg_out_request",
+            self.rpc_url
+        ))
+        .timeout(REQUEST_TIMEOUT)
+        .call()
+        .map_err(Box::new)?
+        .into_json()?;
+        Ok(serde_json::from_value(json["peg_out_request"].clone())?)
+    }
+
+    /// The x-only bitcoin wallet public key registered in the sBTC contract, if any
+    pub fn bitcoin_wallet_public_key(
+        &self,
+        sbtc_contract: &str,
+    ) -> Result<Option<[u8; 32]>, Error> {
+        let public_key: Option<XOnlyPublicKey> =
+            self.call_read(sbtc_contract, "get-bitcoin-wallet-public-key", &[])?;
+        Ok(public_key.map(|public_key| public_key.serialize()))
+    }
+
+    /// The burn block height of the node's chain tip
+    pub fn burn_block_height(&self) -> Result<u64, Error> {
+        debug!("Retrieving burn block height...");
+        let json: Value = ureq::get(&format!("{}/v2/info", self.rpc_url))
+            .timeout(REQUEST_TIMEOUT)
+            .call()
+            .map_err(Box::new)?
+            .into_json()?;
+        json["burn_block_height"]
+            .as_u64()
+            .ok_or_else(|| Error::MissingInfo("burn_block_height".to_string()))
+    }
+
+    /// The signers, counted from 1, their key ids, the threshold and the coordinator registered
+    /// in the sBTC contract
+    pub fn signer_set(&self, sbtc_contract: &str) -> Result<SignerSet, Error> {
+        let keys_threshold = self.call_read(sbtc_contract, "get-threshold", &[])?;
+        let coordinator: Option<CoordinatorData> =
+            self.call_read(sbtc_contract, "get-coordinator-data", &[])?;
+        let coordinator_public_key = coordinator.ok_or(Error::NoCoordinator)?.key;
+
+        let num_signers: u128 = self.call_read(sbtc_contract, "get-num-signers", &[])?;
+        let mut public_keys = PublicKeys::default();
+        let mut signer_key_ids = SignerKeyIds::default();
+        for id in 1..=num_signers {
+            let signer_data: Option<SignerData> =
+                self.call_read(sbtc_contract, "get-signer-data", &[ClarityValue::UInt(id)])?;
+            let SignerData {
+                public_key,
+                key_ids,
+            } = signer_data.ok_or(Error::NoSignerData(id))?;
+            let signer_id = u32::try_from(id).map_err(|_| Error::NoSignerData(id))?;
+            for key_id in &key_ids {
+                public_keys.key_ids.insert(*key_id, public_key);
+            }
+            public_keys.signers.insert(signer_id, public_key);
+            signer_key_ids.insert(signer_id, key_ids);
+        }
+        Ok(SignerSet {
+            keys_threshold,
+            coordinator_public_key,
+            public_keys,
+            signer_key_ids,
+        })
+    }
+
+    /// Call a read-only function of the contract and decode its result
+    fn call_read<T: FromClarityValue>(
+        &self,
+        contract: &str,
+        function_name: &str,
+        function_args: &[ClarityValue],
+    ) -> Result<T, Error> {
+        let (address, name) = contract
+            .split_once('.')
+            .ok_or_else(|| Error::InvalidContract(contract.to_string()))?;
+        debug!("Calling read-only function {name}.{function_name}...");
+        // Arguments are passed to the node as hex encoded consensus serialized values
+        let arguments: Vec<String> = function_args
+            .iter()
+            .map(|arg| format!("0x{}", hex::encode(arg.serialize_to_vec())))
+            .collect();
+        let response: Value = ureq::post(&format!(
+            "{}/v2/contracts/call-read/{address}/{name}/{function_name}",
+            self.rpc_url
+        ))
+        .timeout(REQUEST_TIMEOUT)
+        .send_json(json!({"sender": address, "arguments": arguments}))
+        .map_err(Box::new)?
+        .into_json()?;
+        if response["okay"].as_bool() != Some(true) {
+            return Err(Error::ReadOnlyFailure(
+                function_name.to_string(),
+                response["cause"].to_string(),
+            ));
+        }
+        let result = response["result"].as_str().ok_or_else(|| {
+            Error::ReadOnlyFailure(function_name.to_string(), "missing result".to_string())
+        })?;
+        let result = ClarityValue::try_deserialize_hex_untyped(result)
+            .map_err(|e| Error::UnexpectedValue(function_name.to_string(), e.to_string()))?;
+        T::from_clarity_value(result)
+            .map_err(|e| Error::MalformedClarityValue(function_name.to_string(), e))
+    }
+}
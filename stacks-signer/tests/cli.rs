@@ -33,3 +33,30 @@ fn secp256k1_to_file() {
     cmd.assert().success();
     assert!(output_path.exists());
 }
+
+#[test]
+fn config_show_applies_overrides_and_redacts_secrets() {
+    let mut cmd = Command::cargo_bin("stacks-signer").unwrap();
+    cmd.args(["config", "-c", "conf/signer.toml", "show"])
+        .args(["--set", "keys_threshold=3"])
+        .env("SBTC_HTTP_RELAY_URL", "http://relay.example:9776");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("keys_threshold = 3"))
+        .stdout(predicate::str::contains(
+            "http_relay_url = \"http://relay.example:9776\"",
+        ))
+        .stdout(predicate::str::contains(
+            "network_private_key = \"<redacted>\"",
+        ))
+        .stdout(predicate::str::contains("9aSCCR6eirt1NAHwJtSz4HMwBHTyMo62SyPMvVDt5DQn").not());
+}
+
+#[test]
+fn status_fails_without_a_running_signer() {
+    let mut cmd = Command::cargo_bin("stacks-signer").unwrap();
+    cmd.args(["status", "-a", "127.0.0.1:1"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "An error occurred querying the signer status at 127.0.0.1:1",
+    ));
+}
@@ -29,8 +29,8 @@ use wsts::{
         test_helpers::{dkg, sign},
         SchnorrProof,
     },
-    v1::{SignatureAggregator, Signer},
-    Point,
+    traits::Signer,
+    v1, v2, Point,
 };
 
 use nix::sys::signal::{self, Signal};
@@ -345,12 +345,18 @@ pub fn get_raw_transaction(
     Transaction::consensus_decode(&mut hex::decode(tx_raw).unwrap().as_slice())
 }
 
+/// The signers of a [`SignerHelper`], in one of the wsts protocol versions
+enum Signers {
+    V1([v1::Signer; 3]),
+    V2([v2::Party; 3]),
+}
+
 /// A helper struct for executing DKG rounds and generating Schnorr signatures
 pub struct SignerHelper {
     threshold: u32,
     total: u32,
     rng: OsRng,
-    signers: [Signer; 3],
+    signers: Signers,
 }
 
 impl Default for SignerHelper {
@@ -360,29 +366,51 @@ impl Default for SignerHelper {
         let total = 4;
         let mut rng = OsRng;
         let signers = [
-            Signer::new(1, &[0, 1], total, threshold, &mut rng),
-            Signer::new(2, &[2], total, threshold, &mut rng),
-            Signer::new(3, &[3], total, threshold, &mut rng),
+            v1::Signer::new(1, &[0, 1], total, threshold, &mut rng),
+            v1::Signer::new(2, &[2], total, threshold, &mut rng),
+            v1::Signer::new(3, &[3], total, threshold, &mut rng),
         ];
 
         Self {
             threshold,
             total,
             rng,
-            signers,
+            signers: Signers::V1(signers),
         }
     }
 }
 
 impl SignerHelper {
+    /// The same signers and key ids as the default, running wsts v2
+    pub fn v2() -> Self {
+        let threshold = 3;
+        let total = 4;
+        let mut rng = OsRng;
+        let signers = [
+            v2::Party::new(0, &[0, 1], 3, total, threshold, &mut rng),
+            v2::Party::new(1, &[2], 3, total, threshold, &mut rng),
+            v2::Party::new(2, &[3], 3, total, threshold, &mut rng),
+        ];
+
+        Self {
+            threshold,
+            total,
+            rng,
+            signers: Signers::V2(signers),
+        }
+    }
+
     pub fn run_distributed_key_generation(
         &mut self,
         merkle_root: Option<[u8; 32]>,
     ) -> (Vec<PolyCommitment>, Point, bitcoin::PublicKey) {
         // DKG (Distributed Key Generation)
 
-        let public_commitments = dkg(&mut self.signers, &mut self.rng, merkle_root)
-            .expect("Failed to run distributed key generation.");
+        let public_commitments = match &mut self.signers {
+            Signers::V1(signers) => dkg(signers, &mut self.rng, merkle_root),
+            Signers::V2(signers) => dkg(signers, &mut self.rng, merkle_root),
+        }
+        .expect("Failed to run distributed key generation.");
         let group_public_key_point = public_commitments
             .iter()
             .fold(Point::new(), |s, poly| s + poly.A[0]);
@@ -407,15 +435,30 @@ impl SignerHelper {
         merkle_root: Option<[u8; 32]>,
     ) -> SchnorrProof {
         // decide which signers will be used
-        let mut signers = [self.signers[0].clone(), self.signers[1].clone()];
+        let sig = match &self.signers {
+            Signers::V1(signers) => {
+                let mut signers = [signers[0].clone(), signers[1].clone()];
 
-        let (nonces, shares) = sign(message, &mut signers, &mut self.rng, merkle_root);
+                let (nonces, shares) = sign(message, &mut signers, &mut self.rng, merkle_root);
 
-        let mut agg = SignatureAggregator::new(self.total, self.threshold, public_commitments)
-            .expect("Failed to create signature aggregator.");
-        let sig = agg
-            .sign_taproot(message, &nonces, &shares, merkle_root)
-            .expect("Failed to create signature.");
+                let mut agg =
+                    v1::SignatureAggregator::new(self.total, self.threshold, public_commitments)
+                        .expect("Failed to create signature aggregator.");
+                agg.sign_taproot(message, &nonces, &shares, merkle_root)
+            }
+            Signers::V2(signers) => {
+                let mut signers = [signers[0].clone(), signers[1].clone()];
+                let key_ids: Vec<u32> = signers.iter().flat_map(Signer::get_key_ids).collect();
+
+                let (nonces, shares) = sign(message, &mut signers, &mut self.rng, merkle_root);
+
+                let mut agg =
+                    v2::SignatureAggregator::new(self.total, self.threshold, public_commitments)
+                        .expect("Failed to create signature aggregator.");
+                agg.sign_taproot(message, &nonces, &shares, &key_ids, merkle_root)
+            }
+        }
+        .expect("Failed to create signature.");
 
         SchnorrProof::new(&sig).expect("Failed to create Schnorr proof.")
     }